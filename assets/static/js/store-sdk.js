@@ -85,7 +85,7 @@ function slugify(t) {
 // ─── Tabelas de labels e cores de status ──────────────────────────────────────
 const STATUS_LABEL = {
   pending: 'Pendente', confirmed: 'Confirmado', processing: 'Processando',
-  shipped: 'Enviado', delivered: 'Entregue', cancelled: 'Cancelado',
+  shipped: 'Enviado', delivered: 'Entregue', canceled: 'Cancelado',
   awaiting: 'Aguardando', paid: 'Pago', failed: 'Falhou', refunded: 'Reembolsado',
   not_fulfilled: 'Não enviado', fulfilled: 'Enviado', partially_fulfilled: 'Parcial',
   active: 'Ativo', draft: 'Rascunho', archived: 'Arquivado',
@@ -97,7 +97,7 @@ const STATUS_CLASS = {
   processing: 'bg-blue-100 text-blue-800',
   shipped: 'bg-indigo-100 text-indigo-800',
   delivered: 'bg-green-100 text-green-800',
-  cancelled: 'bg-red-100 text-red-800',
+  canceled: 'bg-red-100 text-red-800',
   awaiting: 'bg-yellow-100 text-yellow-800',
   paid: 'bg-green-100 text-green-800',
   failed: 'bg-red-100 text-red-800',
@@ -12,6 +12,26 @@ mod m20260225_000008_carts;
 mod m20260225_000009_orders;
 mod m20260226_000010_store_collaborators_shippings;
 mod m20260227_000011_remove_stores;
+mod m20260228_000012_warehouses_items_stocks;
+mod m20260228_000013_add_user_role;
+mod m20260301_000014_stock_reservations;
+mod m20260302_000015_order_sequences;
+mod m20260303_000016_coupons;
+mod m20260304_000017_shipping_rates;
+mod m20260305_000018_shipment_tracking_events;
+mod m20260306_000019_payment_events;
+mod m20260307_000020_refunds;
+mod m20260308_000021_order_status_history;
+mod m20260309_000022_order_notes;
+mod m20260310_000023_returns;
+mod m20260311_000024_order_edits;
+mod m20260312_000025_shipment_items;
+mod m20260313_000026_stock_movements;
+mod m20260314_000027_stock_transfers;
+mod m20260315_000028_warehouse_postal_code;
+mod m20260316_000029_purchase_orders;
+mod m20260317_000030_order_payment_expiry;
+mod m20260318_000031_entity_columns;
 
 pub struct Migrator;
 
@@ -30,6 +50,26 @@ impl MigratorTrait for Migrator {
             Box::new(m20260225_000009_orders::Migration),
             Box::new(m20260226_000010_store_collaborators_shippings::Migration),
             Box::new(m20260227_000011_remove_stores::Migration),
+            Box::new(m20260228_000012_warehouses_items_stocks::Migration),
+            Box::new(m20260228_000013_add_user_role::Migration),
+            Box::new(m20260301_000014_stock_reservations::Migration),
+            Box::new(m20260302_000015_order_sequences::Migration),
+            Box::new(m20260303_000016_coupons::Migration),
+            Box::new(m20260304_000017_shipping_rates::Migration),
+            Box::new(m20260305_000018_shipment_tracking_events::Migration),
+            Box::new(m20260306_000019_payment_events::Migration),
+            Box::new(m20260307_000020_refunds::Migration),
+            Box::new(m20260308_000021_order_status_history::Migration),
+            Box::new(m20260309_000022_order_notes::Migration),
+            Box::new(m20260310_000023_returns::Migration),
+            Box::new(m20260311_000024_order_edits::Migration),
+            Box::new(m20260312_000025_shipment_items::Migration),
+            Box::new(m20260313_000026_stock_movements::Migration),
+            Box::new(m20260314_000027_stock_transfers::Migration),
+            Box::new(m20260315_000028_warehouse_postal_code::Migration),
+            Box::new(m20260316_000029_purchase_orders::Migration),
+            Box::new(m20260317_000030_order_payment_expiry::Migration),
+            Box::new(m20260318_000031_entity_columns::Migration),
             // inject-above (do not remove this comment)
         ]
     }
@@ -1,4 +1,5 @@
 use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
 
 #[derive(DeriveMigrationName)]
 pub struct Migration;
@@ -6,21 +7,24 @@ pub struct Migration;
 #[async_trait::async_trait]
 impl MigrationTrait for Migration {
     async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
-        // When using SQLite (e.g. in tests) we skip this destructive
-        // migration because dropping columns with foreign keys is
-        // not supported and the schema can remain unchanged for tests.
-        if cfg!(feature = "sqlite") {
-            return Ok(());
-        }
-
         // Cria tabela de backup antes de remover
         manager
             .get_connection()
             .execute_unprepared("CREATE TABLE IF NOT EXISTS backup_stores AS SELECT * FROM stores")
             .await?;
 
+        // SQLite não remove coluna usada em chave estrangeira
+        if manager.get_database_backend() == DatabaseBackend::Sqlite {
+            for table in STORE_SCOPED_TABLES {
+                rebuild_without_store(manager, table).await?;
+            }
+            manager
+                .drop_table(Table::drop().table(Stores::Table).if_exists().to_owned())
+                .await?;
+            return Ok(());
+        }
+
         // Remove FK constraints e coluna store_id de store_collaborators
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared("ALTER TABLE store_collaborators DROP CONSTRAINT fk_collab_store")
@@ -37,7 +41,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK e coluna store_id de order_shippings
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared(
@@ -56,7 +59,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK e coluna store_id de orders
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared("ALTER TABLE orders DROP CONSTRAINT IF EXISTS fk_orders_store")
@@ -73,7 +75,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK e coluna store_id de carts
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared("ALTER TABLE carts DROP CONSTRAINT IF EXISTS fk_carts_store")
@@ -90,7 +91,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK e coluna store_id de customers
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared(
@@ -109,7 +109,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK e coluna store_id de collections
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared(
@@ -128,7 +127,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK e coluna store_id de products
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared("ALTER TABLE products DROP CONSTRAINT IF EXISTS fk_products_store")
@@ -145,7 +143,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK e coluna store_id de categories
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared(
@@ -164,7 +161,6 @@ impl MigrationTrait for Migration {
             .await?;
 
         // Remove FK da tabela stores para users (owner)
-        #[cfg(not(feature = "sqlite"))]
         manager
             .get_connection()
             .execute_unprepared("ALTER TABLE stores DROP CONSTRAINT IF EXISTS fk_stores_owner")
@@ -328,6 +324,94 @@ impl MigrationTrait for Migration {
     }
 }
 
+/// Tabelas com `store_id`
+const STORE_SCOPED_TABLES: [&str; 8] = [
+    "store_collaborators",
+    "order_shippings",
+    "orders",
+    "carts",
+    "customers",
+    "collections",
+    "products",
+    "categories",
+];
+
+/// Recria a tabela sem `store_id` no SQLite, seguindo os passos de "Making
+/// Other Kinds Of Table Schema Changes" da documentação do SQLite: cria a nova
+/// tabela, copia as linhas, troca uma pela outra e recria os índices. Índices
+/// por loja somem com a coluna, como o `DROP COLUMN` faz no Postgres.
+async fn rebuild_without_store(manager: &SchemaManager<'_>, table: &str) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let backend = manager.get_database_backend();
+    let schema = |sql: String, values: Vec<Value>| {
+        db.query_all(Statement::from_sql_and_values(backend, sql, values))
+    };
+
+    let create = schema(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?".to_string(),
+        vec![table.into()],
+    )
+    .await?
+    .first()
+    .map(|row| row.try_get_by_index::<String>(0))
+    .transpose()?
+    .ok_or_else(|| DbErr::Migration(format!("Tabela {table} não encontrada")))?;
+    let create = create
+        .replacen(r#""store_id" integer NOT NULL, "#, "", 1)
+        .replacen(
+            r#", FOREIGN KEY ("store_id") REFERENCES "stores" ("id") ON DELETE CASCADE"#,
+            "",
+            1,
+        )
+        .replacen(
+            &format!(r#"CREATE TABLE "{table}""#),
+            &format!(r#"CREATE TABLE "{table}_new""#),
+            1,
+        );
+    if create.contains("store_id") || !create.contains(&format!(r#""{table}_new""#)) {
+        return Err(DbErr::Migration(format!(
+            "Definição inesperada da tabela {table}: {create}"
+        )));
+    }
+
+    let mut columns = Vec::new();
+    for row in schema(format!(r#"PRAGMA table_info("{table}")"#), vec![]).await? {
+        let name: String = row.try_get("", "name")?;
+        if name != "store_id" {
+            columns.push(format!(r#""{name}""#));
+        }
+    }
+    let columns = columns.join(", ");
+
+    let mut indexes = Vec::new();
+    for row in schema(
+        "SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ? AND sql IS NOT NULL"
+            .to_string(),
+        vec![table.into()],
+    )
+    .await?
+    {
+        let index: String = row.try_get_by_index(0)?;
+        if !index.contains("store_id") {
+            indexes.push(format!("{index};"));
+        }
+    }
+
+    // Um único lote: o pragma vale só para a conexão que o executa
+    db.execute_unprepared(&format!(
+        r#"PRAGMA foreign_keys = OFF;
+        {create};
+        INSERT INTO "{table}_new" ({columns}) SELECT {columns} FROM "{table}";
+        DROP TABLE "{table}";
+        ALTER TABLE "{table}_new" RENAME TO "{table}";
+        {indexes}
+        PRAGMA foreign_keys = ON;"#,
+        indexes = indexes.join("\n")
+    ))
+    .await?;
+    Ok(())
+}
+
 #[derive(Iden)]
 enum Stores {
     Table,
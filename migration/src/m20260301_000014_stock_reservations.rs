@@ -0,0 +1,185 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    #[allow(clippy::too_many_lines)]
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Quantidade reservada por checkouts em andamento.
+        // Disponível para venda = quantity - reserved
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Stocks::Table)
+                    .add_column(
+                        ColumnDef::new(Stocks::Reserved)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── stock_reservations ──────────────────────────────────────
+        // Cada linha reserva `quantity` unidades de uma linha de `stocks`
+        // para um pedido. `stock_id` nulo = backorder (sem estoque físico).
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockReservations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StockReservations::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservations::Pid)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservations::OrderId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservations::VariantId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(StockReservations::StockId).integer())
+                    .col(
+                        ColumnDef::new(StockReservations::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    // Status: 'active' | 'committed' | 'released' | 'expired'
+                    .col(
+                        ColumnDef::new(StockReservations::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("active"),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservations::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(StockReservations::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_reservation_order")
+                            .from(StockReservations::Table, StockReservations::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_reservation_variant")
+                            .from(StockReservations::Table, StockReservations::VariantId)
+                            .to(ProductVariants::Table, ProductVariants::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_reservation_stock")
+                            .from(StockReservations::Table, StockReservations::StockId)
+                            .to(Stocks::Table, Stocks::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_reservation_order")
+                    .table(StockReservations::Table)
+                    .col(StockReservations::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Usado pelo worker de expiração
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_reservation_status_expires")
+                    .table(StockReservations::Table)
+                    .col(StockReservations::Status)
+                    .col(StockReservations::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StockReservations::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Stocks::Table)
+                    .drop_column(Stocks::Reserved)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum StockReservations {
+    Table,
+    Id,
+    Pid,
+    OrderId,
+    VariantId,
+    StockId,
+    Quantity,
+    Status,
+    ExpiresAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Stocks {
+    Table,
+    Id,
+    Reserved,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ProductVariants {
+    Table,
+    Id,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, Statement};
+
+/// Prefixo usado pelos pedidos criados antes da sequência existir
+const LEGACY_PREFIX: &str = "LFS-";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── order_sequences ─────────────────────────────────────────
+        // Contador de números de pedido por prefixo. O incremento acontece
+        // na mesma transação do insert do pedido, então um rollback devolve
+        // o número (ao contrário de um SEQUENCE do Postgres, que deixa buracos).
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderSequences::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderSequences::Name)
+                            .string_len(32)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderSequences::LastValue)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(OrderSequences::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OrderSequences::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Continua a numeração a partir do maior número já emitido
+        let backend = manager.get_database_backend();
+        let rows = manager
+            .get_connection()
+            .query_all(Statement::from_string(
+                backend,
+                "SELECT order_number FROM orders".to_owned(),
+            ))
+            .await?;
+        let last_value = rows
+            .iter()
+            .filter_map(|r| r.try_get::<String>("", "order_number").ok())
+            .filter_map(|n| {
+                n.strip_prefix(LEGACY_PREFIX)
+                    .and_then(|seq| seq.parse::<i64>().ok())
+            })
+            .max()
+            .unwrap_or(0);
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(OrderSequences::Table)
+                    .columns([OrderSequences::Name, OrderSequences::LastValue])
+                    .values_panic([LEGACY_PREFIX.into(), last_value.into()])
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderSequences::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum OrderSequences {
+    Table,
+    Name,
+    LastValue,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,302 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    #[allow(clippy::too_many_lines)]
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── coupons ─────────────────────────────────────────────────
+        manager
+            .create_table(
+                Table::create()
+                    .table(Coupons::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Coupons::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Coupons::Pid).uuid().not_null().unique_key())
+                    .col(
+                        ColumnDef::new(Coupons::Code)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Coupons::Description).text())
+                    // Tipo: 'percentage' | 'fixed_amount' | 'free_shipping'
+                    .col(
+                        ColumnDef::new(Coupons::DiscountType)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    // percentage: 1..100 | fixed_amount: centavos | free_shipping: ignorado
+                    .col(
+                        ColumnDef::new(Coupons::Value)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Coupons::MinSubtotal).big_integer())
+                    .col(ColumnDef::new(Coupons::UsageLimit).integer())
+                    .col(ColumnDef::new(Coupons::UsageLimitPerCustomer).integer())
+                    .col(
+                        ColumnDef::new(Coupons::UsageCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    // Escopo (listas de IDs); vazio = carrinho inteiro
+                    .col(
+                        ColumnDef::new(Coupons::ProductIds)
+                            .json_binary()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(
+                        ColumnDef::new(Coupons::CategoryIds)
+                            .json_binary()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(
+                        ColumnDef::new(Coupons::CollectionIds)
+                            .json_binary()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(ColumnDef::new(Coupons::StartsAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Coupons::EndsAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Coupons::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(Coupons::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Coupons::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── coupon_redemptions ──────────────────────────────────────
+        // Uso de um cupom por pedido; base para os limites de uso.
+        manager
+            .create_table(
+                Table::create()
+                    .table(CouponRedemptions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CouponRedemptions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemptions::CouponId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemptions::OrderId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemptions::CustomerId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemptions::DiscountAmount)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemptions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(CouponRedemptions::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_redemption_coupon")
+                            .from(CouponRedemptions::Table, CouponRedemptions::CouponId)
+                            .to(Coupons::Table, Coupons::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_redemption_order")
+                            .from(CouponRedemptions::Table, CouponRedemptions::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_redemption_customer")
+                            .from(CouponRedemptions::Table, CouponRedemptions::CustomerId)
+                            .to(Customers::Table, Customers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_redemption_coupon_customer")
+                    .table(CouponRedemptions::Table)
+                    .col(CouponRedemptions::CouponId)
+                    .col(CouponRedemptions::CustomerId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── carts: desconto aplicado ────────────────────────────────
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Carts::Table)
+                    .add_column(
+                        ColumnDef::new(Carts::Discount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Carts::Table)
+                    .add_column(ColumnDef::new(Carts::CouponCode).string_len(64))
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── orders: cupom usado no checkout ─────────────────────────
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Orders::Table)
+                    .add_column(ColumnDef::new(Orders::CouponCode).string_len(64))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Orders::Table)
+                    .drop_column(Orders::CouponCode)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Carts::Table)
+                    .drop_column(Carts::CouponCode)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Carts::Table)
+                    .drop_column(Carts::Discount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CouponRedemptions::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Coupons::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Coupons {
+    Table,
+    Id,
+    Pid,
+    Code,
+    Description,
+    DiscountType,
+    Value,
+    MinSubtotal,
+    UsageLimit,
+    UsageLimitPerCustomer,
+    UsageCount,
+    ProductIds,
+    CategoryIds,
+    CollectionIds,
+    StartsAt,
+    EndsAt,
+    Active,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum CouponRedemptions {
+    Table,
+    Id,
+    CouponId,
+    OrderId,
+    CustomerId,
+    DiscountAmount,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Carts {
+    Table,
+    Discount,
+    CouponCode,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+    CouponCode,
+}
+
+#[derive(Iden)]
+enum Customers {
+    Table,
+    Id,
+}
@@ -0,0 +1,143 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── shipping_rates ──────────────────────────────────────────
+        // Tarifas dos providers internos (retirada, fixo, grátis, tabela por CEP)
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShippingRates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ShippingRates::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingRates::Pid)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(ShippingRates::Name).string().not_null())
+                    // Tipo: 'pickup' | 'flat_rate' | 'free_shipping' | 'table_rate'
+                    .col(
+                        ColumnDef::new(ShippingRates::Kind)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    // Preço em centavos (ignorado em 'free_shipping')
+                    .col(
+                        ColumnDef::new(ShippingRates::Price)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    // Subtotal mínimo para a tarifa valer (obrigatório em 'free_shipping')
+                    .col(ColumnDef::new(ShippingRates::MinSubtotal).big_integer())
+                    // Faixa de CEP (8 dígitos, inclusiva); vazio = qualquer destino
+                    .col(ColumnDef::new(ShippingRates::PostalCodeStart).string_len(8))
+                    .col(ColumnDef::new(ShippingRates::PostalCodeEnd).string_len(8))
+                    // Faixa de peso total do envio (g)
+                    .col(ColumnDef::new(ShippingRates::MinWeightGrams).integer())
+                    .col(ColumnDef::new(ShippingRates::MaxWeightGrams).integer())
+                    .col(
+                        ColumnDef::new(ShippingRates::DeliveryDays)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    // Local de retirada ('pickup')
+                    .col(ColumnDef::new(ShippingRates::WarehouseId).integer())
+                    .col(ColumnDef::new(ShippingRates::Instructions).text())
+                    .col(
+                        ColumnDef::new(ShippingRates::SortOrder)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingRates::Active)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingRates::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ShippingRates::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_shipping_rate_warehouse")
+                            .from(ShippingRates::Table, ShippingRates::WarehouseId)
+                            .to(Warehouses::Table, Warehouses::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_shipping_rates_kind_active")
+                    .table(ShippingRates::Table)
+                    .col(ShippingRates::Kind)
+                    .col(ShippingRates::Active)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShippingRates::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum ShippingRates {
+    Table,
+    Id,
+    Pid,
+    Name,
+    Kind,
+    Price,
+    MinSubtotal,
+    PostalCodeStart,
+    PostalCodeEnd,
+    MinWeightGrams,
+    MaxWeightGrams,
+    DeliveryDays,
+    WarehouseId,
+    Instructions,
+    SortOrder,
+    Active,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Warehouses {
+    Table,
+    Id,
+}
@@ -0,0 +1,124 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── shipment_tracking_events ────────────────────────────────
+        // Histórico de rastreio recebido por webhook ou consulta periódica
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShipmentTrackingEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::ShippingId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::Status)
+                            .string_len(30)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::Description)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ShipmentTrackingEvents::Location).string())
+                    // Data/hora como devolvida pelo provider
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::OccurredAt)
+                            .string_len(40)
+                            .not_null(),
+                    )
+                    // Origem: 'webhook' | 'poll'
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::Source)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ShipmentTrackingEvents::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_tracking_event_shipping")
+                            .from(
+                                ShipmentTrackingEvents::Table,
+                                ShipmentTrackingEvents::ShippingId,
+                            )
+                            .to(OrderShippings::Table, OrderShippings::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // O mesmo evento pode chegar por webhook e pela consulta periódica
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_tracking_event_unique")
+                    .table(ShipmentTrackingEvents::Table)
+                    .col(ShipmentTrackingEvents::ShippingId)
+                    .col(ShipmentTrackingEvents::OccurredAt)
+                    .col(ShipmentTrackingEvents::Status)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ShipmentTrackingEvents::Table)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum ShipmentTrackingEvents {
+    Table,
+    Id,
+    ShippingId,
+    Status,
+    Description,
+    Location,
+    OccurredAt,
+    Source,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum OrderShippings {
+    Table,
+    Id,
+}
@@ -0,0 +1,127 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── payment_events ──────────────────────────────────────────
+        // Todo evento recebido do gateway, uma linha por ID de evento
+        manager
+            .create_table(
+                Table::create()
+                    .table(PaymentEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PaymentEvents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PaymentEvents::Provider)
+                            .string_len(30)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PaymentEvents::EventId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PaymentEvents::EventType)
+                            .string_len(60)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PaymentEvents::PaymentId).string())
+                    .col(ColumnDef::new(PaymentEvents::OrderId).integer())
+                    // Status do pagamento resultante (mesmos valores de orders.payment_status)
+                    .col(ColumnDef::new(PaymentEvents::PaymentStatus).string_len(20))
+                    // Data do evento no gateway (ordena o processamento)
+                    .col(ColumnDef::new(PaymentEvents::OccurredAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(PaymentEvents::Payload)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    // 'pending' | 'processed' | 'skipped' | 'failed'
+                    .col(
+                        ColumnDef::new(PaymentEvents::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(PaymentEvents::Error).text())
+                    .col(ColumnDef::new(PaymentEvents::ProcessedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(PaymentEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(PaymentEvents::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_payment_event_order")
+                            .from(PaymentEvents::Table, PaymentEvents::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_payment_events_order")
+                    .table(PaymentEvents::Table)
+                    .col(PaymentEvents::OrderId)
+                    .col(PaymentEvents::OccurredAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PaymentEvents::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum PaymentEvents {
+    Table,
+    Id,
+    Provider,
+    EventId,
+    EventType,
+    PaymentId,
+    OrderId,
+    PaymentStatus,
+    OccurredAt,
+    Payload,
+    Status,
+    Error,
+    ProcessedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── refunds ─────────────────────────────────────────────────
+        // Estornos (totais ou parciais) feitos no gateway
+        manager
+            .create_table(
+                Table::create()
+                    .table(Refunds::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Refunds::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Refunds::Pid).uuid().not_null().unique_key())
+                    .col(ColumnDef::new(Refunds::OrderId).integer().not_null())
+                    // Centavos
+                    .col(ColumnDef::new(Refunds::Amount).big_integer().not_null())
+                    .col(ColumnDef::new(Refunds::Reason).text())
+                    // 'pending' | 'done' | 'failed'
+                    .col(
+                        ColumnDef::new(Refunds::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(Refunds::Provider).string_len(30).not_null())
+                    .col(ColumnDef::new(Refunds::ProviderPaymentId).string())
+                    // Itens devolvidos ao estoque: [{ order_item_id, variant_id, quantity }]
+                    .col(
+                        ColumnDef::new(Refunds::Items)
+                            .json_binary()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .col(
+                        ColumnDef::new(Refunds::Restock)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Refunds::RawData)
+                            .json_binary()
+                            .not_null()
+                            .default("{}"),
+                    )
+                    .col(ColumnDef::new(Refunds::Error).text())
+                    .col(ColumnDef::new(Refunds::CreatedBy).integer())
+                    .col(ColumnDef::new(Refunds::RefundedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Refunds::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Refunds::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_refund_order")
+                            .from(Refunds::Table, Refunds::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_refund_created_by")
+                            .from(Refunds::Table, Refunds::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refunds_order")
+                    .table(Refunds::Table)
+                    .col(Refunds::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Refunds::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Refunds {
+    Table,
+    Id,
+    Pid,
+    OrderId,
+    Amount,
+    Reason,
+    Status,
+    Provider,
+    ProviderPaymentId,
+    Items,
+    Restock,
+    RawData,
+    Error,
+    CreatedBy,
+    RefundedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,130 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── order_status_history ────────────────────────────────────
+        // Uma linha por transição de status do pedido (quem, quando, por quê)
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderStatusHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::OrderId)
+                            .integer()
+                            .not_null(),
+                    )
+                    // 'status' | 'payment_status' | 'fulfillment_status'
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::Axis)
+                            .string_len(30)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::FromStatus)
+                            .string_len(30)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::ToStatus)
+                            .string_len(30)
+                            .not_null(),
+                    )
+                    // Usuário que fez a mudança; nulo nas mudanças automáticas
+                    .col(ColumnDef::new(OrderStatusHistory::ChangedBy).integer())
+                    // Origem: 'admin' | 'painel' | 'webhook' | 'refund' | 'worker' | ...
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::Source)
+                            .string_len(30)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderStatusHistory::Reason).text())
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OrderStatusHistory::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_status_history_order")
+                            .from(OrderStatusHistory::Table, OrderStatusHistory::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_status_history_user")
+                            .from(OrderStatusHistory::Table, OrderStatusHistory::ChangedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_status_history_order")
+                    .table(OrderStatusHistory::Table)
+                    .col(OrderStatusHistory::OrderId)
+                    .col(OrderStatusHistory::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderStatusHistory::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum OrderStatusHistory {
+    Table,
+    Id,
+    OrderId,
+    Axis,
+    FromStatus,
+    ToStatus,
+    ChangedBy,
+    Source,
+    Reason,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
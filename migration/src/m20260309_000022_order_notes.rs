@@ -0,0 +1,196 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── order_notes ─────────────────────────────────────────────
+        // Notas internas dos colaboradores no pedido (não visíveis ao cliente)
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderNotes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderNotes::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderNotes::Pid)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(OrderNotes::OrderId).integer().not_null())
+                    .col(ColumnDef::new(OrderNotes::AuthorId).integer())
+                    .col(ColumnDef::new(OrderNotes::Body).text().not_null())
+                    .col(
+                        ColumnDef::new(OrderNotes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OrderNotes::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_note_order")
+                            .from(OrderNotes::Table, OrderNotes::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_note_author")
+                            .from(OrderNotes::Table, OrderNotes::AuthorId)
+                            .to(StoreCollaborators::Table, StoreCollaborators::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_notes_order")
+                    .table(OrderNotes::Table)
+                    .col(OrderNotes::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── order_note_mentions ─────────────────────────────────────
+        // Colaboradores mencionados (@) em uma nota
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderNoteMentions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderNoteMentions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderNoteMentions::NoteId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderNoteMentions::CollaboratorId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OrderNoteMentions::ReadAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(OrderNoteMentions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OrderNoteMentions::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_note_mention_note")
+                            .from(OrderNoteMentions::Table, OrderNoteMentions::NoteId)
+                            .to(OrderNotes::Table, OrderNotes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_note_mention_collaborator")
+                            .from(OrderNoteMentions::Table, OrderNoteMentions::CollaboratorId)
+                            .to(StoreCollaborators::Table, StoreCollaborators::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_note_mentions_unique")
+                    .table(OrderNoteMentions::Table)
+                    .col(OrderNoteMentions::NoteId)
+                    .col(OrderNoteMentions::CollaboratorId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_note_mentions_collaborator")
+                    .table(OrderNoteMentions::Table)
+                    .col(OrderNoteMentions::CollaboratorId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderNoteMentions::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(OrderNotes::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum OrderNotes {
+    Table,
+    Id,
+    Pid,
+    OrderId,
+    AuthorId,
+    Body,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum OrderNoteMentions {
+    Table,
+    Id,
+    NoteId,
+    CollaboratorId,
+    ReadAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum StoreCollaborators {
+    Table,
+    Id,
+}
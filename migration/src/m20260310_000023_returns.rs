@@ -0,0 +1,329 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── returns ─────────────────────────────────────────────────
+        // Solicitações de devolução/troca (RMA)
+        manager
+            .create_table(
+                Table::create()
+                    .table(Returns::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Returns::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Returns::Pid).uuid().not_null().unique_key())
+                    .col(ColumnDef::new(Returns::OrderId).integer().not_null())
+                    .col(ColumnDef::new(Returns::CustomerId).integer().not_null())
+                    // 'requested' | 'approved' | 'rejected' | 'received' | 'completed'
+                    .col(
+                        ColumnDef::new(Returns::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("requested"),
+                    )
+                    // 'refund' | 'exchange'
+                    .col(
+                        ColumnDef::new(Returns::Resolution)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Returns::Reason).text().not_null())
+                    .col(ColumnDef::new(Returns::AdminNotes).text())
+                    .col(ColumnDef::new(Returns::RejectionReason).text())
+                    // Etiqueta de logística reversa
+                    .col(ColumnDef::new(Returns::Carrier).string_len(50))
+                    .col(ColumnDef::new(Returns::TrackingCode).string_len(100))
+                    .col(ColumnDef::new(Returns::LabelUrl).text())
+                    .col(
+                        ColumnDef::new(Returns::ProviderData)
+                            .json_binary()
+                            .not_null()
+                            .default("{}"),
+                    )
+                    // Depósito que recebeu os itens
+                    .col(ColumnDef::new(Returns::WarehouseId).integer())
+                    .col(ColumnDef::new(Returns::RefundId).integer())
+                    .col(ColumnDef::new(Returns::ExchangeOrderId).integer())
+                    .col(ColumnDef::new(Returns::ReviewedBy).integer())
+                    .col(ColumnDef::new(Returns::ReviewedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Returns::ReceivedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Returns::CompletedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Returns::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Returns::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_order")
+                            .from(Returns::Table, Returns::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_customer")
+                            .from(Returns::Table, Returns::CustomerId)
+                            .to(Customers::Table, Customers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_warehouse")
+                            .from(Returns::Table, Returns::WarehouseId)
+                            .to(Warehouses::Table, Warehouses::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_refund")
+                            .from(Returns::Table, Returns::RefundId)
+                            .to(Refunds::Table, Refunds::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_exchange_order")
+                            .from(Returns::Table, Returns::ExchangeOrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_reviewed_by")
+                            .from(Returns::Table, Returns::ReviewedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_returns_order")
+                    .table(Returns::Table)
+                    .col(Returns::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_returns_status")
+                    .table(Returns::Table)
+                    .col(Returns::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── return_items ────────────────────────────────────────────
+        // Itens do pedido devolvidos, com a inspeção no recebimento
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReturnItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ReturnItems::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ReturnItems::ReturnId).integer().not_null())
+                    .col(
+                        ColumnDef::new(ReturnItems::OrderItemId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ReturnItems::Quantity).integer().not_null())
+                    .col(ColumnDef::new(ReturnItems::Reason).text())
+                    // Variante a enviar na troca
+                    .col(ColumnDef::new(ReturnItems::ExchangeVariantId).integer())
+                    // Inspeção: 'sellable' | 'damaged'
+                    .col(ColumnDef::new(ReturnItems::Condition).string_len(20))
+                    .col(
+                        ColumnDef::new(ReturnItems::RestockedQuantity)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    // Linha de estoque que recebeu as unidades
+                    .col(ColumnDef::new(ReturnItems::StockId).integer())
+                    .col(
+                        ColumnDef::new(ReturnItems::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ReturnItems::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_item_return")
+                            .from(ReturnItems::Table, ReturnItems::ReturnId)
+                            .to(Returns::Table, Returns::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_item_order_item")
+                            .from(ReturnItems::Table, ReturnItems::OrderItemId)
+                            .to(OrderItems::Table, OrderItems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_item_exchange_variant")
+                            .from(ReturnItems::Table, ReturnItems::ExchangeVariantId)
+                            .to(ProductVariants::Table, ProductVariants::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_return_item_stock")
+                            .from(ReturnItems::Table, ReturnItems::StockId)
+                            .to(Stocks::Table, Stocks::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_return_items_return")
+                    .table(ReturnItems::Table)
+                    .col(ReturnItems::ReturnId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReturnItems::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Returns::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Returns {
+    Table,
+    Id,
+    Pid,
+    OrderId,
+    CustomerId,
+    Status,
+    Resolution,
+    Reason,
+    AdminNotes,
+    RejectionReason,
+    Carrier,
+    TrackingCode,
+    LabelUrl,
+    ProviderData,
+    WarehouseId,
+    RefundId,
+    ExchangeOrderId,
+    ReviewedBy,
+    ReviewedAt,
+    ReceivedAt,
+    CompletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum ReturnItems {
+    Table,
+    Id,
+    ReturnId,
+    OrderItemId,
+    Quantity,
+    Reason,
+    ExchangeVariantId,
+    Condition,
+    RestockedQuantity,
+    StockId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum OrderItems {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Customers {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Warehouses {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Refunds {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ProductVariants {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Stocks {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
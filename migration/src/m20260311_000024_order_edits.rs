@@ -0,0 +1,184 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── order_edits ─────────────────────────────────────────────
+        // Edições do pedido depois de criado (itens, endereço, frete) e o
+        // acerto da diferença de valor
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderEdits::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderEdits::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderEdits::Pid)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(OrderEdits::OrderId).integer().not_null())
+                    .col(ColumnDef::new(OrderEdits::EditedBy).integer())
+                    .col(ColumnDef::new(OrderEdits::Reason).text())
+                    // [{ action, order_item_pid, variant_id, from, to }]
+                    .col(
+                        ColumnDef::new(OrderEdits::Changes)
+                            .json_binary()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    // Centavos
+                    .col(
+                        ColumnDef::new(OrderEdits::PreviousTotal)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderEdits::NewTotal)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderEdits::Difference)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    // 'none' | 'charge' | 'refund' | 'reissue'
+                    .col(
+                        ColumnDef::new(OrderEdits::Settlement)
+                            .string_len(20)
+                            .not_null()
+                            .default("none"),
+                    )
+                    // 'none' | 'pending' | 'done' | 'failed'
+                    .col(
+                        ColumnDef::new(OrderEdits::SettlementStatus)
+                            .string_len(20)
+                            .not_null()
+                            .default("none"),
+                    )
+                    // Cobrança adicional no gateway
+                    .col(ColumnDef::new(OrderEdits::PaymentId).string())
+                    .col(ColumnDef::new(OrderEdits::RefundId).integer())
+                    .col(
+                        ColumnDef::new(OrderEdits::SettlementData)
+                            .json_binary()
+                            .not_null()
+                            .default("{}"),
+                    )
+                    .col(
+                        ColumnDef::new(OrderEdits::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OrderEdits::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_edit_order")
+                            .from(OrderEdits::Table, OrderEdits::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_edit_edited_by")
+                            .from(OrderEdits::Table, OrderEdits::EditedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_edit_refund")
+                            .from(OrderEdits::Table, OrderEdits::RefundId)
+                            .to(Refunds::Table, Refunds::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_edits_order")
+                    .table(OrderEdits::Table)
+                    .col(OrderEdits::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_edits_payment")
+                    .table(OrderEdits::Table)
+                    .col(OrderEdits::PaymentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderEdits::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum OrderEdits {
+    Table,
+    Id,
+    Pid,
+    OrderId,
+    EditedBy,
+    Reason,
+    Changes,
+    PreviousTotal,
+    NewTotal,
+    Difference,
+    Settlement,
+    SettlementStatus,
+    PaymentId,
+    RefundId,
+    SettlementData,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Refunds {
+    Table,
+    Id,
+}
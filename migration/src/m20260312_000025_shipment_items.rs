@@ -0,0 +1,176 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::DatabaseBackend;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── order_shippings.warehouse_id ────────────────────────────
+        // Depósito de onde o volume foi despachado
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderShippings::Table)
+                    .add_column(ColumnDef::new(OrderShippings::WarehouseId).integer())
+                    .to_owned(),
+            )
+            .await?;
+        // SQLite não adiciona chave estrangeira em tabela existente
+        if manager.get_database_backend() != DatabaseBackend::Sqlite {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(OrderShippings::Table)
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name("fk_order_shipping_warehouse")
+                                .from_tbl(OrderShippings::Table)
+                                .from_col(OrderShippings::WarehouseId)
+                                .to_tbl(Warehouses::Table)
+                                .to_col(Warehouses::Id)
+                                .on_delete(ForeignKeyAction::SetNull),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        // ── order_shipping_items ────────────────────────────────────
+        // Itens do pedido (e quantidades) contidos em cada envio
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderShippingItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrderShippingItems::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderShippingItems::ShippingId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderShippingItems::OrderItemId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderShippingItems::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(OrderShippingItems::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(OrderShippingItems::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_shipping_item_shipping")
+                            .from(OrderShippingItems::Table, OrderShippingItems::ShippingId)
+                            .to(OrderShippings::Table, OrderShippings::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_order_shipping_item_order_item")
+                            .from(OrderShippingItems::Table, OrderShippingItems::OrderItemId)
+                            .to(OrderItems::Table, OrderItems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_shipping_items_shipping")
+                    .table(OrderShippingItems::Table)
+                    .col(OrderShippingItems::ShippingId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_shipping_items_order_item")
+                    .table(OrderShippingItems::Table)
+                    .col(OrderShippingItems::OrderItemId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderShippingItems::Table).to_owned())
+            .await?;
+        if manager.get_database_backend() != DatabaseBackend::Sqlite {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(OrderShippings::Table)
+                        .drop_foreign_key(Alias::new("fk_order_shipping_warehouse"))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(OrderShippings::Table)
+                    .drop_column(OrderShippings::WarehouseId)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum OrderShippingItems {
+    Table,
+    Id,
+    ShippingId,
+    OrderItemId,
+    Quantity,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum OrderShippings {
+    Table,
+    Id,
+    WarehouseId,
+}
+
+#[derive(Iden)]
+enum OrderItems {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Warehouses {
+    Table,
+    Id,
+}
@@ -0,0 +1,203 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── stock_movements ─────────────────────────────────────────
+        // Razão de estoque (só inserção): todo movimento de `stocks`
+        // passa por aqui e `stocks.quantity` é o saldo acumulado
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockMovements::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StockMovements::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(StockMovements::StockId).integer().not_null())
+                    // 'receipt' | 'sale' | 'reservation' | 'release' | 'adjustment' | 'transfer' | 'return'
+                    .col(
+                        ColumnDef::new(StockMovements::Kind)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    // Variação de `quantity` e de `reserved` (com sinal)
+                    .col(
+                        ColumnDef::new(StockMovements::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockMovements::Reserved)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    // Saldo da linha depois do movimento
+                    .col(
+                        ColumnDef::new(StockMovements::BalanceAfter)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockMovements::ReservedAfter)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    // Código do motivo (ex.: 'inventory_count', 'damaged')
+                    .col(ColumnDef::new(StockMovements::Reason).string_len(50))
+                    .col(ColumnDef::new(StockMovements::Notes).text())
+                    .col(ColumnDef::new(StockMovements::OrderId).integer())
+                    // Documento de origem (ex.: PID da devolução)
+                    .col(ColumnDef::new(StockMovements::Reference).string_len(100))
+                    .col(ColumnDef::new(StockMovements::CreatedBy).integer())
+                    .col(
+                        ColumnDef::new(StockMovements::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(StockMovements::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_movement_stock")
+                            .from(StockMovements::Table, StockMovements::StockId)
+                            .to(Stocks::Table, Stocks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_movement_order")
+                            .from(StockMovements::Table, StockMovements::OrderId)
+                            .to(Orders::Table, Orders::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_movement_created_by")
+                            .from(StockMovements::Table, StockMovements::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stock_movements_stock")
+                    .table(StockMovements::Table)
+                    .col(StockMovements::StockId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stock_movements_order")
+                    .table(StockMovements::Table)
+                    .col(StockMovements::OrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Saldo de abertura: o razão começa batendo com as linhas existentes
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(StockMovements::Table)
+                    .columns([
+                        StockMovements::StockId,
+                        StockMovements::Kind,
+                        StockMovements::Quantity,
+                        StockMovements::Reserved,
+                        StockMovements::BalanceAfter,
+                        StockMovements::ReservedAfter,
+                        StockMovements::Reason,
+                    ])
+                    .select_from(
+                        Query::select()
+                            .column(Stocks::Id)
+                            .expr(Expr::val("adjustment"))
+                            .column(Stocks::Quantity)
+                            .column(Stocks::Reserved)
+                            .column(Stocks::Quantity)
+                            .column(Stocks::Reserved)
+                            .expr(Expr::val("opening_balance"))
+                            .from(Stocks::Table)
+                            .cond_where(
+                                Cond::any()
+                                    .add(Expr::col(Stocks::Quantity).ne(0))
+                                    .add(Expr::col(Stocks::Reserved).ne(0)),
+                            )
+                            .to_owned(),
+                    )
+                    .map_err(|e| DbErr::Custom(e.to_string()))?
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StockMovements::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum StockMovements {
+    Table,
+    Id,
+    StockId,
+    Kind,
+    Quantity,
+    Reserved,
+    BalanceAfter,
+    ReservedAfter,
+    Reason,
+    Notes,
+    OrderId,
+    Reference,
+    CreatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Stocks {
+    Table,
+    Id,
+    Quantity,
+    Reserved,
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,294 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── stock_transfers ─────────────────────────────────────────
+        // Transferências de itens entre depósitos
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockTransfers::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StockTransfers::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransfers::Pid)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransfers::SourceWarehouseId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransfers::DestinationWarehouseId)
+                            .integer()
+                            .not_null(),
+                    )
+                    // 'pending' | 'shipped' | 'received' | 'canceled'
+                    .col(
+                        ColumnDef::new(StockTransfers::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    // Recebido com quantidade diferente da enviada
+                    .col(
+                        ColumnDef::new(StockTransfers::Discrepancy)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(StockTransfers::Notes).text())
+                    .col(ColumnDef::new(StockTransfers::CreatedBy).integer())
+                    .col(ColumnDef::new(StockTransfers::ShippedBy).integer())
+                    .col(ColumnDef::new(StockTransfers::ReceivedBy).integer())
+                    .col(ColumnDef::new(StockTransfers::ShippedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(StockTransfers::ReceivedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(StockTransfers::CanceledAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(StockTransfers::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransfers::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_source")
+                            .from(StockTransfers::Table, StockTransfers::SourceWarehouseId)
+                            .to(Warehouses::Table, Warehouses::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_destination")
+                            .from(
+                                StockTransfers::Table,
+                                StockTransfers::DestinationWarehouseId,
+                            )
+                            .to(Warehouses::Table, Warehouses::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_created_by")
+                            .from(StockTransfers::Table, StockTransfers::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_shipped_by")
+                            .from(StockTransfers::Table, StockTransfers::ShippedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_received_by")
+                            .from(StockTransfers::Table, StockTransfers::ReceivedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stock_transfers_status")
+                    .table(StockTransfers::Table)
+                    .col(StockTransfers::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── stock_transfer_items ────────────────────────────────────
+        // Itens transferidos: quantidade pedida, enviada e recebida
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockTransferItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StockTransferItems::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransferItems::TransferId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransferItems::ItemId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransferItems::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransferItems::ShippedQuantity)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(StockTransferItems::ReceivedQuantity).integer())
+                    // Motivo da diferença entre enviado e recebido
+                    .col(ColumnDef::new(StockTransferItems::DiscrepancyReason).text())
+                    // Linhas de estoque movimentadas na origem e no destino
+                    .col(ColumnDef::new(StockTransferItems::SourceStockId).integer())
+                    .col(ColumnDef::new(StockTransferItems::DestinationStockId).integer())
+                    .col(
+                        ColumnDef::new(StockTransferItems::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(StockTransferItems::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_item_transfer")
+                            .from(StockTransferItems::Table, StockTransferItems::TransferId)
+                            .to(StockTransfers::Table, StockTransfers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_item_item")
+                            .from(StockTransferItems::Table, StockTransferItems::ItemId)
+                            .to(Items::Table, Items::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_item_source_stock")
+                            .from(StockTransferItems::Table, StockTransferItems::SourceStockId)
+                            .to(Stocks::Table, Stocks::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_transfer_item_destination_stock")
+                            .from(
+                                StockTransferItems::Table,
+                                StockTransferItems::DestinationStockId,
+                            )
+                            .to(Stocks::Table, Stocks::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stock_transfer_items_transfer")
+                    .table(StockTransferItems::Table)
+                    .col(StockTransferItems::TransferId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StockTransferItems::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(StockTransfers::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum StockTransfers {
+    Table,
+    Id,
+    Pid,
+    SourceWarehouseId,
+    DestinationWarehouseId,
+    Status,
+    Discrepancy,
+    Notes,
+    CreatedBy,
+    ShippedBy,
+    ReceivedBy,
+    ShippedAt,
+    ReceivedAt,
+    CanceledAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum StockTransferItems {
+    Table,
+    Id,
+    TransferId,
+    ItemId,
+    Quantity,
+    ShippedQuantity,
+    ReceivedQuantity,
+    DiscrepancyReason,
+    SourceStockId,
+    DestinationStockId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Warehouses {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Items {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Stocks {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
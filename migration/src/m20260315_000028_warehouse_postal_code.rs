@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // CEP do depósito: origem do frete dos envios que saem dele
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouses::Table)
+                    .add_column(ColumnDef::new(Warehouses::PostalCode).string_len(9))
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouses::Table)
+                    .drop_column(Warehouses::PostalCode)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Warehouses {
+    Table,
+    PostalCode,
+}
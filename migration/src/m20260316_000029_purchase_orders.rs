@@ -0,0 +1,481 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ── suppliers ───────────────────────────────────────────────
+        manager
+            .create_table(
+                Table::create()
+                    .table(Suppliers::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Suppliers::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Suppliers::Pid)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Suppliers::Name).string().not_null())
+                    // CNPJ/CPF
+                    .col(ColumnDef::new(Suppliers::Document).string_len(20))
+                    .col(ColumnDef::new(Suppliers::Email).string())
+                    .col(ColumnDef::new(Suppliers::Phone).string_len(30))
+                    .col(ColumnDef::new(Suppliers::Notes).text())
+                    .col(ColumnDef::new(Suppliers::DeletedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Suppliers::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Suppliers::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── purchase_orders ─────────────────────────────────────────
+        // Pedidos de compra a um fornecedor, recebidos em um depósito
+        manager
+            .create_table(
+                Table::create()
+                    .table(PurchaseOrders::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PurchaseOrders::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrders::Pid)
+                            .uuid()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrders::SupplierId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrders::WarehouseId)
+                            .integer()
+                            .not_null(),
+                    )
+                    // 'open' | 'partially_received' | 'received' | 'canceled'
+                    .col(
+                        ColumnDef::new(PurchaseOrders::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("open"),
+                    )
+                    // Número do pedido/nota fiscal no fornecedor
+                    .col(ColumnDef::new(PurchaseOrders::SupplierReference).string_len(100))
+                    // Frete e demais despesas (impostos, seguro), em centavos,
+                    // rateados no custo dos itens
+                    .col(
+                        ColumnDef::new(PurchaseOrders::FreightCost)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrders::OtherCosts)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(PurchaseOrders::ExpectedAt).date())
+                    .col(ColumnDef::new(PurchaseOrders::Notes).text())
+                    .col(ColumnDef::new(PurchaseOrders::CreatedBy).integer())
+                    .col(ColumnDef::new(PurchaseOrders::ReceivedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(PurchaseOrders::CanceledAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(PurchaseOrders::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrders::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_supplier")
+                            .from(PurchaseOrders::Table, PurchaseOrders::SupplierId)
+                            .to(Suppliers::Table, Suppliers::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_warehouse")
+                            .from(PurchaseOrders::Table, PurchaseOrders::WarehouseId)
+                            .to(Warehouses::Table, Warehouses::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_created_by")
+                            .from(PurchaseOrders::Table, PurchaseOrders::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_purchase_orders_status")
+                    .table(PurchaseOrders::Table)
+                    .col(PurchaseOrders::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_purchase_orders_supplier")
+                    .table(PurchaseOrders::Table)
+                    .col(PurchaseOrders::SupplierId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── purchase_order_items ────────────────────────────────────
+        // Variantes compradas: quantidade, custo unitário e custo com rateio
+        manager
+            .create_table(
+                Table::create()
+                    .table(PurchaseOrderItems::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::PurchaseOrderId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::VariantId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::ReceivedQuantity)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::UnitCost)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::LandedUnitCost)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderItems::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_item_order")
+                            .from(
+                                PurchaseOrderItems::Table,
+                                PurchaseOrderItems::PurchaseOrderId,
+                            )
+                            .to(PurchaseOrders::Table, PurchaseOrders::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_item_variant")
+                            .from(PurchaseOrderItems::Table, PurchaseOrderItems::VariantId)
+                            .to(ProductVariants::Table, ProductVariants::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_purchase_order_items_order")
+                    .table(PurchaseOrderItems::Table)
+                    .col(PurchaseOrderItems::PurchaseOrderId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── purchase_order_receipts ─────────────────────────────────
+        // Cada conferência de um item do pedido vira um lote (`items`)
+        manager
+            .create_table(
+                Table::create()
+                    .table(PurchaseOrderReceipts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PurchaseOrderReceipts::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderReceipts::PurchaseOrderItemId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderReceipts::ItemId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PurchaseOrderReceipts::StockId).integer())
+                    .col(
+                        ColumnDef::new(PurchaseOrderReceipts::Quantity)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderReceipts::LandedUnitCost)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PurchaseOrderReceipts::ReceivedBy).integer())
+                    .col(
+                        ColumnDef::new(PurchaseOrderReceipts::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(PurchaseOrderReceipts::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_receipt_item")
+                            .from(
+                                PurchaseOrderReceipts::Table,
+                                PurchaseOrderReceipts::PurchaseOrderItemId,
+                            )
+                            .to(PurchaseOrderItems::Table, PurchaseOrderItems::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_receipt_batch")
+                            .from(PurchaseOrderReceipts::Table, PurchaseOrderReceipts::ItemId)
+                            .to(Items::Table, Items::Id)
+                            .on_delete(ForeignKeyAction::Restrict),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_receipt_stock")
+                            .from(PurchaseOrderReceipts::Table, PurchaseOrderReceipts::StockId)
+                            .to(Stocks::Table, Stocks::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_purchase_order_receipt_received_by")
+                            .from(
+                                PurchaseOrderReceipts::Table,
+                                PurchaseOrderReceipts::ReceivedBy,
+                            )
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_purchase_order_receipts_item")
+                    .table(PurchaseOrderReceipts::Table)
+                    .col(PurchaseOrderReceipts::PurchaseOrderItemId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // ── items.landed_cost ───────────────────────────────────────
+        // Custo unitário do lote com frete e despesas rateados (centavos)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Items::Table)
+                    .add_column(ColumnDef::new(Items::LandedCost).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Items::Table)
+                    .drop_column(Items::LandedCost)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PurchaseOrderReceipts::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PurchaseOrderItems::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PurchaseOrders::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Suppliers::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Suppliers {
+    Table,
+    Id,
+    Pid,
+    Name,
+    Document,
+    Email,
+    Phone,
+    Notes,
+    DeletedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum PurchaseOrders {
+    Table,
+    Id,
+    Pid,
+    SupplierId,
+    WarehouseId,
+    Status,
+    SupplierReference,
+    FreightCost,
+    OtherCosts,
+    ExpectedAt,
+    Notes,
+    CreatedBy,
+    ReceivedAt,
+    CanceledAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum PurchaseOrderItems {
+    Table,
+    Id,
+    PurchaseOrderId,
+    VariantId,
+    Quantity,
+    ReceivedQuantity,
+    UnitCost,
+    LandedUnitCost,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum PurchaseOrderReceipts {
+    Table,
+    Id,
+    PurchaseOrderItemId,
+    ItemId,
+    StockId,
+    Quantity,
+    LandedUnitCost,
+    ReceivedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Warehouses {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ProductVariants {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Items {
+    Table,
+    Id,
+    LandedCost,
+}
+
+#[derive(Iden)]
+enum Stocks {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DatabaseBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Vencimento da cobrança em coluna, para o worker de expiração
+        // filtrar no banco em vez de varrer todos os pedidos não pagos
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Orders::Table)
+                    .add_column(ColumnDef::new(Orders::PaymentDueDate).date())
+                    .to_owned(),
+            )
+            .await?;
+        // Última tentativa do worker: pedidos cujo cancelamento no gateway
+        // falha vão para o fim da fila
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Orders::Table)
+                    .add_column(
+                        ColumnDef::new(Orders::PaymentExpiryCheckedAt).timestamp_with_time_zone(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_orders_payment_due_date")
+                    .table(Orders::Table)
+                    .col(Orders::PaymentDueDate)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Preenche a partir de `payment_data.due_date` das cobranças existentes
+        let backfill = match manager.get_database_backend() {
+            DatabaseBackend::Postgres => {
+                "UPDATE orders SET payment_due_date = (payment_data->>'due_date')::date \
+                 WHERE payment_data->>'due_date' ~ '^\\d{4}-\\d{2}-\\d{2}$'"
+            }
+            _ => {
+                "UPDATE orders SET payment_due_date = date(json_extract(payment_data, '$.due_date')) \
+                 WHERE json_extract(payment_data, '$.due_date') IS NOT NULL"
+            }
+        };
+        manager
+            .get_connection()
+            .execute_unprepared(backfill)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_orders_payment_due_date")
+                    .table(Orders::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Orders::Table)
+                    .drop_column(Orders::PaymentExpiryCheckedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Orders::Table)
+                    .drop_column(Orders::PaymentDueDate)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Orders {
+    Table,
+    PaymentDueDate,
+    PaymentExpiryCheckedAt,
+}
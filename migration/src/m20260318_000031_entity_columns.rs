@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Tabelas de estoque criadas sem as colunas de data que os models leem
+const TIMESTAMPED: [&str; 3] = ["warehouses", "items", "stocks"];
+/// Das tabelas acima, as que também têm exclusão lógica
+const SOFT_DELETED: [&str; 2] = ["warehouses", "items"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Bancos em que as colunas já foram criadas à mão ficam como estão
+        for table in TIMESTAMPED {
+            for column in ["created_at", "updated_at"] {
+                if !manager.has_column(table, column).await? {
+                    manager
+                        .alter_table(
+                            Table::alter()
+                                .table(Alias::new(table))
+                                .add_column(
+                                    ColumnDef::new(Alias::new(column))
+                                        .timestamp_with_time_zone()
+                                        .not_null()
+                                        .default(Expr::current_timestamp()),
+                                )
+                                .to_owned(),
+                        )
+                        .await?;
+                }
+            }
+        }
+        for table in SOFT_DELETED {
+            if !manager.has_column(table, "deleted_at").await? {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Alias::new(table))
+                            .add_column(
+                                ColumnDef::new(Alias::new("deleted_at")).timestamp_with_time_zone(),
+                            )
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        // `AddressLine1` vira `address_line1`, mas o model lê `address_line_1`
+        for (old, new) in [
+            ("address_line1", "address_line_1"),
+            ("address_line2", "address_line_2"),
+        ] {
+            if manager.has_column("addresses", old).await? {
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(Addresses::Table)
+                            .rename_column(Alias::new(old), Alias::new(new))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // `up` só completa o que faltava; sem saber o que ele criou, desfazer
+        // apagaria colunas que já existiam com dados
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Addresses {
+    Table,
+}
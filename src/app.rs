@@ -24,7 +24,9 @@ use crate::controllers::{painel, painel_api};
 use crate::{
     controllers, initializers, tasks, workers::abandoned_cart::AbandonedCartWorker,
     workers::analytics_flush::AnalyticsFlushWorker, workers::downloader::DownloadWorker,
-    workers::lead_scoring::LeadScoringWorker,
+    workers::lead_scoring::LeadScoringWorker, workers::payment_expiry::PaymentExpiryWorker,
+    workers::shipment_tracking::ShipmentTrackingWorker,
+    workers::stock_reservations::StockReservationExpiryWorker,
 }; // import store collaborator panel
 
 pub struct App;
@@ -71,17 +73,26 @@ impl Hooks for App {
             .add_route(controllers::categories::admin_routes())
             .add_route(controllers::products::admin_routes())
             .add_route(controllers::orders::admin_routes())
+            .add_route(controllers::order_edits::admin_routes())
+            .add_route(controllers::refunds::admin_routes())
+            .add_route(controllers::returns::admin_routes())
             .add_route(controllers::customers::admin_routes())
+            .add_route(controllers::coupons::admin_routes())
+            .add_route(controllers::shipping_rates::admin_routes())
             .add_route(controllers::products::routes())
             .add_route(controllers::categories::routes())
             .add_route(controllers::warehouses::routes())
             .add_route(controllers::items::routes())
             .add_route(controllers::stocks::routes())
+            .add_route(controllers::stock_transfers::routes())
+            .add_route(controllers::purchase_orders::routes())
             .add_route(controllers::carts::routes())
             .add_route(controllers::orders::routes())
+            .add_route(controllers::returns::routes())
             .add_route(controllers::customers::routes())
             .add_route(controllers::collections::routes())
             .add_route(controllers::payments::routes())
+            .add_route(controllers::shipping_webhooks::routes())
             .add_route(painel::routes())
             .add_route(painel_api::routes())
     }
@@ -91,11 +102,17 @@ impl Hooks for App {
         queue.register(AnalyticsFlushWorker::build(ctx)).await?;
         queue.register(AbandonedCartWorker::build(ctx)).await?;
         queue.register(LeadScoringWorker::build(ctx)).await?;
+        queue.register(StockReservationExpiryWorker::build(ctx)).await?;
+        queue.register(ShipmentTrackingWorker::build(ctx)).await?;
+        queue.register(PaymentExpiryWorker::build(ctx)).await?;
         Ok(())
     }
 
     #[allow(unused_variables)]
     fn register_tasks(tasks: &mut Tasks) {
+        tasks.register(tasks::expire_stock_reservations::ExpireStockReservations);
+        tasks.register(tasks::poll_shipment_tracking::PollShipmentTracking);
+        tasks.register(tasks::expire_payments::ExpirePayments);
         // tasks-inject (do not remove)
     }
     async fn truncate(ctx: &AppContext) -> Result<()> {
@@ -116,6 +133,7 @@ impl Hooks for App {
                     name: "Default".to_string(),
                     latitude: 0.0,
                     longitude: 0.0,
+                    postal_code: None,
                 },
             )
             .await?;
@@ -78,13 +78,13 @@ pub async fn stats(State(ctx): State<AppContext>) -> Result<Response> {
         .await?;
 
     let cancelled_orders = orders::Entity::find()
-        .filter(orders::Column::Status.eq("cancelled"))
+        .filter(orders::Column::Status.eq("canceled"))
         .count(&ctx.db)
         .await?;
 
     // ── Receita total (todos pedidos não cancelados) ────────────────────────
     let all_orders = orders::Entity::find()
-        .filter(orders::Column::Status.ne("cancelled"))
+        .filter(orders::Column::Status.ne("canceled"))
         .all(&ctx.db)
         .await?;
 
@@ -96,7 +96,7 @@ pub async fn stats(State(ctx): State<AppContext>) -> Result<Response> {
 
     let recent_all_orders = orders::Entity::find()
         .filter(orders::Column::CreatedAt.gte(thirty_days_ago_dt))
-        .filter(orders::Column::Status.ne("cancelled"))
+        .filter(orders::Column::Status.ne("canceled"))
         .all(&ctx.db)
         .await?;
 
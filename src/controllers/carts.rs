@@ -8,10 +8,9 @@ use crate::{
         entities::{CartItemResponse, CartResponse},
         response::ApiResponse,
     },
-    models::{
-        carts::{AddToCartParams, Model as CartModel},
-        product_variants::Model as VariantModel,
-    },
+    models::carts::{AddToCartParams, Model as CartModel},
+    services::routing,
+    shipping::{self, quote},
 };
 
 #[derive(Debug, Deserialize)]
@@ -19,6 +18,53 @@ pub struct CartQuery {
     pub session_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ApplyCouponParams {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShippingOptionsParams {
+    /// CEP de destino
+    pub postal_code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SelectShippingOptionParams {
+    pub postal_code: String,
+    pub provider: String,
+    pub service_code: String,
+}
+
+/// Monta os parâmetros de frete do carrinho e a chave de cache da cotação.
+/// A origem é o depósito mais próximo do destino com saldo para o carrinho
+/// (ou o CEP da loja). Carrinho vazio ou sem CEP de origem vêm como
+/// `ModelError::Message`.
+async fn cart_freight(
+    ctx: &AppContext,
+    cart: &crate::models::_entities::carts::Model,
+    postal_code: &str,
+) -> ModelResult<(shipping::FreightParams, String)> {
+    let lines: Vec<(i32, i32)> = CartModel::get_items(&ctx.db, cart.id)
+        .await?
+        .into_iter()
+        .map(|i| (i.variant_id, i.quantity))
+        .collect();
+    let warehouse = routing::fulfilling_warehouse(&ctx.db, Some(postal_code), &lines).await?;
+    let origin = routing::origin_postal_code(warehouse.as_ref())
+        .map_err(|e| ModelError::Message(e.to_string()))?;
+    let parcels = CartModel::parcel_items(&ctx.db, cart.id).await?;
+    if parcels.is_empty() {
+        return Err(ModelError::msg("Carrinho está vazio"));
+    }
+    // Empacota com o limite do provider mais restritivo: a mesma cotação vale para todos
+    let max_weight = shipping::max_package_weight(&shipping::configured_providers());
+    let params = shipping::FreightParams::from_items(&origin, postal_code, &parcels, max_weight)
+        .map_err(|e| ModelError::Message(e.to_string()))?;
+    let key = quote::cache_key(&cart.pid, &params);
+    Ok((params, key))
+}
+
 /// POST /api/v1/carts - Cria ou retorna carrinho pela session
 #[debug_handler]
 async fn get_or_create(
@@ -60,16 +106,27 @@ async fn add_item(
     Path(pid): Path<Uuid>,
     Json(params): Json<AddToCartParams>,
 ) -> Result<Response> {
-    let cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
+    let mut cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
+    if let Some(region) = params.region.as_deref() {
+        if cart.region().as_deref() != Some(region) {
+            cart = match CartModel::set_region(&ctx.db, cart, region).await {
+                Ok(cart) => cart,
+                Err(ModelError::Message(msg)) => {
+                    return format::json(ApiResponse::<()>::error("PRICE_NOT_FOUND", &msg))
+                }
+                Err(e) => return Err(e.into()),
+            };
+        }
+    }
 
-    // Busca preço da variante
-    let price =
-        VariantModel::get_active_price(&ctx.db, params.variant_id, &cart.currency, params.quantity)
-            .await
-            .map(|p| p.amount)
-            .unwrap_or(0);
+    match CartModel::add_item(&ctx.db, &cart, params.variant_id, params.quantity).await {
+        Ok(_) => {}
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("PRICE_NOT_FOUND", &msg))
+        }
+        Err(e) => return Err(e.into()),
+    }
 
-    CartModel::add_item(&ctx.db, cart.id, params.variant_id, params.quantity, price).await?;
     let cart = CartModel::recalculate_totals(&ctx.db, cart.id).await?;
 
     let items = CartModel::get_items(&ctx.db, cart.id).await?;
@@ -90,9 +147,15 @@ async fn update_item(
     let cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
 
     if params.quantity <= 0 {
-        CartModel::remove_item(&ctx.db, item_id).await?;
+        CartModel::remove_item(&ctx.db, &cart, item_id).await?;
     } else {
-        CartModel::update_item_quantity(&ctx.db, item_id, params.quantity).await?;
+        match CartModel::update_item_quantity(&ctx.db, &cart, item_id, params.quantity).await {
+            Ok(_) => {}
+            Err(ModelError::Message(msg)) => {
+                return format::json(ApiResponse::<()>::error("PRICE_NOT_FOUND", &msg))
+            }
+            Err(e) => return Err(e.into()),
+        }
     }
 
     let cart = CartModel::recalculate_totals(&ctx.db, cart.id).await?;
@@ -111,7 +174,7 @@ async fn remove_item(
     Path((pid, item_id)): Path<(Uuid, i32)>,
 ) -> Result<Response> {
     let cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
-    CartModel::remove_item(&ctx.db, item_id).await?;
+    CartModel::remove_item(&ctx.db, &cart, item_id).await?;
     let cart = CartModel::recalculate_totals(&ctx.db, cart.id).await?;
 
     let items = CartModel::get_items(&ctx.db, cart.id).await?;
@@ -122,6 +185,103 @@ async fn remove_item(
     format::json(ApiResponse::success(response))
 }
 
+/// POST /api/v1/carts/:pid/coupon - Aplica cupom ao carrinho
+#[debug_handler]
+async fn apply_coupon(
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ApplyCouponParams>,
+) -> Result<Response> {
+    let cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
+    let cart = match CartModel::apply_coupon(&ctx.db, cart.id, &params.code).await {
+        Ok(cart) => cart,
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("INVALID_COUPON", &msg))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let items = CartModel::get_items(&ctx.db, cart.id).await?;
+    let item_responses: Vec<CartItemResponse> =
+        items.into_iter().map(CartItemResponse::from).collect();
+    let mut response = CartResponse::from(cart);
+    response.items = Some(item_responses);
+    format::json(ApiResponse::success(response))
+}
+
+/// DELETE /api/v1/carts/:pid/coupon - Remove cupom do carrinho
+#[debug_handler]
+async fn remove_coupon(State(ctx): State<AppContext>, Path(pid): Path<Uuid>) -> Result<Response> {
+    let cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
+    let cart = CartModel::remove_coupon(&ctx.db, cart.id).await?;
+
+    let items = CartModel::get_items(&ctx.db, cart.id).await?;
+    let item_responses: Vec<CartItemResponse> =
+        items.into_iter().map(CartItemResponse::from).collect();
+    let mut response = CartResponse::from(cart);
+    response.items = Some(item_responses);
+    format::json(ApiResponse::success(response))
+}
+
+/// POST /api/v1/carts/:pid/shipping-options - Cota frete em todas as transportadoras
+#[debug_handler]
+async fn shipping_options(
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ShippingOptionsParams>,
+) -> Result<Response> {
+    let cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
+    let (freight, key) = match cart_freight(&ctx, &cart, &params.postal_code).await {
+        Ok(found) => found,
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("SHIPPING_UNAVAILABLE", &msg))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let options = quote::quote_cached(&ctx.db, &key, &freight).await;
+    format::json(ApiResponse::success(
+        serde_json::json!({ "options": options }),
+    ))
+}
+
+/// POST /api/v1/carts/:pid/shipping-options/select - Escolhe a opção de frete
+#[debug_handler]
+async fn select_shipping_option(
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<SelectShippingOptionParams>,
+) -> Result<Response> {
+    let cart = CartModel::find_by_pid(&ctx.db, &pid).await?;
+    let (freight, key) = match cart_freight(&ctx, &cart, &params.postal_code).await {
+        Ok(found) => found,
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("SHIPPING_UNAVAILABLE", &msg))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // Só aceita opções efetivamente cotadas para este carrinho/destino
+    let options = quote::quote_cached(&ctx.db, &key, &freight).await;
+    let Some(option) = options
+        .iter()
+        .find(|o| o.provider == params.provider && o.service_code == params.service_code)
+    else {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_SHIPPING_OPTION",
+            "Opção de frete não disponível para este carrinho",
+        ));
+    };
+
+    let cart = CartModel::select_shipping_option(&ctx.db, cart, option).await?;
+    let items = CartModel::get_items(&ctx.db, cart.id).await?;
+    let item_responses: Vec<CartItemResponse> =
+        items.into_iter().map(CartItemResponse::from).collect();
+    let mut response = CartResponse::from(cart);
+    response.items = Some(item_responses);
+    format::json(ApiResponse::success(response))
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("/api/v1/carts")
@@ -130,4 +290,11 @@ pub fn routes() -> Routes {
         .add("/{pid}/items", post(add_item))
         .add("/{pid}/items/{item_id}", put(update_item))
         .add("/{pid}/items/{item_id}", delete(remove_item))
+        .add("/{pid}/coupon", post(apply_coupon))
+        .add("/{pid}/coupon", delete(remove_coupon))
+        .add("/{pid}/shipping-options", post(shipping_options))
+        .add(
+            "/{pid}/shipping-options/select",
+            post(select_shipping_option),
+        )
 }
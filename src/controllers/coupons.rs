@@ -0,0 +1,112 @@
+use loco_rs::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    dto::{entities::CouponResponse, response::ApiResponse},
+    models::{
+        _entities::users,
+        coupons::{CreateCouponParams, Model as CouponModel, UpdateCouponParams},
+    },
+};
+
+/// GET /api/admin/coupons - Lista cupons
+#[debug_handler]
+async fn list(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let coupons = CouponModel::list_all(&ctx.db).await?;
+    let response: Vec<CouponResponse> = coupons.into_iter().map(CouponResponse::from).collect();
+    format::json(ApiResponse::success(response))
+}
+
+/// POST /api/admin/coupons - Cria cupom
+#[debug_handler]
+async fn create(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<CreateCouponParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    if CouponModel::find_by_code(&ctx.db, &params.code)
+        .await
+        .is_ok()
+    {
+        return format::json(ApiResponse::<()>::error(
+            "DUPLICATE_CODE",
+            "Já existe um cupom com esse código",
+        ));
+    }
+
+    match CouponModel::create_coupon(&ctx.db, &params).await {
+        Ok(coupon) => format::json(ApiResponse::success(CouponResponse::from(coupon))),
+        Err(ModelError::Message(msg)) => {
+            format::json(ApiResponse::<()>::error("INVALID_COUPON", &msg))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// GET /api/admin/coupons/:pid
+#[debug_handler]
+async fn get_one(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let coupon = CouponModel::find_by_pid(&ctx.db, &pid).await?;
+    format::json(ApiResponse::success(CouponResponse::from(coupon)))
+}
+
+/// PUT /api/admin/coupons/:pid
+#[debug_handler]
+async fn update(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateCouponParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let coupon = CouponModel::find_by_pid(&ctx.db, &pid).await?;
+    match CouponModel::update_coupon(&ctx.db, coupon, &params).await {
+        Ok(updated) => format::json(ApiResponse::success(CouponResponse::from(updated))),
+        Err(ModelError::Message(msg)) => {
+            format::json(ApiResponse::<()>::error("INVALID_COUPON", &msg))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// DELETE /api/admin/coupons/:pid - Desativa o cupom (mantém o histórico de uso)
+#[debug_handler]
+async fn remove(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let coupon = CouponModel::find_by_pid(&ctx.db, &pid).await?;
+    let mut active: crate::models::_entities::coupons::ActiveModel = coupon.into();
+    active.active = ActiveValue::set(false);
+    active.update(&ctx.db).await?;
+    format::json(ApiResponse::<()>::success(()))
+}
+
+pub fn admin_routes() -> Routes {
+    Routes::new()
+        .prefix("/api/admin")
+        .add("/coupons", get(list))
+        .add("/coupons", post(create))
+        .add("/coupons/{pid}", get(get_one))
+        .add("/coupons/{pid}", put(update))
+        .add("/coupons/{pid}", delete(remove))
+}
@@ -5,15 +5,23 @@ pub mod carts;
 pub mod warehouses;
 pub mod items;
 pub mod stocks;
+pub mod stock_transfers;
+pub mod purchase_orders;
 pub mod categories;
 pub mod collections;
+pub mod coupons;
 pub mod customers;
+pub mod order_edits;
 pub mod orders;
 pub mod painel;
 pub mod painel_api;
 pub mod payments;
 pub mod products;
+pub mod refunds;
+pub mod returns;
 pub mod setup;
+pub mod shipping_rates;
+pub mod shipping_webhooks;
 
 // dashboard controller for main admin page
 pub mod dashboard;
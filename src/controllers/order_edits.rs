@@ -0,0 +1,330 @@
+//! Edição de pedidos pelo admin
+//!
+//! `POST /api/admin/orders/{pid}/edits` altera itens, endereço e frete de um
+//! pedido ainda não separado e acerta a diferença com o gateway: cobrança
+//! adicional, estorno parcial ou cancelamento da cobrança em aberto (o
+//! cliente paga o novo total pelo fluxo normal de pagamento).
+//!
+//! Acerto recusado pelo gateway fica `failed`; `POST
+//! /api/admin/orders/{pid}/edits/{edit_pid}/settle` tenta de novo.
+
+use loco_rs::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    controllers::payments::payment_customer,
+    dto::{
+        entities::{OrderEditResponse, OrderItemResponse, OrderResponse},
+        response::ApiResponse,
+    },
+    models::{
+        _entities::{customers, users},
+        order_edits::{EditOrderParams, Model as OrderEditModel},
+        orders::Model as OrderModel,
+        refunds::CreateRefundParams,
+    },
+    payments::{self, ChargeParams},
+    services::refunds::{issue_refund, RefundOutcome},
+};
+
+/// GET /api/admin/orders/:pid/edits
+#[debug_handler]
+async fn list(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    let edits = OrderEditModel::list_for_order(&ctx.db, order.id).await?;
+    let response: Vec<OrderEditResponse> = edits.into_iter().map(OrderEditResponse::from).collect();
+    format::json(ApiResponse::success(response))
+}
+
+/// POST /api/admin/orders/:pid/edits
+#[debug_handler]
+async fn create(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<EditOrderParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let method = params.billing_type.as_deref().unwrap_or("PIX");
+    if let Some(response) = invalid_billing_type(method) {
+        return response;
+    }
+
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    let (edit, order) = match OrderEditModel::apply(&ctx.db, order.id, &params, Some(user.id)).await
+    {
+        Ok(applied) => applied,
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("INVALID_EDIT", &msg));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let edit = settle_difference(&ctx, &order, edit, method, user.id).await?;
+    edit_response(&ctx, order, edit).await
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RetrySettlementParams {
+    /// Forma da cobrança adicional; sem ela repete a da tentativa anterior
+    pub billing_type: Option<String>,
+}
+
+/// POST /api/admin/orders/:pid/edits/:edit_pid/settle - Refaz o acerto falho
+#[debug_handler]
+async fn retry_settlement(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path((pid, edit_pid)): Path<(Uuid, Uuid)>,
+    Json(params): Json<RetrySettlementParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    let edit = OrderEditModel::find_for_order(&ctx.db, order.id, &edit_pid).await?;
+    let method = params
+        .billing_type
+        .or_else(|| {
+            edit.settlement_data
+                .get("method")
+                .and_then(|m| m.as_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| "PIX".to_string());
+    if let Some(response) = invalid_billing_type(&method) {
+        return response;
+    }
+
+    let edit = match OrderEditModel::begin_retry(&ctx.db, &order, edit).await {
+        Ok(edit) => edit,
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("INVALID_EDIT", &msg));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let edit = settle_difference(&ctx, &order, edit, &method, user.id).await?;
+    edit_response(&ctx, order, edit).await
+}
+
+/// Erro para forma de cobrança adicional não suportada
+fn invalid_billing_type(method: &str) -> Option<Result<Response>> {
+    (!matches!(method, "PIX" | "BOLETO")).then(|| {
+        format::json(ApiResponse::<()>::error(
+            "INVALID_EDIT",
+            "A cobrança adicional aceita PIX ou BOLETO",
+        ))
+    })
+}
+
+/// Acerta a diferença da edição com o gateway
+async fn settle_difference(
+    ctx: &AppContext,
+    order: &OrderModel,
+    edit: OrderEditModel,
+    method: &str,
+    user_id: i32,
+) -> Result<OrderEditModel> {
+    match edit.settlement.as_str() {
+        "charge" => charge_difference(ctx, order, edit, method).await,
+        "refund" => refund_difference(ctx, order, edit, user_id).await,
+        "reissue" => cancel_open_charge(ctx, order, edit).await,
+        _ => Ok(edit),
+    }
+}
+
+async fn edit_response(
+    ctx: &AppContext,
+    order: OrderModel,
+    edit: OrderEditModel,
+) -> Result<Response> {
+    let items = OrderModel::get_items(&ctx.db, order.id).await?;
+    let mut order_response = OrderResponse::from(order);
+    order_response.items = Some(items.into_iter().map(OrderItemResponse::from).collect());
+    format::json(ApiResponse::success(serde_json::json!({
+        "edit": OrderEditResponse::from(edit),
+        "order": order_response,
+    })))
+}
+
+/// Provider de pagamento do pedido (o da cobrança original ou o padrão)
+fn order_provider(order: &OrderModel) -> Option<Box<dyn payments::PaymentProvider>> {
+    let slug = order
+        .payment_provider
+        .clone()
+        .unwrap_or_else(payments::default_provider);
+    payments::provider_for(&slug)
+}
+
+/// Pedido pago que ficou mais caro: cobrança adicional no gateway. O acerto
+/// fica `pending` até o webhook confirmar o pagamento.
+async fn charge_difference(
+    ctx: &AppContext,
+    order: &OrderModel,
+    edit: OrderEditModel,
+    method: &str,
+) -> Result<OrderEditModel> {
+    let Some(provider) = order_provider(order) else {
+        let data = serde_json::json!({
+            "method": method,
+            "error": "Gateway sem integração configurada",
+        });
+        return Ok(OrderEditModel::settle(&ctx.db, edit, "failed", None, None, data).await?);
+    };
+    let customer = customers::Entity::find_by_id(order.customer_id)
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let charge_params = ChargeParams {
+        order_reference: order.pid.to_string(),
+        order_number: order.order_number.clone(),
+        amount_cents: edit.difference,
+        method: method.to_string(),
+        description: edit
+            .reason
+            .clone()
+            .unwrap_or_else(|| format!("Diferença da alteração do pedido {}", order.order_number)),
+        due_date: None,
+        customer: payment_customer(provider.name(), &customer),
+        card: None,
+    };
+    let edit = match provider.create_charge(charge_params).await {
+        Ok(charge) => {
+            let data = serde_json::json!({
+                "provider": provider.name(),
+                "method": method,
+                "invoice_url": charge.invoice_url,
+                "bank_slip_url": charge.bank_slip_url,
+                "pix_qr_code": charge.pix_qr_code,
+                "due_date": charge.due_date,
+            });
+            OrderEditModel::settle(
+                &ctx.db,
+                edit,
+                "pending",
+                Some(charge.provider_id),
+                None,
+                data,
+            )
+            .await?
+        }
+        Err(e) => {
+            tracing::warn!(order = %order.pid, error = %e, "Falha na cobrança adicional da edição");
+            let data = serde_json::json!({ "method": method, "error": e.to_string() });
+            OrderEditModel::settle(&ctx.db, edit, "failed", None, None, data).await?
+        }
+    };
+    Ok(edit)
+}
+
+/// Pedido pago que ficou mais barato: estorno parcial da diferença. O
+/// estoque já foi devolvido pela edição. O valor já estornado em tentativas
+/// anteriores fica em `settlement_data.refunded` e não é estornado de novo.
+async fn refund_difference(
+    ctx: &AppContext,
+    order: &OrderModel,
+    edit: OrderEditModel,
+    user_id: i32,
+) -> Result<OrderEditModel> {
+    let previous = edit
+        .settlement_data
+        .get("refunded")
+        .and_then(serde_json::Value::as_i64)
+        .unwrap_or(0);
+    let mut refund_pids: Vec<serde_json::Value> = edit
+        .settlement_data
+        .get("refund_pids")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let refund_params = CreateRefundParams {
+        amount: Some(-edit.difference - previous),
+        reason: edit.reason.clone().or_else(|| {
+            Some(format!(
+                "Diferença da alteração do pedido {}",
+                order.order_number
+            ))
+        }),
+        restock: false,
+        items: vec![],
+    };
+    let edit = match issue_refund(&ctx.db, order, &refund_params, user_id).await? {
+        RefundOutcome::Done(refunds, _) => {
+            let done: Vec<_> = refunds.iter().filter(|r| r.status == "done").collect();
+            let refunded = previous + done.iter().map(|r| r.amount).sum::<i64>();
+            refund_pids.extend(done.iter().map(|r| serde_json::json!(r.pid)));
+            let mut data = serde_json::json!({
+                "refunded": refunded,
+                "refund_pids": refund_pids,
+            });
+            let status = if refunded >= -edit.difference {
+                "done"
+            } else {
+                data["error"] = serde_json::json!(refunds
+                    .iter()
+                    .find_map(|r| r.error.clone())
+                    .unwrap_or_default());
+                "failed"
+            };
+            let refund_id = edit.refund_id.or(done.first().map(|r| r.id));
+            OrderEditModel::settle(&ctx.db, edit, status, None, refund_id, data).await?
+        }
+        RefundOutcome::Rejected(code, msg) => {
+            let data = serde_json::json!({
+                "code": code,
+                "error": msg,
+                "refunded": previous,
+                "refund_pids": refund_pids,
+            });
+            OrderEditModel::settle(&ctx.db, edit, "failed", None, None, data).await?
+        }
+    };
+    Ok(edit)
+}
+
+/// Pedido em aberto com cobrança emitida no valor antigo: a cobrança é
+/// cancelada no gateway e o cliente gera uma nova para o novo total
+async fn cancel_open_charge(
+    ctx: &AppContext,
+    order: &OrderModel,
+    edit: OrderEditModel,
+) -> Result<OrderEditModel> {
+    let (Some(provider), Some(payment_id)) = (order_provider(order), edit.payment_id.clone())
+    else {
+        return Ok(edit);
+    };
+    let edit = match provider.cancel(&payment_id).await {
+        Ok(raw) => {
+            let data = serde_json::json!({ "canceled": raw });
+            OrderEditModel::settle(&ctx.db, edit, "done", None, None, data).await?
+        }
+        Err(e) => {
+            tracing::warn!(order = %order.pid, error = %e, "Falha ao cancelar cobrança substituída");
+            let data = serde_json::json!({ "error": e.to_string() });
+            OrderEditModel::settle(&ctx.db, edit, "failed", None, None, data).await?
+        }
+    };
+    Ok(edit)
+}
+
+pub fn admin_routes() -> Routes {
+    Routes::new()
+        .prefix("/api/admin")
+        .add("/orders/{pid}/edits", get(list))
+        .add("/orders/{pid}/edits", post(create))
+        .add(
+            "/orders/{pid}/edits/{edit_pid}/settle",
+            post(retry_settlement),
+        )
+}
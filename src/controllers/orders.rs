@@ -6,13 +6,15 @@ use uuid::Uuid;
 
 use crate::{
     dto::{
-        entities::{OrderItemResponse, OrderResponse},
+        entities::{OrderItemResponse, OrderResponse, OrderStatusHistoryResponse},
         response::ApiResponse,
     },
     models::{
         _entities::users,
         carts::Model as CartModel,
-        orders::{CreateOrderFromCartParams, Model as OrderModel},
+        order_status::{FulfillmentStatus, OrderStatus, PaymentStatus, TransitionError},
+        order_status_history::{Model as StatusHistoryModel, StatusChange},
+        orders::{CreateOrderFromCartParams, Model as OrderModel, StatusUpdate},
     },
 };
 
@@ -29,6 +31,25 @@ pub struct UpdateOrderStatusParams {
     pub payment_status: Option<String>,
     pub fulfillment_status: Option<String>,
     pub payment_data: Option<serde_json::Value>,
+    /// Motivo da mudança, gravado no histórico
+    pub reason: Option<String>,
+}
+
+/// Resposta de erro para uma mudança de status recusada. Transição inválida
+/// vem como `INVALID_TRANSITION`, com os próximos status permitidos em
+/// `details.allowed`.
+pub fn status_error(err: ModelError) -> Result<Response> {
+    if let Some(rejected) = TransitionError::from_model_error(&err) {
+        return format::json(ApiResponse::<()>::error_with_details(
+            "INVALID_TRANSITION",
+            &rejected.to_string(),
+            serde_json::json!(rejected),
+        ));
+    }
+    match err {
+        ModelError::Message(msg) => format::json(ApiResponse::<()>::error("INVALID_STATUS", &msg)),
+        e => Err(e.into()),
+    }
 }
 
 /// POST /api/v1/orders - Cria pedido a partir de carrinho
@@ -54,7 +75,23 @@ async fn create(
         ));
     }
 
-    let order = OrderModel::create_from_cart(&ctx.db, &cart, &cart_items, &params).await?;
+    // Reavalia o cupom e os totais antes de gerar o pedido
+    let had_coupon = cart.coupon_code.is_some();
+    let cart = CartModel::recalculate_totals(&ctx.db, cart.id).await?;
+    if had_coupon && cart.coupon_code.is_none() {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_COUPON",
+            "O cupom aplicado não é mais válido e foi removido do carrinho",
+        ));
+    }
+
+    let order = match OrderModel::create_from_cart(&ctx.db, &cart, &cart_items, &params).await {
+        Ok(order) => order,
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("CHECKOUT_REJECTED", &msg));
+        }
+        Err(e) => return Err(e.into()),
+    };
 
     // Marca carrinho como completed
     CartModel::complete(&ctx.db, cart.id).await?;
@@ -109,30 +146,49 @@ async fn update_status(
     Path(pid): Path<Uuid>,
     Json(params): Json<UpdateOrderStatusParams>,
 ) -> Result<Response> {
-    let _user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
     let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
 
-    let mut updated = order;
-    if let Some(status) = params.status {
-        updated = OrderModel::update_status(&ctx.db, updated.id, &status).await?;
-    }
-    if let Some(payment_status) = params.payment_status {
-        updated = OrderModel::update_payment_status(
-            &ctx.db,
-            updated.id,
-            &payment_status,
-            params.payment_data,
-        )
-        .await?;
-    }
-    if let Some(fulfillment_status) = params.fulfillment_status {
-        updated =
-            OrderModel::update_fulfillment_status(&ctx.db, updated.id, &fulfillment_status).await?;
-    }
+    let update = StatusUpdate {
+        status: params.status,
+        payment_status: params.payment_status,
+        fulfillment_status: params.fulfillment_status,
+        payment_data: params.payment_data,
+    };
+    let change = StatusChange::by_user(user.id, "admin", params.reason);
+    let updated = match OrderModel::apply_status_update(&ctx.db, order.id, &update, &change).await {
+        Ok(updated) => updated,
+        Err(e) => return status_error(e),
+    };
 
     format::json(ApiResponse::success(OrderResponse::from(updated)))
 }
 
+/// GET /api/v1/orders/:pid/history - Histórico de status e próximas transições
+#[debug_handler]
+async fn history(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let _user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    let entries = StatusHistoryModel::list_for_order(&ctx.db, order.id).await?;
+
+    let history: Vec<OrderStatusHistoryResponse> = entries
+        .into_iter()
+        .map(OrderStatusHistoryResponse::from)
+        .collect();
+    format::json(ApiResponse::success(serde_json::json!({
+        "history": history,
+        "allowed": {
+            "status": OrderStatus::allowed_for(&order),
+            "payment_status": PaymentStatus::allowed_for(&order),
+            "fulfillment_status": FulfillmentStatus::allowed_for(&order),
+        },
+    })))
+}
+
 /// Stats para painel admin
 #[derive(Debug, Serialize)]
 pub struct OrderStats {
@@ -172,7 +228,7 @@ async fn admin_stats(State(ctx): State<AppContext>) -> Result<Response> {
         .count(&ctx.db)
         .await?;
     let cancelled = orders::Entity::find()
-        .filter(orders::Column::Status.eq("cancelled"))
+        .filter(orders::Column::Status.eq("canceled"))
         .count(&ctx.db)
         .await?;
 
@@ -206,6 +262,7 @@ pub fn routes() -> Routes {
         .add("/", get(list))
         .add("/{pid}", get(get_one))
         .add("/{pid}/status", put(update_status))
+        .add("/{pid}/history", get(history))
 }
 
 pub fn admin_routes() -> Routes {
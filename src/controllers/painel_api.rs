@@ -6,14 +6,19 @@ use uuid::Uuid;
 
 use crate::{
     dto::response::ApiResponse,
+    mailers::orders::OrderMailer,
     models::{
         _entities::users,
+        order_notes::{CreateNoteParams, Model as NoteModel},
         order_shippings::{
             CreateShippingParams, Model as ShippingModel, UpdateShippingStatusParams,
         },
-        orders::Model as OrderModel,
+        order_status_history::StatusChange,
+        order_timeline,
+        orders::{Model as OrderModel, StatusUpdate},
         store_collaborators::Model as CollaboratorModel,
     },
+    services::shipments::{create_shipment, CreatedShipment, ShipmentOutcome},
     shipping,
 };
 
@@ -32,6 +37,15 @@ pub struct UpdateOrderStatusParams {
     pub status: Option<String>,
     pub payment_status: Option<String>,
     pub fulfillment_status: Option<String>,
+    /// Motivo da mudança, gravado no histórico
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MentionListQuery {
+    /// Só as ainda não lidas
+    pub unread: Option<bool>,
+    pub limit: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -183,6 +197,25 @@ pub async fn get_order(
     let order = OrderModel::find_by_pid(&ctx.db, &order_pid).await?;
     let items = OrderModel::get_items(&ctx.db, order.id).await?;
     let shipping = ShippingModel::find_by_order(&ctx.db, order.id).await?;
+    let shipped = ShippingModel::shipped_quantities(&ctx.db, order.id).await?;
+    let mut shipments = vec![];
+    for s in ShippingModel::list_for_order(&ctx.db, order.id).await? {
+        let lines = s.items(&ctx.db).await?;
+        shipments.push(serde_json::json!({
+            "pid": s.pid.to_string(),
+            "carrier": s.carrier,
+            "tracking_code": s.tracking_code,
+            "status": s.status,
+            "warehouse_id": s.warehouse_id,
+            "shipped_at": s.shipped_at.map(|t| t.to_string()),
+            "delivered_at": s.delivered_at.map(|t| t.to_string()),
+            "items": lines.into_iter().map(|(line, item)| serde_json::json!({
+                "order_item_pid": item.as_ref().map(|i| i.pid.to_string()),
+                "sku": item.map(|i| i.sku),
+                "quantity": line.quantity,
+            })).collect::<Vec<_>>(),
+        }));
+    }
 
     let shipping_address = if let Some(addr_id) = order.shipping_address_id {
         crate::models::_entities::addresses::Entity::find_by_id(addr_id)
@@ -225,9 +258,11 @@ pub async fn get_order(
             "title": i.title,
             "sku": i.sku,
             "quantity": i.quantity,
+            "shipped_quantity": shipped.get(&i.id).copied().unwrap_or(0),
             "unit_price": i.unit_price,
             "total": i.total,
         })).collect::<Vec<_>>(),
+        "shipments": shipments,
         "shipping": shipping.map(|s| serde_json::json!({
             "pid": s.pid.to_string(),
             "carrier": s.carrier,
@@ -252,20 +287,20 @@ pub async fn update_order_status(
     Path(order_pid): Path<Uuid>,
     Json(params): Json<UpdateOrderStatusParams>,
 ) -> Result<Response> {
-    let (_, _) = require_collab(&ctx.db, &auth.claims.pid, true).await?;
+    let (user, _) = require_collab(&ctx.db, &auth.claims.pid, true).await?;
 
     let order = OrderModel::find_by_pid(&ctx.db, &order_pid).await?;
-    let mut updated = order;
-
-    if let Some(ref status) = params.status {
-        updated = OrderModel::update_status(&ctx.db, updated.id, status).await?;
-    }
-    if let Some(ref ps) = params.payment_status {
-        updated = OrderModel::update_payment_status(&ctx.db, updated.id, ps, None).await?;
-    }
-    if let Some(ref fs) = params.fulfillment_status {
-        updated = OrderModel::update_fulfillment_status(&ctx.db, updated.id, fs).await?;
-    }
+    let update = StatusUpdate {
+        status: params.status,
+        payment_status: params.payment_status,
+        fulfillment_status: params.fulfillment_status,
+        payment_data: None,
+    };
+    let change = StatusChange::by_user(user.id, "painel", params.reason);
+    let updated = match OrderModel::apply_status_update(&ctx.db, order.id, &update, &change).await {
+        Ok(updated) => updated,
+        Err(e) => return crate::controllers::orders::status_error(e),
+    };
 
     format::json(ApiResponse::success(serde_json::json!({
         "pid": updated.pid.to_string(),
@@ -276,9 +311,122 @@ pub async fn update_order_status(
     })))
 }
 
+// ── Linha do tempo e notas ────────────────────────────────────────────────────
+
+/// GET /api/painel/pedidos/:order_pid/timeline
+#[debug_handler]
+pub async fn order_timeline(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(order_pid): Path<Uuid>,
+) -> Result<Response> {
+    let (_, _) = require_collab(&ctx.db, &auth.claims.pid, false).await?;
+
+    let order = OrderModel::find_by_pid(&ctx.db, &order_pid).await?;
+    let entries = order_timeline::for_order(&ctx.db, &order).await?;
+    format::json(ApiResponse::success(entries))
+}
+
+/// POST /api/painel/pedidos/:order_pid/notas
+///
+/// Nota interna; os colaboradores mencionados com `@` recebem um email.
+#[debug_handler]
+pub async fn add_order_note(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(order_pid): Path<Uuid>,
+    Json(params): Json<CreateNoteParams>,
+) -> Result<Response> {
+    let (user, collab) = require_collab(&ctx.db, &auth.claims.pid, false).await?;
+
+    let order = OrderModel::find_by_pid(&ctx.db, &order_pid).await?;
+    let (note, mentioned) = match NoteModel::create(&ctx.db, order.id, &collab, &params).await {
+        Ok(created) => created,
+        Err(ModelError::Message(msg)) => {
+            return format::json(ApiResponse::<()>::error("INVALID_NOTE", &msg));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    for c in &mentioned {
+        if let Err(e) =
+            OrderMailer::send_note_mention(&ctx, &c.user, &user.name, &order, &note).await
+        {
+            tracing::warn!(user_id = c.user.id, error = %e, "Falha ao avisar menção");
+        }
+    }
+
+    format::json(ApiResponse::success(serde_json::json!({
+        "pid": note.pid.to_string(),
+        "body": note.body,
+        "author": user.name,
+        "mentions": mentioned.iter().map(|c| serde_json::json!({
+            "user_id": c.user.id,
+            "name": c.user.name,
+            "handle": c.handle(),
+        })).collect::<Vec<_>>(),
+        "created_at": note.created_at.to_string(),
+    })))
+}
+
+/// GET /api/painel/mencoes?unread=true - Menções ao colaborador logado
+#[debug_handler]
+pub async fn list_mentions(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Query(query): Query<MentionListQuery>,
+) -> Result<Response> {
+    let (_, collab) = require_collab(&ctx.db, &auth.claims.pid, false).await?;
+
+    let mentions = NoteModel::mentions_for(
+        &ctx.db,
+        collab.id,
+        query.unread.unwrap_or(false),
+        query.limit.unwrap_or(50),
+    )
+    .await?;
+
+    let mut result = vec![];
+    for (mention, note) in mentions {
+        let order = crate::models::_entities::orders::Entity::find_by_id(note.order_id)
+            .one(&ctx.db)
+            .await?;
+        result.push(serde_json::json!({
+            "id": mention.id,
+            "read_at": mention.read_at.map(|t| t.to_string()),
+            "note_pid": note.pid.to_string(),
+            "body": note.body,
+            "order_pid": order.as_ref().map(|o| o.pid.to_string()),
+            "order_number": order.map(|o| o.order_number),
+            "created_at": note.created_at.to_string(),
+        }));
+    }
+    format::json(ApiResponse::success(result))
+}
+
+/// PUT /api/painel/mencoes/:id/lida
+#[debug_handler]
+pub async fn mark_mention_read(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(mention_id): Path<i32>,
+) -> Result<Response> {
+    let (_, collab) = require_collab(&ctx.db, &auth.claims.pid, false).await?;
+
+    let mention = NoteModel::mark_mention_read(&ctx.db, mention_id, collab.id).await?;
+    format::json(ApiResponse::success(serde_json::json!({
+        "id": mention.id,
+        "read_at": mention.read_at.map(|t| t.to_string()),
+    })))
+}
+
 // ── Envios ────────────────────────────────────────────────────────────────────
 
 /// POST /api/painel/pedidos/:order_pid/envio
+///
+/// Despacha um volume do pedido (ver [`create_shipment`]). Para carriers com
+/// integração (ex.: `melhor_envio`) a etiqueta é comprada no provider e o
+/// rastreio retornado é gravado no envio.
 #[debug_handler]
 pub async fn create_shipping(
     auth: auth::JWT,
@@ -286,21 +434,23 @@ pub async fn create_shipping(
     Path(order_pid): Path<Uuid>,
     Json(params): Json<CreateShippingParams>,
 ) -> Result<Response> {
-    let (_, _) = require_collab(&ctx.db, &auth.claims.pid, true).await?;
+    let (user, _) = require_collab(&ctx.db, &auth.claims.pid, true).await?;
     let order = OrderModel::find_by_pid(&ctx.db, &order_pid).await?;
 
-    let (provider_name, provider_data) =
-        if let Some(_provider) = shipping::provider_for(&params.carrier) {
-            (Some(params.carrier.as_str()), None)
-        } else {
-            (None, None)
-        };
-
-    let shipping =
-        ShippingModel::create(&ctx.db, order.id, &params, provider_name, provider_data).await?;
-
-    // Atualiza fulfillment_status do pedido para 'fulfilled'
-    OrderModel::update_fulfillment_status(&ctx.db, order.id, "fulfilled").await?;
+    let CreatedShipment {
+        shipping,
+        lines,
+        warehouse,
+        order,
+    } = match create_shipment(&ctx.db, &order, &params, user.id).await? {
+        ShipmentOutcome::Done(created) => created,
+        ShipmentOutcome::Rejected(code, msg, Some(details)) => {
+            return format::json(ApiResponse::<()>::error_with_details(code, &msg, details));
+        }
+        ShipmentOutcome::Rejected(code, msg, None) => {
+            return format::json(ApiResponse::<()>::error(code, &msg));
+        }
+    };
 
     format::json(ApiResponse::success(serde_json::json!({
         "pid": shipping.pid.to_string(),
@@ -308,8 +458,20 @@ pub async fn create_shipping(
         "service": shipping.service,
         "tracking_code": shipping.tracking_code,
         "tracking_url": shipping.tracking_url,
+        "label_url": shipping.provider_data.get("label_url"),
         "status": shipping.status,
         "estimated_delivery_at": shipping.estimated_delivery_at.map(|t| t.to_string()),
+        "warehouse": warehouse.map(|w| serde_json::json!({
+            "pid": w.pid.to_string(),
+            "name": w.name,
+            "postal_code": w.postal_code,
+        })),
+        "items": lines.iter().map(|(item, qty)| serde_json::json!({
+            "order_item_pid": item.pid.to_string(),
+            "sku": item.sku,
+            "quantity": qty,
+        })).collect::<Vec<_>>(),
+        "fulfillment_status": order.fulfillment_status,
     })))
 }
 
@@ -324,12 +486,9 @@ pub async fn update_shipping_status(
     let (_, _) = require_collab(&ctx.db, &auth.claims.pid, true).await?;
 
     let shipping = ShippingModel::find_by_pid(&ctx.db, &shipping_pid).await?;
+    // Envio entregue também marca o pedido como 'delivered'
     let updated = ShippingModel::update_status(&ctx.db, shipping.id, &params).await?;
 
-    if updated.status == "delivered" {
-        let _ = OrderModel::update_fulfillment_status(&ctx.db, updated.order_id, "delivered").await;
-    }
-
     format::json(ApiResponse::success(serde_json::json!({
         "pid": updated.pid.to_string(),
         "status": updated.status,
@@ -349,13 +508,12 @@ pub async fn calculate_freight(
 ) -> Result<Response> {
     let (_, _) = require_collab(&ctx.db, &auth.claims.pid, false).await?;
 
-    let carrier = "melhor_envio";
-    let Some(provider) = shipping::provider_for(carrier) else {
+    if shipping::available_providers(&ctx.db).await.is_empty() {
         return format::json(ApiResponse::success(serde_json::json!({
             "options": [],
-            "message": format!("Provider '{}' não configurado. Defina MELHOR_ENVIO_TOKEN.", carrier),
+            "message": "Nenhuma transportadora configurada. Defina MELHOR_ENVIO_TOKEN, as credenciais CORREIOS_* ou cadastre tarifas em /api/admin/shipping-rates.",
         })));
-    };
+    }
 
     let freight_params = shipping::FreightParams {
         origin_postal_code: params.origin_postal_code,
@@ -365,14 +523,13 @@ pub async fn calculate_freight(
         width_cm: params.width_cm,
         height_cm: params.height_cm,
         declared_value_cents: params.declared_value_cents,
+        packages: Vec::new(),
     };
 
-    match provider.calculate_freight(freight_params).await {
-        Ok(options) => format::json(ApiResponse::success(
-            serde_json::json!({ "options": options }),
-        )),
-        Err(e) => format::json(ApiResponse::<()>::error("FREIGHT_ERROR", &e.to_string())),
-    }
+    let options = shipping::quote::quote_all(&ctx.db, &freight_params).await;
+    format::json(ApiResponse::success(
+        serde_json::json!({ "options": options }),
+    ))
 }
 
 /// GET /api/painel/envios
@@ -415,6 +572,39 @@ pub async fn list_shippings(
     format::json(ApiResponse::paginated(data, cursor, has_more, count))
 }
 
+/// GET /api/painel/envios/:shipping_pid/rastreio
+#[debug_handler]
+pub async fn shipping_tracking(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(shipping_pid): Path<Uuid>,
+) -> Result<Response> {
+    let (_, _) = require_collab(&ctx.db, &auth.claims.pid, false).await?;
+
+    let shipping = ShippingModel::find_by_pid(&ctx.db, &shipping_pid).await?;
+    let events = ShippingModel::tracking_history(&ctx.db, shipping.id).await?;
+
+    let data: Vec<serde_json::Value> = events
+        .into_iter()
+        .map(|e| {
+            serde_json::json!({
+                "status": e.status,
+                "description": e.description,
+                "location": e.location,
+                "occurred_at": e.occurred_at,
+                "source": e.source,
+            })
+        })
+        .collect();
+
+    format::json(ApiResponse::success(serde_json::json!({
+        "pid": shipping.pid.to_string(),
+        "status": shipping.status,
+        "tracking_code": shipping.tracking_code,
+        "events": data,
+    })))
+}
+
 // ── Colaboradores ─────────────────────────────────────────────────────────────
 
 /// GET /api/painel/colaboradores
@@ -494,12 +684,27 @@ pub fn routes() -> Routes {
             "/api/painel/pedidos/{order_pid}/frete",
             post(calculate_freight),
         )
+        .add(
+            "/api/painel/pedidos/{order_pid}/timeline",
+            get(order_timeline),
+        )
+        .add(
+            "/api/painel/pedidos/{order_pid}/notas",
+            post(add_order_note),
+        )
+        // Menções
+        .add("/api/painel/mencoes", get(list_mentions))
+        .add("/api/painel/mencoes/{id}/lida", put(mark_mention_read))
         // Envios
         .add("/api/painel/envios", get(list_shippings))
         .add(
             "/api/painel/envios/{shipping_pid}/status",
             put(update_shipping_status),
         )
+        .add(
+            "/api/painel/envios/{shipping_pid}/rastreio",
+            get(shipping_tracking),
+        )
         // Colaboradores
         .add("/api/painel/colaboradores", get(list_collaborators))
         .add("/api/painel/colaboradores", post(add_collaborator))
@@ -1,4 +1,10 @@
-use axum::extract::Path;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, Path, Query},
+    http::HeaderMap,
+};
+use ipnetwork::IpNetwork;
 use loco_rs::prelude::*;
 use serde::Deserialize;
 use uuid::Uuid;
@@ -7,124 +13,325 @@ use crate::{
     dto::response::ApiResponse,
     models::{
         _entities::{carts, customers},
+        order_status::{OrderStatus, PaymentStatus},
+        order_status_history::StatusChange,
         orders::Model as OrderModel,
+        payment_events::{EventOutcome, Model as PaymentEventModel, NewPaymentEvent},
+    },
+    payments::{
+        self, installments::InstallmentTable, CardCharge, CardDetails, CardHolderInfo,
+        ChargeParams, PaymentCustomer, PaymentError, SavedCard,
     },
     services::{
         analytics::{AnalyticsEvent, AnalyticsService},
-        asaas::{AsaasClient, AsaasWebhookPayload},
+        asaas::AsaasClient,
     },
 };
 
 #[derive(Debug, Deserialize)]
-pub struct CreateAsaasPaymentParams {
+pub struct CreatePaymentParams {
+    /// 'PIX' | 'BOLETO' | 'CREDIT_CARD' (padrão PIX)
     pub billing_type: Option<String>,
     pub due_date: Option<String>,
     pub description: Option<String>,
+    /// Parcelas no cartão (padrão 1)
+    pub installments: Option<u32>,
+    /// Cartão novo (tokenizado no gateway)
+    pub credit_card: Option<CardDetails>,
+    pub credit_card_holder: Option<CardHolderInfo>,
+    /// Paga com o cartão salvo no cliente (compra com um clique)
+    #[serde(default)]
+    pub use_saved_card: bool,
+    /// Salva o cartão novo para as próximas compras (padrão true)
+    pub save_card: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstallmentsQuery {
+    pub amount_cents: i64,
+}
+
+/// Proxies reversos cujos `X-Forwarded-For` são aceitos: `TRUSTED_PROXIES`,
+/// IPs ou faixas CIDR separados por vírgula
+fn trusted_proxies() -> Vec<IpNetwork> {
+    crate::env::load();
+    std::env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|proxy| proxy.trim().parse().ok())
+        .collect()
 }
 
+/// IP do comprador. Os cabeçalhos de proxy só valem quando a conexão vem de
+/// um proxy confiável; sem isso qualquer cliente informaria o IP que quisesse.
+/// Atrás de proxies encadeados vale o último endereço não confiável da lista.
+fn remote_ip(peer: IpAddr, headers: &HeaderMap) -> String {
+    let proxies = trusted_proxies();
+    let trusted = |ip: &IpAddr| proxies.iter().any(|proxy| proxy.contains(*ip));
+    if !trusted(&peer) {
+        return peer.to_string();
+    }
+
+    let forwarded: Vec<IpAddr> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|ip| ip.trim().parse().ok())
+        .collect();
+    forwarded
+        .iter()
+        .rev()
+        .find(|ip| !trusted(ip))
+        .or(forwarded.first())
+        .copied()
+        .or_else(|| {
+            headers
+                .get("x-real-ip")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok())
+        })
+        .unwrap_or(peer)
+        .to_string()
+}
+
+/// Pagador a partir do cliente, com o ID já cadastrado no gateway
+/// (`metadata.{provider}_customer_id`), quando houver
+pub fn payment_customer(provider: &str, customer: &customers::Model) -> PaymentCustomer {
+    PaymentCustomer {
+        name: format!("{} {}", customer.first_name, customer.last_name)
+            .trim()
+            .to_string(),
+        email: customer.email.clone(),
+        phone: customer.phone.clone(),
+        external_reference: customer.pid.to_string(),
+        provider_customer_id: customer
+            .metadata
+            .get(format!("{}_customer_id", provider))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+    }
+}
+
+/// Resposta de erro do gateway no envelope da API
+fn provider_error(e: &PaymentError) -> Result<Response> {
+    format::json(ApiResponse::<()>::error(
+        "PAYMENT_PROVIDER_ERROR",
+        &e.to_string(),
+    ))
+}
+
+/// POST /api/v1/orders/:order_pid/payments/:provider - Cria a cobrança no gateway
 #[debug_handler]
 async fn create_payment(
     auth: auth::JWT,
     State(ctx): State<AppContext>,
-    Path(order_pid): Path<Uuid>,
-    Json(params): Json<CreateAsaasPaymentParams>,
+    Path((order_pid, provider_slug)): Path<(Uuid, String)>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(params): Json<CreatePaymentParams>,
 ) -> Result<Response> {
-    let _user =
+    let user =
         crate::models::_entities::users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
     let order = OrderModel::find_by_pid(&ctx.db, &order_pid).await?;
 
+    // Pedido cancelado ou já pago não recebe nova cobrança
+    if order.status == OrderStatus::Canceled.as_str()
+        || !PaymentStatus::allowed_for(&order).contains(&PaymentStatus::Paid)
+    {
+        return format::json(ApiResponse::<()>::error(
+            "ORDER_NOT_PAYABLE",
+            &format!(
+                "Pedido com status '{}' e pagamento '{}' não aceita cobrança",
+                order.status, order.payment_status
+            ),
+        ));
+    }
+
+    let Some(provider) = payments::provider_for(&provider_slug) else {
+        return format::json(ApiResponse::<()>::error(
+            "UNSUPPORTED_PROVIDER",
+            &format!("Gateway sem integração configurada: {}", provider_slug),
+        ));
+    };
+
     let customer = customers::Entity::find_by_id(order.customer_id)
         .one(&ctx.db)
         .await?
         .ok_or(loco_rs::Error::NotFound)?;
+    // Só o próprio cliente (ou admin) cobra o pedido — inclusive no cartão salvo
+    if !user.is_admin() && customer.user_id != Some(user.id) {
+        return Err(Error::Unauthorized("Pedido de outro cliente".into()));
+    }
 
-    let client = AsaasClient::from_env()?;
+    // Reaproveita o cliente já cadastrado no gateway (ID em metadata)
+    let customer_key = format!("{}_customer_id", provider.name());
+    let payer = payment_customer(provider.name(), &customer);
+    let provider_customer_id = payer.provider_customer_id.clone();
 
-    // Garante que temos um customer no Asaas e persiste o ID em metadata
-    let mut metadata = customer.metadata.clone();
-    let asaas_customer_id = if let Some(existing) = metadata
-        .get("asaas_customer_id")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
-    {
-        existing
-    } else {
-        let full_name = format!("{} {}", customer.first_name, customer.last_name)
-            .trim()
-            .to_string();
-        let created = client
-            .create_customer(
-                &full_name,
-                &customer.email,
-                customer.phone.as_deref(),
-                Some(customer.pid.to_string()),
-            )
-            .await?;
-        metadata["asaas_customer_id"] = serde_json::json!(created.id.clone());
-
-        let mut active: customers::ActiveModel = customer.clone().into();
-        active.metadata = ActiveValue::set(metadata.clone());
-        let _ = active.update(&ctx.db).await?;
-        created.id
+    let method = params.billing_type.as_deref().unwrap_or("PIX").to_string();
+
+    // Cartão: parcelamento pela tabela de juros e token salvo ou cartão novo
+    let card_key = format!("{}_card", provider.name());
+    let mut installment = None;
+    let mut card = None;
+    if method == "CREDIT_CARD" {
+        let installments = params.installments.unwrap_or(1);
+        let Some(option) = InstallmentTable::from_env().option_for(order.total, installments)
+        else {
+            return format::json(ApiResponse::<()>::error(
+                "INVALID_INSTALLMENTS",
+                &format!(
+                    "Parcelamento em {}x indisponível para este valor",
+                    installments
+                ),
+            ));
+        };
+        let token = if params.use_saved_card {
+            let saved = customer
+                .metadata
+                .get(&card_key)
+                .and_then(|v| serde_json::from_value::<SavedCard>(v.clone()).ok());
+            let Some(saved) = saved else {
+                return format::json(ApiResponse::<()>::error(
+                    "NO_SAVED_CARD",
+                    "Cliente sem cartão salvo",
+                ));
+            };
+            Some(saved.token)
+        } else {
+            None
+        };
+        card = Some(CardCharge {
+            installments,
+            installment_cents: option.installment_cents,
+            token,
+            card: params.credit_card.clone(),
+            holder: params.credit_card_holder.clone(),
+            remote_ip: remote_ip(peer.ip(), &headers),
+        });
+        installment = Some(option);
+    }
+
+    // Parcelado com juros, o cliente paga mais que o total do pedido
+    let amount_cents = installment.as_ref().map_or(order.total, |i| i.total_cents);
+    let charge_params = ChargeParams {
+        order_reference: order.pid.to_string(),
+        order_number: order.order_number.clone(),
+        amount_cents,
+        method: method.clone(),
+        description: params
+            .description
+            .as_deref()
+            .unwrap_or("Pagamento de pedido")
+            .to_string(),
+        due_date: params.due_date,
+        customer: payer,
+        card,
     };
 
-    let billing_type = params.billing_type.as_deref().unwrap_or("PIX").to_string();
-    let description = params
-        .description
-        .as_deref()
-        .unwrap_or("Pagamento de pedido")
-        .to_string();
-
-    let payment = client
-        .create_payment(
-            &asaas_customer_id,
-            order.total,
-            &description,
-            &order.pid.to_string(),
-            &billing_type,
-            params.due_date,
-        )
-        .await?;
+    let charge = match provider.create_charge(charge_params).await {
+        Ok(charge) => charge,
+        Err(e) => return provider_error(&e),
+    };
+
+    let new_customer_id = charge
+        .provider_customer_id
+        .as_ref()
+        .filter(|_| provider_customer_id.is_none());
+    let card_to_save = charge
+        .saved_card
+        .as_ref()
+        .filter(|_| params.save_card.unwrap_or(true));
+    if new_customer_id.is_some() || card_to_save.is_some() {
+        let mut metadata = customer.metadata.clone();
+        if let Some(id) = new_customer_id {
+            metadata[customer_key.as_str()] = serde_json::json!(id);
+        }
+        if let Some(saved) = card_to_save {
+            metadata[card_key.as_str()] = serde_json::json!(saved);
+        }
+        let mut active: customers::ActiveModel = customer.into();
+        active.metadata = ActiveValue::set(metadata);
+        let _ = active.update(&ctx.db).await?;
+    }
 
-    let status = client.map_status(payment.status.as_deref(), None);
     let payment_data = serde_json::json!({
-        "provider": "asaas",
-        "payment_id": payment.id,
-        "status": payment.status,
-        "invoice_url": payment.invoiceUrl,
-        "bank_slip_url": payment.bankSlipUrl,
-        "pix_qr_code": payment.pixQrCode,
-        "pix_qr_code_id": payment.pixQrCodeId,
-        "checkout_url": payment.checkoutUrl,
+        "provider": provider.name(),
+        "payment_id": charge.provider_id,
+        "status": charge.raw_data.get("status"),
+        "invoice_url": charge.invoice_url,
+        "bank_slip_url": charge.bank_slip_url,
+        "pix_qr_code": charge.pix_qr_code,
+        "pix_qr_code_id": charge.pix_qr_code_id,
+        "checkout_url": charge.checkout_url,
+        "due_date": charge.due_date,
+        "amount_cents": amount_cents,
+        "installments": installment.as_ref().map(|i| i.installments),
+        "installment_cents": installment.as_ref().map(|i| i.installment_cents),
+        "interest_cents": installment.as_ref().map(|i| i.interest_cents),
+        "card_last4": charge.saved_card.as_ref().map(|c| c.last4.clone()),
     });
 
-    let updated =
-        OrderModel::update_payment_status(&ctx.db, order.id, &status, Some(payment_data)).await?;
+    OrderModel::set_payment_method(&ctx.db, order.id, provider.name(), &method).await?;
+    let updated = OrderModel::update_payment_status(
+        &ctx.db,
+        order.id,
+        charge.status.parse()?,
+        Some(payment_data),
+        &StatusChange::by_user(user.id, "checkout", None),
+    )
+    .await?;
 
     format::json(ApiResponse::success(serde_json::json!({
         "order_pid": updated.pid,
         "payment_status": updated.payment_status,
-        "asaas_payment_id": payment.id,
-        "invoice_url": payment.invoiceUrl,
-        "bank_slip_url": payment.bankSlipUrl,
-        "pix_qr_code": payment.pixQrCode,
-        "checkout_url": payment.checkoutUrl,
+        "provider": provider.name(),
+        "payment_id": charge.provider_id,
+        "invoice_url": charge.invoice_url,
+        "bank_slip_url": charge.bank_slip_url,
+        "pix_qr_code": charge.pix_qr_code,
+        "checkout_url": charge.checkout_url,
+        "installments": installment,
     })))
 }
 
+/// GET /api/v1/payments/installments?amount_cents= - Simula o parcelamento no cartão
+#[debug_handler]
+async fn installments(Query(query): Query<InstallmentsQuery>) -> Result<Response> {
+    let options = InstallmentTable::from_env().simulate(query.amount_cents);
+    format::json(ApiResponse::success(options))
+}
+
+/// POST /api/payments/:provider/webhook
+///
+/// O provider autentica a notificação (no Asaas, header `asaas-access-token`
+/// igual a `ASAAS_WEBHOOK_AUTH_TOKEN`). Cada evento é gravado em
+/// `payment_events` pelo ID: reenvios já processados são ignorados e eventos
+/// fora de ordem não sobrescrevem um status mais recente do pedido.
 #[debug_handler]
 async fn webhook(
     State(ctx): State<AppContext>,
-    Json(payload): Json<AsaasWebhookPayload>,
+    Path(provider_slug): Path<String>,
+    headers: HeaderMap,
+    Json(raw): Json<serde_json::Value>,
 ) -> Result<Response> {
-    let Some(payment) = payload.payment else {
+    let Some(provider) = payments::provider_for(&provider_slug) else {
         return format::json(ApiResponse::<()>::error(
-            "NO_PAYMENT",
-            "Payload sem payment",
+            "UNSUPPORTED_PROVIDER",
+            &format!("Gateway sem integração configurada: {}", provider_slug),
         ));
     };
 
-    let Some(external_reference) = payment.externalReference.clone() else {
+    let event = match provider.parse_webhook(&headers, &raw) {
+        Ok(event) => event,
+        Err(PaymentError::Unauthorized(msg)) => return unauthorized(&msg),
+        Err(e) => {
+            return format::json(ApiResponse::<()>::error("BAD_PAYLOAD", &e.to_string()));
+        }
+    };
+
+    let Some(external_reference) = event.order_reference.clone() else {
         return format::json(ApiResponse::<()>::error(
             "NO_REFERENCE",
             "Sem externalReference",
@@ -139,19 +346,42 @@ async fn webhook(
     };
 
     let order = OrderModel::find_by_pid(&ctx.db, &order_pid).await?;
-    let client = AsaasClient::from_env()?;
-    let status = client.map_status(payment.status.as_deref(), payload.event.as_deref());
+    let was_paid = order.payment_status == "paid";
 
-    let payment_data = serde_json::json!({
-        "provider": "asaas",
-        "event": payload.event,
-        "payment": payment,
-    });
+    let (recorded, _) = PaymentEventModel::record(
+        &ctx.db,
+        &NewPaymentEvent {
+            provider: provider.name().to_string(),
+            event_id: event.event_id.clone(),
+            event_type: event.event_type.clone(),
+            payment_id: event.payment_id.clone(),
+            order_id: Some(order.id),
+            payment_status: event.payment_status.clone(),
+            occurred_at: event.occurred_at,
+            payload: raw,
+        },
+    )
+    .await?;
 
-    let updated =
-        OrderModel::update_payment_status(&ctx.db, order.id, &status, Some(payment_data)).await?;
+    let updated = match PaymentEventModel::process(&ctx.db, recorded).await? {
+        EventOutcome::Applied(updated) => updated,
+        outcome => {
+            // Responde sucesso para o gateway não reenviar (nem pausar a fila)
+            let result = match outcome {
+                EventOutcome::Stale => "stale",
+                EventOutcome::Duplicate => "duplicate",
+                EventOutcome::Rejected(_) => "rejected",
+                EventOutcome::OrderEdit(_) => "order_edit",
+                _ => "ignored",
+            };
+            return format::json(ApiResponse::success(serde_json::json!({
+                "event_id": event.event_id,
+                "result": result,
+            })));
+        }
+    };
 
-    if status == "paid" {
+    if updated.payment_status == "paid" && !was_paid {
         if let Some(cart_id) = updated.cart_id {
             if let Some(cart) = carts::Entity::find_by_id(cart_id).one(&ctx.db).await? {
                 let session_id = cart.session_id.clone();
@@ -167,8 +397,8 @@ async fn webhook(
                             entity_type: Some("order".to_string()),
                             entity_id: Some(updated.pid.to_string()),
                             metadata: serde_json::json!({
-                                "provider": "asaas",
-                                "status": status,
+                                "provider": provider.name(),
+                                "status": updated.payment_status,
                             }),
                             timestamp: chrono::Utc::now().timestamp(),
                         })
@@ -179,6 +409,8 @@ async fn webhook(
     }
 
     format::json(ApiResponse::success(serde_json::json!({
+        "event_id": event.event_id,
+        "result": "applied",
         "order_pid": updated.pid,
         "payment_status": updated.payment_status,
     })))
@@ -196,9 +428,10 @@ pub fn routes() -> Routes {
     Routes::new()
         .prefix("/api")
         .add(
-            "/v1/orders/{order_pid}/payments/asaas",
+            "/v1/orders/{order_pid}/payments/{provider}",
             post(create_payment),
         )
-        .add("/payments/asaas/webhook", post(webhook))
+        .add("/v1/payments/installments", get(installments))
+        .add("/payments/{provider}/webhook", post(webhook))
         .add("/payments/asaas/webhooks", get(list_asaas_webhooks))
 }
@@ -2,12 +2,13 @@ use axum::extract::Multipart;
 use axum::extract::Query;
 use axum::http::header;
 use loco_rs::prelude::*;
+use serde::Deserialize;
 use std::io::{Cursor, Write};
 use uuid::Uuid;
 
 use crate::{
     dto::{
-        entities::{PriceResponse, ProductResponse, VariantResponse},
+        entities::{PriceQuoteResponse, PriceResponse, ProductResponse, VariantResponse},
         response::ApiResponse,
     },
     models::{
@@ -15,8 +16,18 @@ use crate::{
         product_variants::{CreateVariantParams, Model as VariantModel},
         products::{CreateProductParams, ProductListParams, UpdateProductParams},
     },
+    services::pricing::{self, PriceQuery},
 };
 
+#[derive(Debug, Deserialize)]
+pub struct PriceQuoteQuery {
+    /// PID da variante
+    pub variant: Uuid,
+    pub qty: Option<i32>,
+    pub region: Option<String>,
+    pub currency: Option<String>,
+}
+
 /// POST /api/v1/products - Cria um produto
 #[debug_handler]
 async fn create(
@@ -148,6 +159,56 @@ async fn create_variant(
     format::json(ApiResponse::success(VariantResponse::from(variant)))
 }
 
+/// GET /api/v1/products/:pid/price-quote?variant=&qty=&region= - Cotação de preço
+#[debug_handler]
+async fn price_quote(
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Query(query): Query<PriceQuoteQuery>,
+) -> Result<Response> {
+    let product = crate::models::products::Model::find_by_pid(&ctx.db, &pid).await?;
+    let variant = VariantModel::find_by_pid(&ctx.db, &query.variant).await?;
+    if variant.product_id != product.id {
+        return format::json(ApiResponse::<()>::error(
+            "VARIANT_MISMATCH",
+            "Variante não pertence ao produto",
+        ));
+    }
+
+    let quantity = query.qty.unwrap_or(1);
+    if quantity <= 0 {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_QUANTITY",
+            "Quantidade deve ser positiva",
+        ));
+    }
+
+    let currency = query.currency.unwrap_or_else(|| "BRL".to_string());
+    let price_query = PriceQuery::now(variant.id, &currency, quantity, query.region.as_deref());
+    let quote = match pricing::resolve_price(&ctx.db, &price_query).await {
+        Ok(quote) => quote,
+        Err(ModelError::EntityNotFound) => {
+            return format::json(ApiResponse::<()>::error(
+                "PRICE_NOT_FOUND",
+                "Nenhum preço aplicável para a quantidade/região informadas",
+            ));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let response = PriceQuoteResponse {
+        variant_pid: variant.pid,
+        currency,
+        region: quote.price.region.clone(),
+        quantity: quote.quantity,
+        unit_price: quote.unit_price,
+        total: quote.total,
+        ends_at: quote.price.ends_at.map(|t| t.to_string()),
+        price: PriceResponse::from(quote.price),
+    };
+    format::json(ApiResponse::success(response))
+}
+
 /// GET /api/v1/products/export/csv - Exporta todos os produtos em CSV
 #[debug_handler]
 async fn export_csv(State(ctx): State<AppContext>) -> Result<Response> {
@@ -602,6 +663,7 @@ pub fn routes() -> Routes {
         .add("/{pid}", put(update))
         .add("/{pid}", delete(remove))
         .add("/{pid}/variants", post(create_variant))
+        .add("/{pid}/price-quote", get(price_quote))
 }
 
 pub fn admin_routes() -> Routes {
@@ -0,0 +1,217 @@
+//! Fornecedores e pedidos de compra (`/api/v1/purchase-orders`), restritos a
+//! usuários de depósito
+
+use axum::extract::Query;
+use loco_rs::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        entities::{
+            ItemResponse, PurchaseOrderItemResponse, PurchaseOrderReceiptResponse,
+            PurchaseOrderResponse, SupplierResponse,
+        },
+        response::ApiResponse,
+    },
+    models::{
+        _entities::users,
+        purchase_orders::{
+            CreatePurchaseOrderParams, Model as PurchaseOrderModel, ReceivePurchaseOrderParams,
+        },
+        suppliers::{Model as SupplierModel, SupplierParams},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct PurchaseOrderQuery {
+    pub status: Option<String>,
+    pub supplier_id: Option<i32>,
+    pub warehouse_id: Option<i32>,
+    pub cursor: Option<i32>,
+    pub limit: Option<u64>,
+}
+
+/// Erro de validação do pedido de compra vira resposta `INVALID_PURCHASE_ORDER`
+fn purchase_order_error(err: ModelError) -> Result<Response> {
+    match err {
+        ModelError::Message(msg) => {
+            format::json(ApiResponse::<()>::error("INVALID_PURCHASE_ORDER", &msg))
+        }
+        e => Err(e.into()),
+    }
+}
+
+/// Pedido de compra com os itens e os recebimentos
+async fn with_details(
+    db: &DatabaseConnection,
+    order: PurchaseOrderModel,
+) -> Result<PurchaseOrderResponse> {
+    let items = order.items(db).await?;
+    let receipts = order.receipts(db).await?;
+    let mut response = PurchaseOrderResponse::from(order);
+    response.items = Some(
+        items
+            .into_iter()
+            .map(PurchaseOrderItemResponse::from)
+            .collect(),
+    );
+    response.receipts = Some(
+        receipts
+            .into_iter()
+            .map(|(receipt, item)| {
+                let mut r = PurchaseOrderReceiptResponse::from(receipt);
+                r.item = item.map(ItemResponse::from);
+                r
+            })
+            .collect(),
+    );
+    Ok(response)
+}
+
+async fn warehouse_user(db: &DatabaseConnection, auth: &auth::JWT) -> Result<users::Model> {
+    let user = users::Model::find_by_pid(db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_warehouse(&user).await?;
+    Ok(user)
+}
+
+/// GET /api/v1/purchase-orders/suppliers
+#[debug_handler]
+async fn list_suppliers(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+    let suppliers: Vec<SupplierResponse> = SupplierModel::list(&ctx.db)
+        .await?
+        .into_iter()
+        .map(SupplierResponse::from)
+        .collect();
+    format::json(ApiResponse::success(suppliers))
+}
+
+/// POST /api/v1/purchase-orders/suppliers
+#[debug_handler]
+async fn create_supplier(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<SupplierParams>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+    match SupplierModel::create(&ctx.db, &params).await {
+        Ok(supplier) => format::json(ApiResponse::success(SupplierResponse::from(supplier))),
+        Err(e) => purchase_order_error(e),
+    }
+}
+
+/// PUT /api/v1/purchase-orders/suppliers/:pid
+#[debug_handler]
+async fn update_supplier(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<SupplierParams>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+    let supplier = SupplierModel::find_by_pid(&ctx.db, &pid).await?;
+    match supplier.update(&ctx.db, &params).await {
+        Ok(supplier) => format::json(ApiResponse::success(SupplierResponse::from(supplier))),
+        Err(e) => purchase_order_error(e),
+    }
+}
+
+/// GET /api/v1/purchase-orders
+#[debug_handler]
+async fn list(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Query(query): Query<PurchaseOrderQuery>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+
+    let limit = query.limit.unwrap_or(20).min(100);
+    let orders = PurchaseOrderModel::list(
+        &ctx.db,
+        query.status.as_deref(),
+        query.supplier_id,
+        query.warehouse_id,
+        query.cursor,
+        limit,
+    )
+    .await?;
+    let has_more = orders.len() as u64 >= limit;
+    let cursor = orders.last().map(|o| o.id.to_string());
+    let count = orders.len();
+    let response: Vec<PurchaseOrderResponse> = orders
+        .into_iter()
+        .map(PurchaseOrderResponse::from)
+        .collect();
+    format::json(ApiResponse::paginated(response, cursor, has_more, count))
+}
+
+/// POST /api/v1/purchase-orders
+#[debug_handler]
+async fn create(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<CreatePurchaseOrderParams>,
+) -> Result<Response> {
+    let user = warehouse_user(&ctx.db, &auth).await?;
+    match PurchaseOrderModel::create(&ctx.db, &params, Some(user.id)).await {
+        Ok(order) => format::json(ApiResponse::success(with_details(&ctx.db, order).await?)),
+        Err(e) => purchase_order_error(e),
+    }
+}
+
+/// GET /api/v1/purchase-orders/:pid
+#[debug_handler]
+async fn get_one(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+    let order = PurchaseOrderModel::find_by_pid(&ctx.db, &pid).await?;
+    format::json(ApiResponse::success(with_details(&ctx.db, order).await?))
+}
+
+/// POST /api/v1/purchase-orders/:pid/receive - Entrada no depósito (parcial ou total)
+#[debug_handler]
+async fn receive(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ReceivePurchaseOrderParams>,
+) -> Result<Response> {
+    let user = warehouse_user(&ctx.db, &auth).await?;
+    let order = PurchaseOrderModel::find_by_pid(&ctx.db, &pid).await?;
+    match PurchaseOrderModel::receive(&ctx.db, order, &params, Some(user.id)).await {
+        Ok(order) => format::json(ApiResponse::success(with_details(&ctx.db, order).await?)),
+        Err(e) => purchase_order_error(e),
+    }
+}
+
+/// POST /api/v1/purchase-orders/:pid/cancel
+#[debug_handler]
+async fn cancel(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+    let order = PurchaseOrderModel::find_by_pid(&ctx.db, &pid).await?;
+    match PurchaseOrderModel::cancel(&ctx.db, order).await {
+        Ok(order) => format::json(ApiResponse::success(with_details(&ctx.db, order).await?)),
+        Err(e) => purchase_order_error(e),
+    }
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("/api/v1/purchase-orders")
+        .add("/", get(list))
+        .add("/", post(create))
+        .add("/suppliers", get(list_suppliers))
+        .add("/suppliers", post(create_supplier))
+        .add("/suppliers/{pid}", put(update_supplier))
+        .add("/{pid}", get(get_one))
+        .add("/{pid}/receive", post(receive))
+        .add("/{pid}/cancel", post(cancel))
+}
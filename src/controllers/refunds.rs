@@ -0,0 +1,68 @@
+//! Estornos de pedidos
+//!
+//! `POST /api/admin/orders/{pid}/refunds` estorna o pagamento no gateway, total
+//! (sem `amount`) ou parcial, e opcionalmente devolve itens ao estoque.
+//! O estorno é gravado como `pending` antes da chamada ao gateway e vira
+//! `done` ou `failed` conforme a resposta. Pedido com cobranças adicionais
+//! de edições recebe um estorno por cobrança, cada um limitado ao valor dela.
+
+use loco_rs::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    dto::{entities::RefundResponse, response::ApiResponse},
+    models::{
+        _entities::users,
+        orders::Model as OrderModel,
+        refunds::{CreateRefundParams, Model as RefundModel},
+    },
+    services::refunds::{issue_refund, RefundOutcome},
+};
+
+/// GET /api/admin/orders/:pid/refunds
+#[debug_handler]
+async fn list(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    let refunds = RefundModel::list_for_order(&ctx.db, order.id).await?;
+    let response: Vec<RefundResponse> = refunds.into_iter().map(RefundResponse::from).collect();
+    format::json(ApiResponse::success(response))
+}
+
+/// POST /api/admin/orders/:pid/refunds
+#[debug_handler]
+async fn create(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<CreateRefundParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    match issue_refund(&ctx.db, &order, &params, user.id).await? {
+        RefundOutcome::Done(refunds, order) => {
+            format::json(ApiResponse::success(serde_json::json!({
+                "refund": RefundResponse::from(refunds[0].clone()),
+                "refunds": refunds.into_iter().map(RefundResponse::from).collect::<Vec<_>>(),
+                "order_pid": order.pid,
+                "payment_status": order.payment_status,
+            })))
+        }
+        RefundOutcome::Rejected(code, msg) => format::json(ApiResponse::<()>::error(code, &msg)),
+    }
+}
+
+pub fn admin_routes() -> Routes {
+    Routes::new()
+        .prefix("/api/admin")
+        .add("/orders/{pid}/refunds", get(list))
+        .add("/orders/{pid}/refunds", post(create))
+}
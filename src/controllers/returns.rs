@@ -0,0 +1,505 @@
+//! Devoluções e trocas (RMA)
+//!
+//! O cliente abre a devolução de itens de um pedido entregue
+//! (`POST /api/v1/orders/{pid}/returns`); o admin aprova ou recusa, gera a
+//! etiqueta de logística reversa, registra o recebimento com a inspeção dos
+//! itens e conclui com estorno no gateway ou com um pedido de troca.
+//!
+//! `requested → approved → received → completed`, ou `requested → rejected`.
+
+use axum::extract::Query;
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        entities::{RefundResponse, ReturnItemResponse, ReturnResponse},
+        response::ApiResponse,
+    },
+    models::{
+        _entities::{customers, orders, users},
+        order_shippings::ShippingPackageParams,
+        orders::Model as OrderModel,
+        product_variants::Model as ProductVariantModel,
+        refunds::CreateRefundParams,
+        returns::{Model as ReturnModel, OpenReturnParams, ReceiveReturnParams},
+    },
+    services::refunds::{issue_refund, RefundOutcome},
+    shipping,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ReturnListQuery {
+    pub status: Option<String>,
+    pub cursor: Option<i32>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReviewReturnParams {
+    /// Observação interna (aprovação) ou motivo da recusa
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReturnLabelParams {
+    pub carrier: String,
+    pub service_code: String,
+    /// Volume informado; sem ele os itens devolvidos são empacotados
+    pub package: Option<ShippingPackageParams>,
+}
+
+/// Erros de validação do model viram `INVALID_RETURN`
+fn return_error(err: ModelError) -> Result<Response> {
+    match err {
+        ModelError::Message(msg) => format::json(ApiResponse::<()>::error("INVALID_RETURN", &msg)),
+        e => Err(e.into()),
+    }
+}
+
+/// Devolução com os itens
+async fn with_items(db: &DatabaseConnection, ret: ReturnModel) -> Result<ReturnResponse> {
+    let items = ret.items(db).await?;
+    let mut response = ReturnResponse::from(ret);
+    response.items = Some(items.into_iter().map(ReturnItemResponse::from).collect());
+    Ok(response)
+}
+
+/// Admin ou o próprio cliente do pedido
+async fn ensure_order_access(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    order: &OrderModel,
+) -> Result<()> {
+    if user.is_admin() {
+        return Ok(());
+    }
+    let customer = customers::Entity::find_by_id(order.customer_id)
+        .one(db)
+        .await?
+        .ok_or(Error::NotFound)?;
+    if customer.user_id == Some(user.id) {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized("Pedido de outro cliente".into()))
+    }
+}
+
+/// POST /api/v1/orders/:pid/returns - Abre devolução ou troca
+#[debug_handler]
+async fn open(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<OpenReturnParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    ensure_order_access(&ctx.db, &user, &order).await?;
+
+    let (ret, items) = match ReturnModel::open(&ctx.db, &order, &params).await {
+        Ok(created) => created,
+        Err(e) => return return_error(e),
+    };
+    let mut response = ReturnResponse::from(ret);
+    response.items = Some(items.into_iter().map(ReturnItemResponse::from).collect());
+    format::json(ApiResponse::success(response))
+}
+
+/// GET /api/v1/orders/:pid/returns - Devoluções do pedido
+#[debug_handler]
+async fn list_for_order(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    let order = OrderModel::find_by_pid(&ctx.db, &pid).await?;
+    ensure_order_access(&ctx.db, &user, &order).await?;
+
+    let mut response = vec![];
+    for ret in ReturnModel::list_for_order(&ctx.db, order.id).await? {
+        response.push(with_items(&ctx.db, ret).await?);
+    }
+    format::json(ApiResponse::success(response))
+}
+
+/// GET /api/admin/returns?status=requested - Fila de devoluções
+#[debug_handler]
+async fn admin_list(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Query(query): Query<ReturnListQuery>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let list = ReturnModel::list(
+        &ctx.db,
+        query.status.as_deref(),
+        query.cursor,
+        query.limit.unwrap_or(50),
+    )
+    .await?;
+    let response: Vec<ReturnResponse> = list.into_iter().map(ReturnResponse::from).collect();
+    format::json(ApiResponse::success(response))
+}
+
+/// GET /api/admin/returns/:pid
+#[debug_handler]
+async fn admin_get(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let ret = ReturnModel::find_by_pid(&ctx.db, &pid).await?;
+    let order = orders::Entity::find_by_id(ret.order_id)
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let refund_amount = ret.refund_amount(&ctx.db).await?;
+    let response = with_items(&ctx.db, ret).await?;
+    format::json(ApiResponse::success(serde_json::json!({
+        "return": response,
+        "order_pid": order.pid,
+        "order_number": order.order_number,
+        "refund_amount": refund_amount,
+    })))
+}
+
+/// POST /api/admin/returns/:pid/approve
+#[debug_handler]
+async fn approve(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ReviewReturnParams>,
+) -> Result<Response> {
+    review(auth, ctx, pid, params, true).await
+}
+
+/// POST /api/admin/returns/:pid/reject
+#[debug_handler]
+async fn reject(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ReviewReturnParams>,
+) -> Result<Response> {
+    review(auth, ctx, pid, params, false).await
+}
+
+async fn review(
+    auth: auth::JWT,
+    ctx: AppContext,
+    pid: Uuid,
+    params: ReviewReturnParams,
+    approved: bool,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let ret = ReturnModel::find_by_pid(&ctx.db, &pid).await?;
+    match ReturnModel::review(&ctx.db, ret, approved, user.id, params.notes).await {
+        Ok(ret) => format::json(ApiResponse::success(ReturnResponse::from(ret))),
+        Err(e) => return_error(e),
+    }
+}
+
+/// POST /api/admin/returns/:pid/label - Etiqueta de logística reversa
+///
+/// O cliente é o remetente e a loja o destinatário.
+#[debug_handler]
+async fn label(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ReturnLabelParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let ret = ReturnModel::find_by_pid(&ctx.db, &pid).await?;
+    if ret.status != "approved" {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_RETURN",
+            "Só devoluções aprovadas recebem etiqueta",
+        ));
+    }
+    // Cada chamada compra uma etiqueta paga: não gera uma segunda, só
+    // retoma a que ficou pela metade
+    let unfinished = ret.unfinished_label_id().map(String::from);
+    if unfinished.is_none()
+        && (ret.tracking_code.is_some()
+            || ret
+                .provider_data
+                .get("provider_id")
+                .is_some_and(|v| !v.is_null()))
+    {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_RETURN",
+            "Devolução já tem etiqueta de logística reversa",
+        ));
+    }
+    if unfinished.is_some() && ret.carrier.as_deref() != Some(params.carrier.as_str()) {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_RETURN",
+            "Etiqueta reversa em andamento em outro carrier",
+        ));
+    }
+    let Some(provider) = shipping::provider_for(&params.carrier) else {
+        return format::json(ApiResponse::<()>::error(
+            "UNSUPPORTED_CARRIER",
+            &format!("Carrier sem integração: {}", params.carrier),
+        ));
+    };
+
+    let mut shipment =
+        match return_shipment_params(&ctx.db, &ret, &params, provider.max_package_weight_grams())
+            .await
+        {
+            Ok(shipment) => shipment,
+            Err(e) => return return_error(e),
+        };
+
+    // O ID do provider é gravado antes de pagar a etiqueta
+    let ret = match unfinished {
+        Some(provider_id) => {
+            shipment.provider_id = Some(provider_id);
+            ret
+        }
+        None => match provider.prepare_shipment(&shipment).await {
+            Ok(Some(provider_id)) => {
+                let ret = match ReturnModel::set_label_pending(
+                    &ctx.db,
+                    ret,
+                    &params.carrier,
+                    &provider_id,
+                )
+                .await
+                {
+                    Ok(ret) => ret,
+                    Err(e) => return return_error(e),
+                };
+                shipment.provider_id = Some(provider_id);
+                ret
+            }
+            Ok(None) => ret,
+            Err(e) => {
+                tracing::error!(carrier = %params.carrier, error = %e, "Falha ao criar etiqueta reversa");
+                return format::json(ApiResponse::<()>::error(
+                    "SHIPPING_PROVIDER_ERROR",
+                    &e.to_string(),
+                ));
+            }
+        },
+    };
+
+    let result = match provider.create_shipment(shipment).await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!(carrier = %params.carrier, error = %e, "Falha ao criar etiqueta reversa");
+            return format::json(ApiResponse::<()>::error(
+                "SHIPPING_PROVIDER_ERROR",
+                &e.to_string(),
+            ));
+        }
+    };
+
+    let ret = ReturnModel::set_label(&ctx.db, ret, &params.carrier, &result).await?;
+    format::json(ApiResponse::success(ReturnResponse::from(ret)))
+}
+
+/// Etiqueta reversa: remetente é o endereço de entrega do pedido, destino é
+/// a loja; volumes pelos itens devolvidos
+async fn return_shipment_params(
+    db: &DatabaseConnection,
+    ret: &ReturnModel,
+    params: &ReturnLabelParams,
+    max_weight_grams: u32,
+) -> ModelResult<shipping::CreateShipmentParams> {
+    let order = orders::Entity::find_by_id(ret.order_id)
+        .one(db)
+        .await?
+        .ok_or(ModelError::EntityNotFound)?;
+    let sender = order.shipping_contact(db).await?;
+    let recipient =
+        shipping::ContactInfo::store_sender().map_err(|e| ModelError::Message(e.to_string()))?;
+
+    let order_items = OrderModel::get_items(db, order.id).await?;
+    let mut parcels = vec![];
+    let mut items = vec![];
+    for line in ret.items(db).await? {
+        let Some(item) = order_items.iter().find(|i| i.id == line.order_item_id) else {
+            continue;
+        };
+        if params.package.is_none() {
+            let variant_id = item
+                .variant_id
+                .ok_or_else(|| ModelError::msg("Item sem variante: informe package"))?;
+            parcels.push(
+                ProductVariantModel::parcel_item(db, variant_id, line.quantity, item.unit_price)
+                    .await?,
+            );
+        }
+        items.push(shipping::ShipmentItem {
+            name: if item.title.is_empty() {
+                item.sku.clone()
+            } else {
+                item.title.clone()
+            },
+            quantity: line.quantity.max(0) as u32,
+            unit_price_cents: item.unit_price,
+        });
+    }
+
+    let packages = match &params.package {
+        Some(package) => vec![shipping::packing::Package {
+            box_name: None,
+            length_cm: package.length_cm,
+            width_cm: package.width_cm,
+            height_cm: package.height_cm,
+            weight_grams: package.weight_grams,
+            declared_value_cents: items
+                .iter()
+                .map(|i| i.unit_price_cents * i64::from(i.quantity))
+                .sum(),
+        }],
+        None => shipping::packing::pack(&parcels, &shipping::packing::catalog(), max_weight_grams)
+            .map_err(|e| ModelError::Message(e.to_string()))?,
+    };
+    if packages.is_empty() {
+        return Err(ModelError::msg("Devolução sem itens para despachar"));
+    }
+
+    Ok(shipping::CreateShipmentParams {
+        service_code: params.service_code.clone(),
+        order_number: format!("{}-RMA", order.order_number),
+        freight: shipping::FreightParams::from_packages(
+            &sender.postal_code,
+            &recipient.postal_code,
+            packages,
+        ),
+        sender,
+        recipient,
+        items,
+        provider_id: None,
+    })
+}
+
+/// POST /api/admin/returns/:pid/receive - Recebimento e inspeção
+#[debug_handler]
+async fn receive(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ReceiveReturnParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let ret = ReturnModel::find_by_pid(&ctx.db, &pid).await?;
+    match ReturnModel::receive(&ctx.db, ret, &params, Some(user.id)).await {
+        Ok(ret) => format::json(ApiResponse::success(with_items(&ctx.db, ret).await?)),
+        Err(e) => return_error(e),
+    }
+}
+
+/// POST /api/admin/returns/:pid/resolve - Conclui com estorno ou troca
+///
+/// Estorno: valor pago pelos itens devolvidos, sem nova baixa de estoque (o
+/// retorno ao estoque já aconteceu na inspeção). Troca: gera um pedido pago,
+/// de total zero, com as variantes escolhidas.
+#[debug_handler]
+async fn resolve(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let ret = ReturnModel::find_by_pid(&ctx.db, &pid).await?;
+    if ret.status != "received" {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_RETURN",
+            &format!(
+                "Devolução com status '{}' não pode ser concluída",
+                ret.status
+            ),
+        ));
+    }
+
+    if ret.resolution == "exchange" {
+        let (ret, order) = match ReturnModel::complete_with_exchange(&ctx.db, ret).await {
+            Ok(done) => done,
+            Err(e) => return return_error(e),
+        };
+        return format::json(ApiResponse::success(serde_json::json!({
+            "return": ReturnResponse::from(ret),
+            "exchange_order_pid": order.pid,
+            "exchange_order_number": order.order_number,
+        })));
+    }
+
+    let order = orders::Entity::find_by_id(ret.order_id)
+        .one(&ctx.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let params = CreateRefundParams {
+        amount: Some(ret.refund_amount(&ctx.db).await?),
+        reason: Some(format!("Devolução {}: {}", ret.pid, ret.reason)),
+        restock: false,
+        items: vec![],
+    };
+    // Marca a devolução antes do gateway: conclusão concorrente não estorna de novo
+    let ret = match ReturnModel::start_refund(&ctx.db, ret).await {
+        Ok(ret) => ret,
+        Err(e) => return return_error(e),
+    };
+    let outcome = match issue_refund(&ctx.db, &order, &params, user.id).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            ReturnModel::abort_refund(&ctx.db, ret).await?;
+            return Err(e.into());
+        }
+    };
+    match outcome {
+        RefundOutcome::Done(refunds, order) => {
+            let ret = ReturnModel::complete_with_refund(&ctx.db, ret, refunds[0].id).await?;
+            format::json(ApiResponse::success(serde_json::json!({
+                "return": ReturnResponse::from(ret),
+                "refund": RefundResponse::from(refunds[0].clone()),
+                "refunds": refunds.into_iter().map(RefundResponse::from).collect::<Vec<_>>(),
+                "payment_status": order.payment_status,
+            })))
+        }
+        RefundOutcome::Rejected(code, msg) => {
+            ReturnModel::abort_refund(&ctx.db, ret).await?;
+            format::json(ApiResponse::<()>::error(code, &msg))
+        }
+    }
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("/api/v1/orders")
+        .add("/{pid}/returns", post(open))
+        .add("/{pid}/returns", get(list_for_order))
+}
+
+pub fn admin_routes() -> Routes {
+    Routes::new()
+        .prefix("/api/admin")
+        .add("/returns", get(admin_list))
+        .add("/returns/{pid}", get(admin_get))
+        .add("/returns/{pid}/approve", post(approve))
+        .add("/returns/{pid}/reject", post(reject))
+        .add("/returns/{pid}/label", post(label))
+        .add("/returns/{pid}/receive", post(receive))
+        .add("/returns/{pid}/resolve", post(resolve))
+}
@@ -0,0 +1,103 @@
+use loco_rs::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    dto::{entities::ShippingRateResponse, response::ApiResponse},
+    models::{
+        _entities::users,
+        shipping_rates::{
+            CreateShippingRateParams, Model as ShippingRateModel, UpdateShippingRateParams,
+        },
+    },
+};
+
+/// GET /api/admin/shipping-rates - Lista tarifas de frete
+#[debug_handler]
+async fn list(auth: auth::JWT, State(ctx): State<AppContext>) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let rates = ShippingRateModel::list_all(&ctx.db).await?;
+    let response: Vec<ShippingRateResponse> =
+        rates.into_iter().map(ShippingRateResponse::from).collect();
+    format::json(ApiResponse::success(response))
+}
+
+/// POST /api/admin/shipping-rates - Cria tarifa
+#[debug_handler]
+async fn create(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<CreateShippingRateParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    match ShippingRateModel::create_rate(&ctx.db, &params).await {
+        Ok(rate) => format::json(ApiResponse::success(ShippingRateResponse::from(rate))),
+        Err(ModelError::Message(msg)) => {
+            format::json(ApiResponse::<()>::error("INVALID_SHIPPING_RATE", &msg))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// GET /api/admin/shipping-rates/:pid
+#[debug_handler]
+async fn get_one(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let rate = ShippingRateModel::find_by_pid(&ctx.db, &pid).await?;
+    format::json(ApiResponse::success(ShippingRateResponse::from(rate)))
+}
+
+/// PUT /api/admin/shipping-rates/:pid
+#[debug_handler]
+async fn update(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<UpdateShippingRateParams>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let rate = ShippingRateModel::find_by_pid(&ctx.db, &pid).await?;
+    match ShippingRateModel::update_rate(&ctx.db, rate, &params).await {
+        Ok(updated) => format::json(ApiResponse::success(ShippingRateResponse::from(updated))),
+        Err(ModelError::Message(msg)) => {
+            format::json(ApiResponse::<()>::error("INVALID_SHIPPING_RATE", &msg))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// DELETE /api/admin/shipping-rates/:pid - Remove a tarifa
+#[debug_handler]
+async fn remove(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_admin(&user).await?;
+
+    let rate = ShippingRateModel::find_by_pid(&ctx.db, &pid).await?;
+    rate.delete(&ctx.db).await?;
+    format::json(ApiResponse::<()>::success(()))
+}
+
+pub fn admin_routes() -> Routes {
+    Routes::new()
+        .prefix("/api/admin")
+        .add("/shipping-rates", get(list))
+        .add("/shipping-rates", post(create))
+        .add("/shipping-rates/{pid}", get(get_one))
+        .add("/shipping-rates/{pid}", put(update))
+        .add("/shipping-rates/{pid}", delete(remove))
+}
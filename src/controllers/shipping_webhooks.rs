@@ -0,0 +1,122 @@
+//! Webhooks de rastreio das transportadoras
+//!
+//! `POST /api/shipping/{carrier}/webhook` recebe a notificação, localiza o
+//! envio pelo ID no provider ou código de rastreio e consulta o rastreio
+//! completo no próprio provider — o payload só identifica o envio.
+//!
+//! Autenticação: o token de `SHIPPING_WEBHOOK_TOKEN` deve vir no header
+//! `x-webhook-token` ou no parâmetro `?token=` da URL cadastrada no provider.
+//!
+//! Formatos aceitos para identificar o envio:
+//! - MelhorEnvio: `{ "event": "order.posted", "data": { "id": "…", "tracking": "…" } }`
+//! - genérico: `{ "tracking_code": "…" }` ou `{ "codigoObjeto": "…" }`
+
+use axum::{extract::Query, http::HeaderMap};
+use loco_rs::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    dto::response::ApiResponse, models::order_shippings::Model as ShippingModel,
+    services::constant_time_eq, shipping,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookQuery {
+    pub token: Option<String>,
+}
+
+/// Confere o token do webhook; sem `SHIPPING_WEBHOOK_TOKEN` nada é aceito
+fn authorized(headers: &HeaderMap, query: &WebhookQuery) -> bool {
+    crate::env::load();
+    let Ok(expected) = std::env::var("SHIPPING_WEBHOOK_TOKEN") else {
+        return false;
+    };
+    if expected.is_empty() {
+        return false;
+    }
+    let received = headers
+        .get("x-webhook-token")
+        .and_then(|v| v.to_str().ok())
+        .or(query.token.as_deref());
+    received.is_some_and(|received| constant_time_eq(received.as_bytes(), expected.as_bytes()))
+}
+
+/// Identificadores do envio presentes no payload, na ordem de preferência
+fn payload_references(payload: &serde_json::Value) -> Vec<String> {
+    [
+        &payload["data"]["id"],
+        &payload["data"]["tracking"],
+        &payload["tracking_code"],
+        &payload["codigoObjeto"],
+    ]
+    .into_iter()
+    .filter_map(|v| v.as_str())
+    .filter(|v| !v.is_empty())
+    .map(String::from)
+    .collect()
+}
+
+/// POST /api/shipping/:carrier/webhook
+#[debug_handler]
+async fn webhook(
+    State(ctx): State<AppContext>,
+    Path(carrier): Path<String>,
+    Query(query): Query<WebhookQuery>,
+    headers: HeaderMap,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Response> {
+    if !authorized(&headers, &query) {
+        return unauthorized("webhook token inválido");
+    }
+
+    let Some(provider) = shipping::provider_for(&carrier) else {
+        return format::json(ApiResponse::<()>::error(
+            "UNSUPPORTED_CARRIER",
+            &format!("Carrier sem integração configurada: {}", carrier),
+        ));
+    };
+
+    let mut shipment = None;
+    for reference in payload_references(&payload) {
+        match ShippingModel::find_by_provider_reference(&ctx.db, &carrier, &reference).await {
+            Ok(found) => {
+                shipment = Some(found);
+                break;
+            }
+            Err(ModelError::EntityNotFound) => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let Some(shipment) = shipment else {
+        // Responde sucesso para o provider não reenviar notificações de envios desconhecidos
+        tracing::warn!(carrier = %carrier, "Webhook de rastreio para envio desconhecido");
+        return format::json(ApiResponse::success(serde_json::json!({ "ignored": true })));
+    };
+
+    let Some(reference) = shipment.tracking_reference() else {
+        return format::json(ApiResponse::success(serde_json::json!({ "ignored": true })));
+    };
+    let info = match provider.track(&reference).await {
+        Ok(info) => info,
+        Err(e) => {
+            return format::json(ApiResponse::<()>::error(
+                "SHIPPING_PROVIDER_ERROR",
+                &e.to_string(),
+            ));
+        }
+    };
+
+    let updated = ShippingModel::record_tracking(&ctx.db, shipment.id, &info, "webhook").await?;
+    format::json(ApiResponse::success(serde_json::json!({
+        "pid": updated.pid.to_string(),
+        "status": updated.status,
+        "shipped_at": updated.shipped_at.map(|t| t.to_string()),
+        "delivered_at": updated.delivered_at.map(|t| t.to_string()),
+    })))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("/api")
+        .add("/shipping/{carrier}/webhook", post(webhook))
+}
@@ -0,0 +1,170 @@
+//! Transferências de estoque entre depósitos (`/api/v1/transfers`), restritas
+//! a usuários de depósito
+
+use axum::extract::Query;
+use loco_rs::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        entities::{StockTransferItemResponse, StockTransferResponse},
+        response::ApiResponse,
+    },
+    models::{
+        _entities::users,
+        stock_transfers::{CreateTransferParams, Model as TransferModel, ReceiveTransferParams},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct TransferQuery {
+    pub status: Option<String>,
+    /// Transferências que saem ou chegam neste depósito
+    pub warehouse_id: Option<i32>,
+    pub cursor: Option<i32>,
+    pub limit: Option<u64>,
+}
+
+/// Erro de validação da transferência vira resposta `INVALID_TRANSFER`
+fn transfer_error(err: ModelError) -> Result<Response> {
+    match err {
+        ModelError::Message(msg) => {
+            format::json(ApiResponse::<()>::error("INVALID_TRANSFER", &msg))
+        }
+        e => Err(e.into()),
+    }
+}
+
+/// Transferência com os itens
+async fn with_items(
+    db: &DatabaseConnection,
+    transfer: TransferModel,
+) -> Result<StockTransferResponse> {
+    let items = transfer.items(db).await?;
+    let mut response = StockTransferResponse::from(transfer);
+    response.items = Some(
+        items
+            .into_iter()
+            .map(StockTransferItemResponse::from)
+            .collect(),
+    );
+    Ok(response)
+}
+
+async fn warehouse_user(db: &DatabaseConnection, auth: &auth::JWT) -> Result<users::Model> {
+    let user = users::Model::find_by_pid(db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_warehouse(&user).await?;
+    Ok(user)
+}
+
+/// GET /api/v1/transfers
+#[debug_handler]
+async fn list(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Query(query): Query<TransferQuery>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+
+    let limit = query.limit.unwrap_or(20).min(100);
+    let transfers = TransferModel::list(
+        &ctx.db,
+        query.status.as_deref(),
+        query.warehouse_id,
+        query.cursor,
+        limit,
+    )
+    .await?;
+    let has_more = transfers.len() as u64 >= limit;
+    let cursor = transfers.last().map(|t| t.id.to_string());
+    let count = transfers.len();
+    let response: Vec<StockTransferResponse> = transfers
+        .into_iter()
+        .map(StockTransferResponse::from)
+        .collect();
+    format::json(ApiResponse::paginated(response, cursor, has_more, count))
+}
+
+/// POST /api/v1/transfers
+#[debug_handler]
+async fn create(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Json(params): Json<CreateTransferParams>,
+) -> Result<Response> {
+    let user = warehouse_user(&ctx.db, &auth).await?;
+    match TransferModel::create(&ctx.db, &params, Some(user.id)).await {
+        Ok(transfer) => format::json(ApiResponse::success(with_items(&ctx.db, transfer).await?)),
+        Err(e) => transfer_error(e),
+    }
+}
+
+/// GET /api/v1/transfers/:pid
+#[debug_handler]
+async fn get_one(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+    let transfer = TransferModel::find_by_pid(&ctx.db, &pid).await?;
+    format::json(ApiResponse::success(with_items(&ctx.db, transfer).await?))
+}
+
+/// POST /api/v1/transfers/:pid/ship - Baixa na origem
+#[debug_handler]
+async fn ship(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    let user = warehouse_user(&ctx.db, &auth).await?;
+    let transfer = TransferModel::find_by_pid(&ctx.db, &pid).await?;
+    match TransferModel::ship(&ctx.db, transfer, Some(user.id)).await {
+        Ok(transfer) => format::json(ApiResponse::success(with_items(&ctx.db, transfer).await?)),
+        Err(e) => transfer_error(e),
+    }
+}
+
+/// POST /api/v1/transfers/:pid/receive - Entrada no destino
+#[debug_handler]
+async fn receive(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+    Json(params): Json<ReceiveTransferParams>,
+) -> Result<Response> {
+    let user = warehouse_user(&ctx.db, &auth).await?;
+    let transfer = TransferModel::find_by_pid(&ctx.db, &pid).await?;
+    match TransferModel::receive(&ctx.db, transfer, &params, Some(user.id)).await {
+        Ok(transfer) => format::json(ApiResponse::success(with_items(&ctx.db, transfer).await?)),
+        Err(e) => transfer_error(e),
+    }
+}
+
+/// POST /api/v1/transfers/:pid/cancel
+#[debug_handler]
+async fn cancel(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(pid): Path<Uuid>,
+) -> Result<Response> {
+    warehouse_user(&ctx.db, &auth).await?;
+    let transfer = TransferModel::find_by_pid(&ctx.db, &pid).await?;
+    match TransferModel::cancel(&ctx.db, transfer).await {
+        Ok(transfer) => format::json(ApiResponse::success(with_items(&ctx.db, transfer).await?)),
+        Err(e) => transfer_error(e),
+    }
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("/api/v1/transfers")
+        .add("/", get(list))
+        .add("/", post(create))
+        .add("/{pid}", get(get_one))
+        .add("/{pid}/ship", post(ship))
+        .add("/{pid}/receive", post(receive))
+        .add("/{pid}/cancel", post(cancel))
+}
@@ -1,14 +1,22 @@
+use std::collections::{BTreeMap, HashMap};
+
 use crate::models::_entities::stocks::Column as StockColumn;
+
 use axum::extract::Query;
 use loco_rs::prelude::*;
 use sea_orm::QueryFilter;
 use serde::Deserialize;
 
 use crate::{
-    dto::{entities::StockResponse, response::ApiResponse},
+    dto::{
+        entities::{StockMovementResponse, StockResponse},
+        response::ApiResponse,
+    },
     models::{
         _entities::users,
-        stocks::{self, Model as StockModel, UpdateStockParams},
+        stock_movements::Model as MovementModel,
+        stocks::{self, ExpiringBatch, Model as StockModel, UpdateStockParams},
+        warehouses,
     },
 };
 
@@ -18,6 +26,20 @@ pub struct StockQuery {
     pub item_id: Option<i32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ExpiringQuery {
+    /// Janela em dias a partir de hoje (padrão 30)
+    pub days: Option<i64>,
+    pub warehouse_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MovementQuery {
+    pub kind: Option<String>,
+    pub cursor: Option<i32>,
+    pub limit: Option<u64>,
+}
+
 /// POST /api/v1/stocks - Upsert stock
 #[debug_handler]
 async fn upsert(
@@ -27,8 +49,13 @@ async fn upsert(
 ) -> Result<Response> {
     let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
     crate::controllers::guards::ensure_warehouse(&user).await?;
-    let stock = StockModel::set_stock(&ctx.db, &params).await?;
-    format::json(ApiResponse::success(StockResponse::from(stock)))
+    match StockModel::set_stock(&ctx.db, &params, Some(user.id)).await {
+        Ok(stock) => format::json(ApiResponse::success(StockResponse::from(stock))),
+        Err(ModelError::Message(msg)) => {
+            format::json(ApiResponse::<()>::error("INVALID_STOCK", &msg))
+        }
+        Err(e) => Err(e.into()),
+    }
 }
 
 /// GET /api/v1/stocks - Lista estoques
@@ -72,16 +99,108 @@ async fn remove(
         .one(&ctx.db)
         .await?
         .ok_or_else(|| Error::NotFound)?;
+    // Saldo existente precisa sair pelo razão (ajuste) antes da remoção
+    if stock.quantity != 0 || stock.reserved != 0 {
+        return format::json(ApiResponse::<()>::error(
+            "INVALID_STOCK",
+            "Zere o saldo e as reservas da linha antes de removê-la",
+        ));
+    }
     let active: stocks::ActiveModel = stock.into();
     active.delete(&ctx.db).await?;
     format::json(ApiResponse::<()>::success(()))
 }
 
+/// GET /api/v1/stocks/:id/movements - Razão da linha de estoque
+#[debug_handler]
+async fn movements(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    Query(query): Query<MovementQuery>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_warehouse(&user).await?;
+    let stock = stocks::Entity::find_by_id(id)
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| Error::NotFound)?;
+
+    let limit = query.limit.unwrap_or(50).min(100);
+    let movements = MovementModel::list_for_stock(
+        &ctx.db,
+        stock.id,
+        query.kind.as_deref(),
+        query.cursor,
+        limit,
+    )
+    .await?;
+    let has_more = movements.len() as u64 >= limit;
+    let cursor = movements.last().map(|m| m.id.to_string());
+    let count = movements.len();
+    let response: Vec<StockMovementResponse> = movements
+        .into_iter()
+        .map(StockMovementResponse::from)
+        .collect();
+    format::json(ApiResponse::paginated(response, cursor, has_more, count))
+}
+
+/// GET /api/v1/stocks/expiring - Lotes vencidos ou a vencer em até N dias,
+/// agrupados por depósito
+#[debug_handler]
+async fn expiring(
+    auth: auth::JWT,
+    State(ctx): State<AppContext>,
+    Query(query): Query<ExpiringQuery>,
+) -> Result<Response> {
+    let user = users::Model::find_by_pid(&ctx.db, &auth.claims.pid).await?;
+    crate::controllers::guards::ensure_warehouse(&user).await?;
+
+    let days = query.days.unwrap_or(30).clamp(0, 365);
+    let batches = StockModel::expiring_batches(&ctx.db, days, query.warehouse_id).await?;
+
+    let warehouse_names: HashMap<i32, String> = warehouses::Entity::find()
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(|w| (w.id, w.name))
+        .collect();
+    let mut by_warehouse: BTreeMap<i32, Vec<ExpiringBatch>> = BTreeMap::new();
+    for batch in batches {
+        by_warehouse
+            .entry(batch.warehouse_id)
+            .or_default()
+            .push(batch);
+    }
+    let report: Vec<serde_json::Value> = by_warehouse
+        .into_iter()
+        .map(|(warehouse_id, batches)| {
+            serde_json::json!({
+                "warehouse_id": warehouse_id,
+                "warehouse_name": warehouse_names.get(&warehouse_id),
+                "expired_quantity": batches
+                    .iter()
+                    .filter(|b| b.days_left < 0)
+                    .map(|b| b.quantity)
+                    .sum::<i32>(),
+                "batches": batches,
+            })
+        })
+        .collect();
+
+    format::json(ApiResponse::success(serde_json::json!({
+        "days": days,
+        "warehouses": report,
+    })))
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("/api/v1/stocks")
         .add("/", post(upsert))
         .add("/", get(list))
+        .add("/expiring", get(expiring))
         .add("/{id}", get(get_one))
         .add("/{id}", delete(remove))
+        .add("/{id}/movements", get(movements))
 }
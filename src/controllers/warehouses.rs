@@ -68,6 +68,7 @@ async fn update(
     active.name = ActiveValue::set(params.name.clone());
     active.latitude = ActiveValue::set(params.latitude);
     active.longitude = ActiveValue::set(params.longitude);
+    active.postal_code = ActiveValue::set(params.postal_code.clone());
     let updated = active.update(&ctx.db).await?;
     format::json(ApiResponse::success(WarehouseResponse::from(updated)))
 }
@@ -36,6 +36,7 @@ pub struct WarehouseResponse {
     pub name: String,
     pub latitude: f64,
     pub longitude: f64,
+    pub postal_code: Option<String>,
 }
 
 impl From<crate::models::_entities::warehouses::Model> for WarehouseResponse {
@@ -45,6 +46,7 @@ impl From<crate::models::_entities::warehouses::Model> for WarehouseResponse {
             name: m.name,
             latitude: m.latitude,
             longitude: m.longitude,
+            postal_code: m.postal_code,
         }
     }
 }
@@ -148,6 +150,18 @@ impl From<crate::models::_entities::prices::Model> for PriceResponse {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceQuoteResponse {
+    pub variant_pid: Uuid,
+    pub currency: String,
+    pub region: Option<String>,
+    pub quantity: i32,
+    pub unit_price: i64,
+    pub total: i64,
+    pub price: PriceResponse,
+    pub ends_at: Option<String>,
+}
+
 // ─── Customer ────────────────────────────────────────────
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -189,6 +203,8 @@ pub struct CartResponse {
     pub subtotal: i64,
     pub tax: i64,
     pub shipping: i64,
+    pub discount: i64,
+    pub coupon_code: Option<String>,
     pub total: i64,
     pub last_activity_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -206,6 +222,8 @@ impl From<crate::models::_entities::carts::Model> for CartResponse {
             subtotal: m.subtotal,
             tax: m.tax,
             shipping: m.shipping,
+            discount: m.discount,
+            coupon_code: m.coupon_code,
             total: m.total,
             last_activity_at: m.last_activity_at.to_string(),
             items: None,
@@ -234,6 +252,94 @@ impl From<crate::models::_entities::cart_items::Model> for CartItemResponse {
     }
 }
 
+// ─── Coupon ──────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CouponResponse {
+    pub pid: Uuid,
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: String,
+    pub value: i64,
+    pub min_subtotal: Option<i64>,
+    pub usage_limit: Option<i32>,
+    pub usage_limit_per_customer: Option<i32>,
+    pub usage_count: i32,
+    pub product_ids: serde_json::Value,
+    pub category_ids: serde_json::Value,
+    pub collection_ids: serde_json::Value,
+    pub starts_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub active: bool,
+    pub created_at: String,
+}
+
+impl From<crate::models::_entities::coupons::Model> for CouponResponse {
+    fn from(m: crate::models::_entities::coupons::Model) -> Self {
+        Self {
+            pid: m.pid,
+            code: m.code,
+            description: m.description,
+            discount_type: m.discount_type,
+            value: m.value,
+            min_subtotal: m.min_subtotal,
+            usage_limit: m.usage_limit,
+            usage_limit_per_customer: m.usage_limit_per_customer,
+            usage_count: m.usage_count,
+            product_ids: m.product_ids,
+            category_ids: m.category_ids,
+            collection_ids: m.collection_ids,
+            starts_at: m.starts_at.map(|t| t.to_string()),
+            ends_at: m.ends_at.map(|t| t.to_string()),
+            active: m.active,
+            created_at: m.created_at.to_string(),
+        }
+    }
+}
+
+// ─── Shipping Rate ───────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShippingRateResponse {
+    pub pid: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub price: i64,
+    pub min_subtotal: Option<i64>,
+    pub postal_code_start: Option<String>,
+    pub postal_code_end: Option<String>,
+    pub min_weight_grams: Option<i32>,
+    pub max_weight_grams: Option<i32>,
+    pub delivery_days: i32,
+    pub warehouse_id: Option<i32>,
+    pub instructions: Option<String>,
+    pub sort_order: i32,
+    pub active: bool,
+    pub created_at: String,
+}
+
+impl From<crate::models::_entities::shipping_rates::Model> for ShippingRateResponse {
+    fn from(m: crate::models::_entities::shipping_rates::Model) -> Self {
+        Self {
+            pid: m.pid,
+            name: m.name,
+            kind: m.kind,
+            price: m.price,
+            min_subtotal: m.min_subtotal,
+            postal_code_start: m.postal_code_start,
+            postal_code_end: m.postal_code_end,
+            min_weight_grams: m.min_weight_grams,
+            max_weight_grams: m.max_weight_grams,
+            delivery_days: m.delivery_days,
+            warehouse_id: m.warehouse_id,
+            instructions: m.instructions,
+            sort_order: m.sort_order,
+            active: m.active,
+            created_at: m.created_at.to_string(),
+        }
+    }
+}
+
 // ─── Order ───────────────────────────────────────────────
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -248,6 +354,7 @@ pub struct OrderResponse {
     pub tax: i64,
     pub shipping: i64,
     pub discount: i64,
+    pub coupon_code: Option<String>,
     pub total: i64,
     pub payment_method: Option<String>,
     pub notes: Option<String>,
@@ -270,6 +377,7 @@ impl From<crate::models::_entities::orders::Model> for OrderResponse {
             tax: m.tax,
             shipping: m.shipping,
             discount: m.discount,
+            coupon_code: m.coupon_code,
             total: m.total,
             payment_method: m.payment_method,
             notes: m.notes,
@@ -303,6 +411,170 @@ impl From<crate::models::_entities::order_items::Model> for OrderItemResponse {
     }
 }
 
+// ─── Order Status History ────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderStatusHistoryResponse {
+    pub axis: String,
+    pub from_status: String,
+    pub to_status: String,
+    pub changed_by: Option<i32>,
+    pub source: String,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+impl From<crate::models::_entities::order_status_history::Model> for OrderStatusHistoryResponse {
+    fn from(m: crate::models::_entities::order_status_history::Model) -> Self {
+        Self {
+            axis: m.axis,
+            from_status: m.from_status,
+            to_status: m.to_status,
+            changed_by: m.changed_by,
+            source: m.source,
+            reason: m.reason,
+            created_at: m.created_at.to_string(),
+        }
+    }
+}
+
+// ─── Order Edit ──────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderEditResponse {
+    pub pid: Uuid,
+    pub reason: Option<String>,
+    pub changes: serde_json::Value,
+    pub previous_total: i64,
+    pub new_total: i64,
+    pub difference: i64,
+    pub settlement: String,
+    pub settlement_status: String,
+    pub payment_id: Option<String>,
+    pub settlement_data: serde_json::Value,
+    pub edited_by: Option<i32>,
+    pub created_at: String,
+}
+
+impl From<crate::models::_entities::order_edits::Model> for OrderEditResponse {
+    fn from(m: crate::models::_entities::order_edits::Model) -> Self {
+        Self {
+            pid: m.pid,
+            reason: m.reason,
+            changes: m.changes,
+            previous_total: m.previous_total,
+            new_total: m.new_total,
+            difference: m.difference,
+            settlement: m.settlement,
+            settlement_status: m.settlement_status,
+            payment_id: m.payment_id,
+            settlement_data: m.settlement_data,
+            edited_by: m.edited_by,
+            created_at: m.created_at.to_string(),
+        }
+    }
+}
+
+// ─── Refund ──────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefundResponse {
+    pub pid: Uuid,
+    pub amount: i64,
+    pub reason: Option<String>,
+    pub status: String,
+    pub provider: String,
+    pub restock: bool,
+    pub items: serde_json::Value,
+    pub error: Option<String>,
+    pub refunded_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<crate::models::_entities::refunds::Model> for RefundResponse {
+    fn from(m: crate::models::_entities::refunds::Model) -> Self {
+        Self {
+            pid: m.pid,
+            amount: m.amount,
+            reason: m.reason,
+            status: m.status,
+            provider: m.provider,
+            restock: m.restock,
+            items: m.items,
+            error: m.error,
+            refunded_at: m.refunded_at.map(|t| t.to_string()),
+            created_at: m.created_at.to_string(),
+        }
+    }
+}
+
+// ─── Return ──────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReturnResponse {
+    pub pid: Uuid,
+    pub status: String,
+    pub resolution: String,
+    pub reason: String,
+    pub admin_notes: Option<String>,
+    pub rejection_reason: Option<String>,
+    pub carrier: Option<String>,
+    pub tracking_code: Option<String>,
+    pub label_url: Option<String>,
+    pub reviewed_at: Option<String>,
+    pub received_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<ReturnItemResponse>>,
+}
+
+impl From<crate::models::_entities::returns::Model> for ReturnResponse {
+    fn from(m: crate::models::_entities::returns::Model) -> Self {
+        Self {
+            pid: m.pid,
+            status: m.status,
+            resolution: m.resolution,
+            reason: m.reason,
+            admin_notes: m.admin_notes,
+            rejection_reason: m.rejection_reason,
+            carrier: m.carrier,
+            tracking_code: m.tracking_code,
+            label_url: m.label_url,
+            reviewed_at: m.reviewed_at.map(|t| t.to_string()),
+            received_at: m.received_at.map(|t| t.to_string()),
+            completed_at: m.completed_at.map(|t| t.to_string()),
+            created_at: m.created_at.to_string(),
+            items: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReturnItemResponse {
+    pub id: i32,
+    pub order_item_id: i32,
+    pub quantity: i32,
+    pub reason: Option<String>,
+    pub exchange_variant_id: Option<i32>,
+    pub condition: Option<String>,
+    pub restocked_quantity: i32,
+}
+
+impl From<crate::models::_entities::return_items::Model> for ReturnItemResponse {
+    fn from(m: crate::models::_entities::return_items::Model) -> Self {
+        Self {
+            id: m.id,
+            order_item_id: m.order_item_id,
+            quantity: m.quantity,
+            reason: m.reason,
+            exchange_variant_id: m.exchange_variant_id,
+            condition: m.condition,
+            restocked_quantity: m.restocked_quantity,
+        }
+    }
+}
+
 // ─── Collection ──────────────────────────────────────────
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -371,6 +643,7 @@ pub struct ItemResponse {
     pub variant_id: i32,
     pub batch: Option<String>,
     pub expiration: Option<chrono::NaiveDate>,
+    pub landed_cost: Option<i64>,
 }
 
 impl From<crate::models::_entities::items::Model> for ItemResponse {
@@ -380,6 +653,7 @@ impl From<crate::models::_entities::items::Model> for ItemResponse {
             variant_id: m.variant_id,
             batch: m.batch,
             expiration: m.expiration,
+            landed_cost: m.landed_cost,
         }
     }
 }
@@ -392,6 +666,7 @@ pub struct StockResponse {
     pub warehouse_id: i32,
     pub item_id: i32,
     pub quantity: i32,
+    pub reserved: i32,
 }
 
 impl From<crate::models::_entities::stocks::Model> for StockResponse {
@@ -401,6 +676,230 @@ impl From<crate::models::_entities::stocks::Model> for StockResponse {
             warehouse_id: m.warehouse_id,
             item_id: m.item_id,
             quantity: m.quantity,
+            reserved: m.reserved,
+        }
+    }
+}
+
+// ─── Stock Transfer ──────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockTransferResponse {
+    pub pid: Uuid,
+    pub source_warehouse_id: i32,
+    pub destination_warehouse_id: i32,
+    pub status: String,
+    pub discrepancy: bool,
+    pub notes: Option<String>,
+    pub shipped_at: Option<String>,
+    pub received_at: Option<String>,
+    pub canceled_at: Option<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<StockTransferItemResponse>>,
+}
+
+impl From<crate::models::_entities::stock_transfers::Model> for StockTransferResponse {
+    fn from(m: crate::models::_entities::stock_transfers::Model) -> Self {
+        Self {
+            pid: m.pid,
+            source_warehouse_id: m.source_warehouse_id,
+            destination_warehouse_id: m.destination_warehouse_id,
+            status: m.status,
+            discrepancy: m.discrepancy,
+            notes: m.notes,
+            shipped_at: m.shipped_at.map(|t| t.to_string()),
+            received_at: m.received_at.map(|t| t.to_string()),
+            canceled_at: m.canceled_at.map(|t| t.to_string()),
+            created_at: m.created_at.to_string(),
+            items: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockTransferItemResponse {
+    pub id: i32,
+    pub item_id: i32,
+    pub quantity: i32,
+    pub shipped_quantity: i32,
+    pub received_quantity: Option<i32>,
+    pub discrepancy_reason: Option<String>,
+}
+
+impl From<crate::models::_entities::stock_transfer_items::Model> for StockTransferItemResponse {
+    fn from(m: crate::models::_entities::stock_transfer_items::Model) -> Self {
+        Self {
+            id: m.id,
+            item_id: m.item_id,
+            quantity: m.quantity,
+            shipped_quantity: m.shipped_quantity,
+            received_quantity: m.received_quantity,
+            discrepancy_reason: m.discrepancy_reason,
+        }
+    }
+}
+
+// ─── Stock Movement ──────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockMovementResponse {
+    pub id: i32,
+    pub stock_id: i32,
+    pub kind: String,
+    pub quantity: i32,
+    pub reserved: i32,
+    pub balance_after: i32,
+    pub reserved_after: i32,
+    pub reason: Option<String>,
+    pub notes: Option<String>,
+    pub order_id: Option<i32>,
+    pub reference: Option<String>,
+    pub created_by: Option<i32>,
+    pub created_at: String,
+}
+
+impl From<crate::models::_entities::stock_movements::Model> for StockMovementResponse {
+    fn from(m: crate::models::_entities::stock_movements::Model) -> Self {
+        Self {
+            id: m.id,
+            stock_id: m.stock_id,
+            kind: m.kind,
+            quantity: m.quantity,
+            reserved: m.reserved,
+            balance_after: m.balance_after,
+            reserved_after: m.reserved_after,
+            reason: m.reason,
+            notes: m.notes,
+            order_id: m.order_id,
+            reference: m.reference,
+            created_by: m.created_by,
+            created_at: m.created_at.to_string(),
+        }
+    }
+}
+
+// ─── Supplier ────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierResponse {
+    pub id: i32,
+    pub pid: Uuid,
+    pub name: String,
+    pub document: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: String,
+}
+
+impl From<crate::models::_entities::suppliers::Model> for SupplierResponse {
+    fn from(m: crate::models::_entities::suppliers::Model) -> Self {
+        Self {
+            id: m.id,
+            pid: m.pid,
+            name: m.name,
+            document: m.document,
+            email: m.email,
+            phone: m.phone,
+            notes: m.notes,
+            created_at: m.created_at.to_string(),
+        }
+    }
+}
+
+// ─── Purchase Order ──────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderResponse {
+    pub pid: Uuid,
+    pub supplier_id: i32,
+    pub warehouse_id: i32,
+    pub status: String,
+    pub supplier_reference: Option<String>,
+    pub freight_cost: i64,
+    pub other_costs: i64,
+    pub expected_at: Option<chrono::NaiveDate>,
+    pub notes: Option<String>,
+    pub received_at: Option<String>,
+    pub canceled_at: Option<String>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<PurchaseOrderItemResponse>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipts: Option<Vec<PurchaseOrderReceiptResponse>>,
+}
+
+impl From<crate::models::_entities::purchase_orders::Model> for PurchaseOrderResponse {
+    fn from(m: crate::models::_entities::purchase_orders::Model) -> Self {
+        Self {
+            pid: m.pid,
+            supplier_id: m.supplier_id,
+            warehouse_id: m.warehouse_id,
+            status: m.status,
+            supplier_reference: m.supplier_reference,
+            freight_cost: m.freight_cost,
+            other_costs: m.other_costs,
+            expected_at: m.expected_at,
+            notes: m.notes,
+            received_at: m.received_at.map(|t| t.to_string()),
+            canceled_at: m.canceled_at.map(|t| t.to_string()),
+            created_at: m.created_at.to_string(),
+            items: None,
+            receipts: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderItemResponse {
+    pub id: i32,
+    pub variant_id: i32,
+    pub quantity: i32,
+    pub received_quantity: i32,
+    pub unit_cost: i64,
+    pub landed_unit_cost: i64,
+}
+
+impl From<crate::models::_entities::purchase_order_items::Model> for PurchaseOrderItemResponse {
+    fn from(m: crate::models::_entities::purchase_order_items::Model) -> Self {
+        Self {
+            id: m.id,
+            variant_id: m.variant_id,
+            quantity: m.quantity,
+            received_quantity: m.received_quantity,
+            unit_cost: m.unit_cost,
+            landed_unit_cost: m.landed_unit_cost,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderReceiptResponse {
+    pub id: i32,
+    pub purchase_order_item_id: i32,
+    pub stock_id: Option<i32>,
+    pub quantity: i32,
+    pub landed_unit_cost: i64,
+    pub received_by: Option<i32>,
+    pub created_at: String,
+    /// Lote criado no recebimento
+    pub item: Option<ItemResponse>,
+}
+
+impl From<crate::models::_entities::purchase_order_receipts::Model>
+    for PurchaseOrderReceiptResponse
+{
+    fn from(m: crate::models::_entities::purchase_order_receipts::Model) -> Self {
+        Self {
+            id: m.id,
+            purchase_order_item_id: m.purchase_order_item_id,
+            stock_id: m.stock_id,
+            quantity: m.quantity,
+            landed_unit_cost: m.landed_unit_cost,
+            received_by: m.received_by,
+            created_at: m.created_at.to_string(),
+            item: None,
         }
     }
 }
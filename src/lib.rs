@@ -8,6 +8,7 @@ pub mod env;
 pub mod initializers;
 pub mod mailers;
 pub mod models;
+pub mod payments;
 pub mod services;
 pub mod shipping;
 pub mod tasks;
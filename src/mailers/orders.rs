@@ -0,0 +1,96 @@
+// orders mailer
+#![allow(non_upper_case_globals)]
+
+use loco_rs::prelude::*;
+use serde_json::json;
+
+use crate::models::_entities::{customers, order_notes, orders, users};
+
+static payment_expired: Dir<'_> = include_dir!("src/mailers/orders/payment_expired");
+static note_mention: Dir<'_> = include_dir!("src/mailers/orders/note_mention");
+
+/// Valor em centavos como "R$ 1.234,56"
+fn format_brl(cents: i64) -> String {
+    let reais = (cents.abs() / 100).to_string();
+    let mut grouped = String::new();
+    for (i, digit) in reais.chars().enumerate() {
+        if i > 0 && (reais.len() - i) % 3 == 0 {
+            grouped.push('.');
+        }
+        grouped.push(digit);
+    }
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}R$ {},{:02}", sign, grouped, cents.abs() % 100)
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct OrderMailer {}
+impl Mailer for OrderMailer {}
+impl OrderMailer {
+    /// Avisa o cliente que o pedido foi cancelado porque a cobrança venceu
+    ///
+    /// # Errors
+    ///
+    /// When email sending is failed
+    pub async fn send_payment_expired(
+        ctx: &AppContext,
+        customer: &customers::Model,
+        order: &orders::Model,
+    ) -> Result<()> {
+        let due_date = order
+            .payment_due_date()
+            .map(|d| d.format("%d/%m/%Y").to_string())
+            .unwrap_or_default();
+        Self::mail_template(
+            ctx,
+            &payment_expired,
+            mailer::Args {
+                to: customer.email.to_string(),
+                locals: json!({
+                  "name": customer.first_name,
+                  "order_number": order.order_number,
+                  "total": format_brl(order.total),
+                  "due_date": due_date,
+                  "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Avisa o colaborador mencionado (@) em uma nota interna do pedido
+    ///
+    /// # Errors
+    ///
+    /// When email sending is failed
+    pub async fn send_note_mention(
+        ctx: &AppContext,
+        to: &users::Model,
+        author: &str,
+        order: &orders::Model,
+        note: &order_notes::Model,
+    ) -> Result<()> {
+        Self::mail_template(
+            ctx,
+            &note_mention,
+            mailer::Args {
+                to: to.email.to_string(),
+                locals: json!({
+                  "name": to.name,
+                  "author": author,
+                  "order_number": order.order_number,
+                  "order_pid": order.pid.to_string(),
+                  "body": note.body,
+                  "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+}
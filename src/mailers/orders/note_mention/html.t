@@ -0,0 +1,15 @@
+;<html>
+
+<body>
+  <p>Olá {{name}},</p>
+  <p>
+    <strong>{{author}}</strong> mencionou você em uma nota do pedido
+    <strong>{{order_number}}</strong>:
+  </p>
+  <blockquote>{{body}}</blockquote>
+  <p>
+    <a href="{{domain}}/painel/pedidos/{{order_pid}}">Ver pedido no painel</a>
+  </p>
+</body>
+
+</html>
@@ -0,0 +1 @@
+{{author}} mencionou você no pedido {{order_number}}
@@ -0,0 +1,8 @@
+Olá {{name}},
+
+{{author}} mencionou você em uma nota do pedido {{order_number}}:
+
+{{body}}
+
+Ver pedido no painel:
+{{domain}}/painel/pedidos/{{order_pid}}
@@ -0,0 +1,15 @@
+;<html>
+
+<body>
+  <p>Olá {{name}},</p>
+  <p>
+    O pagamento do pedido <strong>{{order_number}}</strong> ({{total}}) venceu em {{due_date}}
+    e não foi identificado, por isso o pedido foi cancelado.
+  </p>
+  <p>
+    Se ainda quiser os produtos, é só fazer um novo pedido na
+    <a href="{{domain}}">loja</a>.
+  </p>
+</body>
+
+</html>
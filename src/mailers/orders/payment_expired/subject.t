@@ -0,0 +1 @@
+Pedido {{order_number}} cancelado por falta de pagamento
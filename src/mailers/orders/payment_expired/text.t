@@ -0,0 +1,6 @@
+Olá {{name}},
+
+O pagamento do pedido {{order_number}} ({{total}}) venceu em {{due_date}} e não foi identificado, por isso o pedido foi cancelado.
+
+Se ainda quiser os produtos, é só fazer um novo pedido na loja:
+{{domain}}
@@ -19,12 +19,14 @@ pub struct Model {
     pub subtotal: i64,
     pub tax: i64,
     pub shipping: i64,
+    pub discount: i64,
     pub total: i64,
     pub metadata: Json,
     pub expires_at: Option<DateTimeWithTimeZone>,
     pub completed_at: Option<DateTimeWithTimeZone>,
     pub last_activity_at: DateTimeWithTimeZone,
     pub recovery_token: Option<String>,
+    pub coupon_code: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,43 @@
+//! `SeaORM` Entity for Coupons
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "coupons")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub pid: Uuid,
+    #[sea_orm(unique)]
+    pub code: String,
+    pub description: Option<String>,
+    /// Tipo: 'percentage' | 'fixed_amount' | 'free_shipping'
+    pub discount_type: String,
+    /// percentage: 1..100 | fixed_amount: centavos
+    pub value: i64,
+    pub min_subtotal: Option<i64>,
+    pub usage_limit: Option<i32>,
+    pub usage_limit_per_customer: Option<i32>,
+    pub usage_count: i32,
+    pub product_ids: Json,
+    pub category_ids: Json,
+    pub collection_ids: Json,
+    pub starts_at: Option<DateTimeWithTimeZone>,
+    pub ends_at: Option<DateTimeWithTimeZone>,
+    pub active: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::coupon_redemptions::Entity")]
+    Redemptions,
+}
+
+impl Related<super::coupon_redemptions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Redemptions.def()
+    }
+}
@@ -12,6 +12,8 @@ pub struct Model {
     pub variant_id: i32,
     pub batch: Option<String>,
     pub expiration: Option<Date>,
+    /// Custo unitário de aquisição com frete e despesas rateados (centavos)
+    pub landed_cost: Option<i64>,
     pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
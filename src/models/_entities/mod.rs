@@ -6,17 +6,39 @@ pub mod carts;
 pub mod categories;
 pub mod collection_products;
 pub mod collections;
+pub mod coupon_redemptions;
+pub mod coupons;
 pub mod customers;
+pub mod order_edits;
 pub mod order_items;
+pub mod order_note_mentions;
+pub mod order_notes;
+pub mod order_sequences;
+pub mod order_status_history;
+pub mod order_shipping_items;
 pub mod order_shippings;
 pub mod orders;
+pub mod payment_events;
 pub mod prelude;
 pub mod prices;
 pub mod product_images;
 pub mod product_variants;
 pub mod products;
+pub mod refunds;
+pub mod return_items;
+pub mod returns;
+pub mod shipment_tracking_events;
+pub mod shipping_rates;
 pub mod store_collaborators;
 pub mod users;
 pub mod warehouses;
 pub mod items;
 pub mod stocks;
+pub mod stock_reservations;
+pub mod stock_movements;
+pub mod stock_transfer_items;
+pub mod stock_transfers;
+pub mod purchase_order_items;
+pub mod purchase_order_receipts;
+pub mod purchase_orders;
+pub mod suppliers;
@@ -0,0 +1,62 @@
+//! `SeaORM` Entity — Edições do pedido depois de criado
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "order_edits")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub pid: Uuid,
+    pub order_id: i32,
+    pub edited_by: Option<i32>,
+    pub reason: Option<String>,
+    /// Alterações aplicadas: `[{ action, order_item_pid, variant_id, from, to }]`
+    pub changes: Json,
+    /// Centavos
+    pub previous_total: i64,
+    pub new_total: i64,
+    /// `new_total - previous_total`
+    pub difference: i64,
+    /// 'none' | 'charge' | 'refund' | 'reissue'
+    pub settlement: String,
+    /// 'none' | 'pending' | 'done' | 'failed'
+    pub settlement_status: String,
+    /// Cobrança adicional no gateway
+    pub payment_id: Option<String>,
+    pub refund_id: Option<i32>,
+    /// Resposta do gateway ou erro do acerto
+    pub settlement_data: Json,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::EditedBy",
+        to = "super::users::Column::Id"
+    )]
+    EditedBy,
+    #[sea_orm(
+        belongs_to = "super::refunds::Entity",
+        from = "Column::RefundId",
+        to = "super::refunds::Column::Id"
+    )]
+    Refund,
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
@@ -0,0 +1,39 @@
+//! `SeaORM` Entity — Colaboradores mencionados em notas do pedido
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "order_note_mentions")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub note_id: i32,
+    /// Colaborador mencionado (`store_collaborators.id`)
+    pub collaborator_id: i32,
+    pub read_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::order_notes::Entity",
+        from = "Column::NoteId",
+        to = "super::order_notes::Column::Id"
+    )]
+    Note,
+    #[sea_orm(
+        belongs_to = "super::store_collaborators::Entity",
+        from = "Column::CollaboratorId",
+        to = "super::store_collaborators::Column::Id"
+    )]
+    Collaborator,
+}
+
+impl Related<super::order_notes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Note.def()
+    }
+}
@@ -0,0 +1,49 @@
+//! `SeaORM` Entity — Notas internas do pedido
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "order_notes")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub pid: Uuid,
+    pub order_id: i32,
+    /// Colaborador autor (`store_collaborators.id`)
+    pub author_id: Option<i32>,
+    pub body: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+    #[sea_orm(
+        belongs_to = "super::store_collaborators::Entity",
+        from = "Column::AuthorId",
+        to = "super::store_collaborators::Column::Id"
+    )]
+    Author,
+    #[sea_orm(has_many = "super::order_note_mentions::Entity")]
+    Mentions,
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
+
+impl Related<super::order_note_mentions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Mentions.def()
+    }
+}
@@ -0,0 +1,18 @@
+//! `SeaORM` Entity — Sequências de número de pedido
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "order_sequences")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    /// Prefixo do número do pedido (ex.: "LFS-")
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+    pub last_value: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
@@ -0,0 +1,44 @@
+//! `SeaORM` Entity — Itens do pedido contidos em cada envio
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "order_shipping_items")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub shipping_id: i32,
+    pub order_item_id: i32,
+    pub quantity: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::order_shippings::Entity",
+        from = "Column::ShippingId",
+        to = "super::order_shippings::Column::Id"
+    )]
+    Shipping,
+    #[sea_orm(
+        belongs_to = "super::order_items::Entity",
+        from = "Column::OrderItemId",
+        to = "super::order_items::Column::Id"
+    )]
+    OrderItem,
+}
+
+impl Related<super::order_shippings::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Shipping.def()
+    }
+}
+
+impl Related<super::order_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderItem.def()
+    }
+}
@@ -23,6 +23,8 @@ pub struct Model {
     pub provider: Option<String>,
     pub provider_data: Json,
     pub notes: Option<String>,
+    /// Depósito de onde o volume saiu
+    pub warehouse_id: Option<i32>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
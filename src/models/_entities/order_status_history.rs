@@ -0,0 +1,45 @@
+//! `SeaORM` Entity — Histórico de transições de status do pedido
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "order_status_history")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub order_id: i32,
+    /// 'status' | 'payment_status' | 'fulfillment_status'
+    pub axis: String,
+    pub from_status: String,
+    pub to_status: String,
+    /// Usuário que fez a mudança; `None` nas mudanças automáticas
+    pub changed_by: Option<i32>,
+    /// Origem: 'admin' | 'painel' | 'webhook' | 'refund' | 'worker' | ...
+    pub source: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::ChangedBy",
+        to = "super::users::Column::Id"
+    )]
+    ChangedBy,
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
@@ -33,6 +33,9 @@ pub struct Model {
     pub metadata: Json,
     pub canceled_at: Option<DateTimeWithTimeZone>,
     pub paid_at: Option<DateTimeWithTimeZone>,
+    pub coupon_code: Option<String>,
+    pub payment_due_date: Option<Date>,
+    pub payment_expiry_checked_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,46 @@
+//! `SeaORM` Entity — Eventos recebidos do gateway de pagamento
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "payment_events")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Gateway: 'asaas'
+    pub provider: String,
+    /// ID do evento no gateway (chave de idempotência)
+    #[sea_orm(unique)]
+    pub event_id: String,
+    /// Ex.: 'PAYMENT_RECEIVED'
+    pub event_type: String,
+    pub payment_id: Option<String>,
+    pub order_id: Option<i32>,
+    /// Status resultante (mesmos valores de `orders.payment_status`)
+    pub payment_status: Option<String>,
+    pub occurred_at: Option<DateTimeWithTimeZone>,
+    pub payload: Json,
+    /// 'pending' | 'processed' | 'skipped' | 'failed'
+    pub status: String,
+    pub error: Option<String>,
+    pub processed_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
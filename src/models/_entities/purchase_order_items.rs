@@ -0,0 +1,52 @@
+//! `SeaORM` Entity — Itens de um pedido de compra
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "purchase_order_items")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub purchase_order_id: i32,
+    pub variant_id: i32,
+    /// Quantidade comprada
+    pub quantity: i32,
+    pub received_quantity: i32,
+    /// Custo unitário no fornecedor (centavos)
+    pub unit_cost: i64,
+    /// Custo unitário com frete e despesas do pedido rateados (centavos)
+    pub landed_unit_cost: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::purchase_orders::Entity",
+        from = "Column::PurchaseOrderId",
+        to = "super::purchase_orders::Column::Id"
+    )]
+    PurchaseOrder,
+    #[sea_orm(
+        belongs_to = "super::product_variants::Entity",
+        from = "Column::VariantId",
+        to = "super::product_variants::Column::Id"
+    )]
+    Variant,
+    #[sea_orm(has_many = "super::purchase_order_receipts::Entity")]
+    Receipts,
+}
+
+impl Related<super::purchase_orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PurchaseOrder.def()
+    }
+}
+
+impl Related<super::purchase_order_receipts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Receipts.def()
+    }
+}
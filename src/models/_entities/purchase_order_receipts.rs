@@ -0,0 +1,48 @@
+//! `SeaORM` Entity — Recebimentos de itens de pedidos de compra
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "purchase_order_receipts")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub purchase_order_item_id: i32,
+    /// Lote criado no recebimento
+    pub item_id: i32,
+    pub stock_id: Option<i32>,
+    pub quantity: i32,
+    pub landed_unit_cost: i64,
+    pub received_by: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::purchase_order_items::Entity",
+        from = "Column::PurchaseOrderItemId",
+        to = "super::purchase_order_items::Column::Id"
+    )]
+    PurchaseOrderItem,
+    #[sea_orm(
+        belongs_to = "super::items::Entity",
+        from = "Column::ItemId",
+        to = "super::items::Column::Id"
+    )]
+    Item,
+}
+
+impl Related<super::purchase_order_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PurchaseOrderItem.def()
+    }
+}
+
+impl Related<super::items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Item.def()
+    }
+}
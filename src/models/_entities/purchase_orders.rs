@@ -0,0 +1,61 @@
+//! `SeaORM` Entity — Pedidos de compra a fornecedores
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "purchase_orders")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub pid: Uuid,
+    pub supplier_id: i32,
+    /// Depósito onde a mercadoria é recebida
+    pub warehouse_id: i32,
+    /// 'open' | 'partially_received' | 'received' | 'canceled'
+    pub status: String,
+    /// Número do pedido/nota fiscal no fornecedor
+    pub supplier_reference: Option<String>,
+    /// Frete da compra (centavos), rateado no custo dos itens
+    pub freight_cost: i64,
+    /// Impostos, seguro e demais despesas (centavos), rateados no custo
+    pub other_costs: i64,
+    pub expected_at: Option<Date>,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+    pub received_at: Option<DateTimeWithTimeZone>,
+    pub canceled_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::purchase_order_items::Entity")]
+    Items,
+    #[sea_orm(
+        belongs_to = "super::suppliers::Entity",
+        from = "Column::SupplierId",
+        to = "super::suppliers::Column::Id"
+    )]
+    Supplier,
+    #[sea_orm(
+        belongs_to = "super::warehouses::Entity",
+        from = "Column::WarehouseId",
+        to = "super::warehouses::Column::Id"
+    )]
+    Warehouse,
+}
+
+impl Related<super::purchase_order_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Items.def()
+    }
+}
+
+impl Related<super::suppliers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Supplier.def()
+    }
+}
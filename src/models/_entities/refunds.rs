@@ -0,0 +1,55 @@
+//! `SeaORM` Entity — Estornos de pagamento
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "refunds")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub pid: Uuid,
+    pub order_id: i32,
+    /// Centavos
+    pub amount: i64,
+    pub reason: Option<String>,
+    /// 'pending' | 'done' | 'failed'
+    pub status: String,
+    /// Gateway: 'asaas'
+    pub provider: String,
+    /// Cobrança estornada no gateway
+    pub provider_payment_id: Option<String>,
+    /// Itens devolvidos ao estoque: `[{ order_item_id, variant_id, quantity }]`
+    pub items: Json,
+    pub restock: bool,
+    /// Resposta do gateway
+    pub raw_data: Json,
+    pub error: Option<String>,
+    pub created_by: Option<i32>,
+    pub refunded_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::CreatedBy",
+        to = "super::users::Column::Id"
+    )]
+    CreatedBy,
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
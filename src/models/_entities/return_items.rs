@@ -0,0 +1,52 @@
+//! `SeaORM` Entity — Itens de devoluções
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "return_items")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub return_id: i32,
+    pub order_item_id: i32,
+    pub quantity: i32,
+    pub reason: Option<String>,
+    /// Variante a enviar na troca
+    pub exchange_variant_id: Option<i32>,
+    /// Inspeção no recebimento: 'sellable' | 'damaged'
+    pub condition: Option<String>,
+    pub restocked_quantity: i32,
+    /// Linha de estoque que recebeu as unidades
+    pub stock_id: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::returns::Entity",
+        from = "Column::ReturnId",
+        to = "super::returns::Column::Id"
+    )]
+    Return,
+    #[sea_orm(
+        belongs_to = "super::order_items::Entity",
+        from = "Column::OrderItemId",
+        to = "super::order_items::Column::Id"
+    )]
+    OrderItem,
+}
+
+impl Related<super::returns::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Return.def()
+    }
+}
+
+impl Related<super::order_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderItem.def()
+    }
+}
@@ -0,0 +1,69 @@
+//! `SeaORM` Entity — Devoluções e trocas (RMA)
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "returns")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub pid: Uuid,
+    pub order_id: i32,
+    pub customer_id: i32,
+    /// 'requested' | 'approved' | 'rejected' | 'received' | 'refunding' |
+    /// 'completed'
+    pub status: String,
+    /// 'refund' | 'exchange'
+    pub resolution: String,
+    pub reason: String,
+    pub admin_notes: Option<String>,
+    pub rejection_reason: Option<String>,
+    /// Etiqueta de logística reversa
+    pub carrier: Option<String>,
+    pub tracking_code: Option<String>,
+    pub label_url: Option<String>,
+    pub provider_data: Json,
+    /// Depósito que recebeu os itens
+    pub warehouse_id: Option<i32>,
+    pub refund_id: Option<i32>,
+    /// Pedido de troca gerado
+    pub exchange_order_id: Option<i32>,
+    pub reviewed_by: Option<i32>,
+    pub reviewed_at: Option<DateTimeWithTimeZone>,
+    pub received_at: Option<DateTimeWithTimeZone>,
+    pub completed_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+    #[sea_orm(
+        belongs_to = "super::customers::Entity",
+        from = "Column::CustomerId",
+        to = "super::customers::Column::Id"
+    )]
+    Customer,
+    #[sea_orm(has_many = "super::return_items::Entity")]
+    Items,
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
+
+impl Related<super::return_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Items.def()
+    }
+}
@@ -0,0 +1,38 @@
+//! `SeaORM` Entity — Histórico de rastreio de envios
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "shipment_tracking_events")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub shipping_id: i32,
+    /// Mesmos valores de `order_shippings.status`
+    pub status: String,
+    pub description: String,
+    pub location: Option<String>,
+    /// Data/hora como devolvida pelo provider
+    pub occurred_at: String,
+    /// Origem: 'webhook' | 'poll'
+    pub source: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::order_shippings::Entity",
+        from = "Column::ShippingId",
+        to = "super::order_shippings::Column::Id"
+    )]
+    Shipping,
+}
+
+impl Related<super::order_shippings::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Shipping.def()
+    }
+}
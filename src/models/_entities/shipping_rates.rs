@@ -0,0 +1,47 @@
+//! `SeaORM` Entity for ShippingRates
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "shipping_rates")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub pid: Uuid,
+    pub name: String,
+    /// Tipo: 'pickup' | 'flat_rate' | 'free_shipping' | 'table_rate'
+    pub kind: String,
+    /// Centavos (ignorado em 'free_shipping')
+    pub price: i64,
+    pub min_subtotal: Option<i64>,
+    pub postal_code_start: Option<String>,
+    pub postal_code_end: Option<String>,
+    pub min_weight_grams: Option<i32>,
+    pub max_weight_grams: Option<i32>,
+    pub delivery_days: i32,
+    pub warehouse_id: Option<i32>,
+    pub instructions: Option<String>,
+    pub sort_order: i32,
+    pub active: bool,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::warehouses::Entity",
+        from = "Column::WarehouseId",
+        to = "super::warehouses::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Warehouses,
+}
+
+impl Related<super::warehouses::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Warehouses.def()
+    }
+}
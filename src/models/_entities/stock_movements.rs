@@ -0,0 +1,59 @@
+//! `SeaORM` Entity — Razão de movimentações de estoque
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "stock_movements")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub stock_id: i32,
+    /// 'receipt' | 'sale' | 'reservation' | 'release' | 'adjustment' | 'transfer' | 'return'
+    pub kind: String,
+    /// Variação de `stocks.quantity` (com sinal)
+    pub quantity: i32,
+    /// Variação de `stocks.reserved` (com sinal)
+    pub reserved: i32,
+    /// `stocks.quantity` depois do movimento
+    pub balance_after: i32,
+    /// `stocks.reserved` depois do movimento
+    pub reserved_after: i32,
+    /// Código do motivo (ex.: 'inventory_count', 'damaged')
+    pub reason: Option<String>,
+    pub notes: Option<String>,
+    pub order_id: Option<i32>,
+    /// Documento de origem (ex.: PID da devolução)
+    pub reference: Option<String>,
+    pub created_by: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::stocks::Entity",
+        from = "Column::StockId",
+        to = "super::stocks::Column::Id"
+    )]
+    Stock,
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::CreatedBy",
+        to = "super::users::Column::Id"
+    )]
+    CreatedBy,
+}
+
+impl Related<super::stocks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Stock.def()
+    }
+}
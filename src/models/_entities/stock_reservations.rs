@@ -0,0 +1,44 @@
+//! `SeaORM` Entity — Reservas de estoque por pedido
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "stock_reservations")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub pid: Uuid,
+    pub order_id: i32,
+    pub variant_id: i32,
+    /// Linha de `stocks` reservada; `None` = backorder
+    pub stock_id: Option<i32>,
+    pub quantity: i32,
+    /// Status: 'active' | 'committed' | 'released' | 'expired'
+    pub status: String,
+    pub expires_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::orders::Entity",
+        from = "Column::OrderId",
+        to = "super::orders::Column::Id"
+    )]
+    Order,
+    #[sea_orm(
+        belongs_to = "super::stocks::Entity",
+        from = "Column::StockId",
+        to = "super::stocks::Column::Id"
+    )]
+    Stock,
+}
+
+impl Related<super::orders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Order.def()
+    }
+}
@@ -0,0 +1,46 @@
+//! `SeaORM` Entity — Itens de uma transferência de estoque
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "stock_transfer_items")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub transfer_id: i32,
+    pub item_id: i32,
+    /// Quantidade solicitada
+    pub quantity: i32,
+    pub shipped_quantity: i32,
+    /// `None` até o recebimento
+    pub received_quantity: Option<i32>,
+    /// Motivo da diferença entre enviado e recebido
+    pub discrepancy_reason: Option<String>,
+    pub source_stock_id: Option<i32>,
+    pub destination_stock_id: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::stock_transfers::Entity",
+        from = "Column::TransferId",
+        to = "super::stock_transfers::Column::Id"
+    )]
+    Transfer,
+    #[sea_orm(
+        belongs_to = "super::items::Entity",
+        from = "Column::ItemId",
+        to = "super::items::Column::Id"
+    )]
+    Item,
+}
+
+impl Related<super::stock_transfers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transfer.def()
+    }
+}
@@ -0,0 +1,52 @@
+//! `SeaORM` Entity — Transferências de estoque entre depósitos
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "stock_transfers")]
+pub struct Model {
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub pid: Uuid,
+    pub source_warehouse_id: i32,
+    pub destination_warehouse_id: i32,
+    /// 'pending' | 'shipped' | 'received' | 'canceled'
+    pub status: String,
+    /// Recebido com quantidade diferente da enviada
+    pub discrepancy: bool,
+    pub notes: Option<String>,
+    pub created_by: Option<i32>,
+    pub shipped_by: Option<i32>,
+    pub received_by: Option<i32>,
+    pub shipped_at: Option<DateTimeWithTimeZone>,
+    pub received_at: Option<DateTimeWithTimeZone>,
+    pub canceled_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::stock_transfer_items::Entity")]
+    Items,
+    #[sea_orm(
+        belongs_to = "super::warehouses::Entity",
+        from = "Column::SourceWarehouseId",
+        to = "super::warehouses::Column::Id"
+    )]
+    SourceWarehouse,
+    #[sea_orm(
+        belongs_to = "super::warehouses::Entity",
+        from = "Column::DestinationWarehouseId",
+        to = "super::warehouses::Column::Id"
+    )]
+    DestinationWarehouse,
+}
+
+impl Related<super::stock_transfer_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Items.def()
+    }
+}
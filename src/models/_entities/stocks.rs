@@ -11,6 +11,8 @@ pub struct Model {
     pub warehouse_id: i32,
     pub item_id: i32,
     pub quantity: i32,
+    /// Unidades reservadas por pedidos ainda não pagos
+    pub reserved: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
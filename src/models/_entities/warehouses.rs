@@ -12,6 +12,8 @@ pub struct Model {
     pub name: String,
     pub latitude: f64,
     pub longitude: f64,
+    /// CEP do depósito (origem do frete)
+    pub postal_code: Option<String>,
     pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
@@ -3,6 +3,10 @@ use uuid::Uuid;
 
 pub use super::_entities::cart_items;
 pub use super::_entities::carts::{self, ActiveModel, Entity, Model};
+use super::coupons::{CouponEvaluation, Model as CouponModel};
+use super::product_variants::Model as ProductVariantModel;
+use crate::services::pricing::{self, PriceQuery};
+use crate::shipping::{FreightOption, ParcelItem};
 
 use loco_rs::prelude::*;
 
@@ -10,6 +14,8 @@ use loco_rs::prelude::*;
 pub struct AddToCartParams {
     pub variant_id: i32,
     pub quantity: i32,
+    /// Região usada na resolução de preço (fica gravada no carrinho)
+    pub region: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -40,6 +46,7 @@ impl Model {
             subtotal: ActiveValue::set(0),
             tax: ActiveValue::set(0),
             shipping: ActiveValue::set(0),
+            discount: ActiveValue::set(0),
             total: ActiveValue::set(0),
             metadata: ActiveValue::set(serde_json::json!({})),
             last_activity_at: ActiveValue::set(now.into()),
@@ -72,34 +79,129 @@ impl Model {
         cart.ok_or_else(|| ModelError::EntityNotFound)
     }
 
-    /// Adiciona item ao carrinho
+    /// Região de preço do carrinho (`metadata.region`)
+    pub fn region(&self) -> Option<String> {
+        self.metadata
+            .get("region")
+            .and_then(|r| r.as_str())
+            .map(ToString::to_string)
+    }
+
+    /// Define a região de preço do carrinho e reprecifica todos os itens
+    /// nela, recalculando os totais. Se algum item não tiver preço na
+    /// região, nada é alterado.
+    pub async fn set_region(
+        db: &DatabaseConnection,
+        cart: Self,
+        region: &str,
+    ) -> ModelResult<Self> {
+        let mut metadata = cart.metadata.clone();
+        if let Some(obj) = metadata.as_object_mut() {
+            obj.insert("region".to_string(), serde_json::json!(region));
+        }
+        let mut repriced = cart.clone();
+        repriced.metadata = metadata.clone();
+
+        let mut prices = Vec::new();
+        for item in Self::get_items(db, cart.id).await? {
+            let unit_price =
+                Self::resolve_unit_price(db, &repriced, item.variant_id, item.quantity).await?;
+            prices.push((item, unit_price));
+        }
+
+        let txn = db.begin().await?;
+        for (item, unit_price) in prices {
+            if item.unit_price == unit_price {
+                continue;
+            }
+            let quantity = item.quantity;
+            let mut active: cart_items::ActiveModel = item.into();
+            active.unit_price = ActiveValue::set(unit_price);
+            active.total = ActiveValue::set(unit_price * quantity as i64);
+            active.update(&txn).await?;
+        }
+        let mut active: carts::ActiveModel = cart.into();
+        active.metadata = ActiveValue::set(metadata);
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Self::recalculate_totals(db, updated.id).await
+    }
+
+    /// Resolve o preço unitário da variante para a quantidade, na moeda e
+    /// região do carrinho. Sem preço aplicável retorna `ModelError::Message`.
+    async fn resolve_unit_price(
+        db: &DatabaseConnection,
+        cart: &Self,
+        variant_id: i32,
+        quantity: i32,
+    ) -> ModelResult<i64> {
+        let region = cart.region();
+        let query = PriceQuery::now(variant_id, &cart.currency, quantity, region.as_deref());
+        match pricing::resolve_price(db, &query).await {
+            Ok(quote) => Ok(quote.unit_price),
+            Err(ModelError::EntityNotFound) => Err(ModelError::Message(format!(
+                "Variante {} sem preço em {} para {} unidade(s)",
+                variant_id, cart.currency, quantity
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Descarta o frete escolhido (`shipping` e `metadata.shipping_option`):
+    /// o preço foi cotado para outro conteúdo e precisa ser cotado de novo
+    async fn clear_shipping_option(db: &DatabaseConnection, cart_id: i32) -> ModelResult<()> {
+        let cart = Entity::find_by_id(cart_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let mut metadata = cart.metadata.clone();
+        let removed = metadata
+            .as_object_mut()
+            .and_then(|obj| obj.remove("shipping_option"))
+            .is_some();
+        if !removed && cart.shipping == 0 {
+            return Ok(());
+        }
+        let mut active: carts::ActiveModel = cart.into();
+        active.shipping = ActiveValue::set(0);
+        active.metadata = ActiveValue::set(metadata);
+        active.update(db).await?;
+        Ok(())
+    }
+
+    /// Adiciona item ao carrinho.
+    /// O preço unitário é resolvido para a quantidade final do item.
+    /// O frete escolhido é descartado.
     pub async fn add_item(
         db: &DatabaseConnection,
-        cart_id: i32,
+        cart: &Self,
         variant_id: i32,
         quantity: i32,
-        unit_price: i64,
     ) -> ModelResult<cart_items::Model> {
         // Verifica se já existe item com essa variante
         let existing = cart_items::Entity::find()
-            .filter(cart_items::Column::CartId.eq(cart_id))
+            .filter(cart_items::Column::CartId.eq(cart.id))
             .filter(cart_items::Column::VariantId.eq(variant_id))
             .one(db)
             .await?;
 
         if let Some(existing_item) = existing {
-            // Atualiza quantidade
+            // Atualiza quantidade (pode mudar de faixa de preço)
             let new_qty = existing_item.quantity + quantity;
+            let unit_price = Self::resolve_unit_price(db, cart, variant_id, new_qty).await?;
             let mut active: cart_items::ActiveModel = existing_item.into();
             active.quantity = ActiveValue::set(new_qty);
+            active.unit_price = ActiveValue::set(unit_price);
             active.total = ActiveValue::set(unit_price * new_qty as i64);
             let updated = active.update(db).await?;
+            Self::clear_shipping_option(db, cart.id).await?;
             return Ok(updated);
         }
 
+        let unit_price = Self::resolve_unit_price(db, cart, variant_id, quantity).await?;
         let item = cart_items::ActiveModel {
             pid: ActiveValue::set(Uuid::new_v4()),
-            cart_id: ActiveValue::set(cart_id),
+            cart_id: ActiveValue::set(cart.id),
             variant_id: ActiveValue::set(variant_id),
             quantity: ActiveValue::set(quantity),
             unit_price: ActiveValue::set(unit_price),
@@ -108,36 +210,48 @@ impl Model {
             ..Default::default()
         };
         let item = item.insert(db).await?;
+        Self::clear_shipping_option(db, cart.id).await?;
         Ok(item)
     }
 
-    /// Atualiza quantidade de um item
+    /// Atualiza quantidade de um item, reavaliando o preço unitário.
+    /// O frete escolhido é descartado.
     pub async fn update_item_quantity(
         db: &DatabaseConnection,
+        cart: &Self,
         item_id: i32,
         quantity: i32,
     ) -> ModelResult<cart_items::Model> {
         let item = cart_items::Entity::find_by_id(item_id)
+            .filter(cart_items::Column::CartId.eq(cart.id))
             .one(db)
             .await?
             .ok_or(ModelError::EntityNotFound)?;
 
+        let unit_price = Self::resolve_unit_price(db, cart, item.variant_id, quantity).await?;
         let mut active: cart_items::ActiveModel = item.into();
         active.quantity = ActiveValue::set(quantity);
-        // Recalcula total com unit_price existente
-        let unit_price_val = cart_items::Entity::find_by_id(item_id)
-            .one(db)
-            .await?
-            .map(|i| i.unit_price)
-            .unwrap_or(0);
-        active.total = ActiveValue::set(unit_price_val * quantity as i64);
+        active.unit_price = ActiveValue::set(unit_price);
+        active.total = ActiveValue::set(unit_price * quantity as i64);
         let updated = active.update(db).await?;
+        Self::clear_shipping_option(db, cart.id).await?;
         Ok(updated)
     }
 
-    /// Remove item do carrinho
-    pub async fn remove_item(db: &DatabaseConnection, item_id: i32) -> ModelResult<()> {
-        cart_items::Entity::delete_by_id(item_id).exec(db).await?;
+    /// Remove item do carrinho. O frete escolhido é descartado.
+    pub async fn remove_item(
+        db: &DatabaseConnection,
+        cart: &Self,
+        item_id: i32,
+    ) -> ModelResult<()> {
+        let result = cart_items::Entity::delete_many()
+            .filter(cart_items::Column::Id.eq(item_id))
+            .filter(cart_items::Column::CartId.eq(cart.id))
+            .exec(db)
+            .await?;
+        if result.rows_affected > 0 {
+            Self::clear_shipping_option(db, cart.id).await?;
+        }
         Ok(())
     }
 
@@ -153,7 +267,10 @@ impl Model {
         Ok(items)
     }
 
-    /// Recalcula totais do carrinho
+    /// Recalcula totais do carrinho.
+    ///
+    /// Se houver cupom aplicado ele é reavaliado: um cupom que deixou de
+    /// valer (expirou, esgotou, subtotal abaixo do mínimo) é removido.
     pub async fn recalculate_totals(db: &DatabaseConnection, cart_id: i32) -> ModelResult<Self> {
         let items = Self::get_items(db, cart_id).await?;
         let subtotal: i64 = items.iter().map(|i| i.total).sum();
@@ -163,15 +280,117 @@ impl Model {
             .await?
             .ok_or(ModelError::EntityNotFound)?;
 
-        let total = subtotal + cart.tax + cart.shipping;
+        let mut coupon_code = cart.coupon_code.clone();
+        let mut discount = 0;
+        if let Some(code) = &cart.coupon_code {
+            match CouponModel::find_by_code(db, code).await {
+                Ok(coupon) => match coupon.evaluate(db, &cart, &items, cart.customer_id).await? {
+                    CouponEvaluation::Applied { discount: amount } => discount = amount,
+                    CouponEvaluation::Rejected(reason) => {
+                        tracing::info!(cart_id, code = %code, reason = %reason, "Cupom removido do carrinho");
+                        coupon_code = None;
+                    }
+                },
+                Err(ModelError::EntityNotFound) => coupon_code = None,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let total = (subtotal + cart.tax + cart.shipping - discount).max(0);
         let mut active: carts::ActiveModel = cart.into();
         active.subtotal = ActiveValue::set(subtotal);
+        active.discount = ActiveValue::set(discount);
+        active.coupon_code = ActiveValue::set(coupon_code);
         active.total = ActiveValue::set(total);
         active.last_activity_at = ActiveValue::set(chrono::Utc::now().into());
         let updated = active.update(db).await?;
         Ok(updated)
     }
 
+    /// Aplica um cupom ao carrinho.
+    ///
+    /// Retorna `ModelError::Message` com o motivo quando o cupom não se aplica.
+    pub async fn apply_coupon(
+        db: &DatabaseConnection,
+        cart_id: i32,
+        code: &str,
+    ) -> ModelResult<Self> {
+        let cart = Entity::find_by_id(cart_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let coupon = CouponModel::find_by_code(db, code)
+            .await
+            .map_err(|_| ModelError::msg("Cupom não encontrado"))?;
+
+        let items = Self::get_items(db, cart_id).await?;
+        if let CouponEvaluation::Rejected(reason) =
+            coupon.evaluate(db, &cart, &items, cart.customer_id).await?
+        {
+            return Err(ModelError::Message(reason));
+        }
+
+        let mut active: carts::ActiveModel = cart.into();
+        active.coupon_code = ActiveValue::set(Some(coupon.code));
+        active.update(db).await?;
+        Self::recalculate_totals(db, cart_id).await
+    }
+
+    /// Remove o cupom do carrinho
+    pub async fn remove_coupon(db: &DatabaseConnection, cart_id: i32) -> ModelResult<Self> {
+        let cart = Entity::find_by_id(cart_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        let mut active: carts::ActiveModel = cart.into();
+        active.coupon_code = ActiveValue::set(None);
+        active.update(db).await?;
+        Self::recalculate_totals(db, cart_id).await
+    }
+
+    /// Itens do carrinho com peso e dimensões para o empacotamento
+    /// (ver `ProductVariantModel::parcel_item`).
+    pub async fn parcel_items(
+        db: &DatabaseConnection,
+        cart_id: i32,
+    ) -> ModelResult<Vec<ParcelItem>> {
+        let items = Self::get_items(db, cart_id).await?;
+        let mut parcels = Vec::with_capacity(items.len());
+
+        for item in items {
+            parcels.push(
+                ProductVariantModel::parcel_item(
+                    db,
+                    item.variant_id,
+                    item.quantity,
+                    item.unit_price,
+                )
+                .await?,
+            );
+        }
+        Ok(parcels)
+    }
+
+    /// Grava a opção de frete escolhida (`metadata.shipping_option`) e usa
+    /// o preço dela como `shipping` do carrinho.
+    pub async fn select_shipping_option(
+        db: &DatabaseConnection,
+        cart: Self,
+        option: &FreightOption,
+    ) -> ModelResult<Self> {
+        let cart_id = cart.id;
+        let mut metadata = cart.metadata.clone();
+        if let Some(obj) = metadata.as_object_mut() {
+            obj.insert("shipping_option".to_string(), serde_json::json!(option));
+        }
+        let mut active: carts::ActiveModel = cart.into();
+        active.shipping = ActiveValue::set(option.price_cents);
+        active.metadata = ActiveValue::set(metadata);
+        active.update(db).await?;
+        Self::recalculate_totals(db, cart_id).await
+    }
+
     /// Marca carrinho como completed
     pub async fn complete(db: &DatabaseConnection, cart_id: i32) -> ModelResult<Self> {
         let cart = Entity::find_by_id(cart_id)
@@ -0,0 +1,390 @@
+use sea_orm::{sea_query::Expr, ConnectionTrait, PaginatorTrait, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+pub use super::_entities::coupon_redemptions;
+pub use super::_entities::coupons::{self, ActiveModel, Entity, Model};
+use super::_entities::{cart_items, carts, collection_products, product_variants, products};
+use loco_rs::prelude::*;
+
+/// Tipos de desconto suportados
+pub const DISCOUNT_TYPES: &[&str] = &["percentage", "fixed_amount", "free_shipping"];
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateCouponParams {
+    pub code: String,
+    pub description: Option<String>,
+    /// 'percentage' | 'fixed_amount' | 'free_shipping'
+    pub discount_type: String,
+    /// percentage: 1..100 | fixed_amount: centavos
+    pub value: Option<i64>,
+    pub min_subtotal: Option<i64>,
+    pub usage_limit: Option<i32>,
+    pub usage_limit_per_customer: Option<i32>,
+    pub product_ids: Option<Vec<i32>>,
+    pub category_ids: Option<Vec<i32>>,
+    pub collection_ids: Option<Vec<i32>>,
+    pub starts_at: Option<DateTimeWithTimeZone>,
+    pub ends_at: Option<DateTimeWithTimeZone>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateCouponParams {
+    pub description: Option<String>,
+    pub discount_type: Option<String>,
+    pub value: Option<i64>,
+    pub min_subtotal: Option<i64>,
+    pub usage_limit: Option<i32>,
+    pub usage_limit_per_customer: Option<i32>,
+    pub product_ids: Option<Vec<i32>>,
+    pub category_ids: Option<Vec<i32>>,
+    pub collection_ids: Option<Vec<i32>>,
+    pub starts_at: Option<DateTimeWithTimeZone>,
+    pub ends_at: Option<DateTimeWithTimeZone>,
+    pub active: Option<bool>,
+}
+
+/// Resultado da avaliação de um cupom sobre um carrinho
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CouponEvaluation {
+    Applied { discount: i64 },
+    Rejected(String),
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for coupon_redemptions::ActiveModel {}
+
+/// Códigos são armazenados em maiúsculas, sem espaços nas pontas
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+/// Valida tipo e valor do desconto
+pub fn validate_discount(discount_type: &str, value: i64) -> Result<(), String> {
+    match discount_type {
+        "percentage" if !(1..=100).contains(&value) => {
+            Err("Percentual deve estar entre 1 e 100".to_string())
+        }
+        "fixed_amount" if value <= 0 => Err("Valor do desconto deve ser positivo".to_string()),
+        t if !DISCOUNT_TYPES.contains(&t) => Err(format!("Tipo de desconto inválido: {}", t)),
+        _ => Ok(()),
+    }
+}
+
+fn json_ids(value: &serde_json::Value) -> Vec<i32> {
+    value
+        .as_array()
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|v| v.as_i64().map(|id| id as i32))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Model {
+    /// Cria um cupom
+    pub async fn create_coupon(
+        db: &DatabaseConnection,
+        params: &CreateCouponParams,
+    ) -> ModelResult<Self> {
+        let value = params.value.unwrap_or(0);
+        validate_discount(&params.discount_type, value).map_err(ModelError::Message)?;
+
+        let coupon = coupons::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            code: ActiveValue::set(normalize_code(&params.code)),
+            description: ActiveValue::set(params.description.clone()),
+            discount_type: ActiveValue::set(params.discount_type.clone()),
+            value: ActiveValue::set(value),
+            min_subtotal: ActiveValue::set(params.min_subtotal),
+            usage_limit: ActiveValue::set(params.usage_limit),
+            usage_limit_per_customer: ActiveValue::set(params.usage_limit_per_customer),
+            usage_count: ActiveValue::set(0),
+            product_ids: ActiveValue::set(serde_json::json!(params
+                .product_ids
+                .clone()
+                .unwrap_or_default())),
+            category_ids: ActiveValue::set(serde_json::json!(params
+                .category_ids
+                .clone()
+                .unwrap_or_default())),
+            collection_ids: ActiveValue::set(serde_json::json!(params
+                .collection_ids
+                .clone()
+                .unwrap_or_default())),
+            starts_at: ActiveValue::set(params.starts_at),
+            ends_at: ActiveValue::set(params.ends_at),
+            active: ActiveValue::set(params.active.unwrap_or(true)),
+            ..Default::default()
+        };
+        let coupon = coupon.insert(db).await?;
+        Ok(coupon)
+    }
+
+    /// Atualiza um cupom (o código não pode ser alterado)
+    pub async fn update_coupon(
+        db: &DatabaseConnection,
+        coupon: Self,
+        params: &UpdateCouponParams,
+    ) -> ModelResult<Self> {
+        let discount_type = params
+            .discount_type
+            .clone()
+            .unwrap_or_else(|| coupon.discount_type.clone());
+        let value = params.value.unwrap_or(coupon.value);
+        validate_discount(&discount_type, value).map_err(ModelError::Message)?;
+
+        let mut active: coupons::ActiveModel = coupon.into();
+        active.discount_type = ActiveValue::set(discount_type);
+        active.value = ActiveValue::set(value);
+        if let Some(description) = &params.description {
+            active.description = ActiveValue::set(Some(description.clone()));
+        }
+        if let Some(min_subtotal) = params.min_subtotal {
+            active.min_subtotal = ActiveValue::set(Some(min_subtotal));
+        }
+        if let Some(limit) = params.usage_limit {
+            active.usage_limit = ActiveValue::set(Some(limit));
+        }
+        if let Some(limit) = params.usage_limit_per_customer {
+            active.usage_limit_per_customer = ActiveValue::set(Some(limit));
+        }
+        if let Some(ids) = &params.product_ids {
+            active.product_ids = ActiveValue::set(serde_json::json!(ids));
+        }
+        if let Some(ids) = &params.category_ids {
+            active.category_ids = ActiveValue::set(serde_json::json!(ids));
+        }
+        if let Some(ids) = &params.collection_ids {
+            active.collection_ids = ActiveValue::set(serde_json::json!(ids));
+        }
+        if let Some(starts_at) = params.starts_at {
+            active.starts_at = ActiveValue::set(Some(starts_at));
+        }
+        if let Some(ends_at) = params.ends_at {
+            active.ends_at = ActiveValue::set(Some(ends_at));
+        }
+        if let Some(is_active) = params.active {
+            active.active = ActiveValue::set(is_active);
+        }
+
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Busca pelo PID
+    pub async fn find_by_pid(db: &DatabaseConnection, pid: &Uuid) -> ModelResult<Self> {
+        let coupon = Entity::find()
+            .filter(coupons::Column::Pid.eq(*pid))
+            .one(db)
+            .await?;
+        coupon.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Busca pelo código (case-insensitive)
+    pub async fn find_by_code<C: ConnectionTrait>(db: &C, code: &str) -> ModelResult<Self> {
+        let coupon = Entity::find()
+            .filter(coupons::Column::Code.eq(normalize_code(code)))
+            .one(db)
+            .await?;
+        coupon.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Lista cupons
+    pub async fn list_all(db: &DatabaseConnection) -> ModelResult<Vec<Self>> {
+        let coupons = Entity::find()
+            .order_by_desc(coupons::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(coupons)
+    }
+
+    /// Avalia o cupom sobre o carrinho e calcula o desconto em centavos.
+    ///
+    /// `customer_id` é usado para o limite por cliente; sem cliente
+    /// identificado o limite só é verificado na criação do pedido.
+    pub async fn evaluate<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        cart: &carts::Model,
+        items: &[cart_items::Model],
+        customer_id: Option<i32>,
+    ) -> ModelResult<CouponEvaluation> {
+        let reject = |msg: &str| Ok(CouponEvaluation::Rejected(msg.to_string()));
+        let now = chrono::Utc::now();
+
+        if !self.active {
+            return reject("Cupom inativo");
+        }
+        if self.starts_at.is_some_and(|s| s > now) {
+            return reject("Cupom ainda não está válido");
+        }
+        if self.ends_at.is_some_and(|e| e < now) {
+            return reject("Cupom expirado");
+        }
+        if self.usage_limit.is_some_and(|l| self.usage_count >= l) {
+            return reject("Cupom esgotado");
+        }
+        if let (Some(limit), Some(customer_id)) = (self.usage_limit_per_customer, customer_id) {
+            if self.redemptions_for_customer(db, customer_id).await? >= limit as u64 {
+                return reject("Limite de uso do cupom por cliente atingido");
+            }
+        }
+
+        let subtotal: i64 = items.iter().map(|i| i.total).sum();
+        if let Some(min) = self.min_subtotal {
+            if subtotal < min {
+                return Ok(CouponEvaluation::Rejected(format!(
+                    "Subtotal mínimo para o cupom: {} centavos",
+                    min
+                )));
+            }
+        }
+
+        let eligible = self.eligible_subtotal(db, items).await?;
+        if eligible == 0 {
+            return reject("Nenhum item do carrinho é elegível para o cupom");
+        }
+
+        let discount = match self.discount_type.as_str() {
+            "percentage" => eligible * self.value / 100,
+            "fixed_amount" => self.value.min(eligible),
+            "free_shipping" => cart.shipping,
+            _ => 0,
+        };
+        Ok(CouponEvaluation::Applied { discount })
+    }
+
+    /// Registra o uso do cupom para um pedido.
+    ///
+    /// Roda na transação de criação do pedido: a linha do cupom é bloqueada
+    /// e os limites são conferidos novamente antes de incrementar o uso.
+    pub async fn redeem<C: ConnectionTrait>(
+        db: &C,
+        code: &str,
+        order_id: i32,
+        customer_id: i32,
+        discount_amount: i64,
+    ) -> ModelResult<coupon_redemptions::Model> {
+        let coupon = Entity::find()
+            .filter(coupons::Column::Code.eq(normalize_code(code)))
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        if coupon.usage_limit.is_some_and(|l| coupon.usage_count >= l) {
+            return Err(ModelError::msg("Cupom esgotado"));
+        }
+        if let Some(limit) = coupon.usage_limit_per_customer {
+            if coupon.redemptions_for_customer(db, customer_id).await? >= limit as u64 {
+                return Err(ModelError::msg(
+                    "Limite de uso do cupom por cliente atingido",
+                ));
+            }
+        }
+
+        let coupon_id = coupon.id;
+        let usage_count = coupon.usage_count + 1;
+        let mut active: coupons::ActiveModel = coupon.into();
+        active.usage_count = ActiveValue::set(usage_count);
+        active.update(db).await?;
+
+        let redemption = coupon_redemptions::ActiveModel {
+            coupon_id: ActiveValue::set(coupon_id),
+            order_id: ActiveValue::set(order_id),
+            customer_id: ActiveValue::set(customer_id),
+            discount_amount: ActiveValue::set(discount_amount),
+            ..Default::default()
+        };
+        let redemption = redemption.insert(db).await?;
+        Ok(redemption)
+    }
+
+    /// Desfaz o uso de cupons de um pedido (cancelamento)
+    pub async fn release_for_order<C: ConnectionTrait>(db: &C, order_id: i32) -> ModelResult<()> {
+        let redemptions = coupon_redemptions::Entity::find()
+            .filter(coupon_redemptions::Column::OrderId.eq(order_id))
+            .all(db)
+            .await?;
+
+        for redemption in redemptions {
+            Entity::update_many()
+                .col_expr(
+                    coupons::Column::UsageCount,
+                    Expr::col(coupons::Column::UsageCount).sub(1),
+                )
+                .filter(coupons::Column::Id.eq(redemption.coupon_id))
+                .exec(db)
+                .await?;
+            coupon_redemptions::Entity::delete_by_id(redemption.id)
+                .exec(db)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn redemptions_for_customer<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        customer_id: i32,
+    ) -> ModelResult<u64> {
+        let count = coupon_redemptions::Entity::find()
+            .filter(coupon_redemptions::Column::CouponId.eq(self.id))
+            .filter(coupon_redemptions::Column::CustomerId.eq(customer_id))
+            .count(db)
+            .await?;
+        Ok(count)
+    }
+
+    /// Soma dos itens que estão no escopo do cupom (produto, categoria ou coleção).
+    /// Sem escopo definido, todo o carrinho é elegível.
+    async fn eligible_subtotal<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        items: &[cart_items::Model],
+    ) -> ModelResult<i64> {
+        let product_scope = json_ids(&self.product_ids);
+        let category_scope = json_ids(&self.category_ids);
+        let collection_scope = json_ids(&self.collection_ids);
+
+        if product_scope.is_empty() && category_scope.is_empty() && collection_scope.is_empty() {
+            return Ok(items.iter().map(|i| i.total).sum());
+        }
+
+        let mut eligible_products: HashSet<i32> = product_scope.into_iter().collect();
+        if !category_scope.is_empty() {
+            let in_categories = products::Entity::find()
+                .filter(products::Column::CategoryId.is_in(category_scope))
+                .all(db)
+                .await?;
+            eligible_products.extend(in_categories.into_iter().map(|p| p.id));
+        }
+        if !collection_scope.is_empty() {
+            let in_collections = collection_products::Entity::find()
+                .filter(collection_products::Column::CollectionId.is_in(collection_scope))
+                .all(db)
+                .await?;
+            eligible_products.extend(in_collections.into_iter().map(|cp| cp.product_id));
+        }
+
+        let variant_ids: Vec<i32> = items.iter().map(|i| i.variant_id).collect();
+        let eligible_variants: HashSet<i32> = product_variants::Entity::find()
+            .filter(product_variants::Column::Id.is_in(variant_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|v| eligible_products.contains(&v.product_id))
+            .map(|v| v.id)
+            .collect();
+
+        Ok(items
+            .iter()
+            .filter(|i| eligible_variants.contains(&i.variant_id))
+            .map(|i| i.total)
+            .sum())
+    }
+}
@@ -2,14 +2,30 @@ pub mod _entities;
 pub mod carts;
 pub mod categories;
 pub mod collections;
+pub mod coupons;
 pub mod customers;
+pub mod order_edits;
+pub mod order_notes;
+pub mod order_sequences;
 pub mod order_shippings;
+pub mod order_status;
+pub mod order_status_history;
+pub mod order_timeline;
 pub mod orders;
+pub mod payment_events;
 pub mod product_variants;
 pub mod products;
+pub mod refunds;
+pub mod returns;
+pub mod shipping_rates;
 pub mod store_collaborators;
 pub mod users;
 
 pub mod warehouses;
 pub mod items;
 pub mod stocks;
+pub mod stock_reservations;
+pub mod stock_movements;
+pub mod stock_transfers;
+pub mod purchase_orders;
+pub mod suppliers;
@@ -0,0 +1,553 @@
+//! Edição do pedido depois de criado
+//!
+//! O admin adiciona, remove ou troca itens, muda quantidades, endereço e
+//! frete enquanto o pedido não foi separado. Os totais são recalculados, o
+//! estoque acompanha (reservas refeitas no pedido em aberto; baixa ou
+//! devolução no pedido pago) e a diferença de valor vira um acerto:
+//!
+//! - pedido pago, valor maior: cobrança adicional no gateway (`charge`)
+//! - pedido pago, valor menor: estorno parcial (`refund`)
+//! - pedido em aberto com cobrança emitida: a cobrança é cancelada e o
+//!   cliente paga o novo total (`reissue`)
+//!
+//! Acerto recusado pelo gateway fica `failed` e pode ser refeito
+//! ([`Model::begin_retry`]).
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use sea_orm::{sea_query::Expr, ConnectionTrait, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::order_edits::{self, ActiveModel, Entity, Model};
+use super::_entities::{addresses, order_items, orders, product_variants};
+use super::order_status::{FulfillmentStatus, OrderStatus, PaymentStatus};
+use super::stock_reservations::{Model as StockReservationModel, ReservationLine};
+use crate::services::pricing::{self, PriceQuery};
+use loco_rs::prelude::*;
+
+/// Alteração de um item. Com `order_item_pid` altera o item (quantidade 0
+/// remove; `variant_pid` troca a variante); sem ele adiciona `variant_pid`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EditItemParams {
+    pub order_item_pid: Option<Uuid>,
+    pub variant_pid: Option<Uuid>,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct EditOrderParams {
+    #[serde(default)]
+    pub items: Vec<EditItemParams>,
+    /// Novo endereço de entrega (do mesmo cliente)
+    pub shipping_address_pid: Option<Uuid>,
+    /// Novo valor do frete, em centavos
+    pub shipping: Option<i64>,
+    /// Novo desconto, em centavos; sem ele o desconto atual é mantido
+    /// (limitado ao novo subtotal)
+    pub discount: Option<i64>,
+    /// Motivo, gravado na edição e no acerto com o gateway
+    pub reason: Option<String>,
+    /// Forma da cobrança adicional: 'PIX' (padrão) | 'BOLETO'
+    pub billing_type: Option<String>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Status de pagamento com o valor já recebido
+fn is_paid(order: &orders::Model) -> bool {
+    matches!(
+        PaymentStatus::from_str(&order.payment_status),
+        Ok(PaymentStatus::Paid)
+    )
+}
+
+impl Model {
+    /// Edições do pedido, da mais antiga à mais recente
+    pub async fn list_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let edits = Entity::find()
+            .filter(order_edits::Column::OrderId.eq(order_id))
+            .order_by_asc(order_edits::Column::Id)
+            .all(db)
+            .await?;
+        Ok(edits)
+    }
+
+    /// Edição do pedido pelo PID
+    pub async fn find_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        pid: &Uuid,
+    ) -> ModelResult<Self> {
+        Entity::find()
+            .filter(order_edits::Column::OrderId.eq(order_id))
+            .filter(order_edits::Column::Pid.eq(*pid))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Edição dona da cobrança no gateway (adicional ou substituída)
+    pub async fn find_by_payment_id<C: ConnectionTrait>(
+        db: &C,
+        payment_id: &str,
+    ) -> ModelResult<Option<Self>> {
+        let edit = Entity::find()
+            .filter(order_edits::Column::PaymentId.eq(payment_id))
+            .one(db)
+            .await?;
+        Ok(edit)
+    }
+
+    /// Aplica a edição ao pedido e grava o registro com o acerto pendente.
+    ///
+    /// Tudo roda numa transação com o pedido bloqueado; se faltar estoque
+    /// para o que foi adicionado, nada é persistido.
+    pub async fn apply(
+        db: &DatabaseConnection,
+        order_id: i32,
+        params: &EditOrderParams,
+        edited_by: Option<i32>,
+    ) -> ModelResult<(Self, orders::Model)> {
+        let txn = db.begin().await?;
+        let order = orders::Entity::find_by_id(order_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        Self::ensure_editable(&order)?;
+
+        let mut changes = Vec::new();
+        let before = quantities_by_variant(&order_items_of(&txn, order.id).await?);
+
+        for edit in &params.items {
+            if edit.quantity < 0 {
+                return Err(ModelError::msg("Quantidade não pode ser negativa"));
+            }
+            match edit.order_item_pid {
+                Some(pid) => {
+                    if let Some(change) = Self::edit_item(&txn, &order, pid, edit).await? {
+                        changes.push(change);
+                    }
+                }
+                None => {
+                    let Some(variant_pid) = edit.variant_pid else {
+                        return Err(ModelError::msg(
+                            "Informe order_item_pid ou variant_pid em cada item",
+                        ));
+                    };
+                    if edit.quantity == 0 {
+                        return Err(ModelError::msg("Quantidade do item novo deve ser positiva"));
+                    }
+                    let variant = find_variant(&txn, variant_pid).await?;
+                    let unit_price =
+                        unit_price(&txn, &order.currency, variant.id, edit.quantity).await?;
+                    let item = order_items::ActiveModel {
+                        pid: ActiveValue::set(Uuid::new_v4()),
+                        order_id: ActiveValue::set(order.id),
+                        variant_id: ActiveValue::set(Some(variant.id)),
+                        title: ActiveValue::set(variant.title.clone()),
+                        sku: ActiveValue::set(variant.sku.clone()),
+                        quantity: ActiveValue::set(edit.quantity),
+                        unit_price: ActiveValue::set(unit_price),
+                        total: ActiveValue::set(unit_price * i64::from(edit.quantity)),
+                        metadata: ActiveValue::set(serde_json::json!({})),
+                        ..Default::default()
+                    }
+                    .insert(&txn)
+                    .await?;
+                    changes.push(serde_json::json!({
+                        "action": "add",
+                        "order_item_pid": item.pid,
+                        "sku": item.sku,
+                        "quantity": item.quantity,
+                        "unit_price": item.unit_price,
+                    }));
+                }
+            }
+        }
+
+        let items = order_items_of(&txn, order.id).await?;
+        if items.is_empty() {
+            return Err(ModelError::msg(
+                "O pedido precisa de ao menos um item; para desistir, cancele o pedido",
+            ));
+        }
+        Self::sync_stock(&txn, &order, &before, &quantities_by_variant(&items)).await?;
+
+        let mut active: orders::ActiveModel = order.clone().into();
+        if let Some(address_pid) = params.shipping_address_pid {
+            let address = addresses::Entity::find()
+                .filter(addresses::Column::Pid.eq(address_pid))
+                .filter(addresses::Column::CustomerId.eq(order.customer_id))
+                .one(&txn)
+                .await?
+                .ok_or_else(|| ModelError::msg("Endereço não encontrado para o cliente"))?;
+            if order.shipping_address_id != Some(address.id) {
+                changes.push(serde_json::json!({
+                    "action": "shipping_address",
+                    "from": order.shipping_address_id,
+                    "to": address.id,
+                }));
+                active.shipping_address_id = ActiveValue::set(Some(address.id));
+            }
+        }
+
+        let shipping = params.shipping.unwrap_or(order.shipping);
+        if shipping < 0 {
+            return Err(ModelError::msg("Frete não pode ser negativo"));
+        }
+        if shipping != order.shipping {
+            changes.push(serde_json::json!({
+                "action": "shipping",
+                "from": order.shipping,
+                "to": shipping,
+            }));
+        }
+
+        let subtotal: i64 = items.iter().map(|i| i.total).sum();
+        let discount = match params.discount {
+            Some(discount) if discount < 0 => {
+                return Err(ModelError::msg("Desconto não pode ser negativo"));
+            }
+            Some(discount) => discount,
+            None => order.discount,
+        }
+        .min(subtotal + shipping);
+        if discount != order.discount {
+            changes.push(serde_json::json!({
+                "action": "discount",
+                "from": order.discount,
+                "to": discount,
+            }));
+        }
+        let total = (subtotal + order.tax + shipping - discount).max(0);
+
+        if changes.is_empty() {
+            return Err(ModelError::msg("Nenhuma alteração informada"));
+        }
+
+        let difference = total - order.total;
+        let previous_payment_id = order.current_payment_id();
+        let (settlement, settlement_status, payment_id) = if difference == 0 {
+            ("none", "none", None)
+        } else if is_paid(&order) {
+            (
+                if difference > 0 { "charge" } else { "refund" },
+                "pending",
+                None,
+            )
+        } else if previous_payment_id.is_some() {
+            ("reissue", "pending", previous_payment_id)
+        } else {
+            ("none", "none", None)
+        };
+
+        active.subtotal = ActiveValue::set(subtotal);
+        active.shipping = ActiveValue::set(shipping);
+        active.discount = ActiveValue::set(discount);
+        active.total = ActiveValue::set(total);
+        let order = active.update(&txn).await?;
+
+        let edit = order_edits::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            order_id: ActiveValue::set(order.id),
+            edited_by: ActiveValue::set(edited_by),
+            reason: ActiveValue::set(params.reason.clone()),
+            changes: ActiveValue::set(serde_json::json!(changes)),
+            previous_total: ActiveValue::set(total - difference),
+            new_total: ActiveValue::set(total),
+            difference: ActiveValue::set(difference),
+            settlement: ActiveValue::set(settlement.to_string()),
+            settlement_status: ActiveValue::set(settlement_status.to_string()),
+            payment_id: ActiveValue::set(payment_id),
+            settlement_data: ActiveValue::set(serde_json::json!({})),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+        Ok((edit, order))
+    }
+
+    /// Registra o resultado do acerto com o gateway
+    pub async fn settle<C: ConnectionTrait>(
+        db: &C,
+        edit: Self,
+        status: &str,
+        payment_id: Option<String>,
+        refund_id: Option<i32>,
+        data: serde_json::Value,
+    ) -> ModelResult<Self> {
+        let mut active: order_edits::ActiveModel = edit.into();
+        active.settlement_status = ActiveValue::set(status.to_string());
+        if payment_id.is_some() {
+            active.payment_id = ActiveValue::set(payment_id);
+        }
+        if refund_id.is_some() {
+            active.refund_id = ActiveValue::set(refund_id);
+        }
+        active.settlement_data = ActiveValue::set(data);
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Retoma o acerto de uma edição que falhou no gateway: `failed` volta a
+    /// `pending` numa atualização condicional, então duas tentativas
+    /// simultâneas não acertam a mesma diferença duas vezes.
+    pub async fn begin_retry<C: ConnectionTrait>(
+        db: &C,
+        order: &orders::Model,
+        edit: Self,
+    ) -> ModelResult<Self> {
+        if !matches!(edit.settlement.as_str(), "charge" | "refund" | "reissue") {
+            return Err(ModelError::msg("Edição sem acerto com o gateway"));
+        }
+        if edit.settlement == "charge" && !is_paid(order) {
+            return Err(ModelError::Message(format!(
+                "Pedido com pagamento '{}' não recebe cobrança adicional",
+                order.payment_status
+            )));
+        }
+        let claimed = Entity::update_many()
+            .col_expr(
+                order_edits::Column::SettlementStatus,
+                Expr::value("pending"),
+            )
+            .filter(order_edits::Column::Id.eq(edit.id))
+            .filter(order_edits::Column::SettlementStatus.eq("failed"))
+            .exec(db)
+            .await?;
+        if claimed.rows_affected == 0 {
+            return Err(ModelError::Message(format!(
+                "Acerto com status '{}' não pode ser refeito",
+                edit.settlement_status
+            )));
+        }
+        Entity::find_by_id(edit.id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Status de pagamento vindo do webhook para a cobrança adicional:
+    /// pago conclui o acerto; cancelado, falho ou estornado marca como falho.
+    /// Cobranças substituídas (`reissue`) não mudam mais nada.
+    pub async fn apply_charge_status<C: ConnectionTrait>(
+        db: &C,
+        edit: Self,
+        payment_status: &str,
+    ) -> ModelResult<Self> {
+        if edit.settlement != "charge" {
+            return Ok(edit);
+        }
+        let status = match payment_status {
+            "paid" => "done",
+            "canceled" | "failed" | "refunded" => "failed",
+            _ => return Ok(edit),
+        };
+        if edit.settlement_status == status {
+            return Ok(edit);
+        }
+        let mut active: order_edits::ActiveModel = edit.into();
+        active.settlement_status = ActiveValue::set(status.to_string());
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Pedido ainda editável: não separado, não cancelado nem estornado
+    fn ensure_editable(order: &orders::Model) -> ModelResult<()> {
+        let status_ok = matches!(
+            OrderStatus::from_str(&order.status),
+            Ok(OrderStatus::Pending | OrderStatus::Confirmed | OrderStatus::Processing)
+        );
+        let fulfillment_ok = matches!(
+            FulfillmentStatus::from_str(&order.fulfillment_status),
+            Ok(FulfillmentStatus::NotFulfilled)
+        );
+        let payment_ok = !matches!(
+            PaymentStatus::from_str(&order.payment_status),
+            Ok(PaymentStatus::PartiallyRefunded
+                | PaymentStatus::Refunded
+                | PaymentStatus::Canceled)
+        );
+        if status_ok && fulfillment_ok && payment_ok {
+            Ok(())
+        } else {
+            Err(ModelError::Message(format!(
+                "Pedido com status '{}', pagamento '{}' e fulfillment '{}' não pode ser editado",
+                order.status, order.payment_status, order.fulfillment_status
+            )))
+        }
+    }
+
+    /// Altera, troca ou remove um item existente. `None` quando nada muda.
+    async fn edit_item<C: ConnectionTrait>(
+        db: &C,
+        order: &orders::Model,
+        pid: Uuid,
+        edit: &EditItemParams,
+    ) -> ModelResult<Option<serde_json::Value>> {
+        let item = order_items::Entity::find()
+            .filter(order_items::Column::Pid.eq(pid))
+            .filter(order_items::Column::OrderId.eq(order.id))
+            .one(db)
+            .await?
+            .ok_or_else(|| ModelError::Message(format!("Item {} não pertence ao pedido", pid)))?;
+
+        if edit.quantity == 0 {
+            let change = serde_json::json!({
+                "action": "remove",
+                "order_item_pid": item.pid,
+                "sku": item.sku,
+                "quantity": item.quantity,
+            });
+            order_items::Entity::delete_by_id(item.id).exec(db).await?;
+            return Ok(Some(change));
+        }
+
+        let replacement = match edit.variant_pid {
+            Some(variant_pid) => {
+                let variant = find_variant(db, variant_pid).await?;
+                (Some(variant.id) != item.variant_id).then_some(variant)
+            }
+            None => None,
+        };
+        let mut active: order_items::ActiveModel = item.clone().into();
+        let change = match replacement {
+            Some(variant) => {
+                // Variante nova: preço de tabela atual
+                let unit_price = unit_price(db, &order.currency, variant.id, edit.quantity).await?;
+                active.variant_id = ActiveValue::set(Some(variant.id));
+                active.title = ActiveValue::set(variant.title.clone());
+                active.sku = ActiveValue::set(variant.sku.clone());
+                active.unit_price = ActiveValue::set(unit_price);
+                active.total = ActiveValue::set(unit_price * i64::from(edit.quantity));
+                serde_json::json!({
+                    "action": "replace",
+                    "order_item_pid": item.pid,
+                    "from": { "sku": item.sku, "quantity": item.quantity, "unit_price": item.unit_price },
+                    "to": { "sku": variant.sku, "quantity": edit.quantity, "unit_price": unit_price },
+                })
+            }
+            None if edit.quantity != item.quantity => {
+                // Mesma variante: mantém o preço pelo qual foi vendida
+                active.total = ActiveValue::set(item.unit_price * i64::from(edit.quantity));
+                serde_json::json!({
+                    "action": "quantity",
+                    "order_item_pid": item.pid,
+                    "sku": item.sku,
+                    "from": item.quantity,
+                    "to": edit.quantity,
+                })
+            }
+            None => return Ok(None),
+        };
+        active.quantity = ActiveValue::set(edit.quantity);
+        active.update(db).await?;
+        Ok(Some(change))
+    }
+
+    /// Acompanha o estoque. Pedido em aberto: as reservas ativas são
+    /// refeitas com os itens novos. Pedido pago: o que entrou é reservado e
+    /// baixado na hora; o que saiu volta para o estoque de onde veio.
+    async fn sync_stock<C: ConnectionTrait>(
+        db: &C,
+        order: &orders::Model,
+        before: &HashMap<i32, i32>,
+        after: &HashMap<i32, i32>,
+    ) -> ModelResult<()> {
+        if !is_paid(order) {
+            StockReservationModel::release_for_order(db, order.id).await?;
+            let lines: Vec<ReservationLine> = after
+                .iter()
+                .map(|(&variant_id, &quantity)| ReservationLine {
+                    variant_id,
+                    quantity,
+                })
+                .collect();
+            StockReservationModel::reserve_for_order(db, order.id, &lines).await?;
+            return Ok(());
+        }
+
+        let mut added = Vec::new();
+        for (&variant_id, &quantity) in after {
+            let delta = quantity - before.get(&variant_id).copied().unwrap_or(0);
+            if delta > 0 {
+                added.push(ReservationLine {
+                    variant_id,
+                    quantity: delta,
+                });
+            }
+        }
+        for (&variant_id, &quantity) in before {
+            let delta = quantity - after.get(&variant_id).copied().unwrap_or(0);
+            if delta > 0 {
+                StockReservationModel::restock_for_order(db, order.id, variant_id, delta).await?;
+            }
+        }
+        if !added.is_empty() {
+            StockReservationModel::reserve_for_order(db, order.id, &added).await?;
+            StockReservationModel::commit_for_order(db, order.id).await?;
+        }
+        Ok(())
+    }
+}
+
+async fn order_items_of<C: ConnectionTrait>(
+    db: &C,
+    order_id: i32,
+) -> ModelResult<Vec<order_items::Model>> {
+    let items = order_items::Entity::find()
+        .filter(order_items::Column::OrderId.eq(order_id))
+        .order_by_asc(order_items::Column::Id)
+        .all(db)
+        .await?;
+    Ok(items)
+}
+
+/// Quantidade por variante (itens sem variante não mexem no estoque)
+fn quantities_by_variant(items: &[order_items::Model]) -> HashMap<i32, i32> {
+    let mut quantities = HashMap::new();
+    for item in items {
+        if let Some(variant_id) = item.variant_id {
+            *quantities.entry(variant_id).or_default() += item.quantity;
+        }
+    }
+    quantities
+}
+
+async fn find_variant<C: ConnectionTrait>(
+    db: &C,
+    pid: Uuid,
+) -> ModelResult<product_variants::Model> {
+    product_variants::Entity::find()
+        .filter(product_variants::Column::Pid.eq(pid))
+        .filter(product_variants::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| ModelError::Message(format!("Variante {} não encontrada", pid)))
+}
+
+/// Preço de tabela atual da variante na moeda do pedido
+async fn unit_price<C: ConnectionTrait>(
+    db: &C,
+    currency: &str,
+    variant_id: i32,
+    quantity: i32,
+) -> ModelResult<i64> {
+    let query = PriceQuery::now(variant_id, currency, quantity, None);
+    match pricing::resolve_price(db, &query).await {
+        Ok(quote) => Ok(quote.unit_price),
+        Err(ModelError::EntityNotFound) => Err(ModelError::Message(format!(
+            "Variante {} sem preço em {} para {} unidade(s)",
+            variant_id, currency, quantity
+        ))),
+        Err(e) => Err(e),
+    }
+}
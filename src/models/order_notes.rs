@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use sea_orm::{ConnectionTrait, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::order_notes::{self, ActiveModel, Entity, Model};
+use super::_entities::{order_note_mentions, store_collaborators, users};
+use loco_rs::prelude::*;
+
+/// Tamanho máximo do texto da nota
+const MAX_BODY_CHARS: usize = 5000;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateNoteParams {
+    /// Texto livre; `@fulano` menciona o colaborador cujo email começa com
+    /// `fulano@` (ou `@fulano@loja.com.br`, com o email completo)
+    pub body: String,
+}
+
+/// Colaborador com o usuário correspondente
+#[derive(Debug, Clone)]
+pub struct Collaborator {
+    pub collaborator: store_collaborators::Model,
+    pub user: users::Model,
+}
+
+impl Collaborator {
+    /// Apelido usado nas menções: parte do email antes do `@`
+    pub fn handle(&self) -> String {
+        self.user
+            .email
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for order_note_mentions::ActiveModel {}
+
+fn by_id(
+    rows: Vec<(store_collaborators::Model, Option<users::Model>)>,
+) -> HashMap<i32, Collaborator> {
+    rows.into_iter()
+        .filter_map(|(collaborator, user)| {
+            user.map(|user| (collaborator.id, Collaborator { collaborator, user }))
+        })
+        .collect()
+}
+
+/// `@apelido` citados no texto, em minúsculas e sem repetição
+pub fn mention_handles(body: &str) -> Vec<String> {
+    let mut handles: Vec<String> = Vec::new();
+    let chars: Vec<char> = body.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        // `@` no início ou depois de espaço/pontuação (não dentro de um email)
+        let starts_mention =
+            chars[i] == '@' && (i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '.'));
+        if !starts_mention {
+            i += 1;
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while end < chars.len()
+            && (chars[end].is_alphanumeric() || matches!(chars[end], '.' | '_' | '-' | '+' | '@'))
+        {
+            end += 1;
+        }
+        let handle: String = chars[start..end].iter().collect::<String>();
+        let handle = handle.trim_end_matches(['.', '@']).to_lowercase();
+        if !handle.is_empty() && !handles.contains(&handle) {
+            handles.push(handle);
+        }
+        i = end;
+    }
+    handles
+}
+
+impl Model {
+    /// Colaboradores ativos com seus usuários, por `store_collaborators.id`
+    pub async fn collaborators<C: ConnectionTrait>(
+        db: &C,
+    ) -> ModelResult<HashMap<i32, Collaborator>> {
+        let rows = store_collaborators::Entity::find()
+            .filter(store_collaborators::Column::Active.eq(true))
+            .find_also_related(users::Entity)
+            .all(db)
+            .await?;
+        Ok(by_id(rows))
+    }
+
+    /// Colaboradores com seus usuários pelos IDs, ativos ou não: autores de
+    /// notas antigas continuam identificados depois de desativados
+    pub async fn collaborators_by_ids<C: ConnectionTrait>(
+        db: &C,
+        ids: Vec<i32>,
+    ) -> ModelResult<HashMap<i32, Collaborator>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = store_collaborators::Entity::find()
+            .filter(store_collaborators::Column::Id.is_in(ids))
+            .find_also_related(users::Entity)
+            .all(db)
+            .await?;
+        Ok(by_id(rows))
+    }
+
+    /// Grava a nota e as menções. Retorna os colaboradores mencionados (sem o
+    /// autor), para notificação; apelidos desconhecidos são ignorados.
+    pub async fn create(
+        db: &DatabaseConnection,
+        order_id: i32,
+        author: &store_collaborators::Model,
+        params: &CreateNoteParams,
+    ) -> ModelResult<(Self, Vec<Collaborator>)> {
+        let body = params.body.trim();
+        if body.is_empty() {
+            return Err(ModelError::msg("A nota não pode ser vazia"));
+        }
+        if body.chars().count() > MAX_BODY_CHARS {
+            return Err(ModelError::Message(format!(
+                "A nota passa do limite de {} caracteres",
+                MAX_BODY_CHARS
+            )));
+        }
+
+        let handles = mention_handles(body);
+        let mentioned: Vec<Collaborator> = if handles.is_empty() {
+            vec![]
+        } else {
+            Self::collaborators(db)
+                .await?
+                .into_values()
+                .filter(|c| c.collaborator.id != author.id)
+                .filter(|c| {
+                    handles.contains(&c.handle()) || handles.contains(&c.user.email.to_lowercase())
+                })
+                .collect()
+        };
+
+        let txn = db.begin().await?;
+        let note = order_notes::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            order_id: ActiveValue::set(order_id),
+            author_id: ActiveValue::set(Some(author.id)),
+            body: ActiveValue::set(body.to_string()),
+            ..Default::default()
+        };
+        let note = note.insert(&txn).await?;
+        for c in &mentioned {
+            order_note_mentions::ActiveModel {
+                note_id: ActiveValue::set(note.id),
+                collaborator_id: ActiveValue::set(c.collaborator.id),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok((note, mentioned))
+    }
+
+    /// Notas do pedido, da mais antiga à mais recente
+    pub async fn list_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let notes = Entity::find()
+            .filter(order_notes::Column::OrderId.eq(order_id))
+            .order_by_asc(order_notes::Column::Id)
+            .all(db)
+            .await?;
+        Ok(notes)
+    }
+
+    /// Menções ao colaborador, mais recentes primeiro, com a nota
+    pub async fn mentions_for(
+        db: &DatabaseConnection,
+        collaborator_id: i32,
+        unread_only: bool,
+        limit: u64,
+    ) -> ModelResult<Vec<(order_note_mentions::Model, Self)>> {
+        let mut query = order_note_mentions::Entity::find()
+            .filter(order_note_mentions::Column::CollaboratorId.eq(collaborator_id));
+        if unread_only {
+            query = query.filter(order_note_mentions::Column::ReadAt.is_null());
+        }
+        let rows = query
+            .find_also_related(Entity)
+            .order_by_desc(order_note_mentions::Column::Id)
+            .limit(limit.min(100))
+            .all(db)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(mention, note)| note.map(|note| (mention, note)))
+            .collect())
+    }
+
+    /// Marca a menção como lida; só o colaborador mencionado pode marcar
+    pub async fn mark_mention_read(
+        db: &DatabaseConnection,
+        mention_id: i32,
+        collaborator_id: i32,
+    ) -> ModelResult<order_note_mentions::Model> {
+        let mention = order_note_mentions::Entity::find_by_id(mention_id)
+            .filter(order_note_mentions::Column::CollaboratorId.eq(collaborator_id))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        if mention.read_at.is_some() {
+            return Ok(mention);
+        }
+        let mut active: order_note_mentions::ActiveModel = mention.into();
+        active.read_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+}
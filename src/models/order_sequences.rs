@@ -0,0 +1,110 @@
+use sea_orm::{sea_query::Expr, ConnectionTrait, QueryOrder, QuerySelect};
+
+pub use super::_entities::order_sequences::{self, ActiveModel, Entity, Model};
+use super::_entities::orders;
+use loco_rs::prelude::*;
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Formato do número de pedido: `{prefix}{seq com zeros à esquerda}`
+///
+/// Configurável por ambiente via `ORDER_NUMBER_PREFIX` (padrão `LFS-`)
+/// e `ORDER_NUMBER_PADDING` (padrão `6`).
+#[derive(Debug, Clone)]
+pub struct OrderNumberConfig {
+    pub prefix: String,
+    pub padding: usize,
+}
+
+impl OrderNumberConfig {
+    pub fn from_env() -> Self {
+        crate::env::load();
+        Self {
+            prefix: std::env::var("ORDER_NUMBER_PREFIX").unwrap_or_else(|_| "LFS-".to_string()),
+            padding: std::env::var("ORDER_NUMBER_PADDING")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(6),
+        }
+    }
+
+    pub fn format(&self, seq: i64) -> String {
+        format!("{}{:0width$}", self.prefix, seq, width = self.padding)
+    }
+}
+
+impl Model {
+    /// Aloca o próximo número da sequência `name`.
+    ///
+    /// Deve rodar na mesma transação que insere o pedido: a linha da
+    /// sequência fica bloqueada até o commit, e um rollback devolve o número.
+    pub async fn next_value<C: ConnectionTrait>(db: &C, name: &str) -> ModelResult<i64> {
+        let current = Entity::find_by_id(name.to_string())
+            .lock_exclusive()
+            .one(db)
+            .await?;
+
+        let next = match current {
+            Some(seq) => {
+                let next = seq.last_value + 1;
+                let mut active: order_sequences::ActiveModel = seq.into();
+                active.last_value = ActiveValue::set(next);
+                active.update(db).await?;
+                next
+            }
+            None => {
+                // Primeiro pedido com este prefixo
+                let seq = order_sequences::ActiveModel {
+                    name: ActiveValue::set(name.to_string()),
+                    last_value: ActiveValue::set(1),
+                    ..Default::default()
+                };
+                seq.insert(db).await?;
+                1
+            }
+        };
+        Ok(next)
+    }
+
+    /// Realinha a sequência com o maior número já gravado em `orders`.
+    ///
+    /// Usado após um conflito de `order_number` (ex.: pedidos importados
+    /// ou criados antes da sequência existir).
+    pub async fn resync(db: &DatabaseConnection, config: &OrderNumberConfig) -> ModelResult<()> {
+        // Com zeros à esquerda, o maior número é o mais longo e, entre os de
+        // mesmo tamanho, o maior em ordem alfabética
+        let max_existing = orders::Entity::find()
+            .select_only()
+            .column(orders::Column::OrderNumber)
+            .filter(orders::Column::OrderNumber.starts_with(&config.prefix))
+            .order_by_desc(Expr::cust("LENGTH(order_number)"))
+            .order_by_desc(orders::Column::OrderNumber)
+            .into_tuple::<String>()
+            .one(db)
+            .await?
+            .and_then(|number| {
+                number
+                    .strip_prefix(&config.prefix)
+                    .and_then(|seq| seq.parse::<i64>().ok())
+            })
+            .unwrap_or(0);
+
+        match Entity::find_by_id(config.prefix.clone()).one(db).await? {
+            Some(seq) if seq.last_value < max_existing => {
+                let mut active: order_sequences::ActiveModel = seq.into();
+                active.last_value = ActiveValue::set(max_existing);
+                active.update(db).await?;
+            }
+            Some(_) => {}
+            None => {
+                let seq = order_sequences::ActiveModel {
+                    name: ActiveValue::set(config.prefix.clone()),
+                    last_value: ActiveValue::set(max_existing),
+                    ..Default::default()
+                };
+                seq.insert(db).await?;
+            }
+        }
+        Ok(())
+    }
+}
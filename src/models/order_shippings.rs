@@ -1,11 +1,34 @@
+use std::collections::HashMap;
+
 use chrono::Utc;
-use sea_orm::{QueryOrder, QuerySelect};
+use sea_orm::{
+    sea_query::Expr, ConnectionTrait, PaginatorTrait, QueryOrder, QuerySelect, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+pub use super::_entities::order_shipping_items;
 pub use super::_entities::order_shippings::{self, ActiveModel, Entity, Model};
+pub use super::_entities::shipment_tracking_events;
+use super::_entities::{order_items, orders};
+use super::order_status::{FulfillmentStatus, TransitionError};
+use super::order_status_history::StatusChange;
+use super::orders::Model as OrderModel;
+use crate::shipping::{ShipmentResult, TrackingInfo};
 use loco_rs::prelude::*;
 
+/// Status em que o envio ainda pode mudar (acompanhados pelo rastreio)
+pub const TRACKABLE_STATUSES: &[&str] = &["pending", "posted", "in_transit", "out_for_delivery"];
+
+/// Status finais: o rastreio não os altera mais
+const FINAL_STATUSES: &[&str] = &["delivered", "returned", "failed"];
+
+/// Envios que não chegaram ao cliente: seus itens voltam a ficar pendentes
+pub const VOID_STATUSES: &[&str] = &["failed", "returned"];
+
+/// `provider_data.label_status` do envio gravado antes da compra da etiqueta
+pub const LABEL_PURCHASING: &str = "purchasing";
+
 /// Parâmetros para registrar ou atualizar um envio manualmente
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateShippingParams {
@@ -15,6 +38,34 @@ pub struct CreateShippingParams {
     pub tracking_url: Option<String>,
     pub estimated_delivery_at: Option<String>,
     pub notes: Option<String>,
+    /// Código do serviço no provider (ex.: "1" = PAC no MelhorEnvio).
+    /// Obrigatório quando o carrier tem integração externa.
+    pub service_code: Option<String>,
+    /// Volume único informado manualmente. Sem ele, os itens do pedido são
+    /// empacotados no catálogo de caixas (`shipping::packing`).
+    pub package: Option<ShippingPackageParams>,
+    /// Itens do pedido neste volume. Vazio = todo o saldo ainda não enviado.
+    #[serde(default)]
+    pub items: Vec<ShipmentLineParams>,
+    /// Depósito de onde o volume sai. Sem ele, o depósito é escolhido pelas
+    /// reservas do pedido (`services::routing`).
+    pub warehouse_pid: Option<Uuid>,
+}
+
+/// Quantidade de um item do pedido incluída no envio
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShipmentLineParams {
+    pub order_item_pid: Uuid,
+    pub quantity: i32,
+}
+
+/// Dimensões e peso do volume para geração de etiqueta
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShippingPackageParams {
+    pub weight_grams: u32,
+    pub length_cm: u32,
+    pub width_cm: u32,
+    pub height_cm: u32,
 }
 
 /// Parâmetros para atualizar o status de um envio
@@ -25,16 +76,65 @@ pub struct UpdateShippingStatusParams {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for shipment_tracking_events::ActiveModel {}
+impl ActiveModelBehavior for order_shipping_items::ActiveModel {}
+
+/// Aplica o novo status e preenche `shipped_at`/`delivered_at`
+fn apply_status(active: &mut order_shippings::ActiveModel, current: &Model, status: &str) {
+    let now: chrono::DateTime<chrono::FixedOffset> =
+        Utc::now().with_timezone(&chrono::FixedOffset::east_opt(0).unwrap());
+
+    active.status = ActiveValue::set(status.to_string());
+    match status {
+        "posted" => {
+            active.shipped_at = ActiveValue::set(Some(now));
+        }
+        // Rastreio pode pular a postagem (ex.: primeiro evento já é "em trânsito")
+        "in_transit" | "out_for_delivery" if current.shipped_at.is_none() => {
+            active.shipped_at = ActiveValue::set(Some(now));
+        }
+        "delivered" => {
+            if current.shipped_at.is_none() {
+                active.shipped_at = ActiveValue::set(Some(now));
+            }
+            active.delivered_at = ActiveValue::set(Some(now));
+        }
+        _ => {}
+    }
+}
 
 impl Model {
-    /// Cria registro de envio para um pedido
+    /// Cria registro de envio para um pedido com os itens de `lines`, saindo
+    /// do depósito `warehouse_id`. As quantidades são conferidas de novo,
+    /// com o pedido bloqueado, contra o que já foi enviado.
     pub async fn create(
         db: &DatabaseConnection,
         order_id: i32,
         params: &CreateShippingParams,
+        lines: &[(order_items::Model, i32)],
+        warehouse_id: Option<i32>,
         provider: Option<&str>,
         provider_data: Option<serde_json::Value>,
     ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        // Serializa envios simultâneos do mesmo pedido
+        orders::Entity::find_by_id(order_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let requested: Vec<ShipmentLineParams> = lines
+            .iter()
+            .map(|(item, quantity)| ShipmentLineParams {
+                order_item_pid: item.pid,
+                quantity: *quantity,
+            })
+            .collect();
+        if requested.is_empty() {
+            return Err(ModelError::msg("Envio sem itens"));
+        }
+        let lines = Self::pending_lines(&txn, order_id, &requested).await?;
+
         let estimated = params
             .estimated_delivery_at
             .as_deref()
@@ -53,12 +153,172 @@ impl Model {
             provider: ActiveValue::set(provider.map(String::from)),
             provider_data: ActiveValue::set(provider_data.unwrap_or_else(|| serde_json::json!({}))),
             notes: ActiveValue::set(params.notes.clone()),
+            warehouse_id: ActiveValue::set(warehouse_id),
             ..Default::default()
         };
-        let saved = shipping.insert(db).await?;
+        let saved = shipping.insert(&txn).await?;
+
+        for (item, quantity) in &lines {
+            order_shipping_items::ActiveModel {
+                shipping_id: ActiveValue::set(saved.id),
+                order_item_id: ActiveValue::set(item.id),
+                quantity: ActiveValue::set(*quantity),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
         Ok(saved)
     }
 
+    /// Itens do pedido a incluir num novo envio, com a quantidade de cada um.
+    ///
+    /// Sem `requested`, retorna todo o saldo ainda não enviado. Quantidade
+    /// acima do saldo do item (pedido − já enviado) é recusada.
+    pub async fn pending_lines<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        requested: &[ShipmentLineParams],
+    ) -> ModelResult<Vec<(order_items::Model, i32)>> {
+        let order_items = order_items::Entity::find()
+            .filter(order_items::Column::OrderId.eq(order_id))
+            .order_by_asc(order_items::Column::Id)
+            .all(db)
+            .await?;
+        let shipped = Self::shipped_quantities(db, order_id).await?;
+        let remaining =
+            |item: &order_items::Model| item.quantity - shipped.get(&item.id).copied().unwrap_or(0);
+
+        if requested.is_empty() {
+            let lines: Vec<_> = order_items
+                .into_iter()
+                .filter_map(|item| {
+                    let qty = remaining(&item);
+                    (qty > 0).then_some((item, qty))
+                })
+                .collect();
+            if lines.is_empty() {
+                return Err(ModelError::msg(
+                    "Todos os itens do pedido já foram enviados",
+                ));
+            }
+            return Ok(lines);
+        }
+
+        let mut lines: Vec<(order_items::Model, i32)> = Vec::with_capacity(requested.len());
+        for line in requested {
+            if line.quantity <= 0 {
+                return Err(ModelError::msg("Quantidade deve ser maior que zero"));
+            }
+            let item = order_items
+                .iter()
+                .find(|i| i.pid == line.order_item_pid)
+                .ok_or_else(|| {
+                    ModelError::Message(format!(
+                        "Item {} não pertence ao pedido",
+                        line.order_item_pid
+                    ))
+                })?;
+            // O mesmo item pode aparecer em mais de uma linha
+            let already = lines
+                .iter()
+                .filter(|(i, _)| i.id == item.id)
+                .map(|(_, q)| *q)
+                .sum::<i32>();
+            if line.quantity + already > remaining(item) {
+                return Err(ModelError::Message(format!(
+                    "Item {}: {} unidade(s) pendente(s) de envio",
+                    item.sku,
+                    remaining(item)
+                )));
+            }
+            lines.push((item.clone(), line.quantity));
+        }
+        Ok(lines)
+    }
+
+    /// Quantidade já enviada por item do pedido (`order_item_id → quantidade`),
+    /// ignorando envios falhos ou devolvidos. Envio sem itens registrados
+    /// (anterior aos envios parciais) conta como o pedido inteiro.
+    pub async fn shipped_quantities<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<HashMap<i32, i32>> {
+        let shipping_ids: Vec<i32> = Entity::find()
+            .filter(order_shippings::Column::OrderId.eq(order_id))
+            .filter(order_shippings::Column::Status.is_not_in(VOID_STATUSES.iter().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        if shipping_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let lines = order_shipping_items::Entity::find()
+            .filter(order_shipping_items::Column::ShippingId.is_in(shipping_ids.clone()))
+            .all(db)
+            .await?;
+        let legacy = shipping_ids
+            .iter()
+            .any(|id| !lines.iter().any(|l| l.shipping_id == *id));
+        if legacy {
+            let order_items = order_items::Entity::find()
+                .filter(order_items::Column::OrderId.eq(order_id))
+                .all(db)
+                .await?;
+            return Ok(order_items
+                .into_iter()
+                .map(|i| (i.id, i.quantity))
+                .collect());
+        }
+
+        let mut shipped: HashMap<i32, i32> = HashMap::new();
+        for line in lines {
+            *shipped.entry(line.order_item_id).or_default() += line.quantity;
+        }
+        Ok(shipped)
+    }
+
+    /// Fulfillment derivado das quantidades enviadas: `fulfilled` com todos os
+    /// itens enviados, `partially_fulfilled` com parte deles
+    pub async fn derived_fulfillment<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<FulfillmentStatus> {
+        let order_items = order_items::Entity::find()
+            .filter(order_items::Column::OrderId.eq(order_id))
+            .all(db)
+            .await?;
+        let shipped = Self::shipped_quantities(db, order_id).await?;
+
+        let sent = |item: &order_items::Model| shipped.get(&item.id).copied().unwrap_or(0);
+        if !order_items.is_empty() && order_items.iter().all(|i| sent(i) >= i.quantity) {
+            Ok(FulfillmentStatus::Fulfilled)
+        } else if order_items.iter().any(|i| sent(i) > 0) {
+            Ok(FulfillmentStatus::PartiallyFulfilled)
+        } else {
+            Ok(FulfillmentStatus::NotFulfilled)
+        }
+    }
+
+    /// Itens do pedido contidos neste envio
+    pub async fn items<C: ConnectionTrait>(
+        &self,
+        db: &C,
+    ) -> ModelResult<Vec<(order_shipping_items::Model, Option<order_items::Model>)>> {
+        let items = order_shipping_items::Entity::find()
+            .filter(order_shipping_items::Column::ShippingId.eq(self.id))
+            .order_by_asc(order_shipping_items::Column::Id)
+            .find_also_related(order_items::Entity)
+            .all(db)
+            .await?;
+        Ok(items)
+    }
+
     /// Busca envio pelo PID
     pub async fn find_by_pid(db: &DatabaseConnection, pid: &Uuid) -> ModelResult<Self> {
         Entity::find()
@@ -68,7 +328,20 @@ impl Model {
             .ok_or_else(|| ModelError::EntityNotFound)
     }
 
-    /// Busca o envio ativo de um pedido
+    /// Todos os envios de um pedido, do mais antigo ao mais recente
+    pub async fn list_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let shippings = Entity::find()
+            .filter(order_shippings::Column::OrderId.eq(order_id))
+            .order_by_asc(order_shippings::Column::Id)
+            .all(db)
+            .await?;
+        Ok(shippings)
+    }
+
+    /// Busca o envio mais recente de um pedido
     pub async fn find_by_order(
         db: &DatabaseConnection,
         order_id: i32,
@@ -81,6 +354,91 @@ impl Model {
         Ok(shipping)
     }
 
+    /// Envio do pedido cuja compra de etiqueta no `provider` começou e não
+    /// terminou (ver [`LABEL_PURCHASING`])
+    pub async fn find_unfinished_label(
+        db: &DatabaseConnection,
+        order_id: i32,
+        provider: &str,
+    ) -> ModelResult<Option<Self>> {
+        let shippings = Entity::find()
+            .filter(order_shippings::Column::OrderId.eq(order_id))
+            .filter(order_shippings::Column::Provider.eq(provider))
+            .filter(order_shippings::Column::Status.eq("pending"))
+            .order_by_asc(order_shippings::Column::Id)
+            .all(db)
+            .await?;
+        Ok(shippings.into_iter().find(Self::label_unfinished))
+    }
+
+    /// A etiqueta do envio ainda está sendo comprada no provider
+    pub fn label_unfinished(&self) -> bool {
+        self.provider_data
+            .get("label_status")
+            .and_then(|v| v.as_str())
+            == Some(LABEL_PURCHASING)
+    }
+
+    /// Grava o resultado da compra da etiqueta: rastreio, URL da etiqueta e
+    /// resposta do provider
+    pub async fn complete_label(
+        db: &DatabaseConnection,
+        shipping_id: i32,
+        result: &ShipmentResult,
+    ) -> ModelResult<Self> {
+        let shipping = Entity::find_by_id(shipping_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        let mut active: order_shippings::ActiveModel = shipping.clone().into();
+        active.tracking_code = ActiveValue::set(
+            result
+                .tracking_code
+                .clone()
+                .or(shipping.tracking_code.clone()),
+        );
+        active.tracking_url = ActiveValue::set(
+            result
+                .tracking_url
+                .clone()
+                .or(shipping.tracking_url.clone()),
+        );
+        active.provider_data = ActiveValue::set(serde_json::json!({
+            "provider_id": result.provider_id,
+            "label_url": result.label_url,
+            "raw": result.raw_data,
+        }));
+        Ok(active.update(db).await?)
+    }
+
+    /// Remove o envio gravado para a compra da etiqueta quando nada chegou a
+    /// ser registrado no provider (sem `provider_id`), liberando os itens
+    pub async fn discard_unfinished(db: &DatabaseConnection, shipping_id: i32) -> ModelResult<()> {
+        let txn = db.begin().await?;
+        let Some(shipping) = Entity::find_by_id(shipping_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+        else {
+            return Ok(());
+        };
+        let has_provider_id = shipping
+            .provider_data
+            .get("provider_id")
+            .is_some_and(|v| !v.is_null());
+        if !shipping.label_unfinished() || has_provider_id {
+            return Ok(());
+        }
+        order_shipping_items::Entity::delete_many()
+            .filter(order_shipping_items::Column::ShippingId.eq(shipping.id))
+            .exec(&txn)
+            .await?;
+        Entity::delete_by_id(shipping.id).exec(&txn).await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
     /// Lista envios com filtro opcional de status
     pub async fn list_for_store(
         db: &DatabaseConnection,
@@ -108,6 +466,8 @@ impl Model {
     /// Atualiza status de envio.
     /// Transições permitidas: pending → posted → in_transit → out_for_delivery → delivered
     ///                                                                           ↘ failed / returned
+    ///
+    /// Envio entregue marca o pedido como `delivered`.
     pub async fn update_status(
         db: &DatabaseConnection,
         shipping_id: i32,
@@ -118,30 +478,187 @@ impl Model {
             .await?
             .ok_or(ModelError::EntityNotFound)?;
 
-        let mut active: order_shippings::ActiveModel = shipping.into();
-        active.status = ActiveValue::set(params.status.clone());
+        let mut active: order_shippings::ActiveModel = shipping.clone().into();
+        apply_status(&mut active, &shipping, &params.status);
+
+        if let Some(notes) = &params.notes {
+            active.notes = ActiveValue::set(Some(notes.clone()));
+        }
 
-        let now: chrono::DateTime<chrono::FixedOffset> =
-            Utc::now().with_timezone(&chrono::FixedOffset::east_opt(0).unwrap());
+        let updated = active.update(db).await?;
+        if updated.status == "delivered" {
+            mark_order_delivered(db, updated.order_id).await?;
+        }
+        Ok(updated)
+    }
 
-        match params.status.as_str() {
-            "posted" => {
-                active.shipped_at = ActiveValue::set(Some(now));
+    /// Envios com provider externo ainda em andamento, mais antigos primeiro
+    pub async fn list_trackable(db: &DatabaseConnection, limit: u64) -> ModelResult<Vec<Self>> {
+        let shippings = Entity::find()
+            .filter(order_shippings::Column::Provider.is_not_null())
+            .filter(order_shippings::Column::Status.is_in(TRACKABLE_STATUSES.iter().copied()))
+            .order_by_asc(order_shippings::Column::UpdatedAt)
+            .limit(limit)
+            .all(db)
+            .await?;
+        Ok(shippings)
+    }
+
+    /// Marca a consulta de rastreio do envio, com ou sem novidades: assim
+    /// envios com falha no provider não ficam sempre no início da fila de
+    /// `list_trackable`
+    pub async fn touch_polled(db: &DatabaseConnection, shipping_id: i32) -> ModelResult<()> {
+        Entity::update_many()
+            .col_expr(
+                order_shippings::Column::UpdatedAt,
+                Expr::value(chrono::Utc::now()),
+            )
+            .filter(order_shippings::Column::Id.eq(shipping_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Identificadores do envio no provider: ID(s) de `provider_data.provider_id`
+    /// (vários quando o envio tem mais de um volume) e o código de rastreio
+    pub fn provider_references(&self) -> Vec<String> {
+        let mut refs: Vec<String> = self
+            .provider_data
+            .get("provider_id")
+            .and_then(|v| v.as_str())
+            .map(|ids| ids.split(',').map(|id| id.trim().to_string()).collect())
+            .unwrap_or_default();
+        if let Some(code) = &self.tracking_code {
+            refs.push(code.clone());
+        }
+        refs.retain(|r| !r.is_empty());
+        refs
+    }
+
+    /// Referência usada em `ShippingProvider::track`: o MelhorEnvio rastreia
+    /// pelo ID do envio; os demais, pelo código de rastreio.
+    pub fn tracking_reference(&self) -> Option<String> {
+        match self.provider.as_deref() {
+            Some("melhor_envio") => self.provider_references().into_iter().next(),
+            _ => self.tracking_code.clone(),
+        }
+    }
+
+    /// Busca o envio de um provider por ID no provider ou código de rastreio
+    pub async fn find_by_provider_reference(
+        db: &DatabaseConnection,
+        provider: &str,
+        reference: &str,
+    ) -> ModelResult<Self> {
+        if let Some(shipping) = Entity::find()
+            .filter(order_shippings::Column::Provider.eq(provider))
+            .filter(order_shippings::Column::TrackingCode.eq(reference))
+            .one(db)
+            .await?
+        {
+            return Ok(shipping);
+        }
+
+        // O ID no provider fica em `provider_data`; procura entre os envios em andamento
+        let candidates = Entity::find()
+            .filter(order_shippings::Column::Provider.eq(provider))
+            .filter(order_shippings::Column::Status.is_in(TRACKABLE_STATUSES.iter().copied()))
+            .all(db)
+            .await?;
+        candidates
+            .into_iter()
+            .find(|s| s.provider_references().iter().any(|r| r == reference))
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Registra o rastreio devolvido pelo provider.
+    ///
+    /// Eventos novos entram no histórico (`shipment_tracking_events`; repetidos
+    /// são ignorados), o código de rastreio é preenchido se faltava e o status
+    /// do envio acompanha `current_status` — sem voltar para `pending` nem sair
+    /// de um status final. Entrega marca o pedido como `delivered`.
+    pub async fn record_tracking(
+        db: &DatabaseConnection,
+        shipping_id: i32,
+        info: &TrackingInfo,
+        source: &str,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let shipping = Entity::find_by_id(shipping_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        let mut inserted = 0;
+        for event in &info.events {
+            let exists = shipment_tracking_events::Entity::find()
+                .filter(shipment_tracking_events::Column::ShippingId.eq(shipping.id))
+                .filter(shipment_tracking_events::Column::OccurredAt.eq(&event.timestamp))
+                .filter(shipment_tracking_events::Column::Status.eq(&event.status))
+                .one(&txn)
+                .await?
+                .is_some();
+            if exists {
+                continue;
             }
-            "delivered" => {
-                active.delivered_at = ActiveValue::set(Some(now));
+            shipment_tracking_events::ActiveModel {
+                shipping_id: ActiveValue::set(shipping.id),
+                status: ActiveValue::set(event.status.clone()),
+                description: ActiveValue::set(event.description.clone()),
+                location: ActiveValue::set(event.location.clone()),
+                occurred_at: ActiveValue::set(event.timestamp.clone()),
+                source: ActiveValue::set(source.to_string()),
+                ..Default::default()
             }
-            _ => {}
+            .insert(&txn)
+            .await?;
+            inserted += 1;
         }
 
-        if let Some(notes) = &params.notes {
-            active.notes = ActiveValue::set(Some(notes.clone()));
+        // Nunca volta para 'pending' nem sai de um status final
+        let status_changed = shipping.status != info.current_status
+            && info.current_status != "pending"
+            && !FINAL_STATUSES.contains(&shipping.status.as_str());
+        let missing_code = shipping.tracking_code.is_none() && !info.tracking_code.is_empty();
+        if !status_changed && !missing_code && inserted == 0 {
+            txn.commit().await?;
+            return Ok(shipping);
         }
 
-        let updated = active.update(db).await?;
+        let mut active: order_shippings::ActiveModel = shipping.clone().into();
+        if status_changed {
+            apply_status(&mut active, &shipping, &info.current_status);
+        }
+        if missing_code {
+            active.tracking_code = ActiveValue::set(Some(info.tracking_code.clone()));
+        }
+        // Atualiza `updated_at` mesmo só com eventos novos (ordem da consulta periódica)
+        active.updated_at =
+            ActiveValue::set(Utc::now().with_timezone(&chrono::FixedOffset::east_opt(0).unwrap()));
+        let updated = active.update(&txn).await?;
+
+        if status_changed && updated.status == "delivered" {
+            mark_order_delivered(&txn, updated.order_id).await?;
+        }
+
+        txn.commit().await?;
         Ok(updated)
     }
 
+    /// Histórico de rastreio, do evento mais antigo ao mais recente
+    pub async fn tracking_history<C: ConnectionTrait>(
+        db: &C,
+        shipping_id: i32,
+    ) -> ModelResult<Vec<shipment_tracking_events::Model>> {
+        let events = shipment_tracking_events::Entity::find()
+            .filter(shipment_tracking_events::Column::ShippingId.eq(shipping_id))
+            .order_by_asc(shipment_tracking_events::Column::Id)
+            .all(db)
+            .await?;
+        Ok(events)
+    }
+
     /// Atualiza dados do provider externo (ex.: resposta do MelhorEnvio)
     pub async fn update_provider_data(
         db: &DatabaseConnection,
@@ -161,3 +678,33 @@ impl Model {
         Ok(updated)
     }
 }
+
+/// Marca o pedido como entregue quando todos os itens foram enviados e todos
+/// os envios em andamento chegaram. Transição recusada (pedido cancelado ou
+/// ainda não separado) só é registrada no log: o rastreio do envio segue.
+async fn mark_order_delivered<C: ConnectionTrait>(db: &C, order_id: i32) -> ModelResult<()> {
+    let in_transit = Entity::find()
+        .filter(order_shippings::Column::OrderId.eq(order_id))
+        .filter(order_shippings::Column::Status.is_not_in(["delivered", "failed", "returned"]))
+        .count(db)
+        .await?;
+    if in_transit > 0
+        || Model::derived_fulfillment(db, order_id).await? != FulfillmentStatus::Fulfilled
+    {
+        return Ok(());
+    }
+
+    let change = StatusChange::system("shipping", Some("Envio entregue"));
+    match OrderModel::update_fulfillment_status(db, order_id, FulfillmentStatus::Delivered, &change)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => match TransitionError::from_model_error(&e) {
+            Some(rejected) => {
+                tracing::warn!(order_id, error = %rejected, "Pedido não marcado como entregue");
+                Ok(())
+            }
+            None => Err(e),
+        },
+    }
+}
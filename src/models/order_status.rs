@@ -0,0 +1,365 @@
+//! Máquina de estados do pedido
+//!
+//! O pedido tem três eixos de status, cada um com seu grafo de transições:
+//!
+//! ```text
+//! status:              pending → confirmed → processing → shipped → delivered
+//!                         ↘──────────↘────────────↘→ canceled
+//! payment_status:      pending → awaiting ⇄ overdue → paid → partially_refunded → refunded
+//!                                   ↘ failed / canceled
+//! fulfillment_status:  not_fulfilled → partially_fulfilled → fulfilled → delivered
+//! ```
+//!
+//! Além do grafo, algumas transições dependem dos outros eixos: pedido não
+//! pago não é enviado nem separado, pedido já separado não é cancelado e
+//! pedido cancelado não muda mais o fulfillment. `canceled`, `refunded` e
+//! `delivered` são finais.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::_entities::orders;
+use loco_rs::prelude::*;
+
+/// Status do pedido (`orders.status`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Pending,
+    Confirmed,
+    Processing,
+    Shipped,
+    Delivered,
+    #[serde(alias = "cancelled")]
+    Canceled,
+}
+
+/// Status do pagamento (`orders.payment_status`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    Pending,
+    Awaiting,
+    Overdue,
+    Paid,
+    PartiallyRefunded,
+    Refunded,
+    Failed,
+    #[serde(alias = "cancelled")]
+    Canceled,
+}
+
+/// Status de separação e entrega (`orders.fulfillment_status`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FulfillmentStatus {
+    NotFulfilled,
+    PartiallyFulfilled,
+    Fulfilled,
+    Delivered,
+}
+
+/// Eixo de status, gravado em `order_status_history.axis`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusAxis {
+    Status,
+    PaymentStatus,
+    FulfillmentStatus,
+}
+
+impl StatusAxis {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::PaymentStatus => "payment_status",
+            Self::FulfillmentStatus => "fulfillment_status",
+        }
+    }
+}
+
+impl OrderStatus {
+    pub const ALL: &'static [Self] = &[
+        Self::Pending,
+        Self::Confirmed,
+        Self::Processing,
+        Self::Shipped,
+        Self::Delivered,
+        Self::Canceled,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Confirmed => "confirmed",
+            Self::Processing => "processing",
+            Self::Shipped => "shipped",
+            Self::Delivered => "delivered",
+            Self::Canceled => "canceled",
+        }
+    }
+
+    /// Próximos status pelo grafo, sem olhar os outros eixos
+    pub fn next(self) -> &'static [Self] {
+        match self {
+            Self::Pending => &[Self::Confirmed, Self::Processing, Self::Canceled],
+            Self::Confirmed => &[Self::Processing, Self::Shipped, Self::Canceled],
+            Self::Processing => &[Self::Shipped, Self::Canceled],
+            Self::Shipped => &[Self::Delivered],
+            Self::Delivered | Self::Canceled => &[],
+        }
+    }
+
+    /// Próximos status permitidos para o pedido
+    pub fn allowed_for(order: &orders::Model) -> Vec<Self> {
+        let paid = is_paid(order);
+        let fulfilled = FulfillmentStatus::from_str(&order.fulfillment_status)
+            .is_ok_and(|f| f != FulfillmentStatus::NotFulfilled);
+        current_or_all::<Self>(&order.status)
+            .into_iter()
+            .filter(|next| match next {
+                Self::Shipped => paid,
+                Self::Canceled => !fulfilled,
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+impl PaymentStatus {
+    pub const ALL: &'static [Self] = &[
+        Self::Pending,
+        Self::Awaiting,
+        Self::Overdue,
+        Self::Paid,
+        Self::PartiallyRefunded,
+        Self::Refunded,
+        Self::Failed,
+        Self::Canceled,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Awaiting => "awaiting",
+            Self::Overdue => "overdue",
+            Self::Paid => "paid",
+            Self::PartiallyRefunded => "partially_refunded",
+            Self::Refunded => "refunded",
+            Self::Failed => "failed",
+            Self::Canceled => "canceled",
+        }
+    }
+
+    /// Próximos status pelo grafo. `failed` aceita nova cobrança e
+    /// `partially_refunded` aceita novos estornos parciais.
+    pub fn next(self) -> &'static [Self] {
+        match self {
+            Self::Pending => &[
+                Self::Awaiting,
+                Self::Overdue,
+                Self::Paid,
+                Self::Failed,
+                Self::Canceled,
+            ],
+            Self::Awaiting => &[Self::Overdue, Self::Paid, Self::Failed, Self::Canceled],
+            Self::Overdue => &[Self::Awaiting, Self::Paid, Self::Failed, Self::Canceled],
+            Self::Failed => &[Self::Awaiting, Self::Paid, Self::Canceled],
+            Self::Paid => &[Self::PartiallyRefunded, Self::Refunded],
+            Self::PartiallyRefunded => &[Self::PartiallyRefunded, Self::Refunded],
+            Self::Refunded | Self::Canceled => &[],
+        }
+    }
+
+    /// Próximos status de pagamento permitidos para o pedido
+    pub fn allowed_for(order: &orders::Model) -> Vec<Self> {
+        current_or_all::<Self>(&order.payment_status)
+    }
+}
+
+impl FulfillmentStatus {
+    pub const ALL: &'static [Self] = &[
+        Self::NotFulfilled,
+        Self::PartiallyFulfilled,
+        Self::Fulfilled,
+        Self::Delivered,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotFulfilled => "not_fulfilled",
+            Self::PartiallyFulfilled => "partially_fulfilled",
+            Self::Fulfilled => "fulfilled",
+            Self::Delivered => "delivered",
+        }
+    }
+
+    pub fn next(self) -> &'static [Self] {
+        match self {
+            Self::NotFulfilled => &[Self::PartiallyFulfilled, Self::Fulfilled],
+            Self::PartiallyFulfilled => &[Self::PartiallyFulfilled, Self::Fulfilled],
+            Self::Fulfilled => &[Self::Delivered],
+            Self::Delivered => &[],
+        }
+    }
+
+    /// Próximos status de fulfillment permitidos para o pedido
+    pub fn allowed_for(order: &orders::Model) -> Vec<Self> {
+        if OrderStatus::from_str(&order.status).is_ok_and(|s| s == OrderStatus::Canceled) {
+            return vec![];
+        }
+        let paid = is_paid(order);
+        current_or_all::<Self>(&order.fulfillment_status)
+            .into_iter()
+            .filter(|next| match next {
+                Self::PartiallyFulfilled | Self::Fulfilled => paid,
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+/// Pagamento confirmado (inclusive com estorno parcial)
+fn is_paid(order: &orders::Model) -> bool {
+    matches!(
+        PaymentStatus::from_str(&order.payment_status),
+        Ok(PaymentStatus::Paid | PaymentStatus::PartiallyRefunded)
+    )
+}
+
+/// Status comum aos três eixos, para validar e listar transições
+pub trait Status: Copy + PartialEq + FromStr<Err = ModelError> + 'static {
+    const AXIS: StatusAxis;
+    fn all() -> &'static [Self];
+    fn next(self) -> &'static [Self];
+    fn as_str(self) -> &'static str;
+}
+
+macro_rules! impl_status {
+    ($ty:ty, $axis:expr, $label:literal) => {
+        impl Status for $ty {
+            const AXIS: StatusAxis = $axis;
+            fn all() -> &'static [Self] {
+                Self::ALL
+            }
+            fn next(self) -> &'static [Self] {
+                <$ty>::next(self)
+            }
+            fn as_str(self) -> &'static str {
+                <$ty>::as_str(self)
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = ModelError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                let value = if value == "cancelled" {
+                    "canceled"
+                } else {
+                    value
+                };
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|s| s.as_str() == value)
+                    .ok_or_else(|| {
+                        ModelError::Message(format!(
+                            "{} inválido: '{}'. Valores aceitos: {}",
+                            $label,
+                            value,
+                            join(Self::ALL.iter().map(|s| s.as_str()))
+                        ))
+                    })
+            }
+        }
+
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+impl_status!(OrderStatus, StatusAxis::Status, "Status do pedido");
+impl_status!(
+    PaymentStatus,
+    StatusAxis::PaymentStatus,
+    "Status de pagamento"
+);
+impl_status!(
+    FulfillmentStatus,
+    StatusAxis::FulfillmentStatus,
+    "Status de fulfillment"
+);
+
+/// Próximos status pelo grafo; status desconhecido no banco (dado legado)
+/// não trava o pedido e aceita qualquer valor
+fn current_or_all<S: Status>(current: &str) -> Vec<S> {
+    match S::from_str(current) {
+        Ok(status) => status.next().to_vec(),
+        Err(_) => S::all().to_vec(),
+    }
+}
+
+fn join<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    let values: Vec<&str> = values.collect();
+    if values.is_empty() {
+        "nenhum (status final)".to_string()
+    } else {
+        values.join(", ")
+    }
+}
+
+/// Transição recusada. Vai para a API com os próximos status permitidos.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransitionError {
+    pub axis: StatusAxis,
+    pub from: String,
+    pub to: String,
+    pub allowed: Vec<&'static str>,
+}
+
+impl std::fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Transição inválida de {}: '{}' → '{}'. Permitidos: {}",
+            self.axis.as_str(),
+            self.from,
+            self.to,
+            join(self.allowed.iter().copied())
+        )
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+impl TransitionError {
+    /// Recupera a transição recusada de um erro de model
+    pub fn from_model_error(err: &ModelError) -> Option<&Self> {
+        match err {
+            ModelError::Any(e) => e.downcast_ref::<Self>(),
+            _ => None,
+        }
+    }
+}
+
+/// Valida a transição de `current` para `to` dentre as permitidas.
+/// Retorna `false` quando o status não muda (nada a registrar).
+pub fn check<S: Status>(current: &str, to: S, allowed: &[S]) -> ModelResult<bool> {
+    if S::from_str(current).is_ok_and(|c| c == to) && !allowed.contains(&to) {
+        return Ok(false);
+    }
+    if allowed.contains(&to) {
+        return Ok(true);
+    }
+    Err(ModelError::Any(Box::new(TransitionError {
+        axis: S::AXIS,
+        from: current.to_string(),
+        to: to.as_str().to_string(),
+        allowed: allowed.iter().map(|s| s.as_str()).collect(),
+    })))
+}
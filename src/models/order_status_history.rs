@@ -0,0 +1,75 @@
+use sea_orm::{ConnectionTrait, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+pub use super::_entities::order_status_history::{self, ActiveModel, Entity, Model};
+use super::order_status::StatusAxis;
+use loco_rs::prelude::*;
+
+/// Quem mudou o status, de onde e por quê
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatusChange {
+    pub changed_by: Option<i32>,
+    /// 'admin' | 'painel' | 'checkout' | 'webhook' | 'refund' | 'worker' | 'shipping'
+    pub source: String,
+    pub reason: Option<String>,
+}
+
+impl StatusChange {
+    /// Mudança feita por um usuário
+    pub fn by_user(user_id: i32, source: &str, reason: Option<String>) -> Self {
+        Self {
+            changed_by: Some(user_id),
+            source: source.to_string(),
+            reason,
+        }
+    }
+
+    /// Mudança automática (webhook, worker, rastreio)
+    pub fn system(source: &str, reason: Option<&str>) -> Self {
+        Self {
+            changed_by: None,
+            source: source.to_string(),
+            reason: reason.map(String::from),
+        }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Registra uma transição
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        axis: StatusAxis,
+        from: &str,
+        to: &str,
+        change: &StatusChange,
+    ) -> ModelResult<Self> {
+        let entry = order_status_history::ActiveModel {
+            order_id: ActiveValue::set(order_id),
+            axis: ActiveValue::set(axis.as_str().to_string()),
+            from_status: ActiveValue::set(from.to_string()),
+            to_status: ActiveValue::set(to.to_string()),
+            changed_by: ActiveValue::set(change.changed_by),
+            source: ActiveValue::set(change.source.clone()),
+            reason: ActiveValue::set(change.reason.clone()),
+            ..Default::default()
+        };
+        let entry = entry.insert(db).await?;
+        Ok(entry)
+    }
+
+    /// Histórico do pedido, da transição mais antiga à mais recente
+    pub async fn list_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let entries = Entity::find()
+            .filter(order_status_history::Column::OrderId.eq(order_id))
+            .order_by_asc(order_status_history::Column::Id)
+            .all(db)
+            .await?;
+        Ok(entries)
+    }
+}
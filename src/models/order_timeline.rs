@@ -0,0 +1,191 @@
+//! Linha do tempo do pedido para o painel
+//!
+//! Junta, em ordem cronológica, a criação do pedido, as transições de status
+//! (`order_status_history`), os eventos de pagamento (`payment_events`), o
+//! rastreio dos envios (`shipment_tracking_events`), as edições do pedido
+//! (`order_edits`) e as notas internas.
+
+use std::collections::HashMap;
+
+use sea_orm::QueryOrder;
+use serde::Serialize;
+
+use super::_entities::{order_shippings, orders, payment_events, shipment_tracking_events, users};
+use super::order_edits::Model as OrderEditModel;
+use super::order_notes::Model as NoteModel;
+use super::order_status_history::Model as StatusHistoryModel;
+use loco_rs::prelude::*;
+
+/// Item da linha do tempo
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    /// 'created' | 'status' | 'payment' | 'tracking' | 'edit' | 'note'
+    pub kind: &'static str,
+    pub at: DateTimeWithTimeZone,
+    pub title: String,
+    /// Nome de quem fez a ação; `None` nas ações automáticas
+    pub author: Option<String>,
+    pub data: serde_json::Value,
+}
+
+/// Data do evento de rastreio (`occurred_at`, no formato do provider).
+/// Sem fuso, é horário de Brasília.
+fn tracking_time(occurred_at: &str) -> Option<DateTimeWithTimeZone> {
+    let value = occurred_at.trim();
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(at);
+    }
+    let naive = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%d/%m/%Y %H:%M:%S",
+        "%d/%m/%Y %H:%M",
+    ]
+    .iter()
+    .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())?;
+    let offset = chrono::FixedOffset::west_opt(3 * 3600)?;
+    naive.and_local_timezone(offset).single()
+}
+
+/// Linha do tempo completa do pedido, do mais antigo ao mais recente
+pub async fn for_order(
+    db: &DatabaseConnection,
+    order: &orders::Model,
+) -> ModelResult<Vec<TimelineEntry>> {
+    let mut entries = vec![TimelineEntry {
+        kind: "created",
+        at: order.created_at,
+        title: format!("Pedido {} criado", order.order_number),
+        author: None,
+        data: serde_json::json!({ "total": order.total }),
+    }];
+
+    let history = StatusHistoryModel::list_for_order(db, order.id).await?;
+    let edits = OrderEditModel::list_for_order(db, order.id).await?;
+    let user_ids: Vec<i32> = history
+        .iter()
+        .filter_map(|h| h.changed_by)
+        .chain(edits.iter().filter_map(|e| e.edited_by))
+        .collect();
+    let user_names: HashMap<i32, String> = if user_ids.is_empty() {
+        HashMap::new()
+    } else {
+        users::Entity::find()
+            .filter(users::Column::Id.is_in(user_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|u| (u.id, u.name))
+            .collect()
+    };
+    for h in history {
+        entries.push(TimelineEntry {
+            kind: "status",
+            at: h.created_at,
+            title: format!("{}: {} → {}", h.axis, h.from_status, h.to_status),
+            author: h.changed_by.and_then(|id| user_names.get(&id).cloned()),
+            data: serde_json::json!({
+                "axis": h.axis,
+                "from": h.from_status,
+                "to": h.to_status,
+                "source": h.source,
+                "reason": h.reason,
+            }),
+        });
+    }
+
+    for e in edits {
+        entries.push(TimelineEntry {
+            kind: "edit",
+            at: e.created_at,
+            title: format!("Pedido alterado: {} → {}", e.previous_total, e.new_total),
+            author: e.edited_by.and_then(|id| user_names.get(&id).cloned()),
+            data: serde_json::json!({
+                "pid": e.pid,
+                "reason": e.reason,
+                "changes": e.changes,
+                "difference": e.difference,
+                "settlement": e.settlement,
+                "settlement_status": e.settlement_status,
+            }),
+        });
+    }
+
+    let payment_events = payment_events::Entity::find()
+        .filter(payment_events::Column::OrderId.eq(order.id))
+        .order_by_asc(payment_events::Column::Id)
+        .all(db)
+        .await?;
+    for e in payment_events {
+        entries.push(TimelineEntry {
+            kind: "payment",
+            at: e.occurred_at.unwrap_or(e.created_at),
+            title: format!("{} ({})", e.event_type, e.provider),
+            author: None,
+            data: serde_json::json!({
+                "event_id": e.event_id,
+                "payment_status": e.payment_status,
+                "result": e.status,
+                "error": e.error,
+            }),
+        });
+    }
+
+    let shippings = order_shippings::Entity::find()
+        .filter(order_shippings::Column::OrderId.eq(order.id))
+        .all(db)
+        .await?;
+    if !shippings.is_empty() {
+        let carriers: HashMap<i32, (String, Uuid)> = shippings
+            .iter()
+            .map(|s| (s.id, (s.carrier.clone(), s.pid)))
+            .collect();
+        let events = shipment_tracking_events::Entity::find()
+            .filter(
+                shipment_tracking_events::Column::ShippingId
+                    .is_in(carriers.keys().copied().collect::<Vec<_>>()),
+            )
+            .order_by_asc(shipment_tracking_events::Column::Id)
+            .all(db)
+            .await?;
+        for e in events {
+            let (carrier, shipping_pid) = carriers.get(&e.shipping_id).cloned().unzip();
+            entries.push(TimelineEntry {
+                kind: "tracking",
+                at: tracking_time(&e.occurred_at).unwrap_or(e.created_at),
+                title: e.description,
+                author: None,
+                data: serde_json::json!({
+                    "shipping_pid": shipping_pid,
+                    "carrier": carrier,
+                    "status": e.status,
+                    "location": e.location,
+                    "occurred_at": e.occurred_at,
+                }),
+            });
+        }
+    }
+
+    let notes = NoteModel::list_for_order(db, order.id).await?;
+    if !notes.is_empty() {
+        let author_ids: Vec<i32> = notes.iter().filter_map(|n| n.author_id).collect();
+        let collaborators = NoteModel::collaborators_by_ids(db, author_ids).await?;
+        for n in notes {
+            entries.push(TimelineEntry {
+                kind: "note",
+                at: n.created_at,
+                title: "Nota interna".to_string(),
+                author: n
+                    .author_id
+                    .and_then(|id| collaborators.get(&id))
+                    .map(|c| c.user.name.clone()),
+                data: serde_json::json!({ "pid": n.pid, "body": n.body }),
+            });
+        }
+    }
+
+    // Ordenação estável: no mesmo instante mantém a ordem de inserção acima
+    entries.sort_by_key(|e| e.at);
+    Ok(entries)
+}
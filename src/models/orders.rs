@@ -1,12 +1,24 @@
-use sea_orm::{PaginatorTrait, QueryOrder, QuerySelect};
+use sea_orm::{
+    sea_query::{Expr, NullOrdering, Order},
+    ConnectionTrait, QueryOrder, QuerySelect, SqlErr, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub use super::_entities::order_items;
 pub use super::_entities::orders::{self, ActiveModel, Entity, Model};
+use super::_entities::{addresses, customers, payment_events};
+use super::coupons::Model as CouponModel;
+use super::order_sequences::{Model as OrderSequenceModel, OrderNumberConfig};
+use super::order_status::{self, FulfillmentStatus, OrderStatus, PaymentStatus, StatusAxis};
+use super::order_status_history::{Model as StatusHistoryModel, StatusChange};
+use super::stock_reservations::{Model as StockReservationModel, ReservationLine};
 
 use loco_rs::prelude::*;
 
+/// Status de pagamento de cobranças ainda em aberto
+pub const UNPAID_PAYMENT_STATUSES: &[&str] = &["pending", "awaiting", "overdue"];
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateOrderFromCartParams {
     pub customer_id: i32,
@@ -16,26 +28,80 @@ pub struct CreateOrderFromCartParams {
     pub notes: Option<String>,
 }
 
+/// Mudanças de status pedidas pelo painel/API (valores em texto)
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct StatusUpdate {
+    pub status: Option<String>,
+    pub payment_status: Option<String>,
+    pub fulfillment_status: Option<String>,
+    pub payment_data: Option<serde_json::Value>,
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 impl ActiveModelBehavior for order_items::ActiveModel {}
 
-/// Gera número de pedido: LFS-{seq}
-fn generate_order_number(seq: i64) -> String {
-    format!("LFS-{:06}", seq)
+/// Tentativas de criação do pedido em caso de conflito de `order_number`
+const MAX_ORDER_NUMBER_ATTEMPTS: u32 = 3;
+
+/// Vencimento da cobrança: `due_date` gravado no checkout ou `dueDate` da
+/// cobrança que o Asaas envia no webhook
+fn due_date_of(data: &serde_json::Value) -> Option<chrono::NaiveDate> {
+    [&data["due_date"], &data["payment"]["dueDate"]]
+        .into_iter()
+        .filter_map(|v| v.as_str())
+        .find_map(|v| chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+}
+
+/// Junta os dados novos do pagamento aos já gravados: chaves ausentes no
+/// novo (link da fatura, QR code do PIX, `payment_id`) continuam valendo
+fn merge_payment_data(current: &mut serde_json::Value, data: serde_json::Value) {
+    match (current.as_object_mut(), data) {
+        (Some(current), serde_json::Value::Object(data)) => current.extend(data),
+        (_, data) => *current = data,
+    }
 }
 
 impl Model {
-    /// Cria pedido a partir de um carrinho
+    /// Cria pedido a partir de um carrinho.
+    ///
+    /// Pedido, itens, número do pedido e reservas de estoque são gravados na
+    /// mesma transação; se faltar estoque para algum item, nada é persistido.
+    /// Em caso de conflito no `order_number` a sequência é realinhada e a
+    /// criação é refeita.
     pub async fn create_from_cart(
         db: &DatabaseConnection,
         cart: &super::_entities::carts::Model,
         cart_items: &[super::_entities::cart_items::Model],
         params: &CreateOrderFromCartParams,
     ) -> ModelResult<Self> {
-        // Conta pedidos existentes para gerar número
-        let count = Entity::find().count(db).await?;
+        let config = OrderNumberConfig::from_env();
+        let mut attempt = 1;
+        loop {
+            match Self::insert_from_cart(db, cart, cart_items, params, &config).await {
+                Err(ModelError::DbErr(err))
+                    if attempt < MAX_ORDER_NUMBER_ATTEMPTS
+                        && matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) =>
+                {
+                    tracing::warn!(attempt, error = %err, "Conflito ao gerar número de pedido");
+                    OrderSequenceModel::resync(db, &config).await?;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn insert_from_cart(
+        db: &DatabaseConnection,
+        cart: &super::_entities::carts::Model,
+        cart_items: &[super::_entities::cart_items::Model],
+        params: &CreateOrderFromCartParams,
+        config: &OrderNumberConfig,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
 
-        let order_number = generate_order_number(count as i64 + 1);
+        let seq = OrderSequenceModel::next_value(&txn, &config.prefix).await?;
+        let order_number = config.format(seq);
 
         let order = orders::ActiveModel {
             pid: ActiveValue::set(Uuid::new_v4()),
@@ -49,8 +115,9 @@ impl Model {
             subtotal: ActiveValue::set(cart.subtotal),
             tax: ActiveValue::set(cart.tax),
             shipping: ActiveValue::set(cart.shipping),
-            discount: ActiveValue::set(0),
+            discount: ActiveValue::set(cart.discount),
             total: ActiveValue::set(cart.total),
+            coupon_code: ActiveValue::set(cart.coupon_code.clone()),
             shipping_address_id: ActiveValue::set(params.shipping_address_id),
             billing_address_id: ActiveValue::set(params.billing_address_id),
             payment_method: ActiveValue::set(params.payment_method.clone()),
@@ -59,7 +126,7 @@ impl Model {
             metadata: ActiveValue::set(serde_json::json!({})),
             ..Default::default()
         };
-        let order = order.insert(db).await?;
+        let order = order.insert(&txn).await?;
 
         // Cria itens do pedido (snapshot dos itens do carrinho)
         for item in cart_items {
@@ -75,9 +142,25 @@ impl Model {
                 metadata: ActiveValue::set(serde_json::json!({})),
                 ..Default::default()
             };
-            order_item.insert(db).await?;
+            order_item.insert(&txn).await?;
+        }
+
+        // Reserva estoque para os itens (respeita allow_backorder da variante)
+        let lines: Vec<ReservationLine> = cart_items
+            .iter()
+            .map(|i| ReservationLine {
+                variant_id: i.variant_id,
+                quantity: i.quantity,
+            })
+            .collect();
+        StockReservationModel::reserve_for_order(&txn, order.id, &lines).await?;
+
+        // Registra o uso do cupom (limites conferidos com a linha bloqueada)
+        if let Some(code) = &cart.coupon_code {
+            CouponModel::redeem(&txn, code, order.id, params.customer_id, cart.discount).await?;
         }
 
+        txn.commit().await?;
         Ok(order)
     }
 
@@ -157,34 +240,245 @@ impl Model {
         Ok(items)
     }
 
-    /// Atualiza status do pedido
-    pub async fn update_status(
+    /// Contato de entrega do pedido (endereço de entrega + dados do cliente),
+    /// usado como destinatário da etiqueta ou remetente da logística reversa
+    pub async fn shipping_contact(
+        &self,
         db: &DatabaseConnection,
+    ) -> ModelResult<crate::shipping::ContactInfo> {
+        let address_id = self
+            .shipping_address_id
+            .ok_or_else(|| ModelError::msg("Pedido sem endereço de entrega"))?;
+        let address = addresses::Entity::find_by_id(address_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let customer = customers::Entity::find_by_id(self.customer_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        Ok(crate::shipping::ContactInfo {
+            name: format!("{} {}", address.first_name, address.last_name),
+            email: Some(customer.email),
+            phone: address.phone.or(customer.phone),
+            document: customer
+                .metadata
+                .get("document")
+                .and_then(|d| d.as_str())
+                .map(String::from),
+            address_line1: address.address_line_1,
+            address_line2: address.address_line_2,
+            number: None,
+            district: None,
+            city: address.city,
+            state: address.state,
+            postal_code: address.postal_code,
+            country: address.country,
+        })
+    }
+
+    /// Atualiza status do pedido, validando a transição e registrando-a no
+    /// histórico.
+    /// Ao cancelar, libera as reservas de estoque ainda ativas e devolve o
+    /// uso do cupom.
+    pub async fn update_status<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
         order_id: i32,
-        status: &str,
+        status: OrderStatus,
+        change: &StatusChange,
     ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
         let order = Entity::find_by_id(order_id)
-            .one(db)
+            .lock_exclusive()
+            .one(&txn)
             .await?
             .ok_or(ModelError::EntityNotFound)?;
+        let allowed = OrderStatus::allowed_for(&order);
+        if !order_status::check(&order.status, status, &allowed)? {
+            return Ok(order);
+        }
+        let from = order.status.clone();
 
         let mut active: orders::ActiveModel = order.into();
-        active.status = ActiveValue::set(status.to_string());
+        active.status = ActiveValue::set(status.as_str().to_string());
 
-        if status == "canceled" {
+        if status == OrderStatus::Canceled {
             active.canceled_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+            StockReservationModel::release_for_order(&txn, order_id).await?;
+            CouponModel::release_for_order(&txn, order_id).await?;
         }
 
-        let updated = active.update(db).await?;
+        let updated = active.update(&txn).await?;
+        StatusHistoryModel::record(
+            &txn,
+            order_id,
+            StatusAxis::Status,
+            &from,
+            status.as_str(),
+            change,
+        )
+        .await?;
+        txn.commit().await?;
         Ok(updated)
     }
 
-    /// Atualiza status de pagamento
-    pub async fn update_payment_status(
+    /// Aplica as mudanças de status pedidas pelo painel/API, na ordem
+    /// pagamento → pedido → fulfillment (um pedido pago pode ser enviado na
+    /// mesma chamada). Tudo ou nada: uma transição inválida desfaz as demais.
+    pub async fn apply_status_update(
         db: &DatabaseConnection,
         order_id: i32,
-        payment_status: &str,
-        payment_data: Option<serde_json::Value>,
+        update: &StatusUpdate,
+        change: &StatusChange,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let mut updated = Entity::find_by_id(order_id)
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        if let Some(payment_status) = &update.payment_status {
+            updated = Self::update_payment_status(
+                &txn,
+                order_id,
+                payment_status.parse()?,
+                update.payment_data.clone(),
+                change,
+            )
+            .await?;
+        }
+        if let Some(status) = &update.status {
+            updated = Self::update_status(&txn, order_id, status.parse()?, change).await?;
+        }
+        if let Some(fulfillment_status) = &update.fulfillment_status {
+            updated = Self::update_fulfillment_status(
+                &txn,
+                order_id,
+                fulfillment_status.parse()?,
+                change,
+            )
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    /// ID da cobrança gravado no pedido (`payment_data`), sem consultar os
+    /// eventos de webhook
+    pub fn current_payment_id(&self) -> Option<String> {
+        self.payment_data
+            .get("payment_id")
+            .or_else(|| self.payment_data.pointer("/payment/id"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    }
+
+    /// ID da cobrança no gateway: gravado no pedido ao criar a cobrança ou,
+    /// na falta dele, o do último evento de webhook recebido
+    pub async fn provider_payment_id<C: ConnectionTrait>(
+        &self,
+        db: &C,
+    ) -> ModelResult<Option<String>> {
+        let from_order = self.current_payment_id();
+        if from_order.is_some() {
+            return Ok(from_order);
+        }
+
+        let event = payment_events::Entity::find()
+            .filter(payment_events::Column::OrderId.eq(self.id))
+            .filter(payment_events::Column::PaymentId.is_not_null())
+            .order_by_desc(payment_events::Column::Id)
+            .one(db)
+            .await?;
+        Ok(event.and_then(|e| e.payment_id))
+    }
+
+    /// Vencimento da cobrança (`payment_data.due_date`, AAAA-MM-DD)
+    pub fn payment_due_date(&self) -> Option<chrono::NaiveDate> {
+        self.payment_data
+            .get("due_date")
+            .and_then(|v| v.as_str())
+            .and_then(|v| chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+    }
+
+    /// Pedidos não pagos com a cobrança vencida há mais de `grace_days` dias.
+    /// A data de hoje é a de Brasília, como o vencimento do gateway.
+    ///
+    /// Os pedidos nunca verificados vêm primeiro, depois os verificados há
+    /// mais tempo: um cancelamento que falha no gateway não trava a fila.
+    pub async fn list_payment_expired(
+        db: &DatabaseConnection,
+        grace_days: i64,
+        limit: u64,
+    ) -> ModelResult<Vec<Self>> {
+        let today = (chrono::Utc::now() - chrono::Duration::hours(3)).date_naive();
+        let cutoff = today - chrono::Duration::days(grace_days);
+        let orders = Entity::find()
+            .filter(orders::Column::Status.ne("canceled"))
+            .filter(orders::Column::PaymentStatus.is_in(UNPAID_PAYMENT_STATUSES.iter().copied()))
+            .filter(orders::Column::PaymentDueDate.lt(cutoff))
+            .order_by_with_nulls(
+                orders::Column::PaymentExpiryCheckedAt,
+                Order::Asc,
+                NullOrdering::First,
+            )
+            .order_by_asc(orders::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await?;
+        Ok(orders)
+    }
+
+    /// Marca a tentativa de expiração do pedido (ver `list_payment_expired`)
+    pub async fn touch_payment_expiry_check(
+        db: &DatabaseConnection,
+        order_id: i32,
+    ) -> ModelResult<()> {
+        Entity::update_many()
+            .col_expr(
+                orders::Column::PaymentExpiryCheckedAt,
+                Expr::value(chrono::Utc::now()),
+            )
+            .filter(orders::Column::Id.eq(order_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Cancela o pedido por falta de pagamento: status `canceled`, pagamento
+    /// `canceled`, reservas de estoque e cupom liberados.
+    ///
+    /// Retorna `None` se o pedido foi pago (ou cancelado) enquanto isso —
+    /// o pedido fica bloqueado durante a verificação.
+    pub async fn expire_unpaid(
+        db: &DatabaseConnection,
+        order_id: i32,
+    ) -> ModelResult<Option<Self>> {
+        let txn = db.begin().await?;
+        let order = Entity::find_by_id(order_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        if order.status == "canceled"
+            || !UNPAID_PAYMENT_STATUSES.contains(&order.payment_status.as_str())
+        {
+            return Ok(None);
+        }
+
+        let change = StatusChange::system("worker", Some("Cobrança vencida sem pagamento"));
+        Self::update_payment_status(&txn, order_id, PaymentStatus::Canceled, None, &change).await?;
+        let updated = Self::update_status(&txn, order_id, OrderStatus::Canceled, &change).await?;
+        txn.commit().await?;
+        Ok(Some(updated))
+    }
+
+    /// Registra o gateway e a forma de pagamento da cobrança
+    pub async fn set_payment_method(
+        db: &DatabaseConnection,
+        order_id: i32,
+        provider: &str,
+        method: &str,
     ) -> ModelResult<Self> {
         let order = Entity::find_by_id(order_id)
             .one(db)
@@ -192,34 +486,103 @@ impl Model {
             .ok_or(ModelError::EntityNotFound)?;
 
         let mut active: orders::ActiveModel = order.into();
-        active.payment_status = ActiveValue::set(payment_status.to_string());
+        active.payment_provider = ActiveValue::set(Some(provider.to_string()));
+        active.payment_method = ActiveValue::set(Some(method.to_string()));
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Atualiza status de pagamento, validando a transição e registrando-a
+    /// no histórico. `payment_data` é mesclado ao atual mesmo sem mudança de
+    /// status (nova cobrança no mesmo status, evento do webhook).
+    /// Ao confirmar o pagamento, converte as reservas em baixa de estoque.
+    pub async fn update_payment_status<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        order_id: i32,
+        payment_status: PaymentStatus,
+        payment_data: Option<serde_json::Value>,
+        change: &StatusChange,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let order = Entity::find_by_id(order_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let allowed = PaymentStatus::allowed_for(&order);
+        let changed = order_status::check(&order.payment_status, payment_status, &allowed)?;
+        if !changed && payment_data.is_none() {
+            return Ok(order);
+        }
+        let from = order.payment_status.clone();
+
+        let mut active: orders::ActiveModel = order.into();
+        active.payment_status = ActiveValue::set(payment_status.as_str().to_string());
 
         if let Some(data) = payment_data {
-            active.payment_data = ActiveValue::set(data);
+            // Espelha o vencimento em coluna para o worker de expiração; dado
+            // sem vencimento (ex.: evento do webhook) mantém o atual
+            let due_date = due_date_of(&data);
+            let mut merged = active.payment_data.take().unwrap_or_default();
+            merge_payment_data(&mut merged, data);
+            if let Some(due_date) = due_date {
+                active.payment_due_date = ActiveValue::set(Some(due_date));
+                merged["due_date"] = serde_json::json!(due_date.format("%Y-%m-%d").to_string());
+            }
+            active.payment_data = ActiveValue::set(merged);
         }
 
-        if payment_status == "paid" {
+        if changed && payment_status == PaymentStatus::Paid {
             active.paid_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+            StockReservationModel::commit_for_order(&txn, order_id).await?;
         }
 
-        let updated = active.update(db).await?;
+        let updated = active.update(&txn).await?;
+        if changed {
+            StatusHistoryModel::record(
+                &txn,
+                order_id,
+                StatusAxis::PaymentStatus,
+                &from,
+                payment_status.as_str(),
+                change,
+            )
+            .await?;
+        }
+        txn.commit().await?;
         Ok(updated)
     }
 
-    /// Atualiza status de fulfillment
-    pub async fn update_fulfillment_status(
-        db: &DatabaseConnection,
+    /// Atualiza status de fulfillment, validando a transição e registrando-a
+    /// no histórico
+    pub async fn update_fulfillment_status<C: ConnectionTrait>(
+        db: &C,
         order_id: i32,
-        fulfillment_status: &str,
+        fulfillment_status: FulfillmentStatus,
+        change: &StatusChange,
     ) -> ModelResult<Self> {
         let order = Entity::find_by_id(order_id)
             .one(db)
             .await?
             .ok_or(ModelError::EntityNotFound)?;
+        let allowed = FulfillmentStatus::allowed_for(&order);
+        if !order_status::check(&order.fulfillment_status, fulfillment_status, &allowed)? {
+            return Ok(order);
+        }
+        let from = order.fulfillment_status.clone();
 
         let mut active: orders::ActiveModel = order.into();
-        active.fulfillment_status = ActiveValue::set(fulfillment_status.to_string());
+        active.fulfillment_status = ActiveValue::set(fulfillment_status.as_str().to_string());
         let updated = active.update(db).await?;
+        StatusHistoryModel::record(
+            db,
+            order_id,
+            StatusAxis::FulfillmentStatus,
+            &from,
+            fulfillment_status.as_str(),
+            change,
+        )
+        .await?;
         Ok(updated)
     }
 }
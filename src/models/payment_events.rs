@@ -0,0 +1,241 @@
+use sea_orm::{QueryOrder, QuerySelect, SqlErr, TransactionTrait};
+use serde::{Deserialize, Serialize};
+
+pub use super::_entities::payment_events::{self, ActiveModel, Entity, Model};
+use super::_entities::{order_edits, orders};
+use super::order_edits::Model as OrderEditModel;
+use super::order_status::TransitionError;
+use super::order_status_history::StatusChange;
+use super::orders::Model as OrderModel;
+use loco_rs::prelude::*;
+
+/// Evento recebido do gateway, antes de ser gravado
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NewPaymentEvent {
+    pub provider: String,
+    pub event_id: String,
+    pub event_type: String,
+    pub payment_id: Option<String>,
+    pub order_id: Option<i32>,
+    pub payment_status: Option<String>,
+    pub occurred_at: Option<DateTimeWithTimeZone>,
+    pub payload: serde_json::Value,
+}
+
+/// Resultado do processamento de um evento
+#[derive(Debug, Clone)]
+pub enum EventOutcome {
+    /// Status aplicado ao pedido
+    Applied(orders::Model),
+    /// Evento mais antigo que o último aplicado ao pedido: só registrado
+    Stale,
+    /// Evento já processado (reenvio do gateway)
+    Duplicate,
+    /// Transição de pagamento recusada pela máquina de estados (ex.: pagamento
+    /// de pedido já cancelado): só registrado, com o motivo
+    Rejected(String),
+    /// Evento de uma cobrança criada ou substituída por edição do pedido:
+    /// atualiza o acerto da edição, não o pagamento do pedido
+    OrderEdit(order_edits::Model),
+    /// Evento sem pedido ou sem status de pagamento
+    Ignored,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Precedência dos status para eventos com a mesma data
+fn status_rank(status: Option<&str>) -> u8 {
+    match status {
+        Some("overdue") => 1,
+        Some("canceled") => 2,
+        Some("paid") => 3,
+        Some("partially_refunded") => 4,
+        Some("refunded") => 5,
+        // awaiting e desconhecidos
+        _ => 0,
+    }
+}
+
+impl Model {
+    /// Grava o evento, ou devolve o já existente com o mesmo `event_id`.
+    /// O booleano indica se o evento é novo.
+    pub async fn record(
+        db: &DatabaseConnection,
+        params: &NewPaymentEvent,
+    ) -> ModelResult<(Self, bool)> {
+        if let Some(existing) = Self::find_by_event_id(db, &params.event_id).await? {
+            return Ok((existing, false));
+        }
+
+        let event = payment_events::ActiveModel {
+            provider: ActiveValue::set(params.provider.clone()),
+            event_id: ActiveValue::set(params.event_id.clone()),
+            event_type: ActiveValue::set(params.event_type.clone()),
+            payment_id: ActiveValue::set(params.payment_id.clone()),
+            order_id: ActiveValue::set(params.order_id),
+            payment_status: ActiveValue::set(params.payment_status.clone()),
+            occurred_at: ActiveValue::set(params.occurred_at),
+            payload: ActiveValue::set(params.payload.clone()),
+            status: ActiveValue::set("pending".to_string()),
+            ..Default::default()
+        };
+        match event.insert(db).await {
+            Ok(event) => Ok((event, true)),
+            // Entrega simultânea do mesmo evento: a outra requisição gravou primeiro
+            Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                let existing = Self::find_by_event_id(db, &params.event_id)
+                    .await?
+                    .ok_or(ModelError::EntityNotFound)?;
+                Ok((existing, false))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn find_by_event_id(
+        db: &DatabaseConnection,
+        event_id: &str,
+    ) -> ModelResult<Option<Self>> {
+        let event = Entity::find()
+            .filter(payment_events::Column::EventId.eq(event_id))
+            .one(db)
+            .await?;
+        Ok(event)
+    }
+
+    /// Este evento é anterior ao último aplicado? Compara a data do evento no
+    /// gateway; sem data (ou no empate), vale a precedência do status — um
+    /// `PAYMENT_CREATED` atrasado não desfaz um `PAYMENT_RECEIVED`.
+    fn is_older_than(&self, last: &Self) -> bool {
+        match (self.occurred_at, last.occurred_at) {
+            (Some(current), Some(previous)) if current != previous => current < previous,
+            _ => {
+                status_rank(self.payment_status.as_deref())
+                    < status_rank(last.payment_status.as_deref())
+            }
+        }
+    }
+
+    /// Processa o evento: aplica o status de pagamento ao pedido, salvo se já
+    /// foi processado ou se é mais antigo que o último aplicado.
+    ///
+    /// Os eventos de um pedido são serializados pelo lock na linha do pedido.
+    /// Falhas deixam o evento como `failed` para reprocessar no reenvio.
+    pub async fn process(db: &DatabaseConnection, event: Self) -> ModelResult<EventOutcome> {
+        if event.status == "processed" || event.status == "skipped" {
+            return Ok(EventOutcome::Duplicate);
+        }
+        let (Some(order_id), Some(payment_status)) = (event.order_id, event.payment_status.clone())
+        else {
+            Self::finish(db, event, "skipped", Some("Evento sem pedido ou status")).await?;
+            return Ok(EventOutcome::Ignored);
+        };
+
+        let result: ModelResult<EventOutcome> = async {
+            let txn = db.begin().await?;
+            orders::Entity::find_by_id(order_id)
+                .lock_exclusive()
+                .one(&txn)
+                .await?
+                .ok_or(ModelError::EntityNotFound)?;
+            // Entrega simultânea do mesmo evento: a primeira a pegar o lock
+            // aplica, a outra encontra o evento já concluído
+            let current = Entity::find_by_id(event.id)
+                .lock_exclusive()
+                .one(&txn)
+                .await?
+                .ok_or(ModelError::EntityNotFound)?;
+            if current.status == "processed" || current.status == "skipped" {
+                txn.commit().await?;
+                return Ok(EventOutcome::Duplicate);
+            }
+
+            // Cobrança adicional de uma edição, ou cobrança substituída ainda
+            // não paga: o pagamento principal do pedido não muda
+            let edit = match &event.payment_id {
+                Some(payment_id) => OrderEditModel::find_by_payment_id(&txn, payment_id).await?,
+                None => None,
+            };
+            if let Some(edit) =
+                edit.filter(|e| e.settlement == "charge" || payment_status != "paid")
+            {
+                let edit = OrderEditModel::apply_charge_status(&txn, edit, &payment_status).await?;
+                Self::finish(&txn, event.clone(), "processed", None).await?;
+                txn.commit().await?;
+                return Ok(EventOutcome::OrderEdit(edit));
+            }
+
+            let last_applied = Entity::find()
+                .filter(payment_events::Column::OrderId.eq(order_id))
+                .filter(payment_events::Column::Status.eq("processed"))
+                .order_by_desc(payment_events::Column::OccurredAt)
+                .order_by_desc(payment_events::Column::Id)
+                .one(&txn)
+                .await?;
+            if last_applied.is_some_and(|last| event.is_older_than(&last)) {
+                txn.commit().await?;
+                return Ok(EventOutcome::Stale);
+            }
+
+            let payment_data = serde_json::json!({
+                "provider": event.provider,
+                "event": event.event_type,
+                "event_id": event.event_id,
+                "payment": event.payload.get("payment").unwrap_or(&event.payload),
+            });
+            let updated = OrderModel::update_payment_status(
+                &txn,
+                order_id,
+                payment_status.parse()?,
+                Some(payment_data),
+                &StatusChange::system("webhook", Some(&event.event_type)),
+            )
+            .await?;
+            Self::finish(&txn, event.clone(), "processed", None).await?;
+            txn.commit().await?;
+            Ok(EventOutcome::Applied(updated))
+        }
+        .await;
+        let result = match result {
+            Err(e) => match TransitionError::from_model_error(&e) {
+                Some(rejected) => Ok(EventOutcome::Rejected(rejected.to_string())),
+                None => Err(e),
+            },
+            ok => ok,
+        };
+
+        match &result {
+            Ok(EventOutcome::Stale) => {
+                Self::finish(
+                    db,
+                    event,
+                    "skipped",
+                    Some("Evento anterior ao último aplicado"),
+                )
+                .await?;
+            }
+            Ok(EventOutcome::Rejected(reason)) => {
+                Self::finish(db, event, "skipped", Some(reason)).await?;
+            }
+            Err(e) => {
+                Self::finish(db, event, "failed", Some(&e.to_string())).await?;
+            }
+            _ => {}
+        }
+        result
+    }
+
+    async fn finish<C: ConnectionTrait>(
+        db: &C,
+        event: Self,
+        status: &str,
+        error: Option<&str>,
+    ) -> ModelResult<Self> {
+        let mut active: payment_events::ActiveModel = event.into();
+        active.status = ActiveValue::set(status.to_string());
+        active.error = ActiveValue::set(error.map(String::from));
+        active.processed_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+}
@@ -4,6 +4,10 @@ use uuid::Uuid;
 
 pub use super::_entities::prices;
 pub use super::_entities::product_variants::{self, ActiveModel, Entity, Model};
+use super::_entities::products;
+use crate::services::pricing;
+use crate::shipping::ParcelItem;
+use rust_decimal::prelude::ToPrimitive;
 
 use loco_rs::prelude::*;
 
@@ -132,36 +136,59 @@ impl Model {
         Ok(prices_list)
     }
 
-    /// Retorna o preço ativo para uma moeda e quantidade
+    /// Retorna o preço ativo para uma moeda e quantidade (sem região).
+    /// Ver `services::pricing` para as regras de escolha.
     pub async fn get_active_price(
         db: &DatabaseConnection,
         variant_id: i32,
         currency: &str,
         quantity: i32,
     ) -> ModelResult<prices::Model> {
-        let now = chrono::Utc::now();
-        let price = prices::Entity::find()
-            .filter(prices::Column::VariantId.eq(variant_id))
-            .filter(prices::Column::Currency.eq(currency))
-            .filter(prices::Column::MinQuantity.lte(quantity))
-            .filter(
-                prices::Column::MaxQuantity
-                    .is_null()
-                    .or(prices::Column::MaxQuantity.gte(quantity)),
-            )
-            .filter(
-                prices::Column::StartsAt
-                    .is_null()
-                    .or(prices::Column::StartsAt.lte(now)),
-            )
-            .filter(
-                prices::Column::EndsAt
-                    .is_null()
-                    .or(prices::Column::EndsAt.gte(now)),
-            )
-            .order_by_desc(prices::Column::MinQuantity)
+        let query = pricing::PriceQuery::now(variant_id, currency, quantity, None);
+        let quote = pricing::resolve_price(db, &query).await?;
+        Ok(quote.price)
+    }
+
+    /// Peso (kg → g) e dimensões (JSON `{length, width, height}` em cm) da
+    /// variante para o empacotamento. Usa os dados do produto quando a
+    /// variante não tem os seus.
+    pub async fn parcel_item(
+        db: &DatabaseConnection,
+        variant_id: i32,
+        quantity: i32,
+        unit_value_cents: i64,
+    ) -> ModelResult<ParcelItem> {
+        let variant = Entity::find_by_id(variant_id)
             .one(db)
-            .await?;
-        price.ok_or_else(|| ModelError::EntityNotFound)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let product = products::Entity::find_by_id(variant.product_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        let weight_kg = variant.weight.or(product.weight).unwrap_or_default();
+        let dimensions = variant
+            .dimensions
+            .or(product.dimensions)
+            .unwrap_or_else(|| serde_json::json!({}));
+        let dim = |key: &str| {
+            dimensions
+                .get(key)
+                .and_then(serde_json::Value::as_f64)
+                .map_or(0, |v| v.ceil() as u32)
+        };
+
+        Ok(ParcelItem {
+            weight_grams: (weight_kg * rust_decimal::Decimal::from(1000))
+                .ceil()
+                .to_u32()
+                .unwrap_or(0),
+            length_cm: dim("length"),
+            width_cm: dim("width"),
+            height_cm: dim("height"),
+            quantity: quantity.max(0) as u32,
+            unit_value_cents,
+        })
     }
 }
@@ -175,6 +175,7 @@ impl Model {
                     name: "Default".to_string(),
                     latitude: 0.0,
                     longitude: 0.0,
+                    postal_code: None,
                 }).await?
             }
         };
@@ -184,7 +185,9 @@ impl Model {
             warehouse_id: warehouse.id,
             item_id: item.id,
             quantity: 0,
-        }).await?;
+            reason: None,
+            notes: None,
+        }, None).await?;
 
         Ok(product)
     }
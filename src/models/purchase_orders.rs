@@ -0,0 +1,400 @@
+//! Pedidos de compra a fornecedores
+//!
+//! Fluxo: `open` → `partially_received` → `received`; cancelamento enquanto
+//! houver saldo a receber (o que já entrou permanece). Cada conferência cria
+//! um lote (`items`) com lote/validade e custo de aquisição, e dá entrada na
+//! linha de estoque do depósito pelo razão (`stock_movements`, tipo
+//! `receipt`) com o PID do pedido como referência.
+//!
+//! O custo de aquisição (landed cost) de cada item é o custo unitário mais o
+//! frete e as despesas do pedido, rateados pelo valor de cada linha.
+
+use sea_orm::{sea_query::Expr, ConnectionTrait, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::purchase_orders::{self, ActiveModel, Entity, Model};
+use super::_entities::{
+    items, product_variants, purchase_order_items, purchase_order_receipts, stocks, suppliers,
+    warehouses,
+};
+use super::stock_movements::{Model as MovementModel, MovementKind, MovementSource};
+use loco_rs::prelude::*;
+
+impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for purchase_order_items::ActiveModel {}
+impl ActiveModelBehavior for purchase_order_receipts::ActiveModel {}
+
+/// Parâmetros para abrir um pedido de compra
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePurchaseOrderParams {
+    pub supplier_id: i32,
+    pub warehouse_id: i32,
+    pub supplier_reference: Option<String>,
+    /// Frete da compra, em centavos
+    #[serde(default)]
+    pub freight_cost: i64,
+    /// Impostos, seguro e demais despesas, em centavos
+    #[serde(default)]
+    pub other_costs: i64,
+    pub expected_at: Option<chrono::NaiveDate>,
+    pub notes: Option<String>,
+    pub items: Vec<PurchaseOrderItemParams>,
+}
+
+/// Variante, quantidade e custo unitário (centavos) comprados
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PurchaseOrderItemParams {
+    pub variant_id: i32,
+    pub quantity: i32,
+    pub unit_cost: i64,
+}
+
+/// Conferência de mercadoria recebida
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReceivePurchaseOrderParams {
+    pub items: Vec<ReceivedPurchaseItemParams>,
+    pub notes: Option<String>,
+}
+
+/// Quantidade recebida de um item do pedido, com o lote e a validade
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReceivedPurchaseItemParams {
+    pub purchase_order_item_id: i32,
+    pub quantity: i32,
+    pub batch: Option<String>,
+    pub expiration: Option<chrono::NaiveDate>,
+}
+
+/// Rateia `extra` entre as linhas (quantidade, custo unitário) pelo valor de
+/// cada uma — pela quantidade, se o pedido não tem valor — e retorna o custo
+/// unitário com rateio, arredondado ao centavo
+fn landed_unit_costs(lines: &[(i32, i64)], extra: i64) -> Vec<i64> {
+    let extra = i128::from(extra);
+    let total_value: i128 = lines
+        .iter()
+        .map(|(q, c)| i128::from(*q) * i128::from(*c))
+        .sum();
+    let total_quantity: i128 = lines.iter().map(|(q, _)| i128::from(*q)).sum();
+    lines
+        .iter()
+        .map(|(qty, cost)| {
+            let qty = i128::from(*qty);
+            let share = if total_value > 0 {
+                extra * qty * i128::from(*cost) / total_value
+            } else if total_quantity > 0 {
+                extra * qty / total_quantity
+            } else {
+                0
+            };
+            let per_unit = if qty > 0 { (share + qty / 2) / qty } else { 0 };
+            cost + per_unit as i64
+        })
+        .collect()
+}
+
+impl Model {
+    /// Busca pedido de compra pelo PID
+    pub async fn find_by_pid<C: ConnectionTrait>(db: &C, pid: &Uuid) -> ModelResult<Self> {
+        Entity::find()
+            .filter(purchase_orders::Column::Pid.eq(*pid))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Lista pedidos de compra, mais recentes primeiro, com filtros opcionais
+    pub async fn list<C: ConnectionTrait>(
+        db: &C,
+        status: Option<&str>,
+        supplier_id: Option<i32>,
+        warehouse_id: Option<i32>,
+        cursor: Option<i32>,
+        limit: u64,
+    ) -> ModelResult<Vec<Self>> {
+        let mut query = Entity::find();
+        if let Some(status) = status {
+            query = query.filter(purchase_orders::Column::Status.eq(status));
+        }
+        if let Some(supplier_id) = supplier_id {
+            query = query.filter(purchase_orders::Column::SupplierId.eq(supplier_id));
+        }
+        if let Some(warehouse_id) = warehouse_id {
+            query = query.filter(purchase_orders::Column::WarehouseId.eq(warehouse_id));
+        }
+        if let Some(cursor) = cursor {
+            query = query.filter(purchase_orders::Column::Id.lt(cursor));
+        }
+        let orders = query
+            .order_by_desc(purchase_orders::Column::Id)
+            .limit(limit.min(100))
+            .all(db)
+            .await?;
+        Ok(orders)
+    }
+
+    /// Itens do pedido de compra
+    pub async fn items<C: ConnectionTrait>(
+        &self,
+        db: &C,
+    ) -> ModelResult<Vec<purchase_order_items::Model>> {
+        let items = purchase_order_items::Entity::find()
+            .filter(purchase_order_items::Column::PurchaseOrderId.eq(self.id))
+            .order_by_asc(purchase_order_items::Column::Id)
+            .all(db)
+            .await?;
+        Ok(items)
+    }
+
+    /// Recebimentos do pedido, com o lote criado em cada um
+    pub async fn receipts<C: ConnectionTrait>(
+        &self,
+        db: &C,
+    ) -> ModelResult<Vec<(purchase_order_receipts::Model, Option<items::Model>)>> {
+        let line_ids: Vec<i32> = self.items(db).await?.iter().map(|l| l.id).collect();
+        let receipts = purchase_order_receipts::Entity::find()
+            .filter(purchase_order_receipts::Column::PurchaseOrderItemId.is_in(line_ids))
+            .order_by_asc(purchase_order_receipts::Column::Id)
+            .find_also_related(items::Entity)
+            .all(db)
+            .await?;
+        Ok(receipts)
+    }
+
+    /// Abre um pedido de compra `open`. O custo com rateio de cada linha é
+    /// calculado aqui; o estoque só entra no recebimento.
+    pub async fn create(
+        db: &DatabaseConnection,
+        params: &CreatePurchaseOrderParams,
+        created_by: Option<i32>,
+    ) -> ModelResult<Self> {
+        if params.items.is_empty() {
+            return Err(ModelError::msg("Informe ao menos um item"));
+        }
+        if params.freight_cost < 0 || params.other_costs < 0 {
+            return Err(ModelError::msg("Frete e despesas não podem ser negativos"));
+        }
+        for line in &params.items {
+            if line.quantity <= 0 {
+                return Err(ModelError::msg("Quantidade deve ser maior que zero"));
+            }
+            if line.unit_cost < 0 {
+                return Err(ModelError::msg("Custo unitário não pode ser negativo"));
+            }
+            product_variants::Entity::find_by_id(line.variant_id)
+                .one(db)
+                .await?
+                .ok_or_else(|| {
+                    ModelError::Message(format!("Variante {} não encontrada", line.variant_id))
+                })?;
+        }
+        suppliers::Entity::find_by_id(params.supplier_id)
+            .filter(suppliers::Column::DeletedAt.is_null())
+            .one(db)
+            .await?
+            .ok_or_else(|| {
+                ModelError::Message(format!("Fornecedor {} não encontrado", params.supplier_id))
+            })?;
+        warehouses::Entity::find_by_id(params.warehouse_id)
+            .filter(warehouses::Column::DeletedAt.is_null())
+            .one(db)
+            .await?
+            .ok_or_else(|| {
+                ModelError::Message(format!("Depósito {} não encontrado", params.warehouse_id))
+            })?;
+
+        let txn = db.begin().await?;
+        let order = purchase_orders::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            supplier_id: ActiveValue::set(params.supplier_id),
+            warehouse_id: ActiveValue::set(params.warehouse_id),
+            status: ActiveValue::set("open".to_string()),
+            supplier_reference: ActiveValue::set(params.supplier_reference.clone()),
+            freight_cost: ActiveValue::set(params.freight_cost),
+            other_costs: ActiveValue::set(params.other_costs),
+            expected_at: ActiveValue::set(params.expected_at),
+            notes: ActiveValue::set(params.notes.clone()),
+            created_by: ActiveValue::set(created_by),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        let costs: Vec<(i32, i64)> = params
+            .items
+            .iter()
+            .map(|l| (l.quantity, l.unit_cost))
+            .collect();
+        let landed = landed_unit_costs(&costs, params.freight_cost + params.other_costs);
+        for (line, landed_unit_cost) in params.items.iter().zip(landed) {
+            purchase_order_items::ActiveModel {
+                purchase_order_id: ActiveValue::set(order.id),
+                variant_id: ActiveValue::set(line.variant_id),
+                quantity: ActiveValue::set(line.quantity),
+                received_quantity: ActiveValue::set(0),
+                unit_cost: ActiveValue::set(line.unit_cost),
+                landed_unit_cost: ActiveValue::set(landed_unit_cost),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+        Ok(order)
+    }
+
+    /// Recebe mercadoria no depósito do pedido. Cada linha conferida vira um
+    /// lote com o custo de aquisição e entra no estoque; quantidade acima do
+    /// saldo a receber é recusada. O pedido fica `received` quando todas as
+    /// linhas foram recebidas por completo.
+    pub async fn receive(
+        db: &DatabaseConnection,
+        order: Self,
+        params: &ReceivePurchaseOrderParams,
+        received_by: Option<i32>,
+    ) -> ModelResult<Self> {
+        if params.items.is_empty() {
+            return Err(ModelError::msg("Informe ao menos um item recebido"));
+        }
+
+        let txn = db.begin().await?;
+        let order = Self::lock(&txn, order.id).await?;
+        if !["open", "partially_received"].contains(&order.status.as_str()) {
+            return Err(ModelError::Message(format!(
+                "Pedido de compra com status '{}' não pode ser recebido",
+                order.status
+            )));
+        }
+
+        let mut lines = order.items(&txn).await?;
+        let source = MovementSource {
+            user_id: received_by,
+            reason: Some("purchase_order".to_string()),
+            reference: Some(order.pid.to_string()),
+            notes: order.supplier_reference.clone(),
+            ..Default::default()
+        };
+        let today = chrono::Utc::now().date_naive();
+        for received in &params.items {
+            let Some(line) = lines
+                .iter_mut()
+                .find(|l| l.id == received.purchase_order_item_id)
+            else {
+                return Err(ModelError::Message(format!(
+                    "Item {} não pertence ao pedido de compra",
+                    received.purchase_order_item_id
+                )));
+            };
+            let pending = line.quantity - line.received_quantity;
+            if received.quantity <= 0 || received.quantity > pending {
+                return Err(ModelError::Message(format!(
+                    "Item {}: quantidade recebida deve estar entre 1 e {pending}",
+                    line.id
+                )));
+            }
+            if received.expiration.is_some_and(|e| e < today) {
+                return Err(ModelError::Message(format!(
+                    "Item {}: lote já vencido",
+                    line.id
+                )));
+            }
+
+            let batch = items::ActiveModel {
+                pid: ActiveValue::set(Uuid::new_v4()),
+                variant_id: ActiveValue::set(line.variant_id),
+                batch: ActiveValue::set(received.batch.clone()),
+                expiration: ActiveValue::set(received.expiration),
+                landed_cost: ActiveValue::set(Some(line.landed_unit_cost)),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            let stock = stocks::ActiveModel {
+                warehouse_id: ActiveValue::set(order.warehouse_id),
+                item_id: ActiveValue::set(batch.id),
+                quantity: ActiveValue::set(0),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            MovementModel::apply(
+                &txn,
+                stock.id,
+                MovementKind::Receipt,
+                received.quantity,
+                0,
+                &source,
+            )
+            .await?;
+            product_variants::Entity::update_many()
+                .col_expr(
+                    product_variants::Column::InventoryQuantity,
+                    Expr::col(product_variants::Column::InventoryQuantity).add(received.quantity),
+                )
+                .filter(product_variants::Column::Id.eq(line.variant_id))
+                .exec(&txn)
+                .await?;
+
+            purchase_order_receipts::ActiveModel {
+                purchase_order_item_id: ActiveValue::set(line.id),
+                item_id: ActiveValue::set(batch.id),
+                stock_id: ActiveValue::set(Some(stock.id)),
+                quantity: ActiveValue::set(received.quantity),
+                landed_unit_cost: ActiveValue::set(line.landed_unit_cost),
+                received_by: ActiveValue::set(received_by),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+
+            line.received_quantity += received.quantity;
+            let mut active: purchase_order_items::ActiveModel = line.clone().into();
+            active.received_quantity = ActiveValue::set(line.received_quantity);
+            active.update(&txn).await?;
+        }
+
+        let complete = lines.iter().all(|l| l.received_quantity >= l.quantity);
+        let notes = match (&order.notes, &params.notes) {
+            (Some(old), Some(new)) => Some(format!("{old}\n{new}")),
+            (old, new) => new.clone().or_else(|| old.clone()),
+        };
+        let mut active: purchase_orders::ActiveModel = order.into();
+        active.notes = ActiveValue::set(notes);
+        if complete {
+            active.status = ActiveValue::set("received".to_string());
+            active.received_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        } else {
+            active.status = ActiveValue::set("partially_received".to_string());
+        }
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    /// Cancela o saldo ainda não recebido; o que já entrou permanece
+    pub async fn cancel(db: &DatabaseConnection, order: Self) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let order = Self::lock(&txn, order.id).await?;
+        if !["open", "partially_received"].contains(&order.status.as_str()) {
+            return Err(ModelError::Message(format!(
+                "Pedido de compra com status '{}' não pode ser cancelado",
+                order.status
+            )));
+        }
+        let mut active: purchase_orders::ActiveModel = order.into();
+        active.status = ActiveValue::set("canceled".to_string());
+        active.canceled_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    async fn lock<C: ConnectionTrait>(db: &C, id: i32) -> ModelResult<Self> {
+        Entity::find_by_id(id)
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+
+use sea_orm::{ConnectionTrait, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::refunds::{self, ActiveModel, Entity, Model};
+use super::_entities::{order_items, orders};
+use super::order_edits::Model as OrderEditModel;
+use super::order_status::PaymentStatus;
+use super::order_status_history::StatusChange;
+use super::orders::Model as OrderModel;
+use super::stock_reservations::Model as StockReservationModel;
+use loco_rs::prelude::*;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RefundItemParams {
+    pub order_item_pid: Uuid,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateRefundParams {
+    /// Centavos; sem valor estorna todo o saldo ainda não estornado
+    pub amount: Option<i64>,
+    pub reason: Option<String>,
+    /// Devolve os itens ao estoque
+    #[serde(default)]
+    pub restock: bool,
+    /// Itens a devolver; vazio com `restock` devolve tudo que ainda não voltou
+    #[serde(default)]
+    pub items: Vec<RefundItemParams>,
+}
+
+/// Linha devolvida ao estoque (gravada em `refunds.items`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RestockLine {
+    pub order_item_id: i32,
+    pub variant_id: i32,
+    pub quantity: i32,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Status de pagamento que aceitam estorno
+const REFUNDABLE_STATUSES: &[&str] = &["paid", "partially_refunded"];
+
+/// Cobrança paga no gateway: a original do pedido ou a adicional de uma edição
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Charge {
+    pub payment_id: String,
+    /// Centavos
+    pub amount: i64,
+}
+
+impl Model {
+    /// Estornos do pedido, do mais antigo ao mais recente
+    pub async fn list_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let refunds = Entity::find()
+            .filter(refunds::Column::OrderId.eq(order_id))
+            .order_by_asc(refunds::Column::Id)
+            .all(db)
+            .await?;
+        Ok(refunds)
+    }
+
+    /// Valor efetivamente cobrado do cliente: o total do pedido mais os juros
+    /// do parcelamento no cartão, mais o que edições devolveram por estorno,
+    /// menos cobranças adicionais de edições ainda não pagas
+    pub async fn charged_amount<C: ConnectionTrait>(
+        db: &C,
+        order: &orders::Model,
+    ) -> ModelResult<i64> {
+        let adjustment: i64 = OrderEditModel::list_for_order(db, order.id)
+            .await?
+            .iter()
+            .map(
+                |e| match (e.settlement.as_str(), e.settlement_status.as_str()) {
+                    ("refund", _) => -e.difference,
+                    ("charge", status) if status != "done" => -e.difference,
+                    _ => 0,
+                },
+            )
+            .sum();
+        let interest = order
+            .payment_data
+            .get("interest_cents")
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or(0);
+        Ok(order.total + interest + adjustment)
+    }
+
+    /// Cobranças pagas do pedido, da mais recente à original. O gateway só
+    /// estorna até o valor de cada cobrança, então estornos maiores são
+    /// divididos entre elas.
+    pub async fn charges<C: ConnectionTrait>(
+        db: &C,
+        order: &orders::Model,
+    ) -> ModelResult<Vec<Charge>> {
+        let mut charges: Vec<Charge> = OrderEditModel::list_for_order(db, order.id)
+            .await?
+            .into_iter()
+            .rev()
+            .filter(|e| e.settlement == "charge" && e.settlement_status == "done")
+            .filter_map(|e| {
+                Some(Charge {
+                    payment_id: e.payment_id?,
+                    amount: e.difference,
+                })
+            })
+            .collect();
+        let extra: i64 = charges.iter().map(|c| c.amount).sum();
+        if let Some(payment_id) = order.provider_payment_id(db).await? {
+            charges.push(Charge {
+                payment_id,
+                amount: Self::charged_amount(db, order).await? - extra,
+            });
+        }
+        Ok(charges)
+    }
+
+    /// Linhas devolvidas ao estoque por este estorno
+    pub fn restock_lines(&self) -> Vec<RestockLine> {
+        serde_json::from_value(self.items.clone()).unwrap_or_default()
+    }
+
+    /// Valida e registra o estorno como `pending`, antes da chamada ao gateway.
+    ///
+    /// O pedido fica bloqueado durante a validação, então dois estornos
+    /// simultâneos não conseguem passar do total pago. Valor maior que o
+    /// saldo de uma cobrança vira um estorno por cobrança ([`Self::charges`]);
+    /// os itens devolvidos ficam no primeiro.
+    pub async fn start(
+        db: &DatabaseConnection,
+        order_id: i32,
+        params: &CreateRefundParams,
+        created_by: Option<i32>,
+    ) -> ModelResult<Vec<Self>> {
+        let txn = db.begin().await?;
+        let order = orders::Entity::find_by_id(order_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        if !REFUNDABLE_STATUSES.contains(&order.payment_status.as_str()) {
+            return Err(ModelError::Message(format!(
+                "Pedido com pagamento '{}' não pode ser estornado",
+                order.payment_status
+            )));
+        }
+
+        let previous: Vec<Self> = Self::list_for_order(&txn, order.id)
+            .await?
+            .into_iter()
+            .filter(|r| r.status != "failed")
+            .collect();
+        let refunded: i64 = previous.iter().map(|r| r.amount).sum();
+        let remaining = Self::charged_amount(&txn, &order).await? - refunded;
+
+        let amount = params.amount.unwrap_or(remaining);
+        if amount <= 0 {
+            return Err(ModelError::msg("Valor do estorno deve ser positivo"));
+        }
+        if amount > remaining {
+            return Err(ModelError::Message(format!(
+                "Valor do estorno ({}) maior que o saldo estornável ({})",
+                amount, remaining
+            )));
+        }
+
+        let lines = if params.restock {
+            Self::restock_plan(&txn, &order, &previous, &params.items).await?
+        } else {
+            vec![]
+        };
+        let charges = Self::charges(&txn, &order).await?;
+        if charges.is_empty() {
+            return Err(ModelError::msg("Pedido sem cobrança no gateway"));
+        }
+
+        // Saldo de cada cobrança; estornos de cobrança desconhecida contam na original
+        let mut balances: Vec<(String, i64)> = charges
+            .into_iter()
+            .map(|c| (c.payment_id, c.amount))
+            .collect();
+        for refund in &previous {
+            let index = balances
+                .iter()
+                .position(|(id, _)| refund.provider_payment_id.as_deref() == Some(id.as_str()))
+                .unwrap_or(balances.len() - 1);
+            balances[index].1 -= refund.amount;
+        }
+
+        let mut parts = Vec::new();
+        let mut left = amount;
+        for (payment_id, balance) in balances {
+            if left <= 0 {
+                break;
+            }
+            let take = balance.min(left);
+            if take > 0 {
+                parts.push((payment_id, take));
+                left -= take;
+            }
+        }
+        if left > 0 {
+            return Err(ModelError::Message(format!(
+                "Valor do estorno ({}) maior que o saldo das cobranças ({})",
+                amount,
+                amount - left
+            )));
+        }
+
+        let mut refunds = Vec::new();
+        for (index, (payment_id, part)) in parts.into_iter().enumerate() {
+            let items = if index == 0 { lines.clone() } else { vec![] };
+            let refund = refunds::ActiveModel {
+                pid: ActiveValue::set(Uuid::new_v4()),
+                order_id: ActiveValue::set(order.id),
+                amount: ActiveValue::set(part),
+                reason: ActiveValue::set(params.reason.clone()),
+                status: ActiveValue::set("pending".to_string()),
+                provider: ActiveValue::set(
+                    order
+                        .payment_provider
+                        .clone()
+                        .unwrap_or_else(|| "asaas".to_string()),
+                ),
+                provider_payment_id: ActiveValue::set(Some(payment_id)),
+                items: ActiveValue::set(serde_json::json!(items)),
+                restock: ActiveValue::set(params.restock),
+                raw_data: ActiveValue::set(serde_json::json!({})),
+                created_by: ActiveValue::set(created_by),
+                ..Default::default()
+            };
+            refunds.push(refund.insert(&txn).await?);
+        }
+        txn.commit().await?;
+        Ok(refunds)
+    }
+
+    /// Confirma o estorno no gateway: devolve os itens ao estoque e atualiza
+    /// o pagamento do pedido (`refunded` ou `partially_refunded`)
+    pub async fn complete(
+        db: &DatabaseConnection,
+        refund: Self,
+        raw_data: serde_json::Value,
+    ) -> ModelResult<(Self, orders::Model)> {
+        let txn = db.begin().await?;
+        let order = orders::Entity::find_by_id(refund.order_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        for line in refund.restock_lines() {
+            StockReservationModel::restock_for_order(
+                &txn,
+                order.id,
+                line.variant_id,
+                line.quantity,
+            )
+            .await?;
+        }
+
+        let mut active: refunds::ActiveModel = refund.into();
+        active.status = ActiveValue::set("done".to_string());
+        active.raw_data = ActiveValue::set(raw_data);
+        active.refunded_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let refund = active.update(&txn).await?;
+
+        let refunded: i64 = Self::list_for_order(&txn, order.id)
+            .await?
+            .iter()
+            .filter(|r| r.status == "done")
+            .map(|r| r.amount)
+            .sum();
+        let payment_status = if refunded >= Self::charged_amount(&txn, &order).await? {
+            PaymentStatus::Refunded
+        } else {
+            PaymentStatus::PartiallyRefunded
+        };
+        let change = StatusChange {
+            changed_by: refund.created_by,
+            source: "refund".to_string(),
+            reason: refund.reason.clone(),
+        };
+        let order =
+            OrderModel::update_payment_status(&txn, order.id, payment_status, None, &change)
+                .await?;
+
+        txn.commit().await?;
+        Ok((refund, order))
+    }
+
+    /// Marca o estorno como recusado pelo gateway; o saldo volta a ficar disponível
+    pub async fn fail(db: &DatabaseConnection, refund: Self, error: &str) -> ModelResult<Self> {
+        let mut active: refunds::ActiveModel = refund.into();
+        active.status = ActiveValue::set("failed".to_string());
+        active.error = ActiveValue::set(Some(error.to_string()));
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Quantidades a devolver por item, limitadas ao que ainda não voltou ao
+    /// estoque em estornos anteriores
+    async fn restock_plan<C: ConnectionTrait>(
+        db: &C,
+        order: &orders::Model,
+        previous: &[Self],
+        requested: &[RefundItemParams],
+    ) -> ModelResult<Vec<RestockLine>> {
+        let items = order_items::Entity::find()
+            .filter(order_items::Column::OrderId.eq(order.id))
+            .order_by_asc(order_items::Column::Id)
+            .all(db)
+            .await?;
+
+        let mut restocked: HashMap<i32, i32> = HashMap::new();
+        for line in previous.iter().flat_map(Self::restock_lines) {
+            *restocked.entry(line.order_item_id).or_default() += line.quantity;
+        }
+        let available = |item: &order_items::Model, restocked: &HashMap<i32, i32>| {
+            item.quantity - restocked.get(&item.id).copied().unwrap_or(0)
+        };
+
+        let mut lines = Vec::new();
+        if requested.is_empty() {
+            for item in &items {
+                let (Some(variant_id), quantity) = (item.variant_id, available(item, &restocked))
+                else {
+                    continue;
+                };
+                if quantity > 0 {
+                    lines.push(RestockLine {
+                        order_item_id: item.id,
+                        variant_id,
+                        quantity,
+                    });
+                }
+            }
+            return Ok(lines);
+        }
+
+        for request in requested {
+            let item = items
+                .iter()
+                .find(|i| i.pid == request.order_item_pid)
+                .ok_or_else(|| {
+                    ModelError::Message(format!(
+                        "Item {} não pertence ao pedido",
+                        request.order_item_pid
+                    ))
+                })?;
+            let Some(variant_id) = item.variant_id else {
+                return Err(ModelError::Message(format!(
+                    "Item {} sem variante para devolver ao estoque",
+                    item.sku
+                )));
+            };
+            let max = available(item, &restocked);
+            if request.quantity <= 0 || request.quantity > max {
+                return Err(ModelError::Message(format!(
+                    "Quantidade inválida para o item {} (máximo {})",
+                    item.sku, max
+                )));
+            }
+            *restocked.entry(item.id).or_default() += request.quantity;
+            lines.push(RestockLine {
+                order_item_id: item.id,
+                variant_id,
+                quantity: request.quantity,
+            });
+        }
+        Ok(lines)
+    }
+}
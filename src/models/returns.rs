@@ -0,0 +1,711 @@
+use std::collections::HashMap;
+
+use sea_orm::{sea_query::Expr, ConnectionTrait, QueryOrder, QuerySelect, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::returns::{self, ActiveModel, Entity, Model};
+use super::_entities::{items, order_items, orders, product_variants, return_items, stocks};
+use super::order_sequences::{Model as OrderSequenceModel, OrderNumberConfig};
+use super::order_shippings::LABEL_PURCHASING;
+use super::order_status::{FulfillmentStatus, PaymentStatus};
+use super::stock_movements::{Model as MovementModel, MovementKind, MovementSource};
+use super::stock_reservations::{Model as StockReservationModel, ReservationLine};
+use loco_rs::prelude::*;
+
+/// Prazo padrão para pedir devolução, em dias após a compra
+const DEFAULT_RETURN_WINDOW_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReturnItemParams {
+    pub order_item_pid: Uuid,
+    pub quantity: i32,
+    pub reason: Option<String>,
+    /// Troca: variante a receber (mesmo produto); sem ela, a mesma variante
+    pub exchange_variant_pid: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OpenReturnParams {
+    pub reason: String,
+    /// 'refund' (padrão) | 'exchange'
+    pub resolution: Option<String>,
+    pub items: Vec<ReturnItemParams>,
+}
+
+/// Inspeção de um item no recebimento
+#[derive(Debug, Deserialize, Serialize)]
+pub struct InspectItemParams {
+    pub return_item_id: i32,
+    /// 'sellable' volta ao estoque; 'damaged' não
+    pub condition: String,
+    /// Linha de `stocks` que recebe as unidades; sem ela, a primeira linha
+    /// da variante no depósito escolhido
+    pub stock_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReceiveReturnParams {
+    /// Depósito que recebeu os itens
+    pub warehouse_id: i32,
+    pub items: Vec<InspectItemParams>,
+    pub notes: Option<String>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for return_items::ActiveModel {}
+
+/// Parte do desconto do pedido (cupom) que cabe a itens de valor `value`,
+/// proporcional ao subtotal. Arredonda para cima: o estorno nunca passa do
+/// que foi pago.
+fn prorated_discount(value: i64, subtotal: i64, discount: i64) -> i64 {
+    if value <= 0 || subtotal <= 0 || discount <= 0 {
+        return 0;
+    }
+    let (value, subtotal, discount) = (
+        i128::from(value),
+        i128::from(subtotal),
+        i128::from(discount),
+    );
+    let share = (discount * value + subtotal - 1) / subtotal;
+    i64::try_from(share.min(value).min(discount)).unwrap_or(0)
+}
+
+/// Prazo para pedir devolução (`RETURN_WINDOW_DAYS`, padrão 30)
+fn return_window_days() -> i64 {
+    crate::env::load();
+    std::env::var("RETURN_WINDOW_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_RETURN_WINDOW_DAYS)
+}
+
+impl Model {
+    pub async fn find_by_pid(db: &DatabaseConnection, pid: &Uuid) -> ModelResult<Self> {
+        Entity::find()
+            .filter(returns::Column::Pid.eq(*pid))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Devoluções do pedido, da mais antiga à mais recente
+    pub async fn list_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let list = Entity::find()
+            .filter(returns::Column::OrderId.eq(order_id))
+            .order_by_asc(returns::Column::Id)
+            .all(db)
+            .await?;
+        Ok(list)
+    }
+
+    /// Fila do admin, mais recentes primeiro, com filtro opcional de status
+    pub async fn list(
+        db: &DatabaseConnection,
+        status: Option<&str>,
+        cursor: Option<i32>,
+        limit: u64,
+    ) -> ModelResult<Vec<Self>> {
+        let mut query = Entity::find();
+        if let Some(status) = status {
+            query = query.filter(returns::Column::Status.eq(status));
+        }
+        if let Some(cursor) = cursor {
+            query = query.filter(returns::Column::Id.lt(cursor));
+        }
+        let list = query
+            .order_by_desc(returns::Column::Id)
+            .limit(limit.min(100))
+            .all(db)
+            .await?;
+        Ok(list)
+    }
+
+    pub async fn items<C: ConnectionTrait>(&self, db: &C) -> ModelResult<Vec<return_items::Model>> {
+        let list = return_items::Entity::find()
+            .filter(return_items::Column::ReturnId.eq(self.id))
+            .order_by_asc(return_items::Column::Id)
+            .all(db)
+            .await?;
+        Ok(list)
+    }
+
+    /// Abre a devolução. O pedido precisa estar pago, despachado e dentro do
+    /// prazo; cada item não pode passar do que foi comprado menos o que já
+    /// está em outras devoluções não recusadas.
+    pub async fn open(
+        db: &DatabaseConnection,
+        order: &orders::Model,
+        params: &OpenReturnParams,
+    ) -> ModelResult<(Self, Vec<return_items::Model>)> {
+        let resolution = params.resolution.as_deref().unwrap_or("refund");
+        if !matches!(resolution, "refund" | "exchange") {
+            return Err(ModelError::msg(
+                "resolution deve ser 'refund' ou 'exchange'",
+            ));
+        }
+        if params.reason.trim().is_empty() {
+            return Err(ModelError::msg("Informe o motivo da devolução"));
+        }
+        if params.items.is_empty() {
+            return Err(ModelError::msg("Informe os itens a devolver"));
+        }
+        if !matches!(
+            order.payment_status.parse::<PaymentStatus>(),
+            Ok(PaymentStatus::Paid | PaymentStatus::PartiallyRefunded)
+        ) {
+            return Err(ModelError::msg("Só pedidos pagos aceitam devolução"));
+        }
+        if !matches!(
+            order.fulfillment_status.parse::<FulfillmentStatus>(),
+            Ok(FulfillmentStatus::PartiallyFulfilled
+                | FulfillmentStatus::Fulfilled
+                | FulfillmentStatus::Delivered)
+        ) {
+            return Err(ModelError::msg("Pedido ainda não foi enviado"));
+        }
+        let window = return_window_days();
+        let deadline = order.created_at + chrono::Duration::days(window);
+        if chrono::Utc::now() > deadline {
+            return Err(ModelError::Message(format!(
+                "Prazo de devolução ({} dias) encerrado",
+                window
+            )));
+        }
+
+        let txn = db.begin().await?;
+        // Serializa devoluções do mesmo pedido
+        orders::Entity::find_by_id(order.id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        let order_items = order_items::Entity::find()
+            .filter(order_items::Column::OrderId.eq(order.id))
+            .all(&txn)
+            .await?;
+        let mut returned = Self::returned_quantities(&txn, order.id).await?;
+
+        let mut lines = Vec::with_capacity(params.items.len());
+        for request in &params.items {
+            let item = order_items
+                .iter()
+                .find(|i| i.pid == request.order_item_pid)
+                .ok_or_else(|| {
+                    ModelError::Message(format!(
+                        "Item {} não pertence ao pedido",
+                        request.order_item_pid
+                    ))
+                })?;
+            let available = item.quantity - returned.get(&item.id).copied().unwrap_or(0);
+            if request.quantity <= 0 || request.quantity > available {
+                return Err(ModelError::Message(format!(
+                    "Quantidade inválida para o item {} (máximo {})",
+                    item.sku, available
+                )));
+            }
+            *returned.entry(item.id).or_default() += request.quantity;
+
+            let exchange_variant_id = if resolution == "exchange" {
+                Some(Self::exchange_variant(&txn, item, request.exchange_variant_pid).await?)
+            } else {
+                None
+            };
+            lines.push((item.id, request, exchange_variant_id));
+        }
+
+        let ret = returns::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            order_id: ActiveValue::set(order.id),
+            customer_id: ActiveValue::set(order.customer_id),
+            status: ActiveValue::set("requested".to_string()),
+            resolution: ActiveValue::set(resolution.to_string()),
+            reason: ActiveValue::set(params.reason.trim().to_string()),
+            provider_data: ActiveValue::set(serde_json::json!({})),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        let mut saved = Vec::with_capacity(lines.len());
+        for (order_item_id, request, exchange_variant_id) in lines {
+            let item = return_items::ActiveModel {
+                return_id: ActiveValue::set(ret.id),
+                order_item_id: ActiveValue::set(order_item_id),
+                quantity: ActiveValue::set(request.quantity),
+                reason: ActiveValue::set(request.reason.clone()),
+                exchange_variant_id: ActiveValue::set(exchange_variant_id),
+                restocked_quantity: ActiveValue::set(0),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            saved.push(item);
+        }
+
+        txn.commit().await?;
+        Ok((ret, saved))
+    }
+
+    /// Aprova (`approved = true`) ou recusa a devolução ainda em análise
+    pub async fn review(
+        db: &DatabaseConnection,
+        ret: Self,
+        approved: bool,
+        reviewer_id: i32,
+        notes: Option<String>,
+    ) -> ModelResult<Self> {
+        if ret.status != "requested" {
+            return Err(ModelError::Message(format!(
+                "Devolução com status '{}' não pode ser analisada",
+                ret.status
+            )));
+        }
+        let mut active: returns::ActiveModel = ret.into();
+        if approved {
+            active.status = ActiveValue::set("approved".to_string());
+            active.admin_notes = ActiveValue::set(notes);
+        } else {
+            active.status = ActiveValue::set("rejected".to_string());
+            active.rejection_reason = ActiveValue::set(notes);
+        }
+        active.reviewed_by = ActiveValue::set(Some(reviewer_id));
+        active.reviewed_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Grava o ID da etiqueta reversa registrada no provider antes de
+    /// pagá-la (`label_status = purchasing`), para que uma nova tentativa a
+    /// retome. Recusa se outra chamada já gravou uma etiqueta.
+    pub async fn set_label_pending(
+        db: &DatabaseConnection,
+        ret: Self,
+        carrier: &str,
+        provider_id: &str,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let current = Entity::find_by_id(ret.id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        if current.tracking_code.is_some()
+            || current
+                .provider_data
+                .get("provider_id")
+                .is_some_and(|v| !v.is_null())
+        {
+            return Err(ModelError::msg(
+                "Devolução já tem etiqueta de logística reversa",
+            ));
+        }
+        let mut active: returns::ActiveModel = current.into();
+        active.carrier = ActiveValue::set(Some(carrier.to_string()));
+        active.provider_data = ActiveValue::set(serde_json::json!({
+            "provider_id": provider_id,
+            "label_status": LABEL_PURCHASING,
+        }));
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    /// ID da etiqueta reversa cuja compra começou e não terminou
+    pub fn unfinished_label_id(&self) -> Option<&str> {
+        if self
+            .provider_data
+            .get("label_status")
+            .and_then(|v| v.as_str())
+            != Some(LABEL_PURCHASING)
+        {
+            return None;
+        }
+        self.provider_data
+            .get("provider_id")
+            .and_then(|v| v.as_str())
+    }
+
+    /// Grava a etiqueta de logística reversa gerada no provider
+    pub async fn set_label(
+        db: &DatabaseConnection,
+        ret: Self,
+        carrier: &str,
+        result: &crate::shipping::ShipmentResult,
+    ) -> ModelResult<Self> {
+        let mut active: returns::ActiveModel = ret.into();
+        active.carrier = ActiveValue::set(Some(carrier.to_string()));
+        active.tracking_code = ActiveValue::set(result.tracking_code.clone());
+        active.label_url = ActiveValue::set(result.label_url.clone());
+        active.provider_data = ActiveValue::set(serde_json::json!({
+            "provider_id": result.provider_id,
+            "tracking_url": result.tracking_url,
+            "raw": result.raw_data,
+        }));
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Registra o recebimento e a inspeção. Itens em condição de venda voltam
+    /// para a linha de estoque escolhida no depósito (e para o saldo da
+    /// variante); avariados ficam fora do estoque.
+    pub async fn receive(
+        db: &DatabaseConnection,
+        ret: Self,
+        params: &ReceiveReturnParams,
+        received_by: Option<i32>,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        // Recebimento repetido ou simultâneo não devolve as unidades duas vezes
+        if !Self::claim(&txn, ret.id, "approved", "received").await? {
+            return Err(ModelError::Message(format!(
+                "Devolução com status '{}' não pode ser recebida",
+                ret.status
+            )));
+        }
+        let lines = ret.items(&txn).await?;
+        let source = MovementSource {
+            user_id: received_by,
+            ..MovementSource::order(ret.order_id).with_reference(ret.pid.to_string())
+        };
+        for line in &lines {
+            let Some(inspection) = params.items.iter().find(|i| i.return_item_id == line.id) else {
+                return Err(ModelError::Message(format!(
+                    "Informe a inspeção do item {}",
+                    line.id
+                )));
+            };
+            let mut active: return_items::ActiveModel = line.clone().into();
+            active.condition = ActiveValue::set(Some(inspection.condition.clone()));
+            match inspection.condition.as_str() {
+                "sellable" => {
+                    let order_item = order_items::Entity::find_by_id(line.order_item_id)
+                        .one(&txn)
+                        .await?
+                        .ok_or(ModelError::EntityNotFound)?;
+                    let variant_id = order_item.variant_id.ok_or_else(|| {
+                        ModelError::Message(format!(
+                            "Item {} sem variante para devolver ao estoque",
+                            order_item.sku
+                        ))
+                    })?;
+                    let stock = Self::restock_target(
+                        &txn,
+                        params.warehouse_id,
+                        variant_id,
+                        inspection.stock_id,
+                    )
+                    .await?;
+                    restock(&txn, stock.id, variant_id, line.quantity, &source).await?;
+                    active.restocked_quantity = ActiveValue::set(line.quantity);
+                    active.stock_id = ActiveValue::set(Some(stock.id));
+                }
+                "damaged" => {}
+                other => {
+                    return Err(ModelError::Message(format!(
+                        "Condição inválida: '{}' (use 'sellable' ou 'damaged')",
+                        other
+                    )));
+                }
+            }
+            active.update(&txn).await?;
+        }
+
+        let mut active: returns::ActiveModel = ret.into();
+        active.status = ActiveValue::set("received".to_string());
+        active.warehouse_id = ActiveValue::set(Some(params.warehouse_id));
+        active.received_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        if params.notes.is_some() {
+            active.admin_notes = ActiveValue::set(params.notes.clone());
+        }
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    /// Valor a estornar: preço unitário × quantidade devolvida, menos a parte
+    /// proporcional do desconto do pedido
+    pub async fn refund_amount<C: ConnectionTrait>(&self, db: &C) -> ModelResult<i64> {
+        let order = orders::Entity::find_by_id(self.order_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let mut amount = 0;
+        for line in self.items(db).await? {
+            let order_item = order_items::Entity::find_by_id(line.order_item_id)
+                .one(db)
+                .await?
+                .ok_or(ModelError::EntityNotFound)?;
+            amount += order_item.unit_price * i64::from(line.quantity);
+        }
+        Ok(amount - prorated_discount(amount, order.subtotal, order.discount))
+    }
+
+    /// Reserva a devolução recebida para estorno (`received` → `refunding`)
+    /// antes de chamar o gateway; uma segunda conclusão simultânea ou
+    /// repetida é recusada.
+    pub async fn start_refund(db: &DatabaseConnection, ret: Self) -> ModelResult<Self> {
+        if ret.resolution != "refund" || !Self::claim(db, ret.id, "received", "refunding").await? {
+            return Err(ModelError::Message(format!(
+                "Devolução com status '{}' não pode ser concluída",
+                ret.status
+            )));
+        }
+        Self::reload(db, ret.id).await
+    }
+
+    /// Estorno recusado: a devolução volta a `received` para nova tentativa
+    pub async fn abort_refund(db: &DatabaseConnection, ret: Self) -> ModelResult<Self> {
+        Self::claim(db, ret.id, "refunding", "received").await?;
+        Self::reload(db, ret.id).await
+    }
+
+    /// Conclui a devolução com o estorno já feito no gateway
+    pub async fn complete_with_refund(
+        db: &DatabaseConnection,
+        ret: Self,
+        refund_id: i32,
+    ) -> ModelResult<Self> {
+        let mut active: returns::ActiveModel = ret.into();
+        active.status = ActiveValue::set("completed".to_string());
+        active.refund_id = ActiveValue::set(Some(refund_id));
+        active.completed_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Conclui a troca gerando um pedido sem custo com as variantes pedidas.
+    ///
+    /// O pedido de troca já nasce pago (total zero, com o valor dos itens em
+    /// `discount`) e com o estoque baixado, pronto para separação.
+    pub async fn complete_with_exchange(
+        db: &DatabaseConnection,
+        ret: Self,
+    ) -> ModelResult<(Self, orders::Model)> {
+        if ret.resolution != "exchange" {
+            return Err(ModelError::msg("Só trocas recebidas geram pedido de troca"));
+        }
+        let original = orders::Entity::find_by_id(ret.order_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+
+        let txn = db.begin().await?;
+        // Conclusão repetida ou simultânea não gera um segundo pedido de troca
+        if !Self::claim(&txn, ret.id, "received", "completed").await? {
+            return Err(ModelError::msg("Só trocas recebidas geram pedido de troca"));
+        }
+        let config = OrderNumberConfig::from_env();
+        let seq = OrderSequenceModel::next_value(&txn, &config.prefix).await?;
+
+        let mut lines = Vec::new();
+        for line in ret.items(&txn).await? {
+            let order_item = order_items::Entity::find_by_id(line.order_item_id)
+                .one(&txn)
+                .await?
+                .ok_or(ModelError::EntityNotFound)?;
+            let variant_id = line
+                .exchange_variant_id
+                .or(order_item.variant_id)
+                .ok_or_else(|| ModelError::msg("Item da troca sem variante"))?;
+            let variant = product_variants::Entity::find_by_id(variant_id)
+                .one(&txn)
+                .await?
+                .ok_or(ModelError::EntityNotFound)?;
+            lines.push((variant, order_item.unit_price, line.quantity));
+        }
+        let subtotal: i64 = lines.iter().map(|(_, p, q)| p * i64::from(*q)).sum();
+
+        let now: DateTimeWithTimeZone = chrono::Utc::now().into();
+        let order = orders::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            customer_id: ActiveValue::set(original.customer_id),
+            order_number: ActiveValue::set(config.format(seq)),
+            status: ActiveValue::set("confirmed".to_string()),
+            payment_status: ActiveValue::set("paid".to_string()),
+            fulfillment_status: ActiveValue::set("not_fulfilled".to_string()),
+            currency: ActiveValue::set(original.currency.clone()),
+            subtotal: ActiveValue::set(subtotal),
+            tax: ActiveValue::set(0),
+            shipping: ActiveValue::set(0),
+            discount: ActiveValue::set(subtotal),
+            total: ActiveValue::set(0),
+            shipping_address_id: ActiveValue::set(original.shipping_address_id),
+            billing_address_id: ActiveValue::set(original.billing_address_id),
+            payment_method: ActiveValue::set(Some("exchange".to_string())),
+            payment_data: ActiveValue::set(serde_json::json!({})),
+            notes: ActiveValue::set(Some(format!("Troca do pedido {}", original.order_number))),
+            metadata: ActiveValue::set(serde_json::json!({
+                "exchange_of": original.pid,
+                "return_pid": ret.pid,
+            })),
+            paid_at: ActiveValue::set(Some(now)),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        for (variant, unit_price, quantity) in &lines {
+            order_items::ActiveModel {
+                pid: ActiveValue::set(Uuid::new_v4()),
+                order_id: ActiveValue::set(order.id),
+                variant_id: ActiveValue::set(Some(variant.id)),
+                title: ActiveValue::set(variant.title.clone()),
+                sku: ActiveValue::set(variant.sku.clone()),
+                quantity: ActiveValue::set(*quantity),
+                unit_price: ActiveValue::set(*unit_price),
+                total: ActiveValue::set(unit_price * i64::from(*quantity)),
+                metadata: ActiveValue::set(serde_json::json!({})),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        let reservation_lines: Vec<ReservationLine> = lines
+            .iter()
+            .map(|(variant, _, quantity)| ReservationLine {
+                variant_id: variant.id,
+                quantity: *quantity,
+            })
+            .collect();
+        StockReservationModel::reserve_for_order(&txn, order.id, &reservation_lines).await?;
+        StockReservationModel::commit_for_order(&txn, order.id).await?;
+
+        let mut active: returns::ActiveModel = ret.into();
+        active.status = ActiveValue::set("completed".to_string());
+        active.exchange_order_id = ActiveValue::set(Some(order.id));
+        active.completed_at = ActiveValue::set(Some(now));
+        let updated = active.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok((updated, order))
+    }
+
+    /// Muda o status de `from` para `to` só se a devolução ainda estiver em
+    /// `from` (update condicional). `false` quando outra requisição chegou
+    /// antes.
+    async fn claim<C: ConnectionTrait>(db: &C, id: i32, from: &str, to: &str) -> ModelResult<bool> {
+        let result = Entity::update_many()
+            .col_expr(returns::Column::Status, Expr::value(to))
+            .filter(returns::Column::Id.eq(id))
+            .filter(returns::Column::Status.eq(from))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    async fn reload<C: ConnectionTrait>(db: &C, id: i32) -> ModelResult<Self> {
+        Entity::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Quantidade já em devoluções não recusadas, por item do pedido
+    async fn returned_quantities<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<HashMap<i32, i32>> {
+        let mut returned = HashMap::new();
+        for ret in Self::list_for_order(db, order_id).await? {
+            if ret.status == "rejected" {
+                continue;
+            }
+            for line in ret.items(db).await? {
+                *returned.entry(line.order_item_id).or_default() += line.quantity;
+            }
+        }
+        Ok(returned)
+    }
+
+    /// Variante da troca: a pedida (do mesmo produto) ou a do item original
+    async fn exchange_variant<C: ConnectionTrait>(
+        db: &C,
+        item: &order_items::Model,
+        requested: Option<Uuid>,
+    ) -> ModelResult<i32> {
+        let original_id = item.variant_id.ok_or_else(|| {
+            ModelError::Message(format!("Item {} sem variante para troca", item.sku))
+        })?;
+        let Some(pid) = requested else {
+            return Ok(original_id);
+        };
+        let original = product_variants::Entity::find_by_id(original_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let variant = product_variants::Entity::find()
+            .filter(product_variants::Column::Pid.eq(pid))
+            .filter(product_variants::Column::DeletedAt.is_null())
+            .one(db)
+            .await?
+            .ok_or_else(|| ModelError::Message(format!("Variante {} não encontrada", pid)))?;
+        if variant.product_id != original.product_id {
+            return Err(ModelError::Message(format!(
+                "A troca do item {} deve ser por outra variante do mesmo produto",
+                item.sku
+            )));
+        }
+        Ok(variant.id)
+    }
+
+    /// Linha de estoque que recebe a devolução: a informada (validada) ou a
+    /// primeira da variante no depósito
+    async fn restock_target<C: ConnectionTrait>(
+        db: &C,
+        warehouse_id: i32,
+        variant_id: i32,
+        stock_id: Option<i32>,
+    ) -> ModelResult<stocks::Model> {
+        let item_ids: Vec<i32> = items::Entity::find()
+            .filter(items::Column::VariantId.eq(variant_id))
+            .filter(items::Column::DeletedAt.is_null())
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        let mut query = stocks::Entity::find()
+            .filter(stocks::Column::WarehouseId.eq(warehouse_id))
+            .filter(stocks::Column::ItemId.is_in(item_ids));
+        if let Some(stock_id) = stock_id {
+            query = query.filter(stocks::Column::Id.eq(stock_id));
+        }
+        query
+            .order_by_asc(stocks::Column::Id)
+            .one(db)
+            .await?
+            .ok_or_else(|| {
+                ModelError::Message(format!(
+                    "Sem linha de estoque da variante {} no depósito {}",
+                    variant_id, warehouse_id
+                ))
+            })
+    }
+}
+
+/// Soma as unidades na linha de estoque (movimento `return` no razão) e no
+/// saldo da variante
+async fn restock<C: ConnectionTrait>(
+    db: &C,
+    stock_id: i32,
+    variant_id: i32,
+    quantity: i32,
+    source: &MovementSource,
+) -> ModelResult<()> {
+    MovementModel::apply(db, stock_id, MovementKind::Return, quantity, 0, source).await?;
+    product_variants::Entity::update_many()
+        .col_expr(
+            product_variants::Column::InventoryQuantity,
+            Expr::col(product_variants::Column::InventoryQuantity).add(quantity),
+        )
+        .filter(product_variants::Column::Id.eq(variant_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
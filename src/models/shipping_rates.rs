@@ -0,0 +1,258 @@
+use sea_orm::QueryOrder;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::shipping_rates::{self, ActiveModel, Entity, Model};
+use super::_entities::warehouses;
+use loco_rs::prelude::*;
+
+/// Tipos de tarifa dos providers internos
+pub const RATE_KINDS: &[&str] = &["pickup", "flat_rate", "free_shipping", "table_rate"];
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateShippingRateParams {
+    pub name: String,
+    /// 'pickup' | 'flat_rate' | 'free_shipping' | 'table_rate'
+    pub kind: String,
+    /// Centavos (ignorado em 'free_shipping')
+    pub price: Option<i64>,
+    /// Subtotal mínimo (obrigatório em 'free_shipping')
+    pub min_subtotal: Option<i64>,
+    pub postal_code_start: Option<String>,
+    pub postal_code_end: Option<String>,
+    pub min_weight_grams: Option<i32>,
+    pub max_weight_grams: Option<i32>,
+    pub delivery_days: Option<i32>,
+    /// Depósito de retirada (obrigatório em 'pickup')
+    pub warehouse_id: Option<i32>,
+    pub instructions: Option<String>,
+    pub sort_order: Option<i32>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UpdateShippingRateParams {
+    pub name: Option<String>,
+    pub price: Option<i64>,
+    pub min_subtotal: Option<i64>,
+    pub postal_code_start: Option<String>,
+    pub postal_code_end: Option<String>,
+    pub min_weight_grams: Option<i32>,
+    pub max_weight_grams: Option<i32>,
+    pub delivery_days: Option<i32>,
+    pub warehouse_id: Option<i32>,
+    pub instructions: Option<String>,
+    pub sort_order: Option<i32>,
+    pub active: Option<bool>,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// CEP só com dígitos; erro se não tiver 8 dígitos
+pub fn normalize_postal_code(value: &str) -> Result<String, String> {
+    let digits: String = value.chars().filter(char::is_ascii_digit).collect();
+    if digits.len() == 8 {
+        Ok(digits)
+    } else {
+        Err(format!("CEP inválido: {}", value))
+    }
+}
+
+fn normalize_range(
+    start: Option<&str>,
+    end: Option<&str>,
+) -> Result<(Option<String>, Option<String>), String> {
+    let start = start.map(normalize_postal_code).transpose()?;
+    let end = end.map(normalize_postal_code).transpose()?;
+    if let (Some(s), Some(e)) = (&start, &end) {
+        if s > e {
+            return Err("Início da faixa de CEP maior que o fim".to_string());
+        }
+    }
+    Ok((start, end))
+}
+
+/// Confere se o depósito existe e não foi removido
+async fn ensure_warehouse(db: &DatabaseConnection, id: i32) -> ModelResult<i32> {
+    let warehouse = warehouses::Entity::find_by_id(id)
+        .filter(warehouses::Column::DeletedAt.is_null())
+        .one(db)
+        .await?
+        .ok_or_else(|| ModelError::msg("Depósito de retirada não encontrado"))?;
+    Ok(warehouse.id)
+}
+
+impl Model {
+    /// Cria uma tarifa
+    pub async fn create_rate(
+        db: &DatabaseConnection,
+        params: &CreateShippingRateParams,
+    ) -> ModelResult<Self> {
+        if !RATE_KINDS.contains(&params.kind.as_str()) {
+            return Err(ModelError::Message(format!(
+                "Tipo de tarifa inválido: {}",
+                params.kind
+            )));
+        }
+        if params.kind == "free_shipping" && params.min_subtotal.is_none() {
+            return Err(ModelError::msg("Frete grátis exige min_subtotal"));
+        }
+        if params.price.is_some_and(|p| p < 0) {
+            return Err(ModelError::msg("Preço não pode ser negativo"));
+        }
+        let warehouse_id = match params.warehouse_id {
+            Some(id) => Some(ensure_warehouse(db, id).await?),
+            None if params.kind == "pickup" => {
+                return Err(ModelError::msg("Retirada exige warehouse_id"));
+            }
+            None => None,
+        };
+        let (start, end) = normalize_range(
+            params.postal_code_start.as_deref(),
+            params.postal_code_end.as_deref(),
+        )
+        .map_err(ModelError::Message)?;
+
+        let price = if params.kind == "free_shipping" {
+            0
+        } else {
+            params.price.unwrap_or(0)
+        };
+
+        let rate = shipping_rates::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            name: ActiveValue::set(params.name.clone()),
+            kind: ActiveValue::set(params.kind.clone()),
+            price: ActiveValue::set(price),
+            min_subtotal: ActiveValue::set(params.min_subtotal),
+            postal_code_start: ActiveValue::set(start),
+            postal_code_end: ActiveValue::set(end),
+            min_weight_grams: ActiveValue::set(params.min_weight_grams),
+            max_weight_grams: ActiveValue::set(params.max_weight_grams),
+            delivery_days: ActiveValue::set(params.delivery_days.unwrap_or(0)),
+            warehouse_id: ActiveValue::set(warehouse_id),
+            instructions: ActiveValue::set(params.instructions.clone()),
+            sort_order: ActiveValue::set(params.sort_order.unwrap_or(0)),
+            active: ActiveValue::set(params.active.unwrap_or(true)),
+            ..Default::default()
+        };
+        let rate = rate.insert(db).await?;
+        Ok(rate)
+    }
+
+    /// Atualiza uma tarifa (o tipo não pode ser alterado)
+    pub async fn update_rate(
+        db: &DatabaseConnection,
+        rate: Self,
+        params: &UpdateShippingRateParams,
+    ) -> ModelResult<Self> {
+        if params.price.is_some_and(|p| p < 0) {
+            return Err(ModelError::msg("Preço não pode ser negativo"));
+        }
+        let (start, end) = normalize_range(
+            params
+                .postal_code_start
+                .as_deref()
+                .or(rate.postal_code_start.as_deref()),
+            params
+                .postal_code_end
+                .as_deref()
+                .or(rate.postal_code_end.as_deref()),
+        )
+        .map_err(ModelError::Message)?;
+        let warehouse_id = match params.warehouse_id {
+            Some(id) => Some(ensure_warehouse(db, id).await?),
+            None => rate.warehouse_id,
+        };
+        let is_free = rate.kind == "free_shipping";
+
+        let mut active: shipping_rates::ActiveModel = rate.into();
+        active.postal_code_start = ActiveValue::set(start);
+        active.postal_code_end = ActiveValue::set(end);
+        active.warehouse_id = ActiveValue::set(warehouse_id);
+        if let Some(name) = &params.name {
+            active.name = ActiveValue::set(name.clone());
+        }
+        if let Some(price) = params.price.filter(|_| !is_free) {
+            active.price = ActiveValue::set(price);
+        }
+        if let Some(min_subtotal) = params.min_subtotal {
+            active.min_subtotal = ActiveValue::set(Some(min_subtotal));
+        }
+        if let Some(min) = params.min_weight_grams {
+            active.min_weight_grams = ActiveValue::set(Some(min));
+        }
+        if let Some(max) = params.max_weight_grams {
+            active.max_weight_grams = ActiveValue::set(Some(max));
+        }
+        if let Some(days) = params.delivery_days {
+            active.delivery_days = ActiveValue::set(days);
+        }
+        if let Some(instructions) = &params.instructions {
+            active.instructions = ActiveValue::set(Some(instructions.clone()));
+        }
+        if let Some(sort_order) = params.sort_order {
+            active.sort_order = ActiveValue::set(sort_order);
+        }
+        if let Some(is_active) = params.active {
+            active.active = ActiveValue::set(is_active);
+        }
+
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    /// Busca pelo PID
+    pub async fn find_by_pid(db: &DatabaseConnection, pid: &Uuid) -> ModelResult<Self> {
+        let rate = Entity::find()
+            .filter(shipping_rates::Column::Pid.eq(*pid))
+            .one(db)
+            .await?;
+        rate.ok_or_else(|| ModelError::EntityNotFound)
+    }
+
+    /// Lista todas as tarifas
+    pub async fn list_all(db: &DatabaseConnection) -> ModelResult<Vec<Self>> {
+        let rates = Entity::find()
+            .order_by_asc(shipping_rates::Column::Kind)
+            .order_by_asc(shipping_rates::Column::SortOrder)
+            .all(db)
+            .await?;
+        Ok(rates)
+    }
+
+    /// Tarifas ativas com o depósito de retirada (quando houver)
+    pub async fn list_active(
+        db: &DatabaseConnection,
+    ) -> ModelResult<Vec<(Self, Option<warehouses::Model>)>> {
+        let rates = Entity::find()
+            .filter(shipping_rates::Column::Active.eq(true))
+            .order_by_asc(shipping_rates::Column::SortOrder)
+            .find_also_related(warehouses::Entity)
+            .all(db)
+            .await?;
+        Ok(rates)
+    }
+
+    /// A tarifa vale para o destino, peso total e subtotal informados?
+    pub fn applies(&self, postal_code: &str, weight_grams: u32, subtotal: i64) -> bool {
+        let Ok(cep) = normalize_postal_code(postal_code) else {
+            return false;
+        };
+        let weight = i64::from(weight_grams);
+        self.postal_code_start
+            .as_deref()
+            .is_none_or(|s| cep.as_str() >= s)
+            && self
+                .postal_code_end
+                .as_deref()
+                .is_none_or(|e| cep.as_str() <= e)
+            && self
+                .min_weight_grams
+                .is_none_or(|min| weight >= i64::from(min))
+            && self
+                .max_weight_grams
+                .is_none_or(|max| weight <= i64::from(max))
+            && self.min_subtotal.is_none_or(|min| subtotal >= min)
+    }
+}
@@ -0,0 +1,208 @@
+//! Razão de estoque
+//!
+//! Toda alteração de `stocks.quantity`/`stocks.reserved` passa por
+//! [`Model::apply`]: o movimento é gravado em `stock_movements` (só inserção)
+//! junto com o saldo resultante, de modo que o saldo da linha é sempre a soma
+//! dos seus movimentos e cada unidade tem origem, autor e motivo.
+
+use sea_orm::{sea_query::Expr, ConnectionTrait, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+
+pub use super::_entities::stock_movements::{self, ActiveModel, Entity, Model};
+use super::_entities::stocks;
+use loco_rs::prelude::*;
+
+/// Motivos aceitos em ajustes manuais de saldo
+pub const ADJUSTMENT_REASONS: &[&str] = &[
+    "inventory_count",
+    "damaged",
+    "lost",
+    "found",
+    "expired",
+    "correction",
+];
+
+/// Tipo de movimento
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementKind {
+    /// Entrada de mercadoria (compra, cadastro inicial)
+    Receipt,
+    /// Baixa por venda (reserva convertida)
+    Sale,
+    /// Unidades reservadas por pedido ainda não pago
+    Reservation,
+    /// Reserva liberada (cancelamento, expiração)
+    Release,
+    /// Ajuste manual de saldo
+    Adjustment,
+    /// Saída ou entrada por transferência entre depósitos
+    Transfer,
+    /// Devolução ao estoque (estorno, RMA, edição do pedido)
+    Return,
+}
+
+impl MovementKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Receipt => "receipt",
+            Self::Sale => "sale",
+            Self::Reservation => "reservation",
+            Self::Release => "release",
+            Self::Adjustment => "adjustment",
+            Self::Transfer => "transfer",
+            Self::Return => "return",
+        }
+    }
+}
+
+/// Quem movimentou, por quê e a partir de qual documento
+#[derive(Debug, Clone, Default)]
+pub struct MovementSource {
+    pub user_id: Option<i32>,
+    pub order_id: Option<i32>,
+    pub reason: Option<String>,
+    pub reference: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl MovementSource {
+    /// Movimento feito por um usuário
+    pub fn by_user(user_id: Option<i32>, reason: Option<String>) -> Self {
+        Self {
+            user_id,
+            reason,
+            ..Default::default()
+        }
+    }
+
+    /// Movimento automático de um pedido (reserva, baixa, estorno)
+    pub fn order(order_id: i32) -> Self {
+        Self {
+            order_id: Some(order_id),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_reference(mut self, reference: impl Into<String>) -> Self {
+        self.reference = Some(reference.into());
+        self
+    }
+
+    pub fn with_notes(mut self, notes: Option<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Aplica o movimento na linha de estoque e grava no razão.
+    ///
+    /// `quantity` e `reserved` são variações com sinal. Movimento que reduz o
+    /// disponível (`quantity - reserved`) só é aplicado se o saldo comporta;
+    /// caso contrário retorna `None` e nada é gravado. Deve rodar dentro de
+    /// uma transação.
+    pub async fn try_apply<C: ConnectionTrait>(
+        db: &C,
+        stock_id: i32,
+        kind: MovementKind,
+        quantity: i32,
+        reserved: i32,
+        source: &MovementSource,
+    ) -> ModelResult<Option<Self>> {
+        if quantity == 0 && reserved == 0 {
+            return Ok(None);
+        }
+
+        let mut update = stocks::Entity::update_many()
+            .col_expr(
+                stocks::Column::Quantity,
+                Expr::col(stocks::Column::Quantity).add(quantity),
+            )
+            .col_expr(
+                stocks::Column::Reserved,
+                Expr::col(stocks::Column::Reserved).add(reserved),
+            )
+            .filter(stocks::Column::Id.eq(stock_id));
+        let available_delta = quantity - reserved;
+        if available_delta < 0 {
+            update = update.filter(
+                Expr::expr(
+                    Expr::col(stocks::Column::Quantity).sub(Expr::col(stocks::Column::Reserved)),
+                )
+                .gte(-available_delta),
+            );
+        }
+        if update.exec(db).await?.rows_affected == 0 {
+            return Ok(None);
+        }
+
+        let stock = stocks::Entity::find_by_id(stock_id)
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        let movement = stock_movements::ActiveModel {
+            stock_id: ActiveValue::set(stock_id),
+            kind: ActiveValue::set(kind.as_str().to_string()),
+            quantity: ActiveValue::set(quantity),
+            reserved: ActiveValue::set(reserved),
+            balance_after: ActiveValue::set(stock.quantity),
+            reserved_after: ActiveValue::set(stock.reserved),
+            reason: ActiveValue::set(source.reason.clone()),
+            notes: ActiveValue::set(source.notes.clone()),
+            order_id: ActiveValue::set(source.order_id),
+            reference: ActiveValue::set(source.reference.clone()),
+            created_by: ActiveValue::set(source.user_id),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        Ok(Some(movement))
+    }
+
+    /// Como [`Model::try_apply`], mas saldo insuficiente vira
+    /// `ModelError::Message`
+    pub async fn apply<C: ConnectionTrait>(
+        db: &C,
+        stock_id: i32,
+        kind: MovementKind,
+        quantity: i32,
+        reserved: i32,
+        source: &MovementSource,
+    ) -> ModelResult<Option<Self>> {
+        if quantity == 0 && reserved == 0 {
+            return Ok(None);
+        }
+        match Self::try_apply(db, stock_id, kind, quantity, reserved, source).await? {
+            Some(movement) => Ok(Some(movement)),
+            None => Err(ModelError::Message(format!(
+                "Saldo disponível insuficiente na linha de estoque {stock_id}"
+            ))),
+        }
+    }
+
+    /// Movimentos de uma linha de estoque, do mais antigo ao mais recente
+    pub async fn list_for_stock<C: ConnectionTrait>(
+        db: &C,
+        stock_id: i32,
+        kind: Option<&str>,
+        cursor: Option<i32>,
+        limit: u64,
+    ) -> ModelResult<Vec<Self>> {
+        let mut query = Entity::find().filter(stock_movements::Column::StockId.eq(stock_id));
+        if let Some(kind) = kind {
+            query = query.filter(stock_movements::Column::Kind.eq(kind));
+        }
+        if let Some(cursor) = cursor {
+            query = query.filter(stock_movements::Column::Id.gt(cursor));
+        }
+        let movements = query
+            .order_by_asc(stock_movements::Column::Id)
+            .limit(limit.min(100))
+            .all(db)
+            .await?;
+        Ok(movements)
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+
+use sea_orm::{sea_query::Expr, Condition, ConnectionTrait, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::stock_reservations::{self, ActiveModel, Entity, Model};
+use super::_entities::{items, order_items, orders, product_variants, stocks};
+use super::stock_movements::{Model as MovementModel, MovementKind, MovementSource};
+use crate::services::routing;
+use loco_rs::prelude::*;
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Linha a reservar: variante + quantidade
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReservationLine {
+    pub variant_id: i32,
+    pub quantity: i32,
+}
+
+/// Validade da reserva. Por padrão acompanha o vencimento do PIX/boleto
+/// gerado pelo Asaas (2 dias).
+fn reservation_ttl() -> chrono::Duration {
+    crate::env::load();
+    let minutes = std::env::var("STOCK_RESERVATION_TTL_MINUTES")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(2 * 24 * 60);
+    chrono::Duration::minutes(minutes)
+}
+
+impl Model {
+    /// Reserva estoque para os itens de um pedido.
+    ///
+    /// Deve rodar dentro de uma transação: as linhas de `stocks` da variante
+    /// são bloqueadas (`FOR UPDATE` no Postgres) e o incremento de `reserved`
+    /// só acontece se ainda houver saldo, então dois checkouts simultâneos
+    /// não conseguem levar a última unidade.
+    ///
+    /// As unidades saem dos lotes com validade mais próxima primeiro (FEFO);
+    /// lotes vencidos não são reservados. O pedido inteiro sai do depósito
+    /// mais próximo do CEP de entrega com saldo para todas as linhas (o mesmo
+    /// da cotação do frete). Sem nenhum, cada linha sai do mais próximo que
+    /// a atenda inteira ou é dividida do mais próximo ao mais distante.
+    ///
+    /// Se faltar estoque e a variante não aceitar backorder, retorna
+    /// `ModelError::Message` e a transação deve ser descartada.
+    pub async fn reserve_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        lines: &[ReservationLine],
+    ) -> ModelResult<Vec<Self>> {
+        let (reservations, _) = Self::reserve_lines(db, order_id, lines, false).await?;
+        Ok(reservations)
+    }
+
+    /// Reserva as linhas. Com `paid`, falta de estoque não recusa o pedido
+    /// (o pagamento já entrou): a diferença vira reserva sem linha de estoque
+    /// e volta em `shortfall`.
+    async fn reserve_lines<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        lines: &[ReservationLine],
+        paid: bool,
+    ) -> ModelResult<(Vec<Self>, Vec<ReservationLine>)> {
+        let mut shortfall = Vec::new();
+        let expires_at = chrono::Utc::now() + reservation_ttl();
+        let mut reservations = Vec::new();
+        let destination = match orders::Entity::find_by_id(order_id).one(db).await? {
+            Some(order) => routing::order_destination(db, &order).await?,
+            None => None,
+        };
+        let ranking = routing::warehouse_ranking(db, destination.as_deref()).await?;
+        let wanted: Vec<(i32, i32)> = lines.iter().map(|l| (l.variant_id, l.quantity)).collect();
+        let preferred = routing::fulfilling_warehouse(db, destination.as_deref(), &wanted)
+            .await?
+            .map(|w| w.id);
+
+        for line in lines {
+            let variant = product_variants::Entity::find_by_id(line.variant_id)
+                .one(db)
+                .await?
+                .ok_or(ModelError::EntityNotFound)?;
+
+            let mut remaining = line.quantity;
+            let candidates = Self::candidate_stocks(db, variant.id).await?;
+            for stock in routing::route_candidates(candidates, &ranking, line.quantity, preferred) {
+                if remaining <= 0 {
+                    break;
+                }
+                let available = stock.quantity - stock.reserved;
+                if available <= 0 {
+                    continue;
+                }
+                let take = available.min(remaining);
+
+                // Incremento condicional: só reserva se o saldo ainda comporta
+                let reserved = MovementModel::try_apply(
+                    db,
+                    stock.id,
+                    MovementKind::Reservation,
+                    0,
+                    take,
+                    &MovementSource::order(order_id),
+                )
+                .await?;
+                if reserved.is_none() {
+                    continue;
+                }
+
+                reservations.push(
+                    Self::insert_reservation(
+                        db,
+                        order_id,
+                        variant.id,
+                        Some(stock.id),
+                        take,
+                        expires_at,
+                    )
+                    .await?,
+                );
+                remaining -= take;
+            }
+
+            if remaining > 0 {
+                if !variant.allow_backorder {
+                    if !paid {
+                        return Err(ModelError::Message(format!(
+                            "Estoque insuficiente para o SKU {}",
+                            variant.sku
+                        )));
+                    }
+                    shortfall.push(ReservationLine {
+                        variant_id: variant.id,
+                        quantity: remaining,
+                    });
+                }
+                // Backorder: registra a diferença sem linha de estoque física
+                reservations.push(
+                    Self::insert_reservation(db, order_id, variant.id, None, remaining, expires_at)
+                        .await?,
+                );
+            }
+        }
+
+        Ok((reservations, shortfall))
+    }
+
+    /// Converte as reservas ativas do pedido em baixa de estoque
+    /// (chamado quando o pagamento é confirmado).
+    ///
+    /// Pagamento que chega depois de a reserva expirar reserva e baixa de
+    /// novo o que faltar; sem estoque, a diferença fica registrada em
+    /// `orders.metadata.stock_shortfall` para a equipe resolver.
+    ///
+    /// Idempotente: reservas já convertidas ou liberadas são ignoradas.
+    pub async fn commit_for_order<C: ConnectionTrait>(db: &C, order_id: i32) -> ModelResult<()> {
+        for reservation in Self::active_for_order(db, order_id).await? {
+            Self::commit(db, reservation).await?;
+        }
+
+        let missing = Self::uncommitted_lines(db, order_id).await?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+        tracing::warn!(
+            order_id,
+            "Pedido pago com reservas expiradas; reservando de novo"
+        );
+        let (reservations, shortfall) = Self::reserve_lines(db, order_id, &missing, true).await?;
+        for reservation in reservations {
+            Self::commit(db, reservation).await?;
+        }
+
+        if !shortfall.is_empty() {
+            tracing::error!(
+                order_id,
+                ?shortfall,
+                "Pedido pago sem estoque para todos os itens"
+            );
+            if let Some(order) = orders::Entity::find_by_id(order_id).one(db).await? {
+                let mut metadata = order.metadata.clone();
+                metadata["stock_shortfall"] = serde_json::json!(shortfall);
+                let mut active: orders::ActiveModel = order.into();
+                active.metadata = ActiveValue::set(metadata);
+                active.update(db).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Baixa a reserva: sai da linha de estoque e do saldo da variante
+    async fn commit<C: ConnectionTrait>(db: &C, reservation: Self) -> ModelResult<()> {
+        if let Some(stock_id) = reservation.stock_id {
+            MovementModel::apply(
+                db,
+                stock_id,
+                MovementKind::Sale,
+                -reservation.quantity,
+                -reservation.quantity,
+                &MovementSource::order(reservation.order_id),
+            )
+            .await?;
+        }
+
+        product_variants::Entity::update_many()
+            .col_expr(
+                product_variants::Column::InventoryQuantity,
+                Expr::col(product_variants::Column::InventoryQuantity).sub(reservation.quantity),
+            )
+            .filter(product_variants::Column::Id.eq(reservation.variant_id))
+            .exec(db)
+            .await?;
+
+        Self::set_status(db, reservation, "committed").await?;
+        Ok(())
+    }
+
+    /// Quantidade dos itens do pedido ainda sem reserva ativa ou convertida,
+    /// por variante (reservas liberadas por expiração não contam)
+    async fn uncommitted_lines<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<ReservationLine>> {
+        let mut needed: HashMap<i32, i32> = HashMap::new();
+        for item in order_items::Entity::find()
+            .filter(order_items::Column::OrderId.eq(order_id))
+            .all(db)
+            .await?
+        {
+            if let Some(variant_id) = item.variant_id {
+                *needed.entry(variant_id).or_default() += item.quantity;
+            }
+        }
+        for reservation in Self::find_by_order(db, order_id).await? {
+            if matches!(reservation.status.as_str(), "active" | "committed") {
+                if let Some(qty) = needed.get_mut(&reservation.variant_id) {
+                    *qty -= reservation.quantity;
+                }
+            }
+        }
+        let mut lines: Vec<ReservationLine> = needed
+            .into_iter()
+            .filter(|(_, quantity)| *quantity > 0)
+            .map(|(variant_id, quantity)| ReservationLine {
+                variant_id,
+                quantity,
+            })
+            .collect();
+        lines.sort_by_key(|l| l.variant_id);
+        Ok(lines)
+    }
+
+    /// Devolve ao estoque unidades já baixadas de um pedido (estorno).
+    ///
+    /// As unidades voltam para as linhas de `stocks` de onde saíram, pelas
+    /// reservas convertidas da variante; o que não couber (backorder) só
+    /// volta para o saldo da variante.
+    pub async fn restock_for_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        variant_id: i32,
+        quantity: i32,
+    ) -> ModelResult<()> {
+        let committed = Entity::find()
+            .filter(stock_reservations::Column::OrderId.eq(order_id))
+            .filter(stock_reservations::Column::VariantId.eq(variant_id))
+            .filter(stock_reservations::Column::Status.eq("committed"))
+            .filter(stock_reservations::Column::StockId.is_not_null())
+            .order_by_asc(stock_reservations::Column::Id)
+            .all(db)
+            .await?;
+
+        let mut remaining = quantity;
+        for reservation in committed {
+            if remaining <= 0 {
+                break;
+            }
+            let Some(stock_id) = reservation.stock_id else {
+                continue;
+            };
+            let put_back = reservation.quantity.min(remaining);
+            MovementModel::apply(
+                db,
+                stock_id,
+                MovementKind::Return,
+                put_back,
+                0,
+                &MovementSource::order(order_id),
+            )
+            .await?;
+            remaining -= put_back;
+        }
+
+        product_variants::Entity::update_many()
+            .col_expr(
+                product_variants::Column::InventoryQuantity,
+                Expr::col(product_variants::Column::InventoryQuantity).add(quantity),
+            )
+            .filter(product_variants::Column::Id.eq(variant_id))
+            .exec(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Libera as reservas ativas do pedido (cancelamento)
+    pub async fn release_for_order<C: ConnectionTrait>(db: &C, order_id: i32) -> ModelResult<()> {
+        for reservation in Self::active_for_order(db, order_id).await? {
+            Self::release(db, reservation, "released").await?;
+        }
+        Ok(())
+    }
+
+    /// Libera reservas ativas cujo prazo expirou. Retorna quantas foram liberadas.
+    pub async fn release_expired<C: ConnectionTrait>(db: &C) -> ModelResult<usize> {
+        let expired = Entity::find()
+            .filter(stock_reservations::Column::Status.eq("active"))
+            .filter(stock_reservations::Column::ExpiresAt.lt(chrono::Utc::now()))
+            .all(db)
+            .await?;
+
+        let count = expired.len();
+        for reservation in expired {
+            Self::release(db, reservation, "expired").await?;
+        }
+        Ok(count)
+    }
+
+    /// Lista reservas de um pedido
+    pub async fn find_by_order<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+    ) -> ModelResult<Vec<Self>> {
+        let reservations = Entity::find()
+            .filter(stock_reservations::Column::OrderId.eq(order_id))
+            .order_by_asc(stock_reservations::Column::Id)
+            .all(db)
+            .await?;
+        Ok(reservations)
+    }
+
+    /// Linhas de estoque da variante, bloqueadas para atualização, na ordem
+    /// de consumo FEFO: lote que vence primeiro antes, lotes sem validade por
+    /// último. Lotes vencidos ficam de fora e não podem ser vendidos.
+    async fn candidate_stocks<C: ConnectionTrait>(
+        db: &C,
+        variant_id: i32,
+    ) -> ModelResult<Vec<stocks::Model>> {
+        let today = chrono::Utc::now().date_naive();
+        let mut batches: Vec<items::Model> = items::Entity::find()
+            .filter(items::Column::VariantId.eq(variant_id))
+            .filter(items::Column::DeletedAt.is_null())
+            .filter(
+                Condition::any()
+                    .add(items::Column::Expiration.is_null())
+                    .add(items::Column::Expiration.gte(today)),
+            )
+            .all(db)
+            .await?;
+
+        if batches.is_empty() {
+            return Ok(vec![]);
+        }
+        batches.sort_by_key(|i| (i.expiration.is_none(), i.expiration, i.id));
+        let rank: HashMap<i32, usize> = batches
+            .iter()
+            .enumerate()
+            .map(|(pos, item)| (item.id, pos))
+            .collect();
+
+        // Bloqueia na ordem de id (evita deadlock) e só depois ordena por validade
+        let mut rows = stocks::Entity::find()
+            .filter(stocks::Column::ItemId.is_in(rank.keys().copied().collect::<Vec<_>>()))
+            .order_by_asc(stocks::Column::Id)
+            .lock_exclusive()
+            .all(db)
+            .await?;
+        rows.sort_by_key(|stock| (rank.get(&stock.item_id).copied(), stock.id));
+        Ok(rows)
+    }
+
+    async fn active_for_order<C: ConnectionTrait>(db: &C, order_id: i32) -> ModelResult<Vec<Self>> {
+        let reservations = Entity::find()
+            .filter(stock_reservations::Column::OrderId.eq(order_id))
+            .filter(stock_reservations::Column::Status.eq("active"))
+            .lock_exclusive()
+            .all(db)
+            .await?;
+        Ok(reservations)
+    }
+
+    async fn release<C: ConnectionTrait>(
+        db: &C,
+        reservation: Self,
+        status: &str,
+    ) -> ModelResult<()> {
+        if let Some(stock_id) = reservation.stock_id {
+            let source = MovementSource {
+                reason: Some(status.to_string()),
+                ..MovementSource::order(reservation.order_id)
+            };
+            MovementModel::apply(
+                db,
+                stock_id,
+                MovementKind::Release,
+                0,
+                -reservation.quantity,
+                &source,
+            )
+            .await?;
+        }
+        Self::set_status(db, reservation, status).await?;
+        Ok(())
+    }
+
+    async fn set_status<C: ConnectionTrait>(
+        db: &C,
+        reservation: Self,
+        status: &str,
+    ) -> ModelResult<Self> {
+        let mut active: stock_reservations::ActiveModel = reservation.into();
+        active.status = ActiveValue::set(status.to_string());
+        let updated = active.update(db).await?;
+        Ok(updated)
+    }
+
+    async fn insert_reservation<C: ConnectionTrait>(
+        db: &C,
+        order_id: i32,
+        variant_id: i32,
+        stock_id: Option<i32>,
+        quantity: i32,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> ModelResult<Self> {
+        let reservation = stock_reservations::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            order_id: ActiveValue::set(order_id),
+            variant_id: ActiveValue::set(variant_id),
+            stock_id: ActiveValue::set(stock_id),
+            quantity: ActiveValue::set(quantity),
+            status: ActiveValue::set("active".to_string()),
+            expires_at: ActiveValue::set(expires_at.into()),
+            ..Default::default()
+        };
+        let saved = reservation.insert(db).await?;
+        Ok(saved)
+    }
+}
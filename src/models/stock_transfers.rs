@@ -0,0 +1,398 @@
+//! Transferências de estoque entre depósitos
+//!
+//! Fluxo: `pending` → `shipped` (baixa na origem) → `received` (entrada no
+//! destino). Cancelamento só antes do envio. Toda movimentação passa pelo
+//! razão (`stock_movements`, tipo `transfer`) com o PID da transferência como
+//! referência; unidades enviadas e não recebidas ficam registradas como
+//! divergência e saem do saldo da variante.
+
+use sea_orm::{
+    sea_query::Expr, Condition, ConnectionTrait, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::stock_transfers::{self, ActiveModel, Entity, Model};
+use super::_entities::{items, product_variants, stock_transfer_items, stocks, warehouses};
+use super::stock_movements::{Model as MovementModel, MovementKind, MovementSource};
+use loco_rs::prelude::*;
+
+impl ActiveModelBehavior for ActiveModel {}
+impl ActiveModelBehavior for stock_transfer_items::ActiveModel {}
+
+/// Parâmetros para abrir uma transferência
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateTransferParams {
+    pub source_warehouse_id: i32,
+    pub destination_warehouse_id: i32,
+    pub items: Vec<TransferItemParams>,
+    pub notes: Option<String>,
+}
+
+/// Item e quantidade a transferir
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TransferItemParams {
+    pub item_id: i32,
+    pub quantity: i32,
+}
+
+/// Quantidades conferidas no destino
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReceiveTransferParams {
+    /// Itens não informados são recebidos na quantidade enviada
+    #[serde(default)]
+    pub items: Vec<ReceivedItemParams>,
+    pub notes: Option<String>,
+}
+
+/// Conferência de um item da transferência
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReceivedItemParams {
+    pub transfer_item_id: i32,
+    pub received_quantity: i32,
+    /// Obrigatório quando a quantidade difere da enviada
+    pub reason: Option<String>,
+}
+
+impl Model {
+    /// Busca transferência pelo PID
+    pub async fn find_by_pid<C: ConnectionTrait>(db: &C, pid: &Uuid) -> ModelResult<Self> {
+        Entity::find()
+            .filter(stock_transfers::Column::Pid.eq(*pid))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Lista transferências, mais recentes primeiro, com filtros opcionais
+    pub async fn list<C: ConnectionTrait>(
+        db: &C,
+        status: Option<&str>,
+        warehouse_id: Option<i32>,
+        cursor: Option<i32>,
+        limit: u64,
+    ) -> ModelResult<Vec<Self>> {
+        let mut query = Entity::find();
+        if let Some(status) = status {
+            query = query.filter(stock_transfers::Column::Status.eq(status));
+        }
+        if let Some(warehouse_id) = warehouse_id {
+            query = query.filter(
+                Condition::any()
+                    .add(stock_transfers::Column::SourceWarehouseId.eq(warehouse_id))
+                    .add(stock_transfers::Column::DestinationWarehouseId.eq(warehouse_id)),
+            );
+        }
+        if let Some(cursor) = cursor {
+            query = query.filter(stock_transfers::Column::Id.lt(cursor));
+        }
+        let transfers = query
+            .order_by_desc(stock_transfers::Column::Id)
+            .limit(limit.min(100))
+            .all(db)
+            .await?;
+        Ok(transfers)
+    }
+
+    /// Itens da transferência
+    pub async fn items<C: ConnectionTrait>(
+        &self,
+        db: &C,
+    ) -> ModelResult<Vec<stock_transfer_items::Model>> {
+        let items = stock_transfer_items::Entity::find()
+            .filter(stock_transfer_items::Column::TransferId.eq(self.id))
+            .order_by_asc(stock_transfer_items::Column::Id)
+            .all(db)
+            .await?;
+        Ok(items)
+    }
+
+    /// Abre uma transferência `pending`. O saldo só é movimentado no envio.
+    pub async fn create(
+        db: &DatabaseConnection,
+        params: &CreateTransferParams,
+        created_by: Option<i32>,
+    ) -> ModelResult<Self> {
+        if params.source_warehouse_id == params.destination_warehouse_id {
+            return Err(ModelError::msg(
+                "Origem e destino devem ser depósitos diferentes",
+            ));
+        }
+        if params.items.is_empty() {
+            return Err(ModelError::msg("Informe ao menos um item"));
+        }
+        for warehouse_id in [params.source_warehouse_id, params.destination_warehouse_id] {
+            warehouses::Entity::find_by_id(warehouse_id)
+                .filter(warehouses::Column::DeletedAt.is_null())
+                .one(db)
+                .await?
+                .ok_or_else(|| {
+                    ModelError::Message(format!("Depósito {warehouse_id} não encontrado"))
+                })?;
+        }
+
+        let txn = db.begin().await?;
+        let transfer = stock_transfers::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            source_warehouse_id: ActiveValue::set(params.source_warehouse_id),
+            destination_warehouse_id: ActiveValue::set(params.destination_warehouse_id),
+            status: ActiveValue::set("pending".to_string()),
+            discrepancy: ActiveValue::set(false),
+            notes: ActiveValue::set(params.notes.clone()),
+            created_by: ActiveValue::set(created_by),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        for line in &params.items {
+            if line.quantity <= 0 {
+                return Err(ModelError::msg("Quantidade deve ser maior que zero"));
+            }
+            let stock = source_stock(&txn, params.source_warehouse_id, line.item_id).await?;
+            stock_transfer_items::ActiveModel {
+                transfer_id: ActiveValue::set(transfer.id),
+                item_id: ActiveValue::set(line.item_id),
+                quantity: ActiveValue::set(line.quantity),
+                shipped_quantity: ActiveValue::set(0),
+                source_stock_id: ActiveValue::set(Some(stock.id)),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+        Ok(transfer)
+    }
+
+    /// Despacha a transferência: baixa as quantidades da origem. Falta de
+    /// saldo disponível (descontadas as reservas) recusa o envio inteiro.
+    pub async fn ship(
+        db: &DatabaseConnection,
+        transfer: Self,
+        shipped_by: Option<i32>,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let transfer = Self::lock(&txn, transfer.id).await?;
+        if transfer.status != "pending" {
+            return Err(ModelError::Message(format!(
+                "Transferência com status '{}' não pode ser enviada",
+                transfer.status
+            )));
+        }
+
+        let source = MovementSource {
+            user_id: shipped_by,
+            reason: Some("transfer_out".to_string()),
+            reference: Some(transfer.pid.to_string()),
+            ..Default::default()
+        };
+        for line in transfer.items(&txn).await? {
+            let stock = source_stock(&txn, transfer.source_warehouse_id, line.item_id).await?;
+            let moved = MovementModel::try_apply(
+                &txn,
+                stock.id,
+                MovementKind::Transfer,
+                -line.quantity,
+                0,
+                &source,
+            )
+            .await?;
+            if moved.is_none() {
+                return Err(ModelError::Message(format!(
+                    "Saldo insuficiente do item {} no depósito de origem: {} disponível(is)",
+                    line.item_id,
+                    stock.quantity - stock.reserved
+                )));
+            }
+
+            let quantity = line.quantity;
+            let mut active: stock_transfer_items::ActiveModel = line.into();
+            active.shipped_quantity = ActiveValue::set(quantity);
+            active.source_stock_id = ActiveValue::set(Some(stock.id));
+            active.update(&txn).await?;
+        }
+
+        let mut active: stock_transfers::ActiveModel = transfer.into();
+        active.status = ActiveValue::set("shipped".to_string());
+        active.shipped_by = ActiveValue::set(shipped_by);
+        active.shipped_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    /// Recebe a transferência no destino. Cada item entra na linha de estoque
+    /// do depósito de destino (criada se não existir) na quantidade conferida;
+    /// diferença para o enviado exige motivo e marca a divergência.
+    pub async fn receive(
+        db: &DatabaseConnection,
+        transfer: Self,
+        params: &ReceiveTransferParams,
+        received_by: Option<i32>,
+    ) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let transfer = Self::lock(&txn, transfer.id).await?;
+        if transfer.status != "shipped" {
+            return Err(ModelError::Message(format!(
+                "Transferência com status '{}' não pode ser recebida",
+                transfer.status
+            )));
+        }
+
+        let lines = transfer.items(&txn).await?;
+        if let Some(unknown) = params
+            .items
+            .iter()
+            .find(|p| !lines.iter().any(|l| l.id == p.transfer_item_id))
+        {
+            return Err(ModelError::Message(format!(
+                "Item {} não pertence à transferência",
+                unknown.transfer_item_id
+            )));
+        }
+
+        let source = MovementSource {
+            user_id: received_by,
+            reason: Some("transfer_in".to_string()),
+            reference: Some(transfer.pid.to_string()),
+            ..Default::default()
+        };
+        let mut discrepancy = false;
+        for line in lines {
+            let check = params.items.iter().find(|p| p.transfer_item_id == line.id);
+            let received = check.map_or(line.shipped_quantity, |c| c.received_quantity);
+            if received < 0 || received > line.shipped_quantity {
+                return Err(ModelError::Message(format!(
+                    "Item {}: quantidade recebida deve estar entre 0 e {}",
+                    line.item_id, line.shipped_quantity
+                )));
+            }
+            let reason = check.and_then(|c| c.reason.clone());
+            let missing = line.shipped_quantity - received;
+            if missing > 0 {
+                if reason.as_deref().map_or(true, |r| r.trim().is_empty()) {
+                    return Err(ModelError::Message(format!(
+                        "Item {}: informe o motivo da divergência",
+                        line.item_id
+                    )));
+                }
+                discrepancy = true;
+                // Unidades perdidas no caminho saem do saldo da variante
+                write_off(&txn, line.item_id, missing).await?;
+            }
+
+            let stock =
+                destination_stock(&txn, transfer.destination_warehouse_id, line.item_id).await?;
+            MovementModel::apply(&txn, stock.id, MovementKind::Transfer, received, 0, &source)
+                .await?;
+
+            let mut active: stock_transfer_items::ActiveModel = line.into();
+            active.received_quantity = ActiveValue::set(Some(received));
+            active.discrepancy_reason = ActiveValue::set(reason.filter(|_| missing > 0));
+            active.destination_stock_id = ActiveValue::set(Some(stock.id));
+            active.update(&txn).await?;
+        }
+
+        let notes = match (&transfer.notes, &params.notes) {
+            (Some(old), Some(new)) => Some(format!("{old}\n{new}")),
+            (old, new) => new.clone().or_else(|| old.clone()),
+        };
+        let mut active: stock_transfers::ActiveModel = transfer.into();
+        active.status = ActiveValue::set("received".to_string());
+        active.discrepancy = ActiveValue::set(discrepancy);
+        active.notes = ActiveValue::set(notes);
+        active.received_by = ActiveValue::set(received_by);
+        active.received_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    /// Cancela uma transferência ainda não enviada
+    pub async fn cancel(db: &DatabaseConnection, transfer: Self) -> ModelResult<Self> {
+        let txn = db.begin().await?;
+        let transfer = Self::lock(&txn, transfer.id).await?;
+        if transfer.status != "pending" {
+            return Err(ModelError::Message(format!(
+                "Transferência com status '{}' não pode ser cancelada",
+                transfer.status
+            )));
+        }
+        let mut active: stock_transfers::ActiveModel = transfer.into();
+        active.status = ActiveValue::set("canceled".to_string());
+        active.canceled_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+        let updated = active.update(&txn).await?;
+        txn.commit().await?;
+        Ok(updated)
+    }
+
+    async fn lock<C: ConnectionTrait>(db: &C, id: i32) -> ModelResult<Self> {
+        Entity::find_by_id(id)
+            .lock_exclusive()
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+}
+
+/// Linha de estoque do item no depósito de origem
+async fn source_stock<C: ConnectionTrait>(
+    db: &C,
+    warehouse_id: i32,
+    item_id: i32,
+) -> ModelResult<stocks::Model> {
+    stocks::Entity::find()
+        .filter(stocks::Column::WarehouseId.eq(warehouse_id))
+        .filter(stocks::Column::ItemId.eq(item_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ModelError::Message(format!(
+                "Item {item_id} sem estoque no depósito de origem {warehouse_id}"
+            ))
+        })
+}
+
+/// Linha de estoque do item no depósito de destino, criada zerada se faltar
+async fn destination_stock<C: ConnectionTrait>(
+    db: &C,
+    warehouse_id: i32,
+    item_id: i32,
+) -> ModelResult<stocks::Model> {
+    if let Some(stock) = stocks::Entity::find()
+        .filter(stocks::Column::WarehouseId.eq(warehouse_id))
+        .filter(stocks::Column::ItemId.eq(item_id))
+        .one(db)
+        .await?
+    {
+        return Ok(stock);
+    }
+    let stock = stocks::ActiveModel {
+        warehouse_id: ActiveValue::set(warehouse_id),
+        item_id: ActiveValue::set(item_id),
+        quantity: ActiveValue::set(0),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    Ok(stock)
+}
+
+/// Tira do saldo da variante as unidades extraviadas na transferência
+async fn write_off<C: ConnectionTrait>(db: &C, item_id: i32, quantity: i32) -> ModelResult<()> {
+    let item = items::Entity::find_by_id(item_id)
+        .one(db)
+        .await?
+        .ok_or(ModelError::EntityNotFound)?;
+    product_variants::Entity::update_many()
+        .col_expr(
+            product_variants::Column::InventoryQuantity,
+            Expr::col(product_variants::Column::InventoryQuantity).sub(quantity),
+        )
+        .filter(product_variants::Column::Id.eq(item.variant_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
@@ -1,42 +1,160 @@
-use sea_orm::{ActiveModelBehavior, EntityTrait, QueryFilter};
+use std::collections::HashMap;
+
+use sea_orm::{
+    ActiveModelBehavior, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use loco_rs::prelude::*;
 
+use super::_entities::items;
 pub use super::_entities::stocks::{self, ActiveModel, Entity, Model};
+use super::stock_movements::{
+    Model as MovementModel, MovementKind, MovementSource, ADJUSTMENT_REASONS,
+};
 
 impl ActiveModelBehavior for ActiveModel {}
 
+/// Lote com validade próxima (ou vencida) em um depósito
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiringBatch {
+    pub stock_id: i32,
+    pub warehouse_id: i32,
+    pub item_id: i32,
+    pub item_pid: Uuid,
+    pub variant_id: i32,
+    pub batch: Option<String>,
+    pub expiration: chrono::NaiveDate,
+    /// Dias até o vencimento; negativo quando já venceu
+    pub days_left: i64,
+    pub quantity: i32,
+    pub reserved: i32,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UpdateStockParams {
     pub warehouse_id: i32,
     pub item_id: i32,
     pub quantity: i32,
+    /// Motivo do ajuste (`ADJUSTMENT_REASONS`); padrão 'correction'
+    pub reason: Option<String>,
+    pub notes: Option<String>,
 }
 
 impl Model {
+    /// Define o saldo da linha de estoque (criando-a se preciso). A diferença
+    /// para o saldo atual entra no razão como ajuste.
     pub async fn set_stock(
         db: &sea_orm::DatabaseConnection,
         params: &UpdateStockParams,
-    ) -> loco_rs::Result<Self> {
+        user_id: Option<i32>,
+    ) -> ModelResult<Self> {
+        if params.quantity < 0 {
+            return Err(ModelError::msg("Quantidade não pode ser negativa"));
+        }
+        let reason = params.reason.as_deref().unwrap_or("correction");
+        if !ADJUSTMENT_REASONS.contains(&reason) {
+            return Err(ModelError::Message(format!(
+                "Motivo inválido: {reason}. Use um de: {}",
+                ADJUSTMENT_REASONS.join(", ")
+            )));
+        }
+
+        let txn = db.begin().await?;
         // upsert behaviour
         let existing = Entity::find()
             .filter(stocks::Column::WarehouseId.eq(params.warehouse_id))
             .filter(stocks::Column::ItemId.eq(params.item_id))
-            .one(db)
+            .lock_exclusive()
+            .one(&txn)
             .await?;
-        if let Some(e) = existing {
-            let mut am: stocks::ActiveModel = e.into();
-            am.quantity = sea_orm::ActiveValue::set(params.quantity);
-            Ok(am.update(db).await?)
-        } else {
-            let am = stocks::ActiveModel {
-                warehouse_id: sea_orm::ActiveValue::set(params.warehouse_id),
-                item_id: sea_orm::ActiveValue::set(params.item_id),
-                quantity: sea_orm::ActiveValue::set(params.quantity),
-                ..Default::default()
-            };
-            Ok(am.insert(db).await?)
+        let stock = match existing {
+            Some(e) => e,
+            None => {
+                let am = stocks::ActiveModel {
+                    warehouse_id: sea_orm::ActiveValue::set(params.warehouse_id),
+                    item_id: sea_orm::ActiveValue::set(params.item_id),
+                    quantity: sea_orm::ActiveValue::set(0),
+                    ..Default::default()
+                };
+                am.insert(&txn).await?
+            }
+        };
+
+        let delta = params.quantity - stock.quantity;
+        let source = MovementSource::by_user(user_id, Some(reason.to_string()))
+            .with_notes(params.notes.clone());
+        if delta != 0
+            && MovementModel::try_apply(&txn, stock.id, MovementKind::Adjustment, delta, 0, &source)
+                .await?
+                .is_none()
+        {
+            return Err(ModelError::Message(format!(
+                "Saldo não pode ficar abaixo das {} unidade(s) reservadas",
+                stock.reserved
+            )));
+        }
+
+        let stock = Entity::find_by_id(stock.id)
+            .one(&txn)
+            .await?
+            .ok_or(ModelError::EntityNotFound)?;
+        txn.commit().await?;
+        Ok(stock)
+    }
+
+    /// Lotes com saldo que vencem em até `days` dias (incluindo os já
+    /// vencidos), do vencimento mais próximo ao mais distante
+    pub async fn expiring_batches<C: ConnectionTrait>(
+        db: &C,
+        days: i64,
+        warehouse_id: Option<i32>,
+    ) -> ModelResult<Vec<ExpiringBatch>> {
+        let today = chrono::Utc::now().date_naive();
+        let limit = today + chrono::Duration::days(days);
+        let batches: HashMap<i32, items::Model> = items::Entity::find()
+            .filter(items::Column::DeletedAt.is_null())
+            .filter(items::Column::Expiration.is_not_null())
+            .filter(items::Column::Expiration.lte(limit))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|i| (i.id, i))
+            .collect();
+        if batches.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut query = Entity::find()
+            .filter(stocks::Column::ItemId.is_in(batches.keys().copied().collect::<Vec<_>>()))
+            .filter(stocks::Column::Quantity.gt(0));
+        if let Some(warehouse_id) = warehouse_id {
+            query = query.filter(stocks::Column::WarehouseId.eq(warehouse_id));
         }
+
+        let mut report: Vec<ExpiringBatch> = query
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|stock| {
+                let item = batches.get(&stock.item_id)?;
+                let expiration = item.expiration?;
+                Some(ExpiringBatch {
+                    stock_id: stock.id,
+                    warehouse_id: stock.warehouse_id,
+                    item_id: item.id,
+                    item_pid: item.pid,
+                    variant_id: item.variant_id,
+                    batch: item.batch.clone(),
+                    expiration,
+                    days_left: (expiration - today).num_days(),
+                    quantity: stock.quantity,
+                    reserved: stock.reserved,
+                })
+            })
+            .collect();
+        report.sort_by_key(|b| (b.expiration, b.warehouse_id, b.stock_id));
+        Ok(report)
     }
 }
@@ -0,0 +1,81 @@
+use sea_orm::{ConnectionTrait, QueryOrder};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use super::_entities::suppliers::{self, ActiveModel, Entity, Model};
+use loco_rs::prelude::*;
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Parâmetros para cadastrar ou atualizar um fornecedor
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SupplierParams {
+    pub name: String,
+    /// CNPJ/CPF
+    pub document: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl SupplierParams {
+    fn validate(&self) -> ModelResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(ModelError::msg("Nome do fornecedor é obrigatório"));
+        }
+        Ok(())
+    }
+}
+
+impl Model {
+    /// Busca fornecedor ativo pelo PID
+    pub async fn find_by_pid<C: ConnectionTrait>(db: &C, pid: &Uuid) -> ModelResult<Self> {
+        Entity::find()
+            .filter(suppliers::Column::Pid.eq(*pid))
+            .filter(suppliers::Column::DeletedAt.is_null())
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    /// Fornecedores ativos em ordem alfabética
+    pub async fn list<C: ConnectionTrait>(db: &C) -> ModelResult<Vec<Self>> {
+        let suppliers = Entity::find()
+            .filter(suppliers::Column::DeletedAt.is_null())
+            .order_by_asc(suppliers::Column::Name)
+            .all(db)
+            .await?;
+        Ok(suppliers)
+    }
+
+    pub async fn create(db: &DatabaseConnection, params: &SupplierParams) -> ModelResult<Self> {
+        params.validate()?;
+        let supplier = suppliers::ActiveModel {
+            pid: ActiveValue::set(Uuid::new_v4()),
+            name: ActiveValue::set(params.name.trim().to_string()),
+            document: ActiveValue::set(params.document.clone()),
+            email: ActiveValue::set(params.email.clone()),
+            phone: ActiveValue::set(params.phone.clone()),
+            notes: ActiveValue::set(params.notes.clone()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+        Ok(supplier)
+    }
+
+    pub async fn update(
+        self,
+        db: &DatabaseConnection,
+        params: &SupplierParams,
+    ) -> ModelResult<Self> {
+        params.validate()?;
+        let mut active: suppliers::ActiveModel = self.into();
+        active.name = ActiveValue::set(params.name.trim().to_string());
+        active.document = ActiveValue::set(params.document.clone());
+        active.email = ActiveValue::set(params.email.clone());
+        active.phone = ActiveValue::set(params.phone.clone());
+        active.notes = ActiveValue::set(params.notes.clone());
+        Ok(active.update(db).await?)
+    }
+}
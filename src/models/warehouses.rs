@@ -13,6 +13,9 @@ pub struct CreateWarehouseParams {
     pub name: String,
     pub latitude: f64,
     pub longitude: f64,
+    /// CEP do depósito; sem ele o frete sai do CEP da loja
+    #[serde(default)]
+    pub postal_code: Option<String>,
 }
 
 impl Model {
@@ -25,6 +28,7 @@ impl Model {
             name: sea_orm::ActiveValue::set(params.name.clone()),
             latitude: sea_orm::ActiveValue::set(params.latitude),
             longitude: sea_orm::ActiveValue::set(params.longitude),
+            postal_code: sea_orm::ActiveValue::set(params.postal_code.clone()),
             ..Default::default()
         };
         Ok(warehouse.insert(db).await?)
@@ -0,0 +1,208 @@
+//! Gateway Asaas (PIX, boleto e cartão)
+//!
+//! Usa o [`AsaasClient`] de `services::asaas`; credenciais em `ASAAS_API_KEY`
+//! e `ASAAS_BASE_URL`. Webhooks são autenticados pelo header
+//! `asaas-access-token`, comparado com `ASAAS_WEBHOOK_AUTH_TOKEN`.
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+
+use super::{ChargeParams, ChargeResult, PaymentError, PaymentProvider, SavedCard, WebhookEvent};
+use crate::services::asaas::{AsaasClient, AsaasPayment, AsaasWebhookPayload};
+
+pub struct AsaasProvider {
+    client: AsaasClient,
+}
+
+impl AsaasProvider {
+    pub fn new(client: AsaasClient) -> Self {
+        Self { client }
+    }
+
+    /// Cartão novo é tokenizado antes da cobrança; o token volta em
+    /// `saved_card` para a próxima compra
+    async fn create_card_charge(
+        &self,
+        customer_id: &str,
+        params: &ChargeParams,
+    ) -> Result<ChargeResult, PaymentError> {
+        let Some(card) = params.card.as_ref() else {
+            return Err(PaymentError::Rejected(
+                "Faltam os dados do cartão".to_string(),
+            ));
+        };
+
+        let (token, saved_card) = match (&card.token, &card.card, &card.holder) {
+            (Some(token), _, _) => (token.clone(), None),
+            (None, Some(details), Some(holder)) => {
+                let tokenized = self
+                    .client
+                    .tokenize_card(customer_id, details, holder, &card.remote_ip)
+                    .await
+                    .map_err(rejected)?;
+                let saved = SavedCard {
+                    token: tokenized.creditCardToken.clone(),
+                    last4: tokenized
+                        .creditCardNumber
+                        .clone()
+                        .unwrap_or_else(|| details.last4()),
+                    brand: tokenized.creditCardBrand.clone(),
+                };
+                (tokenized.creditCardToken, Some(saved))
+            }
+            _ => {
+                return Err(PaymentError::Rejected(
+                    "Informe o token ou o cartão com os dados do titular".to_string(),
+                ));
+            }
+        };
+
+        let payment = self
+            .client
+            .create_card_payment(
+                customer_id,
+                params.amount_cents,
+                card.installments,
+                &token,
+                &params.description,
+                &params.order_reference,
+                &card.remote_ip,
+            )
+            .await
+            .map_err(rejected)?;
+
+        Ok(self.charge_result(payment, customer_id.to_string(), saved_card))
+    }
+
+    fn charge_result(
+        &self,
+        payment: AsaasPayment,
+        customer_id: String,
+        saved_card: Option<SavedCard>,
+    ) -> ChargeResult {
+        ChargeResult {
+            provider_id: payment.id.clone(),
+            status: self.client.map_status(payment.status.as_deref(), None),
+            provider_customer_id: Some(customer_id),
+            invoice_url: payment.invoiceUrl.clone(),
+            bank_slip_url: payment.bankSlipUrl.clone(),
+            pix_qr_code: payment.pixQrCode.clone(),
+            pix_qr_code_id: payment.pixQrCodeId.clone(),
+            checkout_url: payment.checkoutUrl.clone(),
+            due_date: payment.dueDate.clone(),
+            saved_card,
+            raw_data: serde_json::to_value(&payment).unwrap_or_default(),
+        }
+    }
+}
+
+/// O client devolve `loco_rs::Error::Message` com a resposta do Asaas
+fn rejected(e: loco_rs::Error) -> PaymentError {
+    PaymentError::Rejected(e.to_string())
+}
+
+#[async_trait]
+impl PaymentProvider for AsaasProvider {
+    fn name(&self) -> &'static str {
+        "asaas"
+    }
+
+    async fn create_charge(&self, params: ChargeParams) -> Result<ChargeResult, PaymentError> {
+        let customer_id = match params.customer.provider_customer_id.clone() {
+            Some(id) => id,
+            None => {
+                let customer = &params.customer;
+                self.client
+                    .create_customer(
+                        &customer.name,
+                        &customer.email,
+                        customer.phone.as_deref(),
+                        Some(customer.external_reference.clone()),
+                    )
+                    .await
+                    .map_err(rejected)?
+                    .id
+            }
+        };
+
+        if params.method == "CREDIT_CARD" {
+            return self.create_card_charge(&customer_id, &params).await;
+        }
+
+        let payment = self
+            .client
+            .create_payment(
+                &customer_id,
+                params.amount_cents,
+                &params.description,
+                &params.order_reference,
+                &params.method,
+                params.due_date.clone(),
+            )
+            .await
+            .map_err(rejected)?;
+
+        Ok(self.charge_result(payment, customer_id, None))
+    }
+
+    async fn capture(&self, payment_id: &str) -> Result<String, PaymentError> {
+        let payment = self
+            .client
+            .capture_payment(payment_id)
+            .await
+            .map_err(rejected)?;
+        Ok(self.client.map_status(payment.status.as_deref(), None))
+    }
+
+    async fn cancel(&self, payment_id: &str) -> Result<serde_json::Value, PaymentError> {
+        self.client
+            .cancel_payment(payment_id)
+            .await
+            .map_err(rejected)
+    }
+
+    async fn refund(
+        &self,
+        payment_id: &str,
+        amount_cents: Option<i64>,
+        description: Option<&str>,
+    ) -> Result<serde_json::Value, PaymentError> {
+        self.client
+            .refund_payment(payment_id, amount_cents, description)
+            .await
+            .map_err(rejected)
+    }
+
+    fn parse_webhook(
+        &self,
+        headers: &HeaderMap,
+        payload: &serde_json::Value,
+    ) -> Result<WebhookEvent, PaymentError> {
+        let token = headers
+            .get("asaas-access-token")
+            .and_then(|v| v.to_str().ok());
+        if !self.client.verify_webhook_token(token) {
+            return Err(PaymentError::Unauthorized(
+                "asaas-access-token inválido".to_string(),
+            ));
+        }
+
+        let webhook: AsaasWebhookPayload = serde_json::from_value(payload.clone())
+            .map_err(|e| PaymentError::Parse(e.to_string()))?;
+        let Some(payment) = webhook.payment.as_ref() else {
+            return Err(PaymentError::Parse("Payload sem payment".to_string()));
+        };
+
+        Ok(WebhookEvent {
+            event_id: webhook.event_key(),
+            event_type: webhook.event.clone().unwrap_or_default(),
+            payment_id: payment.id.clone(),
+            order_reference: payment.externalReference.clone(),
+            payment_status: Some(
+                self.client
+                    .map_status(payment.status.as_deref(), webhook.event.as_deref()),
+            ),
+            occurred_at: webhook.occurred_at(),
+        })
+    }
+}
@@ -0,0 +1,183 @@
+//! Gateway fake, para testes e desenvolvimento local
+//!
+//! Não acessa a rede e responde sempre igual para a mesma entrada:
+//!
+//! - PIX e boleto: cobrança `awaiting`, com QR Code/linha fictícios;
+//! - cartão: `paid` na hora, exceto cartões terminados em `0002`, recusados
+//!   como sem saldo; o cartão novo vira o token `fake_tok_<últimos 4>`;
+//! - estorno e cancelamento: sempre aceitos.
+//!
+//! O ciclo de vida continua pelo webhook `POST /api/payments/fake/webhook`,
+//! sem autenticação (o provider só existe com `PAYMENT_FAKE_ENABLED`):
+//!
+//! ```json
+//! { "id": "evt_1", "event": "PAYMENT_RECEIVED", "payment_id": "fake_pay_…",
+//!   "order_reference": "<pid do pedido>", "occurred_at": "2026-03-06T12:00:00-03:00" }
+//! ```
+//!
+//! `status` pode ser informado diretamente; sem ele vem do `event`.
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+
+use super::{ChargeParams, ChargeResult, PaymentError, PaymentProvider, SavedCard, WebhookEvent};
+
+pub struct FakeProvider;
+
+#[derive(Debug, Deserialize)]
+struct FakeWebhook {
+    id: String,
+    event: String,
+    payment_id: Option<String>,
+    order_reference: Option<String>,
+    status: Option<String>,
+    occurred_at: Option<DateTime<FixedOffset>>,
+}
+
+/// Status do pagamento para os eventos simulados (mesmos nomes do Asaas)
+fn status_for_event(event: &str) -> Option<&'static str> {
+    match event {
+        "PAYMENT_CREATED" | "PAYMENT_UPDATED" => Some("awaiting"),
+        "PAYMENT_RECEIVED" | "PAYMENT_CONFIRMED" => Some("paid"),
+        "PAYMENT_OVERDUE" => Some("overdue"),
+        "PAYMENT_PARTIALLY_REFUNDED" => Some("partially_refunded"),
+        "PAYMENT_REFUNDED" => Some("refunded"),
+        "PAYMENT_DELETED" => Some("canceled"),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for FakeProvider {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    async fn create_charge(&self, params: ChargeParams) -> Result<ChargeResult, PaymentError> {
+        let provider_id = format!("fake_pay_{}", params.order_reference);
+        let customer_id = params
+            .customer
+            .provider_customer_id
+            .clone()
+            .unwrap_or_else(|| format!("fake_cus_{}", params.customer.external_reference));
+        let invoice_url = format!("https://pagamentos.fake/i/{}", provider_id);
+
+        let mut result = ChargeResult {
+            provider_id: provider_id.clone(),
+            status: "awaiting".to_string(),
+            provider_customer_id: Some(customer_id),
+            invoice_url: Some(invoice_url),
+            bank_slip_url: None,
+            pix_qr_code: None,
+            pix_qr_code_id: None,
+            checkout_url: None,
+            // Mesmo padrão do Asaas: 2 dias
+            due_date: Some(params.due_date.clone().unwrap_or_else(|| {
+                (chrono::Utc::now() + chrono::Duration::days(2))
+                    .date_naive()
+                    .to_string()
+            })),
+            saved_card: None,
+            raw_data: serde_json::json!({}),
+        };
+
+        match params.method.as_str() {
+            "PIX" => {
+                result.pix_qr_code = Some(format!("00020126FAKEPIX{}", params.order_number));
+                result.pix_qr_code_id = Some(format!("fake_qr_{}", params.order_number));
+            }
+            "BOLETO" => {
+                result.bank_slip_url = Some(format!("https://pagamentos.fake/b/{}", provider_id));
+            }
+            "CREDIT_CARD" => {
+                let card = params.card.as_ref().ok_or_else(|| {
+                    PaymentError::Rejected("Faltam os dados do cartão".to_string())
+                })?;
+                let last4 = match (&card.token, &card.card) {
+                    (Some(token), _) => token.trim_start_matches("fake_tok_").to_string(),
+                    (None, Some(details)) => {
+                        let last4 = details.last4();
+                        result.saved_card = Some(SavedCard {
+                            token: format!("fake_tok_{}", last4),
+                            last4: last4.clone(),
+                            brand: Some("FAKE".to_string()),
+                        });
+                        last4
+                    }
+                    (None, None) => {
+                        return Err(PaymentError::Rejected(
+                            "Informe o token ou o cartão".to_string(),
+                        ));
+                    }
+                };
+                if last4 == "0002" {
+                    return Err(PaymentError::Rejected(
+                        "Cartão recusado: saldo insuficiente".to_string(),
+                    ));
+                }
+                result.status = "paid".to_string();
+            }
+            other => {
+                return Err(PaymentError::Rejected(format!(
+                    "Forma de pagamento inválida: {}",
+                    other
+                )));
+            }
+        }
+
+        result.raw_data = serde_json::json!({
+            "id": provider_id,
+            "method": params.method,
+            "value": params.amount_cents,
+            "installments": params.card.as_ref().map(|c| c.installments),
+            "status": result.status,
+        });
+        Ok(result)
+    }
+
+    async fn capture(&self, _payment_id: &str) -> Result<String, PaymentError> {
+        Ok("paid".to_string())
+    }
+
+    async fn cancel(&self, payment_id: &str) -> Result<serde_json::Value, PaymentError> {
+        Ok(serde_json::json!({ "id": payment_id, "deleted": true }))
+    }
+
+    async fn refund(
+        &self,
+        payment_id: &str,
+        amount_cents: Option<i64>,
+        description: Option<&str>,
+    ) -> Result<serde_json::Value, PaymentError> {
+        Ok(serde_json::json!({
+            "id": payment_id,
+            "status": if amount_cents.is_some() { "PARTIALLY_REFUNDED" } else { "REFUNDED" },
+            "value": amount_cents,
+            "description": description,
+        }))
+    }
+
+    fn parse_webhook(
+        &self,
+        _headers: &HeaderMap,
+        payload: &serde_json::Value,
+    ) -> Result<WebhookEvent, PaymentError> {
+        let webhook: FakeWebhook = serde_json::from_value(payload.clone())
+            .map_err(|e| PaymentError::Parse(e.to_string()))?;
+        let payment_status = webhook
+            .status
+            .clone()
+            .or_else(|| status_for_event(&webhook.event).map(String::from));
+
+        Ok(WebhookEvent {
+            event_id: webhook.id,
+            event_type: webhook.event,
+            payment_id: webhook.payment_id,
+            order_reference: webhook.order_reference,
+            payment_status,
+            occurred_at: webhook.occurred_at,
+        })
+    }
+}
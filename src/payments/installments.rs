@@ -0,0 +1,139 @@
+//! Parcelamento no cartão de crédito
+//!
+//! O valor de cada parcela segue a tabela Price: com taxa mensal `i` e `n`
+//! parcelas, `parcela = valor × i / (1 − (1 + i)^−n)`; sem juros, é o valor
+//! dividido por `n`. A parcela é arredondada para cima no centavo; com
+//! juros o total cobrado é `parcela × n`, sem juros é o próprio valor.
+//!
+//! # Tabela de juros
+//!
+//! `CARD_INSTALLMENTS` aceita um JSON com o limite de parcelas, a parcela
+//! mínima e as faixas de taxa mensal:
+//!
+//! ```json
+//! { "max_installments": 12, "min_installment_cents": 500,
+//!   "rates": [{ "from": 1, "to": 3, "monthly_rate_percent": 0 },
+//!             { "from": 4, "to": 12, "monthly_rate_percent": 1.99 }] }
+//! ```
+//!
+//! Sem a variável, usa [`InstallmentTable::default`]. Parcelas fora de
+//! qualquer faixa são oferecidas sem juros.
+
+use serde::{Deserialize, Serialize};
+
+/// Faixa de parcelas com a mesma taxa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallmentRate {
+    pub from: u32,
+    pub to: u32,
+    /// Taxa de juros ao mês, em %
+    pub monthly_rate_percent: f64,
+}
+
+/// Configuração do parcelamento
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallmentTable {
+    pub max_installments: u32,
+    /// Parcela mínima; limita o número de parcelas em compras pequenas
+    pub min_installment_cents: i64,
+    #[serde(default)]
+    pub rates: Vec<InstallmentRate>,
+}
+
+/// Opção de parcelamento para um valor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallmentOption {
+    pub installments: u32,
+    pub installment_cents: i64,
+    pub total_cents: i64,
+    pub interest_cents: i64,
+    pub monthly_rate_percent: f64,
+}
+
+impl Default for InstallmentTable {
+    /// Até 12x, parcela mínima de R$ 5,00, 3x sem juros e 1,99% a.m. acima
+    fn default() -> Self {
+        Self {
+            max_installments: 12,
+            min_installment_cents: 500,
+            rates: vec![
+                InstallmentRate {
+                    from: 1,
+                    to: 3,
+                    monthly_rate_percent: 0.0,
+                },
+                InstallmentRate {
+                    from: 4,
+                    to: 12,
+                    monthly_rate_percent: 1.99,
+                },
+            ],
+        }
+    }
+}
+
+impl InstallmentTable {
+    /// Tabela configurada (`CARD_INSTALLMENTS` ou a padrão)
+    pub fn from_env() -> Self {
+        crate::env::load();
+        std::env::var("CARD_INSTALLMENTS")
+            .ok()
+            .and_then(|raw| match serde_json::from_str::<Self>(&raw) {
+                Ok(table) if table.max_installments > 0 => Some(table),
+                Ok(_) => None,
+                Err(e) => {
+                    tracing::warn!(error = %e, "CARD_INSTALLMENTS inválido, usando tabela padrão");
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// Taxa mensal (%) para o número de parcelas
+    pub fn rate_for(&self, installments: u32) -> f64 {
+        self.rates
+            .iter()
+            .find(|r| (r.from..=r.to).contains(&installments))
+            .map_or(0.0, |r| r.monthly_rate_percent)
+    }
+
+    /// Calcula uma opção; `None` se passar do limite de parcelas ou se a
+    /// parcela ficar abaixo do mínimo (à vista é sempre aceito)
+    pub fn option_for(&self, amount_cents: i64, installments: u32) -> Option<InstallmentOption> {
+        if installments == 0 || installments > self.max_installments || amount_cents <= 0 {
+            return None;
+        }
+        let rate_percent = self.rate_for(installments);
+        let n = f64::from(installments);
+        let amount = amount_cents as f64;
+        let raw = if rate_percent > 0.0 {
+            let i = rate_percent / 100.0;
+            amount * i / (1.0 - (1.0 + i).powf(-n))
+        } else {
+            amount / n
+        };
+        let installment_cents = raw.ceil() as i64;
+        if installments > 1 && installment_cents < self.min_installment_cents {
+            return None;
+        }
+        let total_cents = if rate_percent > 0.0 {
+            installment_cents * i64::from(installments)
+        } else {
+            amount_cents
+        };
+        Some(InstallmentOption {
+            installments,
+            installment_cents,
+            total_cents,
+            interest_cents: total_cents - amount_cents,
+            monthly_rate_percent: rate_percent,
+        })
+    }
+
+    /// Todas as opções disponíveis para o valor, de 1x ao limite
+    pub fn simulate(&self, amount_cents: i64) -> Vec<InstallmentOption> {
+        (1..=self.max_installments)
+            .filter_map(|n| self.option_for(amount_cents, n))
+            .collect()
+    }
+}
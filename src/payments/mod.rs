@@ -0,0 +1,253 @@
+//! Módulo de integração com gateways de pagamento.
+//!
+//! # Arquitetura
+//!
+//! Cada gateway implementa o trait [`PaymentProvider`].
+//! O sistema seleciona o provider pelo slug da rota
+//! (`/api/v1/orders/{pid}/payments/{provider}`) ou pelo `payment_provider`
+//! gravado no pedido.
+//!
+//! # Como adicionar um novo gateway
+//!
+//! 1. Crie um arquivo `src/payments/meu_gateway.rs`
+//! 2. Implemente o trait `PaymentProvider` para a sua struct
+//! 3. Adicione o módulo em `mod.rs` e registre em `provider_for()`
+//!
+//! # Providers disponíveis
+//!
+//! | Slug     | Status       | Módulo                                       |
+//! |----------|--------------|----------------------------------------------|
+//! | `asaas`  | ✅ pronto    | `asaas.rs`                                   |
+//! | `fake`   | ✅ pronto    | `fake.rs` (sem rede, `PAYMENT_FAKE_ENABLED`) |
+
+pub mod asaas;
+pub mod fake;
+pub mod installments;
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+/// Formas de pagamento aceitas
+pub const METHODS: &[&str] = &["PIX", "BOLETO", "CREDIT_CARD"];
+
+/// Cliente pagador
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentCustomer {
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    /// Referência do cliente na loja (pid)
+    pub external_reference: String,
+    /// ID do cliente já cadastrado no gateway, quando houver
+    pub provider_customer_id: Option<String>,
+}
+
+/// Parâmetros para criar uma cobrança
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargeParams {
+    /// Referência do pedido (pid), devolvida nos webhooks
+    pub order_reference: String,
+    pub order_number: String,
+    pub amount_cents: i64,
+    /// 'PIX' | 'BOLETO' | 'CREDIT_CARD'
+    pub method: String,
+    pub description: String,
+    /// AAAA-MM-DD; sem data vale o padrão do gateway
+    pub due_date: Option<String>,
+    pub customer: PaymentCustomer,
+    /// Dados do cartão (obrigatório em 'CREDIT_CARD')
+    #[serde(default)]
+    pub card: Option<CardCharge>,
+}
+
+/// Dados do cartão digitados no checkout. Número e CVV nunca são
+/// serializados nem aparecem no `Debug`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CardDetails {
+    pub holder_name: String,
+    #[serde(skip_serializing)]
+    pub number: String,
+    pub expiry_month: String,
+    pub expiry_year: String,
+    #[serde(skip_serializing)]
+    pub ccv: String,
+}
+
+impl std::fmt::Debug for CardDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CardDetails")
+            .field("holder_name", &self.holder_name)
+            .field("last4", &self.last4())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CardDetails {
+    /// Quatro últimos dígitos do número
+    pub fn last4(&self) -> String {
+        let digits: Vec<char> = self.number.chars().filter(char::is_ascii_digit).collect();
+        digits[digits.len().saturating_sub(4)..].iter().collect()
+    }
+}
+
+/// Titular do cartão (exigido pelo antifraude do gateway)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardHolderInfo {
+    pub name: String,
+    pub email: String,
+    pub cpf_cnpj: String,
+    pub postal_code: String,
+    pub address_number: String,
+    pub phone: Option<String>,
+}
+
+/// Pagamento no cartão: cartão novo (`card` + `holder`) ou `token` salvo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardCharge {
+    pub installments: u32,
+    /// Valor de cada parcela, já com juros
+    pub installment_cents: i64,
+    pub token: Option<String>,
+    pub card: Option<CardDetails>,
+    pub holder: Option<CardHolderInfo>,
+    /// IP do comprador, exigido pelo gateway
+    pub remote_ip: String,
+}
+
+/// Cartão tokenizado no gateway, salvo no cliente para a próxima compra
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedCard {
+    pub token: String,
+    pub last4: String,
+    pub brand: Option<String>,
+}
+
+/// Cobrança criada no gateway
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChargeResult {
+    /// ID da cobrança no gateway
+    pub provider_id: String,
+    /// Status normalizado (mesmos valores de `orders.payment_status`)
+    pub status: String,
+    /// ID do cliente no gateway, para reaproveitar nas próximas cobranças
+    pub provider_customer_id: Option<String>,
+    pub invoice_url: Option<String>,
+    pub bank_slip_url: Option<String>,
+    pub pix_qr_code: Option<String>,
+    pub pix_qr_code_id: Option<String>,
+    pub checkout_url: Option<String>,
+    /// Vencimento (AAAA-MM-DD); depois dele o pedido não pago é cancelado
+    pub due_date: Option<String>,
+    /// Cartão tokenizado nesta cobrança (cartão novo)
+    pub saved_card: Option<SavedCard>,
+    /// Dados brutos do gateway
+    pub raw_data: serde_json::Value,
+}
+
+/// Evento de webhook já autenticado e normalizado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// ID do evento no gateway (chave de idempotência)
+    pub event_id: String,
+    pub event_type: String,
+    pub payment_id: Option<String>,
+    /// Referência do pedido (pid) enviada na criação da cobrança
+    pub order_reference: Option<String>,
+    /// Status normalizado; `None` para eventos que não mudam o pagamento
+    pub payment_status: Option<String>,
+    pub occurred_at: Option<DateTime<FixedOffset>>,
+}
+
+/// Erro de integração com o gateway
+#[derive(Debug)]
+pub enum PaymentError {
+    NotConfigured(String),
+    Network(String),
+    Parse(String),
+    /// Operação não suportada pelo gateway
+    Unsupported(String),
+    /// Credenciais ou token de webhook recusados
+    Unauthorized(String),
+    /// Requisição recusada pelo gateway (validação, cartão recusado, etc.)
+    Rejected(String),
+}
+
+impl std::fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotConfigured(m) => write!(f, "Gateway não configurado: {}", m),
+            Self::Network(m) => write!(f, "Erro de comunicação com o gateway: {}", m),
+            Self::Parse(m) => write!(f, "Resposta inesperada do gateway: {}", m),
+            Self::Unsupported(m) => write!(f, "Operação não suportada: {}", m),
+            Self::Unauthorized(m) => write!(f, "Não autorizado: {}", m),
+            Self::Rejected(m) => write!(f, "Recusado pelo gateway: {}", m),
+        }
+    }
+}
+
+impl std::error::Error for PaymentError {}
+
+/// Trait que todo gateway de pagamento deve implementar.
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    /// Slug identificador do provider (ex.: "asaas")
+    fn name(&self) -> &'static str;
+
+    /// Cria a cobrança (cadastrando o cliente no gateway, se preciso)
+    async fn create_charge(&self, params: ChargeParams) -> Result<ChargeResult, PaymentError>;
+
+    /// Captura uma cobrança de cartão pré-autorizada; retorna o novo status
+    async fn capture(&self, payment_id: &str) -> Result<String, PaymentError>;
+
+    /// Cancela uma cobrança ainda não paga
+    async fn cancel(&self, payment_id: &str) -> Result<serde_json::Value, PaymentError>;
+
+    /// Estorna a cobrança. `amount_cents = None` estorna o valor total.
+    async fn refund(
+        &self,
+        payment_id: &str,
+        amount_cents: Option<i64>,
+        description: Option<&str>,
+    ) -> Result<serde_json::Value, PaymentError>;
+
+    /// Autentica e normaliza um webhook. Token inválido → `Unauthorized`.
+    fn parse_webhook(
+        &self,
+        headers: &HeaderMap,
+        payload: &serde_json::Value,
+    ) -> Result<WebhookEvent, PaymentError>;
+}
+
+/// Slugs de todos os providers
+pub const PROVIDERS: &[&str] = &["asaas", "fake"];
+
+/// Provider padrão das cobranças (`PAYMENT_PROVIDER`, padrão `asaas`)
+pub fn default_provider() -> String {
+    crate::env::load();
+    std::env::var("PAYMENT_PROVIDER").unwrap_or_else(|_| "asaas".to_string())
+}
+
+/// Retorna o provider correspondente ao slug, se disponível e configurado.
+///
+/// # Como registrar um novo provider
+///
+/// Adicione um braço ao `match` abaixo retornando sua implementação.
+pub fn provider_for(slug: &str) -> Option<Box<dyn PaymentProvider>> {
+    match slug {
+        "asaas" => {
+            let client = crate::services::asaas::AsaasClient::from_env().ok()?;
+            Some(Box::new(asaas::AsaasProvider::new(client)))
+        }
+        "fake" => {
+            // Só existe com PAYMENT_FAKE_ENABLED, para nunca ir a produção por engano
+            crate::env::load();
+            let enabled = std::env::var("PAYMENT_FAKE_ENABLED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false);
+            enabled.then(|| Box::new(fake::FakeProvider) as Box<dyn PaymentProvider>)
+        }
+        _ => None,
+    }
+}
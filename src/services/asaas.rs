@@ -1,7 +1,9 @@
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Utc};
 use loco_rs::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::payments::{CardDetails, CardHolderInfo};
+
 #[allow(non_snake_case)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AsaasCustomer {
@@ -27,6 +29,17 @@ pub struct AsaasPayment {
     pub pixQrCode: Option<String>,
     #[serde(default)]
     pub checkoutUrl: Option<String>,
+    #[serde(default)]
+    pub dueDate: Option<String>,
+}
+
+/// Cartão tokenizado (`/creditCard/tokenizeCreditCard`)
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AsaasCardToken {
+    pub creditCardToken: String,
+    pub creditCardNumber: Option<String>,
+    pub creditCardBrand: Option<String>,
 }
 
 #[allow(non_snake_case)]
@@ -42,11 +55,43 @@ pub struct AsaasWebhookPayment {
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AsaasWebhookPayload {
+    /// ID do evento no Asaas (`evt_...`), único por notificação
+    pub id: Option<String>,
     pub event: Option<String>,
+    /// Data do evento, no horário de Brasília (`2024-06-12 16:45:03`)
+    pub dateCreated: Option<String>,
     #[serde(default)]
     pub payment: Option<AsaasWebhookPayment>,
 }
 
+impl AsaasWebhookPayload {
+    /// Data do evento com o fuso de Brasília (UTC-3)
+    pub fn occurred_at(&self) -> Option<DateTime<FixedOffset>> {
+        let date = self.dateCreated.as_deref()?;
+        let naive = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S").ok()?;
+        let offset = FixedOffset::west_opt(3 * 3600)?;
+        naive.and_local_timezone(offset).single()
+    }
+
+    /// Chave de idempotência: o ID do evento ou, em payloads antigos sem ID,
+    /// a combinação evento + cobrança + status + data
+    pub fn event_key(&self) -> String {
+        if let Some(id) = self.id.as_deref().filter(|id| !id.is_empty()) {
+            return id.to_string();
+        }
+        let payment = self.payment.as_ref();
+        format!(
+            "{}:{}:{}:{}",
+            self.event.as_deref().unwrap_or_default(),
+            payment.and_then(|p| p.id.as_deref()).unwrap_or_default(),
+            payment
+                .and_then(|p| p.status.as_deref())
+                .unwrap_or_default(),
+            self.dateCreated.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct AsaasClient {
     client: reqwest::Client,
@@ -64,7 +109,11 @@ impl AsaasClient {
             .map_err(|_| Error::Message("Faltou ASAAS_API_KEY".to_string()))?;
         let base_url = std::env::var("ASAAS_BASE_URL")
             .unwrap_or_else(|_| "https://sandbox.asaas.com/api/v3".to_string());
-        let webhook_secret = std::env::var("ASAAS_WEBHOOK_SECRET").ok();
+        // Mesmo token cadastrado no webhook pelo initializer (authToken)
+        let webhook_secret = std::env::var("ASAAS_WEBHOOK_AUTH_TOKEN")
+            .or_else(|_| std::env::var("ASAAS_WEBHOOK_SECRET"))
+            .ok()
+            .filter(|token| !token.is_empty());
 
         // build reqwest client with a default user agent to satisfy Asaas requirement
         let client = reqwest::Client::builder()
@@ -283,15 +332,225 @@ impl AsaasClient {
             .map_err(|e| Error::Message(format!("Asaas parse payment: {}", e)))
     }
 
+    /// Tokeniza um cartão para o cliente; o token substitui os dados do
+    /// cartão nas próximas cobranças
+    pub async fn tokenize_card(
+        &self,
+        customer_id: &str,
+        card: &CardDetails,
+        holder: &CardHolderInfo,
+        remote_ip: &str,
+    ) -> Result<AsaasCardToken> {
+        let payload = serde_json::json!({
+            "customer": customer_id,
+            "creditCard": {
+                "holderName": card.holder_name,
+                "number": card.number,
+                "expiryMonth": card.expiry_month,
+                "expiryYear": card.expiry_year,
+                "ccv": card.ccv,
+            },
+            "creditCardHolderInfo": {
+                "name": holder.name,
+                "email": holder.email,
+                "cpfCnpj": holder.cpf_cnpj,
+                "postalCode": holder.postal_code,
+                "addressNumber": holder.address_number,
+                "phone": holder.phone,
+            },
+            "remoteIp": remote_ip,
+        });
+
+        let url = format!("{}/creditCard/tokenizeCreditCard", self.base_url);
+        let res = self
+            .auth(self.client.post(url))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas tokenize_card erro: {}", e)))?;
+
+        if !res.status().is_success() {
+            let body: String = res.text().await.unwrap_or_default();
+            return Err(Error::Message(format!(
+                "Asaas tokenize_card falhou: {}",
+                body
+            )));
+        }
+
+        res.json::<AsaasCardToken>()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas parse card token: {}", e)))
+    }
+
+    /// Cobrança no cartão com token, à vista ou parcelada. `total_cents` é o
+    /// valor total (com juros); o Asaas divide em `installments` parcelas.
+    pub async fn create_card_payment(
+        &self,
+        customer_id: &str,
+        total_cents: i64,
+        installments: u32,
+        card_token: &str,
+        description: &str,
+        external_reference: &str,
+        remote_ip: &str,
+    ) -> Result<AsaasPayment> {
+        let mut payload = serde_json::json!({
+            "customer": customer_id,
+            "billingType": "CREDIT_CARD",
+            "dueDate": Utc::now().date_naive().to_string(),
+            "description": description,
+            "externalReference": external_reference,
+            "creditCardToken": card_token,
+            "remoteIp": remote_ip,
+        });
+        if installments > 1 {
+            payload["installmentCount"] = serde_json::json!(installments);
+            payload["totalValue"] = serde_json::json!((total_cents as f64) / 100.0);
+        } else {
+            payload["value"] = serde_json::json!((total_cents as f64) / 100.0);
+        }
+
+        let url = format!("{}/payments", self.base_url);
+        let res = self
+            .auth(self.client.post(url))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas create_card_payment erro: {}", e)))?;
+
+        if !res.status().is_success() {
+            let body: String = res.text().await.unwrap_or_default();
+            return Err(Error::Message(format!(
+                "Asaas create_card_payment falhou: {}",
+                body
+            )));
+        }
+
+        res.json::<AsaasPayment>()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas parse payment: {}", e)))
+    }
+
+    /// Captura uma cobrança de cartão pré-autorizada (`authorizeOnly`)
+    pub async fn capture_payment(&self, payment_id: &str) -> Result<AsaasPayment> {
+        let url = format!(
+            "{}/payments/{}/captureAuthorizedPayment",
+            self.base_url, payment_id
+        );
+        let res = self
+            .auth(self.client.post(url))
+            .send()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas capture_payment erro: {}", e)))?;
+
+        if !res.status().is_success() {
+            let body: String = res.text().await.unwrap_or_default();
+            return Err(Error::Message(format!(
+                "Asaas capture_payment falhou: {}",
+                body
+            )));
+        }
+
+        res.json::<AsaasPayment>()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas parse payment: {}", e)))
+    }
+
+    /// Remove uma cobrança ainda não paga
+    pub async fn cancel_payment(&self, payment_id: &str) -> Result<serde_json::Value> {
+        let url = format!("{}/payments/{}", self.base_url, payment_id);
+        let res = self
+            .auth(self.client.delete(url))
+            .send()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas cancel_payment erro: {}", e)))?;
+
+        if !res.status().is_success() {
+            let body: String = res.text().await.unwrap_or_default();
+            return Err(Error::Message(format!(
+                "Asaas cancel_payment falhou: {}",
+                body
+            )));
+        }
+
+        res.json::<serde_json::Value>()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas parse cancel: {}", e)))
+    }
+
+    /// Estorna uma cobrança (PIX ou cartão). Sem `value_cents` o estorno é
+    /// total; com valor, parcial. Retorna a cobrança atualizada.
+    pub async fn refund_payment(
+        &self,
+        payment_id: &str,
+        value_cents: Option<i64>,
+        description: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        #[derive(Serialize)]
+        struct Payload<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            value: Option<f64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            description: Option<&'a str>,
+        }
+
+        let payload = Payload {
+            value: value_cents.map(|cents| (cents as f64) / 100.0),
+            description,
+        };
+
+        let url = format!("{}/payments/{}/refund", self.base_url, payment_id);
+        let res = self
+            .auth(self.client.post(url))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas refund_payment erro: {}", e)))?;
+
+        if !res.status().is_success() {
+            let body: String = res.text().await.unwrap_or_default();
+            return Err(Error::Message(format!(
+                "Asaas refund_payment falhou: {}",
+                body
+            )));
+        }
+
+        res.json::<serde_json::Value>()
+            .await
+            .map_err(|e| Error::Message(format!("Asaas parse refund: {}", e)))
+    }
+
+    /// Confere o header `asaas-access-token` do webhook; sem token
+    /// configurado nenhuma notificação é aceita
+    pub fn verify_webhook_token(&self, received: Option<&str>) -> bool {
+        match (&self.webhook_secret, received) {
+            (Some(expected), Some(received)) => {
+                super::constant_time_eq(expected.as_bytes(), received.as_bytes())
+            }
+            _ => false,
+        }
+    }
+
     pub fn map_status(&self, asaas_status: Option<&str>, event: Option<&str>) -> String {
         if let Some(ev) = event {
             if ev.eq_ignore_ascii_case("PAYMENT_CONFIRMED") {
                 return "paid".to_string();
             }
+            if ev.eq_ignore_ascii_case("PAYMENT_REFUNDED") {
+                return "refunded".to_string();
+            }
+            if ev.eq_ignore_ascii_case("PAYMENT_PARTIALLY_REFUNDED") {
+                return "partially_refunded".to_string();
+            }
+            if ev.eq_ignore_ascii_case("PAYMENT_DELETED") {
+                return "canceled".to_string();
+            }
         }
 
         match asaas_status.unwrap_or("") {
             "RECEIVED" | "CONFIRMED" | "RECEIVED_IN_CASH" | "RECEIVED_PIX" => "paid".to_string(),
+            // Estorno solicitado mas ainda não concluído: continua pago
+            "REFUND_REQUESTED" | "REFUND_IN_PROGRESS" => "paid".to_string(),
             "PENDING" | "AWAITING_RISK_ANALYSIS" => "awaiting".to_string(),
             "OVERDUE" => "overdue".to_string(),
             "REFUNDED" | "CHARGEBACK" | "CHARGEBACK_DISPUTE" | "CHARGEBACK_REQUESTED" => {
@@ -1,3 +1,16 @@
 pub mod analytics;
 pub mod asaas;
+pub mod pricing;
+pub mod refunds;
+pub mod routing;
+pub mod shipments;
 pub mod upload;
+
+/// Comparação sem retorno antecipado, para o tempo de resposta não revelar
+/// quantos caracteres de um token conferem
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,107 @@
+//! Resolução de preços de variantes
+//!
+//! A tabela `prices` pode ter várias linhas para a mesma variante e moeda:
+//! faixas de quantidade (`min_quantity`/`max_quantity`), preços regionais
+//! (`region`) e preços programados (`starts_at`/`ends_at`). A escolha segue,
+//! nesta ordem:
+//! 1. só entram linhas da moeda, dentro da faixa de quantidade e vigentes;
+//! 2. preço da região pedida vence o preço sem região (global);
+//! 3. a faixa mais específica (maior `min_quantity`) vence;
+//! 4. preço programado vence o permanente, e o que começou por último vence;
+//! 5. empate final: o menor valor.
+
+use chrono::{DateTime, Utc};
+use loco_rs::prelude::*;
+use sea_orm::ConnectionTrait;
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::prices;
+
+/// Parâmetros da resolução
+#[derive(Debug, Clone)]
+pub struct PriceQuery<'a> {
+    pub variant_id: i32,
+    pub currency: &'a str,
+    pub quantity: i32,
+    pub region: Option<&'a str>,
+    pub at: DateTime<Utc>,
+}
+
+impl<'a> PriceQuery<'a> {
+    /// Consulta para o momento atual
+    pub fn now(variant_id: i32, currency: &'a str, quantity: i32, region: Option<&'a str>) -> Self {
+        Self {
+            variant_id,
+            currency,
+            quantity,
+            region,
+            at: Utc::now(),
+        }
+    }
+}
+
+/// Preço resolvido para uma quantidade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceQuote {
+    pub price: prices::Model,
+    pub quantity: i32,
+    pub unit_price: i64,
+    pub total: i64,
+}
+
+/// Linha vigente e aplicável à consulta?
+fn applies(price: &prices::Model, query: &PriceQuery<'_>) -> bool {
+    let in_tier = price.min_quantity <= query.quantity
+        && price.max_quantity.is_none_or(|max| query.quantity <= max);
+    let in_window = price.starts_at.is_none_or(|s| s <= query.at)
+        && price.ends_at.is_none_or(|e| e >= query.at);
+    let in_region = match (&price.region, query.region) {
+        (None, _) => true,
+        (Some(r), Some(q)) => r.eq_ignore_ascii_case(q),
+        (Some(_), None) => false,
+    };
+    price.currency == query.currency && in_tier && in_window && in_region
+}
+
+/// Escolhe o preço aplicável entre os candidatos (ver regras no topo do módulo)
+pub fn select_price<'p>(
+    candidates: &'p [prices::Model],
+    query: &PriceQuery<'_>,
+) -> Option<&'p prices::Model> {
+    candidates
+        .iter()
+        .filter(|p| applies(p, query))
+        .max_by(|a, b| {
+            a.region
+                .is_some()
+                .cmp(&b.region.is_some())
+                .then(a.min_quantity.cmp(&b.min_quantity))
+                .then(a.starts_at.cmp(&b.starts_at))
+                .then(b.amount.cmp(&a.amount))
+        })
+}
+
+/// Resolve o preço de uma variante consultando o banco.
+///
+/// Retorna `ModelError::EntityNotFound` se nenhuma linha de preço se aplica.
+pub async fn resolve_price<C: ConnectionTrait>(
+    db: &C,
+    query: &PriceQuery<'_>,
+) -> ModelResult<PriceQuote> {
+    let candidates = prices::Entity::find()
+        .filter(prices::Column::VariantId.eq(query.variant_id))
+        .filter(prices::Column::Currency.eq(query.currency))
+        .all(db)
+        .await?;
+
+    let price = select_price(&candidates, query)
+        .cloned()
+        .ok_or(ModelError::EntityNotFound)?;
+
+    Ok(PriceQuote {
+        quantity: query.quantity,
+        unit_price: price.amount,
+        total: price.amount * i64::from(query.quantity),
+        price,
+    })
+}
@@ -0,0 +1,96 @@
+//! Estornos no gateway de pagamento
+//!
+//! [`issue_refund`] registra o estorno ([`RefundModel::start`]), chama o
+//! provider de pagamento para cada cobrança envolvida e conclui ou marca como
+//! falho cada parte. Usado pelo estorno manual, pelas edições de pedido e
+//! pela conclusão de devoluções.
+
+use loco_rs::prelude::*;
+
+use crate::{
+    models::{
+        orders::Model as OrderModel,
+        refunds::{CreateRefundParams, Model as RefundModel},
+    },
+    payments,
+};
+
+/// Resultado de um estorno no gateway
+pub enum RefundOutcome {
+    /// Um estorno por cobrança, o primeiro concluído. Se uma cobrança
+    /// seguinte for recusada, o estorno dela fica `failed` na lista.
+    Done(Vec<RefundModel>, OrderModel),
+    /// Código de erro da API e mensagem
+    Rejected(&'static str, String),
+}
+
+/// Registra o estorno, chama o gateway para cada cobrança envolvida e
+/// conclui (ou marca como falho). Usado pelo estorno manual, pelas edições
+/// de pedido e pela conclusão de devoluções.
+pub async fn issue_refund(
+    db: &DatabaseConnection,
+    order: &OrderModel,
+    params: &CreateRefundParams,
+    user_id: i32,
+) -> ModelResult<RefundOutcome> {
+    let parts = match RefundModel::start(db, order.id, params, Some(user_id)).await {
+        Ok(parts) => parts,
+        Err(ModelError::Message(msg)) => {
+            return Ok(RefundOutcome::Rejected("INVALID_REFUND", msg));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let Some(provider) = payments::provider_for(&parts[0].provider) else {
+        let msg = format!("Gateway sem integração configurada: {}", parts[0].provider);
+        for refund in parts {
+            RefundModel::fail(db, refund, &msg).await?;
+        }
+        return Ok(RefundOutcome::Rejected("UNSUPPORTED_PROVIDER", msg));
+    };
+    let charges = RefundModel::charges(db, order).await?;
+
+    let mut done = Vec::new();
+    let mut latest = None;
+    let mut parts = parts.into_iter();
+    while let Some(refund) = parts.next() {
+        let payment_id = refund.provider_payment_id.clone().unwrap_or_default();
+        // Sem valor o gateway estorna a cobrança inteira; só cabe quando o
+        // estorno leva a cobrança toda
+        let partial = charges
+            .iter()
+            .find(|c| c.payment_id == payment_id)
+            .is_none_or(|c| refund.amount < c.amount);
+        let raw = match provider
+            .refund(
+                &payment_id,
+                partial.then_some(refund.amount),
+                params.reason.as_deref(),
+            )
+            .await
+        {
+            Ok(raw) => raw,
+            Err(e) => {
+                let failed = RefundModel::fail(db, refund, &e.to_string()).await?;
+                tracing::warn!(refund = %failed.pid, error = %e, "Estorno recusado pelo gateway");
+                // As cobranças seguintes não são estornadas: o saldo volta a ficar disponível
+                for pending in parts.by_ref() {
+                    RefundModel::fail(db, pending, "Estorno anterior recusado pelo gateway")
+                        .await?;
+                }
+                let Some(order) = latest else {
+                    return Ok(RefundOutcome::Rejected("REFUND_FAILED", e.to_string()));
+                };
+                done.push(failed);
+                return Ok(RefundOutcome::Done(done, order));
+            }
+        };
+
+        let (refund, order) = RefundModel::complete(db, refund, raw).await?;
+        done.push(refund);
+        latest = Some(order);
+    }
+
+    let order = latest.ok_or(ModelError::EntityNotFound)?;
+    Ok(RefundOutcome::Done(done, order))
+}
@@ -0,0 +1,442 @@
+//! Roteamento de pedidos para o depósito mais próximo
+//!
+//! O CEP de destino vira coordenadas por uma base offline de faixas de CEP
+//! (`src/data/cep_regions.csv`, embutida no binário). Uma base mais detalhada,
+//! no mesmo formato, pode ser indicada em `CEP_COORDINATES_FILE`: a faixa
+//! mais estreita que contém o CEP vence. Os depósitos são ordenados pela
+//! distância em linha reta (haversine) até o destino.
+//!
+//! - Na reserva, o pedido inteiro sai do depósito usado na cotação do frete
+//!   ([`fulfilling_warehouse`]). Só quando nenhum depósito atende sozinho
+//!   cada linha vai para o mais próximo com saldo para ela inteira ou é
+//!   dividida entre os depósitos, do mais próximo ao mais distante
+//!   ([`route_candidates`]).
+//! - No envio, [`next_package`] agrupa os itens pendentes pelo depósito de
+//!   onde as unidades foram reservadas. O depósito fica registrado no envio e
+//!   o CEP dele é a origem do frete ([`origin_postal_code`]).
+
+use std::collections::HashMap;
+
+use loco_rs::prelude::*;
+use once_cell::sync::Lazy;
+use sea_orm::{Condition, ConnectionTrait, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::models::_entities::{
+    addresses, items, order_items, order_shipping_items, order_shippings, orders,
+    stock_reservations, stocks, warehouses,
+};
+use crate::models::order_shippings::VOID_STATUSES;
+use crate::shipping::{self, ShippingError};
+
+/// Base embutida: faixas de CEP (5 dígitos) por região, com coordenadas de
+/// referência da principal cidade
+const BUILTIN_REGIONS: &str = include_str!("../data/cep_regions.csv");
+
+/// Raio médio da Terra em km
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Latitude/longitude em graus
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    /// Distância em linha reta (haversine), em km
+    pub fn distance_km(&self, other: &Self) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.longitude - self.longitude).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+/// Faixa de CEP (8 dígitos) com as coordenadas de referência
+#[derive(Debug, Clone)]
+struct CepRegion {
+    start: u32,
+    end: u32,
+    coordinates: Coordinates,
+}
+
+#[derive(Debug, Deserialize)]
+struct CepRegionRow {
+    cep_start: String,
+    cep_end: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+static REGIONS: Lazy<Vec<CepRegion>> = Lazy::new(|| {
+    let mut regions = parse_regions(BUILTIN_REGIONS);
+    crate::env::load();
+    if let Ok(path) = std::env::var("CEP_COORDINATES_FILE") {
+        match std::fs::read_to_string(&path) {
+            Ok(data) => regions.extend(parse_regions(&data)),
+            Err(e) => tracing::warn!(path, error = %e, "Base de CEP não carregada"),
+        }
+    }
+    regions
+});
+
+/// Lê o CSV `cep_start,cep_end,...,latitude,longitude`. Faixas com 5 dígitos
+/// cobrem todos os sufixos (`01000` → `01000-000`..`01000-999`); linhas
+/// inválidas são ignoradas.
+fn parse_regions(data: &str) -> Vec<CepRegion> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+    reader
+        .deserialize::<CepRegionRow>()
+        .filter_map(Result::ok)
+        .filter_map(|row| {
+            let start = expand_cep(&row.cep_start, 0)?;
+            let end = expand_cep(&row.cep_end, 999)?;
+            (start <= end).then_some(CepRegion {
+                start,
+                end,
+                coordinates: Coordinates {
+                    latitude: row.latitude,
+                    longitude: row.longitude,
+                },
+            })
+        })
+        .collect()
+}
+
+/// CEP de 8 dígitos; prefixo de 5 dígitos recebe `suffix`
+fn expand_cep(value: &str, suffix: u32) -> Option<u32> {
+    let digits: String = value.chars().filter(char::is_ascii_digit).collect();
+    match digits.len() {
+        5 => digits.parse::<u32>().ok().map(|p| p * 1000 + suffix),
+        8 => digits.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Coordenadas aproximadas de um CEP pela base offline
+pub fn geocode(postal_code: &str) -> Option<Coordinates> {
+    let digits: String = postal_code.chars().filter(char::is_ascii_digit).collect();
+    if digits.len() != 8 {
+        return None;
+    }
+    let cep: u32 = digits.parse().ok()?;
+    REGIONS
+        .iter()
+        .filter(|r| r.start <= cep && cep <= r.end)
+        .min_by_key(|r| r.end - r.start)
+        .map(|r| r.coordinates)
+}
+
+/// Coordenadas do depósito: as cadastradas ou, se zeradas, as do seu CEP
+pub fn warehouse_coordinates(warehouse: &warehouses::Model) -> Option<Coordinates> {
+    if warehouse.latitude != 0.0 || warehouse.longitude != 0.0 {
+        return Some(Coordinates {
+            latitude: warehouse.latitude,
+            longitude: warehouse.longitude,
+        });
+    }
+    warehouse.postal_code.as_deref().and_then(geocode)
+}
+
+/// CEP de origem do frete: o do depósito ou, sem ele, o da loja
+/// (`STORE_SENDER_POSTAL_CODE`)
+pub fn origin_postal_code(warehouse: Option<&warehouses::Model>) -> Result<String, ShippingError> {
+    match warehouse.and_then(|w| w.postal_code.clone()) {
+        Some(postal_code) => Ok(postal_code),
+        None => shipping::origin_postal_code(),
+    }
+}
+
+/// CEP de entrega do pedido
+pub async fn order_destination<C: ConnectionTrait>(
+    db: &C,
+    order: &orders::Model,
+) -> ModelResult<Option<String>> {
+    let Some(address_id) = order.shipping_address_id else {
+        return Ok(None);
+    };
+    let address = addresses::Entity::find_by_id(address_id).one(db).await?;
+    Ok(address.map(|a| a.postal_code))
+}
+
+/// Depósitos ativos, do mais próximo ao mais distante do destino, com a
+/// distância em km. Sem coordenadas (do destino ou do depósito) ficam no
+/// fim, na ordem de cadastro.
+pub async fn warehouses_by_distance<C: ConnectionTrait>(
+    db: &C,
+    destination: Option<&str>,
+) -> ModelResult<Vec<(warehouses::Model, Option<f64>)>> {
+    let target = destination.and_then(geocode);
+    let mut ranked: Vec<(warehouses::Model, Option<f64>)> = warehouses::Entity::find()
+        .filter(warehouses::Column::DeletedAt.is_null())
+        .order_by_asc(warehouses::Column::Id)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|w| {
+            let distance = target
+                .zip(warehouse_coordinates(&w))
+                .map(|(t, c)| c.distance_km(&t));
+            (w, distance)
+        })
+        .collect();
+    ranked.sort_by(|(_, a), (_, b)| match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    Ok(ranked)
+}
+
+/// Posição de cada depósito na ordem de proximidade (`warehouse_id → posição`)
+pub async fn warehouse_ranking<C: ConnectionTrait>(
+    db: &C,
+    destination: Option<&str>,
+) -> ModelResult<HashMap<i32, usize>> {
+    Ok(warehouses_by_distance(db, destination)
+        .await?
+        .into_iter()
+        .enumerate()
+        .map(|(pos, (w, _))| (w.id, pos))
+        .collect())
+}
+
+/// Ordena as linhas de estoque candidatas (já em ordem FEFO) para reservar
+/// `quantity`: primeiro o depósito `preferred` (o que atende o pedido
+/// inteiro), depois o mais próximo com saldo para tudo e, sem nenhum, do
+/// mais próximo ao mais distante. Dentro do depósito a ordem FEFO é mantida.
+pub fn route_candidates(
+    mut candidates: Vec<stocks::Model>,
+    ranking: &HashMap<i32, usize>,
+    quantity: i32,
+    preferred: Option<i32>,
+) -> Vec<stocks::Model> {
+    let mut available: HashMap<i32, i32> = HashMap::new();
+    for stock in &candidates {
+        *available.entry(stock.warehouse_id).or_default() +=
+            (stock.quantity - stock.reserved).max(0);
+    }
+    candidates.sort_by_key(|stock| {
+        let covers = available.get(&stock.warehouse_id).copied().unwrap_or(0) >= quantity;
+        (
+            preferred != Some(stock.warehouse_id),
+            !covers,
+            ranking
+                .get(&stock.warehouse_id)
+                .copied()
+                .unwrap_or(usize::MAX),
+        )
+    });
+    candidates
+}
+
+/// Depósito que atende o carrinho/pedido inteiro: o mais próximo do destino
+/// com saldo para todas as linhas (`variant_id`, quantidade). É a origem da
+/// cotação do frete e o depósito de onde a reserva do pedido sai. `None`
+/// quando nenhum depósito atende sozinho.
+pub async fn fulfilling_warehouse<C: ConnectionTrait>(
+    db: &C,
+    destination: Option<&str>,
+    lines: &[(i32, i32)],
+) -> ModelResult<Option<warehouses::Model>> {
+    let mut wanted: HashMap<i32, i32> = HashMap::new();
+    for (variant_id, quantity) in lines {
+        *wanted.entry(*variant_id).or_default() += quantity;
+    }
+    if wanted.is_empty() {
+        return Ok(None);
+    }
+    let today = chrono::Utc::now().date_naive();
+    let variant_ids: Vec<i32> = wanted.keys().copied().collect();
+    let item_variant: HashMap<i32, i32> = items::Entity::find()
+        .filter(items::Column::VariantId.is_in(variant_ids))
+        .filter(items::Column::DeletedAt.is_null())
+        .filter(
+            Condition::any()
+                .add(items::Column::Expiration.is_null())
+                .add(items::Column::Expiration.gte(today)),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|i| (i.id, i.variant_id))
+        .collect();
+    if item_variant.is_empty() {
+        return Ok(None);
+    }
+
+    let mut available: HashMap<(i32, i32), i32> = HashMap::new();
+    for stock in stocks::Entity::find()
+        .filter(stocks::Column::ItemId.is_in(item_variant.keys().copied().collect::<Vec<_>>()))
+        .all(db)
+        .await?
+    {
+        if let Some(variant_id) = item_variant.get(&stock.item_id) {
+            *available
+                .entry((*variant_id, stock.warehouse_id))
+                .or_default() += (stock.quantity - stock.reserved).max(0);
+        }
+    }
+
+    for (warehouse, _) in warehouses_by_distance(db, destination).await? {
+        let covers = wanted.iter().all(|(variant_id, quantity)| {
+            available
+                .get(&(*variant_id, warehouse.id))
+                .copied()
+                .unwrap_or(0)
+                >= *quantity
+        });
+        if covers {
+            return Ok(Some(warehouse));
+        }
+    }
+    Ok(None)
+}
+
+/// Próximo volume a despachar de um pedido
+#[derive(Debug, Clone)]
+pub struct PackagePlan {
+    /// Depósito de onde o volume sai; `None` sem reservas em depósito
+    /// (backorder, pedidos antigos)
+    pub warehouse: Option<warehouses::Model>,
+    pub lines: Vec<(order_items::Model, i32)>,
+}
+
+/// Escolhe o depósito do próximo volume pelas reservas do pedido: o que tem
+/// mais unidades ainda não despachadas entre `lines` (empate: o mais
+/// próximo do destino). Com `restrict`, o volume leva só o que foi
+/// reservado nesse depósito; o restante fica para os próximos volumes.
+pub async fn next_package<C: ConnectionTrait>(
+    db: &C,
+    order: &orders::Model,
+    lines: Vec<(order_items::Model, i32)>,
+    restrict: bool,
+) -> ModelResult<PackagePlan> {
+    let mut allocated = reserved_by_warehouse(db, order.id).await?;
+    if allocated.is_empty() {
+        return Ok(PackagePlan {
+            warehouse: None,
+            lines,
+        });
+    }
+
+    let destination = order_destination(db, order).await?;
+    let ranking = warehouse_ranking(db, destination.as_deref()).await?;
+    let mut scores: HashMap<i32, i32> = HashMap::new();
+    for ((variant_id, warehouse_id), units) in &allocated {
+        let wanted: i32 = lines
+            .iter()
+            .filter(|(item, _)| item.variant_id == Some(*variant_id))
+            .map(|(_, qty)| *qty)
+            .sum();
+        *scores.entry(*warehouse_id).or_default() += wanted.min(*units);
+    }
+    let Some((warehouse_id, _)) = scores
+        .into_iter()
+        .filter(|(_, score)| *score > 0)
+        .max_by_key(|(id, score)| {
+            (
+                *score,
+                std::cmp::Reverse(ranking.get(id).copied().unwrap_or(usize::MAX)),
+            )
+        })
+    else {
+        return Ok(PackagePlan {
+            warehouse: None,
+            lines,
+        });
+    };
+    let warehouse = warehouses::Entity::find_by_id(warehouse_id).one(db).await?;
+
+    let lines = if restrict {
+        lines
+            .into_iter()
+            .filter_map(|(item, qty)| {
+                let units = allocated.get_mut(&(item.variant_id?, warehouse_id))?;
+                let take = qty.min(*units);
+                *units -= take;
+                (take > 0).then_some((item, take))
+            })
+            .collect()
+    } else {
+        lines
+    };
+    Ok(PackagePlan { warehouse, lines })
+}
+
+/// Unidades reservadas do pedido por (variante, depósito), descontado o que
+/// já saiu de cada depósito em envios válidos
+async fn reserved_by_warehouse<C: ConnectionTrait>(
+    db: &C,
+    order_id: i32,
+) -> ModelResult<HashMap<(i32, i32), i32>> {
+    let reservations = stock_reservations::Entity::find()
+        .filter(stock_reservations::Column::OrderId.eq(order_id))
+        .filter(stock_reservations::Column::Status.is_in(["active", "committed"]))
+        .filter(stock_reservations::Column::StockId.is_not_null())
+        .all(db)
+        .await?;
+    if reservations.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let stock_warehouse: HashMap<i32, i32> = stocks::Entity::find()
+        .filter(
+            stocks::Column::Id.is_in(
+                reservations
+                    .iter()
+                    .filter_map(|r| r.stock_id)
+                    .collect::<Vec<_>>(),
+            ),
+        )
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| (s.id, s.warehouse_id))
+        .collect();
+
+    let mut allocated: HashMap<(i32, i32), i32> = HashMap::new();
+    for reservation in &reservations {
+        if let Some(warehouse_id) = reservation.stock_id.and_then(|id| stock_warehouse.get(&id)) {
+            *allocated
+                .entry((reservation.variant_id, *warehouse_id))
+                .or_default() += reservation.quantity;
+        }
+    }
+
+    let shipments: HashMap<i32, i32> = order_shippings::Entity::find()
+        .filter(order_shippings::Column::OrderId.eq(order_id))
+        .filter(order_shippings::Column::Status.is_not_in(VOID_STATUSES.iter().copied()))
+        .filter(order_shippings::Column::WarehouseId.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|s| Some((s.id, s.warehouse_id?)))
+        .collect();
+    if !shipments.is_empty() {
+        let shipped = order_shipping_items::Entity::find()
+            .filter(
+                order_shipping_items::Column::ShippingId
+                    .is_in(shipments.keys().copied().collect::<Vec<_>>()),
+            )
+            .find_also_related(order_items::Entity)
+            .all(db)
+            .await?;
+        for (line, item) in shipped {
+            let (Some(warehouse_id), Some(variant_id)) = (
+                shipments.get(&line.shipping_id),
+                item.and_then(|i| i.variant_id),
+            ) else {
+                continue;
+            };
+            if let Some(units) = allocated.get_mut(&(variant_id, *warehouse_id)) {
+                *units = (*units - line.quantity).max(0);
+            }
+        }
+    }
+    Ok(allocated)
+}
@@ -0,0 +1,340 @@
+//! Despacho de volumes do pedido
+//!
+//! [`create_shipment`] escolhe o depósito e os itens do volume, grava o envio
+//! e, para carriers com integração, compra a etiqueta no provider. Depois
+//! atualiza o fulfillment do pedido pelas quantidades já enviadas.
+
+use loco_rs::prelude::*;
+
+use crate::{
+    models::{
+        _entities::{order_items, warehouses},
+        order_shippings::{CreateShippingParams, Model as ShippingModel, LABEL_PURCHASING},
+        order_status::FulfillmentStatus,
+        order_status_history::StatusChange,
+        orders::Model as OrderModel,
+        product_variants::Model as ProductVariantModel,
+    },
+    services::routing,
+    shipping,
+};
+
+/// Volume despachado
+pub struct CreatedShipment {
+    pub shipping: ShippingModel,
+    /// Itens do pedido no volume, com a quantidade enviada
+    pub lines: Vec<(order_items::Model, i32)>,
+    /// Depósito de origem
+    pub warehouse: Option<warehouses::Model>,
+    /// Pedido com o fulfillment atualizado
+    pub order: OrderModel,
+}
+
+/// Resultado do despacho
+pub enum ShipmentOutcome {
+    Done(CreatedShipment),
+    /// Código de erro da API, mensagem e detalhes
+    Rejected(&'static str, String, Option<serde_json::Value>),
+}
+
+/// Monta os parâmetros de etiqueta a partir do pedido (destinatário, itens)
+/// e do volume informado — ou, sem ele, dos itens empacotados com o limite de
+/// peso do provider. A origem do frete é o CEP do depósito que despacha.
+/// Erros de validação vêm como `ModelError::Message`.
+async fn shipment_params(
+    db: &DatabaseConnection,
+    order: &OrderModel,
+    params: &CreateShippingParams,
+    lines: &[(order_items::Model, i32)],
+    warehouse: Option<&warehouses::Model>,
+    max_weight_grams: u32,
+) -> ModelResult<shipping::CreateShipmentParams> {
+    let service_code = params
+        .service_code
+        .clone()
+        .ok_or_else(|| ModelError::msg("service_code é obrigatório para este carrier"))?;
+
+    let mut sender =
+        shipping::ContactInfo::store_sender().map_err(|e| ModelError::Message(e.to_string()))?;
+    sender.postal_code =
+        routing::origin_postal_code(warehouse).map_err(|e| ModelError::Message(e.to_string()))?;
+    let recipient = order.shipping_contact(db).await?;
+
+    let packages = match &params.package {
+        Some(package) => vec![shipping::packing::Package {
+            box_name: None,
+            length_cm: package.length_cm,
+            width_cm: package.width_cm,
+            height_cm: package.height_cm,
+            weight_grams: package.weight_grams,
+            declared_value_cents: lines
+                .iter()
+                .map(|(item, qty)| item.unit_price * i64::from(*qty))
+                .sum(),
+        }],
+        None => {
+            let mut parcels = Vec::with_capacity(lines.len());
+            for (item, qty) in lines {
+                let variant_id = item
+                    .variant_id
+                    .ok_or_else(|| ModelError::msg("Item sem variante: informe package"))?;
+                parcels.push(
+                    ProductVariantModel::parcel_item(db, variant_id, *qty, item.unit_price).await?,
+                );
+            }
+            shipping::packing::pack(&parcels, &shipping::packing::catalog(), max_weight_grams)
+                .map_err(|e| ModelError::Message(e.to_string()))?
+        }
+    };
+    if packages.is_empty() {
+        return Err(ModelError::msg("Pedido sem itens para despachar"));
+    }
+
+    let items = lines
+        .iter()
+        .map(|(i, qty)| shipping::ShipmentItem {
+            name: if i.title.is_empty() {
+                i.sku.clone()
+            } else {
+                i.title.clone()
+            },
+            quantity: (*qty).max(0) as u32,
+            unit_price_cents: i.unit_price,
+        })
+        .collect();
+
+    Ok(shipping::CreateShipmentParams {
+        service_code,
+        order_number: order.order_number.clone(),
+        freight: shipping::FreightParams::from_packages(
+            &sender.postal_code,
+            &recipient.postal_code,
+            packages,
+        ),
+        sender,
+        recipient,
+        items,
+        provider_id: None,
+    })
+}
+
+/// Despacha um volume do pedido.
+///
+/// O envio é gravado antes da compra da etiqueta: se ela falhar, repetir a
+/// chamada retoma a mesma etiqueta. Um pedido pode ser despachado em vários
+/// volumes (`items`); o fulfillment passa a `partially_fulfilled` até que
+/// todos os itens tenham sido enviados.
+pub async fn create_shipment(
+    db: &DatabaseConnection,
+    order: &OrderModel,
+    params: &CreateShippingParams,
+    user_id: i32,
+) -> ModelResult<ShipmentOutcome> {
+    // Valida antes de comprar a etiqueta: pedido não pago ou cancelado não é enviado.
+    // Pedido já `fulfilled` só volta a despachar itens de envios falhos/devolvidos.
+    if order.fulfillment_status != FulfillmentStatus::Fulfilled.as_str()
+        && !FulfillmentStatus::allowed_for(order).contains(&FulfillmentStatus::Fulfilled)
+    {
+        return Ok(ShipmentOutcome::Rejected(
+            "INVALID_TRANSITION",
+            format!(
+                "Pedido com status '{}', pagamento '{}' e fulfillment '{}' não pode ser enviado",
+                order.status, order.payment_status, order.fulfillment_status
+            ),
+            Some(serde_json::json!({
+                "axis": "fulfillment_status",
+                "from": order.fulfillment_status,
+                "to": "fulfilled",
+                "allowed": FulfillmentStatus::allowed_for(order),
+            })),
+        ));
+    }
+
+    let provider = shipping::provider_for(&params.carrier);
+
+    // Etiqueta cuja compra falhou numa tentativa anterior: retoma o mesmo
+    // envio (itens e depósito já gravados) em vez de comprar outra
+    let unfinished = match &provider {
+        Some(provider) => {
+            ShippingModel::find_unfinished_label(db, order.id, provider.name()).await?
+        }
+        None => None,
+    };
+    let (shipping, lines, warehouse) = match unfinished {
+        Some(shipping) => {
+            let lines: Vec<_> = shipping
+                .items(db)
+                .await?
+                .into_iter()
+                .filter_map(|(line, item)| item.map(|item| (item, line.quantity)))
+                .collect();
+            let warehouse = match shipping.warehouse_id {
+                Some(id) => warehouses::Entity::find_by_id(id).one(db).await?,
+                None => None,
+            };
+            (shipping, lines, warehouse)
+        }
+        None => {
+            let lines = match ShippingModel::pending_lines(db, order.id, &params.items).await {
+                Ok(lines) => lines,
+                Err(ModelError::Message(msg)) => {
+                    return Ok(ShipmentOutcome::Rejected("INVALID_SHIPMENT", msg, None));
+                }
+                Err(e) => return Err(e),
+            };
+
+            // Depósito de origem: o informado ou o das reservas do pedido. Sem
+            // itens explícitos, o volume leva só o que foi reservado nesse depósito.
+            let (warehouse, lines) = match params.warehouse_pid {
+                Some(pid) => {
+                    let warehouse = warehouses::Entity::find()
+                        .filter(warehouses::Column::Pid.eq(pid))
+                        .filter(warehouses::Column::DeletedAt.is_null())
+                        .one(db)
+                        .await?;
+                    match warehouse {
+                        Some(warehouse) => (Some(warehouse), lines),
+                        None => {
+                            return Ok(ShipmentOutcome::Rejected(
+                                "INVALID_SHIPMENT",
+                                "Depósito não encontrado".to_string(),
+                                None,
+                            ));
+                        }
+                    }
+                }
+                None => {
+                    let plan =
+                        routing::next_package(db, order, lines, params.items.is_empty()).await?;
+                    (plan.warehouse, plan.lines)
+                }
+            };
+
+            // Com integração, o envio é gravado antes de pagar a etiqueta, já
+            // com o ID do provider: se a compra falhar, a próxima tentativa
+            // retoma a mesma etiqueta
+            let (provider_name, provider_data) = match &provider {
+                Some(provider) => {
+                    let provider_id = match shipment_params(
+                        db,
+                        order,
+                        params,
+                        &lines,
+                        warehouse.as_ref(),
+                        provider.max_package_weight_grams(),
+                    )
+                    .await
+                    {
+                        Ok(shipment) => provider.prepare_shipment(&shipment).await,
+                        Err(ModelError::Message(msg)) => {
+                            return Ok(ShipmentOutcome::Rejected("INVALID_SHIPMENT", msg, None));
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    let provider_id = match provider_id {
+                        Ok(provider_id) => provider_id,
+                        Err(e) => {
+                            tracing::error!(carrier = %params.carrier, error = %e, "Falha ao criar envio");
+                            return Ok(ShipmentOutcome::Rejected(
+                                "SHIPPING_PROVIDER_ERROR",
+                                e.to_string(),
+                                None,
+                            ));
+                        }
+                    };
+                    (
+                        Some(provider.name()),
+                        Some(serde_json::json!({
+                            "provider_id": provider_id,
+                            "label_status": LABEL_PURCHASING,
+                        })),
+                    )
+                }
+                None => (None, None),
+            };
+
+            let shipping = match ShippingModel::create(
+                db,
+                order.id,
+                params,
+                &lines,
+                warehouse.as_ref().map(|w| w.id),
+                provider_name,
+                provider_data,
+            )
+            .await
+            {
+                Ok(shipping) => shipping,
+                Err(ModelError::Message(msg)) => {
+                    return Ok(ShipmentOutcome::Rejected("INVALID_SHIPMENT", msg, None));
+                }
+                Err(e) => return Err(e),
+            };
+            (shipping, lines, warehouse)
+        }
+    };
+
+    let shipping = match &provider {
+        Some(provider) => {
+            let mut shipment = match shipment_params(
+                db,
+                order,
+                params,
+                &lines,
+                warehouse.as_ref(),
+                provider.max_package_weight_grams(),
+            )
+            .await
+            {
+                Ok(shipment) => shipment,
+                Err(ModelError::Message(msg)) => {
+                    ShippingModel::discard_unfinished(db, shipping.id).await?;
+                    return Ok(ShipmentOutcome::Rejected("INVALID_SHIPMENT", msg, None));
+                }
+                Err(e) => return Err(e),
+            };
+            shipment.provider_id = shipping
+                .provider_data
+                .get("provider_id")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+
+            match provider.create_shipment(shipment).await {
+                Ok(result) => ShippingModel::complete_label(db, shipping.id, &result).await?,
+                Err(e) => {
+                    tracing::error!(carrier = %params.carrier, error = %e, "Falha ao criar envio");
+                    // Sem ID no provider nada foi comprado: libera os itens
+                    ShippingModel::discard_unfinished(db, shipping.id).await?;
+                    return Ok(ShipmentOutcome::Rejected(
+                        "SHIPPING_PROVIDER_ERROR",
+                        e.to_string(),
+                        None,
+                    ));
+                }
+            }
+        }
+        None => shipping,
+    };
+
+    // fulfillment_status segue as quantidades enviadas por item; pedido já
+    // `fulfilled` (reenvio de volume falho) não volta a `partially_fulfilled`
+    let order = if order.fulfillment_status == FulfillmentStatus::Fulfilled.as_str() {
+        order.clone()
+    } else {
+        let fulfillment = ShippingModel::derived_fulfillment(db, order.id).await?;
+        OrderModel::update_fulfillment_status(
+            db,
+            order.id,
+            fulfillment,
+            &StatusChange::by_user(user_id, "painel", None),
+        )
+        .await?
+    };
+
+    Ok(ShipmentOutcome::Done(CreatedShipment {
+        shipping,
+        lines,
+        warehouse,
+        order,
+    }))
+}
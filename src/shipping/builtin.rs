@@ -0,0 +1,142 @@
+//! Providers internos (sem API externa)
+//!
+//! As tarifas vêm da tabela `shipping_rates`, cadastradas em
+//! `/api/admin/shipping-rates`, e a cotação funciona inteiramente offline:
+//!
+//! | Slug            | Tipo da tarifa  | Regra                                        |
+//! |-----------------|-----------------|----------------------------------------------|
+//! | `store_pickup`  | `pickup`        | retirada em um depósito (`warehouses`)       |
+//! | `flat_rate`     | `flat_rate`     | preço fixo                                   |
+//! | `free_shipping` | `free_shipping` | grátis a partir de `min_subtotal`            |
+//! | `table_rate`    | `table_rate`    | preço por faixa de CEP (e de peso)           |
+//!
+//! Todas as tarifas aceitam as mesmas condições opcionais: faixa de CEP,
+//! faixa de peso e subtotal mínimo (comparado com o valor declarado).
+//! Não há etiqueta nem rastreio: o envio é registrado como manual.
+
+use async_trait::async_trait;
+use loco_rs::prelude::*;
+
+use super::{
+    CreateShipmentParams, FreightOption, FreightParams, ShipmentResult, ShippingError,
+    ShippingProvider, TrackingInfo,
+};
+use crate::models::{_entities::warehouses, shipping_rates::Model as ShippingRateModel};
+
+/// Tarifas ativas de um tipo
+#[derive(Debug, Clone)]
+pub struct RateProvider {
+    slug: &'static str,
+    rates: Vec<(ShippingRateModel, Option<warehouses::Model>)>,
+}
+
+/// Slug do provider para o tipo de tarifa
+fn slug_for_kind(kind: &str) -> Option<&'static str> {
+    match kind {
+        "pickup" => Some("store_pickup"),
+        "flat_rate" => Some("flat_rate"),
+        "free_shipping" => Some("free_shipping"),
+        "table_rate" => Some("table_rate"),
+        _ => None,
+    }
+}
+
+/// Monta um provider por tipo com tarifas ativas
+pub async fn load(db: &DatabaseConnection) -> ModelResult<Vec<Box<dyn ShippingProvider>>> {
+    let rates = ShippingRateModel::list_active(db).await?;
+    let mut providers: Vec<RateProvider> = Vec::new();
+
+    for (rate, warehouse) in rates {
+        let Some(slug) = slug_for_kind(&rate.kind) else {
+            continue;
+        };
+        match providers.iter_mut().find(|p| p.slug == slug) {
+            Some(provider) => provider.rates.push((rate, warehouse)),
+            None => providers.push(RateProvider {
+                slug,
+                rates: vec![(rate, warehouse)],
+            }),
+        }
+    }
+
+    Ok(providers
+        .into_iter()
+        .map(|p| Box::new(p) as Box<dyn ShippingProvider>)
+        .collect())
+}
+
+impl RateProvider {
+    fn option_for(
+        &self,
+        rate: &ShippingRateModel,
+        warehouse: Option<&warehouses::Model>,
+    ) -> Option<FreightOption> {
+        let (carrier, service) = match self.slug {
+            "store_pickup" => {
+                // Depósito removido: a retirada deixa de ser oferecida
+                let warehouse = warehouse.filter(|w| w.deleted_at.is_none())?;
+                (
+                    "Retirada na loja".to_string(),
+                    format!("{} ({})", rate.name, warehouse.name),
+                )
+            }
+            _ => ("Entrega própria".to_string(), rate.name.clone()),
+        };
+        Some(FreightOption {
+            provider: self.slug.to_string(),
+            carrier,
+            service,
+            service_code: rate.pid.to_string(),
+            price_cents: rate.price,
+            delivery_days: rate.delivery_days.max(0) as u32,
+            currency: "BRL".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl ShippingProvider for RateProvider {
+    fn name(&self) -> &'static str {
+        self.slug
+    }
+
+    async fn calculate_freight(
+        &self,
+        params: FreightParams,
+    ) -> Result<Vec<FreightOption>, ShippingError> {
+        let options = self
+            .rates
+            .iter()
+            .filter(|(rate, _)| {
+                rate.applies(
+                    &params.destination_postal_code,
+                    params.weight_grams,
+                    params.declared_value_cents,
+                )
+            })
+            .filter_map(|(rate, warehouse)| self.option_for(rate, warehouse.as_ref()))
+            .collect();
+        Ok(options)
+    }
+
+    /// Sem etiqueta: devolve só um identificador para o registro do envio
+    async fn create_shipment(
+        &self,
+        params: CreateShipmentParams,
+    ) -> Result<ShipmentResult, ShippingError> {
+        Ok(ShipmentResult {
+            provider_id: format!("{}:{}", self.slug, params.order_number),
+            tracking_code: None,
+            tracking_url: None,
+            label_url: None,
+            raw_data: serde_json::json!({ "service_code": params.service_code }),
+        })
+    }
+
+    async fn track(&self, _tracking_code: &str) -> Result<TrackingInfo, ShippingError> {
+        Err(ShippingError::UnsupportedCarrier(format!(
+            "{} não tem rastreio",
+            self.slug
+        )))
+    }
+}
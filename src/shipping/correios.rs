@@ -0,0 +1,657 @@
+//! Integração com a API de contrato dos Correios (CWS)
+//!
+//! # Variáveis de ambiente
+//!
+//! | Variável                   | Descrição                                      | Exemplo                 |
+//! |----------------------------|------------------------------------------------|-------------------------|
+//! | `CORREIOS_USUARIO`         | Usuário do Meu Correios (CNPJ/login)           | `12345678000199`        |
+//! | `CORREIOS_CODIGO_ACESSO`   | Código de acesso à API (gerado no CWS)         | `aBcD...`               |
+//! | `CORREIOS_CARTAO_POSTAGEM` | Número do cartão de postagem do contrato       | `0067599079`            |
+//! | `CORREIOS_CONTRATO`        | Número do contrato (opcional, melhora o preço) | `9912208555`            |
+//! | `CORREIOS_DR`              | Diretoria regional do contrato (opcional)      | `20`                    |
+//! | `CORREIOS_SERVICOS`        | Serviços cotados, separados por vírgula        | `03220,03298`           |
+//! | `CORREIOS_SANDBOX`         | Usar homologação (`apihom`)                    | `true`                  |
+//! | `CORREIOS_BASE_URL`        | Sobrescreve a URL base (ex.: servidor stub)    | `http://127.0.0.1:8090` |
+//!
+//! # Endpoints usados
+//!
+//! - `POST /token/v1/autentica/cartaopostagem` — token (Basic usuário:código de acesso)
+//! - `GET  /preco/v1/nacional/{servico}` — preço
+//! - `GET  /prazo/v1/nacional/{servico}` — prazo
+//! - `POST /prepostagem/v1/prepostagens` — pré-postagem (gera o código do objeto)
+//! - `DELETE /prepostagem/v1/prepostagens/cancelar/{id}` — cancela a pré-postagem
+//! - `GET  /srorastro/v1/objetos/{codigo}` — rastreamento SRO
+//!
+//! Os testes exercitam esses endpoints contra um servidor stub local, com
+//! respostas gravadas em `tests/fixtures/correios/`.
+//!
+//! Documentação: <https://www.correios.com.br/atendimento/developers>
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use super::{
+    ContactInfo, CreateShipmentParams, FreightOption, FreightParams, ShipmentResult, ShippingError,
+    ShippingProvider, TrackingEvent, TrackingInfo,
+};
+
+const SANDBOX_BASE: &str = "https://apihom.correios.com.br";
+const PROD_BASE: &str = "https://api.correios.com.br";
+
+/// SEDEX contrato (03220) e PAC contrato (03298)
+const DEFAULT_SERVICES: &str = "03220,03298";
+
+/// Token em cache, compartilhado entre instâncias (`provider_for` cria uma
+/// instância por requisição). Chave: URL base + cartão de postagem.
+static TOKEN_CACHE: Lazy<Mutex<Option<CachedToken>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Clone)]
+struct CachedToken {
+    key: String,
+    token: String,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Credenciais do contrato
+#[derive(Debug, Clone)]
+pub struct CorreiosConfig {
+    pub usuario: String,
+    pub codigo_acesso: String,
+    pub cartao_postagem: String,
+    pub contrato: Option<String>,
+    pub dr: Option<String>,
+    pub servicos: Vec<String>,
+    pub base_url: String,
+}
+
+impl CorreiosConfig {
+    /// Lê a configuração de variáveis de ambiente. `None` se faltar credencial.
+    pub fn from_env() -> Option<Self> {
+        crate::env::load();
+        let sandbox = std::env::var("CORREIOS_SANDBOX")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(true);
+        let base_url = std::env::var("CORREIOS_BASE_URL")
+            .unwrap_or_else(|_| if sandbox { SANDBOX_BASE } else { PROD_BASE }.to_string());
+
+        Some(Self {
+            usuario: std::env::var("CORREIOS_USUARIO").ok()?,
+            codigo_acesso: std::env::var("CORREIOS_CODIGO_ACESSO").ok()?,
+            cartao_postagem: std::env::var("CORREIOS_CARTAO_POSTAGEM").ok()?,
+            contrato: std::env::var("CORREIOS_CONTRATO").ok(),
+            dr: std::env::var("CORREIOS_DR").ok(),
+            servicos: std::env::var("CORREIOS_SERVICOS")
+                .unwrap_or_else(|_| DEFAULT_SERVICES.to_string())
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+pub struct CorreiosApi {
+    config: CorreiosConfig,
+    client: reqwest::Client,
+}
+
+/// Resposta de `/token/v1/autentica/cartaopostagem`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenResponse {
+    token: String,
+    /// Ex.: "2024-05-03T10:00:00"
+    expira_em: Option<String>,
+}
+
+/// Resposta de `/preco/v1/nacional/{servico}`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrecoResponse {
+    co_produto: String,
+    /// Valor em reais com vírgula decimal ("25,50")
+    pc_final: String,
+    tx_erro: Option<String>,
+}
+
+/// Resposta de `/prazo/v1/nacional/{servico}`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrazoResponse {
+    prazo_entrega: Option<u32>,
+}
+
+/// Resposta de `/prepostagem/v1/prepostagens`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrePostagemResponse {
+    id: String,
+    codigo_objeto: Option<String>,
+}
+
+/// Resposta de `/srorastro/v1/objetos/{codigo}`
+#[derive(Debug, Deserialize)]
+struct SroResponse {
+    #[serde(default)]
+    objetos: Vec<SroObjeto>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SroObjeto {
+    cod_objeto: String,
+    #[serde(default)]
+    eventos: Vec<SroEvento>,
+    dt_prevista: Option<String>,
+    mensagem: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SroEvento {
+    codigo: String,
+    tipo: String,
+    dt_hr_criado: String,
+    descricao: String,
+    unidade: Option<SroUnidade>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SroUnidade {
+    endereco: Option<SroEndereco>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SroEndereco {
+    cidade: Option<String>,
+    uf: Option<String>,
+}
+
+/// "25,50" → 2550
+fn brl_to_cents(value: &str) -> Option<i64> {
+    let normalized = value.replace('.', "").replace(',', ".");
+    normalized
+        .parse::<f64>()
+        .ok()
+        .map(|v| (v * 100.0).round() as i64)
+}
+
+fn digits(value: &str) -> String {
+    value.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// Nome comercial dos serviços mais comuns
+fn service_name(code: &str) -> &'static str {
+    match code {
+        "03220" | "04162" | "04014" => "SEDEX",
+        "03298" | "04669" | "04510" => "PAC",
+        "04227" => "Mini Envios",
+        "03140" => "SEDEX 12",
+        "03158" => "SEDEX 10",
+        _ => "Correios",
+    }
+}
+
+/// Mapeia um evento SRO para o status de `order_shippings`.
+///
+/// Códigos: PO = postado, RO/DO = em trânsito, OEC = saiu para entrega,
+/// BDE/BDI/BDR = baixa (tipo 01 = entregue; demais tipos = não entregue),
+/// FC = devolução/retorno.
+fn map_event(codigo: &str, tipo: &str) -> &'static str {
+    match (codigo, tipo) {
+        ("PO", _) => "posted",
+        ("OEC", _) => "out_for_delivery",
+        ("BDE" | "BDI" | "BDR", "01") => "delivered",
+        ("BDE" | "BDI" | "BDR", _) => "failed",
+        ("FC", _) => "returned",
+        _ => "in_transit",
+    }
+}
+
+impl CorreiosApi {
+    pub fn new(config: CorreiosConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .user_agent("LocoFastStore/1.0")
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .expect("falha ao construir reqwest::Client");
+        Self { config, client }
+    }
+
+    fn cache_key(&self) -> String {
+        format!("{}|{}", self.config.base_url, self.config.cartao_postagem)
+    }
+
+    /// Obtém (ou reaproveita) o token de acesso do cartão de postagem
+    async fn token(&self) -> Result<String, ShippingError> {
+        let mut cache = TOKEN_CACHE.lock().await;
+        if let Some(cached) = cache.as_ref() {
+            // Renova com 5 minutos de folga
+            if cached.key == self.cache_key()
+                && cached.expires_at > chrono::Utc::now() + chrono::Duration::minutes(5)
+            {
+                return Ok(cached.token.clone());
+            }
+        }
+
+        let resp = self
+            .client
+            .post(format!(
+                "{}/token/v1/autentica/cartaopostagem",
+                self.config.base_url
+            ))
+            .basic_auth(&self.config.usuario, Some(&self.config.codigo_acesso))
+            .json(&serde_json::json!({ "numero": self.config.cartao_postagem }))
+            .send()
+            .await
+            .map_err(|e| ShippingError::Network(e.to_string()))?;
+
+        let data: TokenResponse = Self::decode(resp, "token").await?;
+        let expires_at = data
+            .expira_em
+            .as_deref()
+            .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").ok())
+            // A API devolve horário de Brasília sem offset
+            .map(|dt| dt.and_utc() + chrono::Duration::hours(3))
+            .unwrap_or_else(|| chrono::Utc::now() + chrono::Duration::hours(1));
+
+        *cache = Some(CachedToken {
+            key: self.cache_key(),
+            token: data.token.clone(),
+            expires_at,
+        });
+        Ok(data.token)
+    }
+
+    /// Decodifica a resposta, mapeando erros HTTP para `ShippingError`.
+    ///
+    /// Formato de erro da API:
+    /// ```json
+    /// { "msgs": ["CEP de destino inválido"], "date": "...", "method": "GET", "path": "..." }
+    /// ```
+    async fn decode<T: serde::de::DeserializeOwned>(
+        resp: reqwest::Response,
+        context: &str,
+    ) -> Result<T, ShippingError> {
+        let status = resp.status();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| ShippingError::Network(e.to_string()))?;
+
+        if !status.is_success() {
+            let parsed: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+            let message = parsed["msgs"]
+                .as_array()
+                .map(|msgs| {
+                    msgs.iter()
+                        .filter_map(|m| m.as_str())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                })
+                .filter(|m| !m.is_empty())
+                .unwrap_or(text);
+            return Err(match status.as_u16() {
+                401 | 403 => ShippingError::Unauthorized(message),
+                400 | 404 | 422 => ShippingError::Rejected(message),
+                code => ShippingError::Network(format!("HTTP {}: {}", code, message)),
+            });
+        }
+
+        serde_json::from_str(&text).map_err(|e| ShippingError::Parse(format!("{}: {}", context, e)))
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ShippingError> {
+        let token = self.token().await?;
+        let resp = self
+            .client
+            .get(format!("{}{}", self.config.base_url, path))
+            .bearer_auth(token)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| ShippingError::Network(e.to_string()))?;
+        Self::decode(resp, path).await
+    }
+
+    /// Cria uma pré-postagem, devolvendo a resposta decodificada e a bruta
+    async fn create_prepostagem(
+        &self,
+        body: &serde_json::Value,
+    ) -> Result<(PrePostagemResponse, serde_json::Value), ShippingError> {
+        let token = self.token().await?;
+        let resp = self
+            .client
+            .post(format!(
+                "{}/prepostagem/v1/prepostagens",
+                self.config.base_url
+            ))
+            .bearer_auth(token)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| ShippingError::Network(e.to_string()))?;
+        let raw: serde_json::Value = Self::decode(resp, "prepostagem").await?;
+        let item: PrePostagemResponse = serde_json::from_value(raw.clone())
+            .map_err(|e| ShippingError::Parse(format!("prepostagem: {}", e)))?;
+        Ok((item, raw))
+    }
+
+    /// Cancela uma pré-postagem ainda não postada
+    async fn cancel_prepostagem(&self, id: &str) -> Result<(), ShippingError> {
+        let token = self.token().await?;
+        let resp = self
+            .client
+            .delete(format!(
+                "{}/prepostagem/v1/prepostagens/cancelar/{}",
+                self.config.base_url, id
+            ))
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| ShippingError::Network(e.to_string()))?;
+        Self::decode::<serde_json::Value>(resp, "cancelar prepostagem").await?;
+        Ok(())
+    }
+
+    /// Cota preço e prazo de um serviço.
+    ///
+    /// A API de preço recebe um objeto por vez: cada volume é cotado
+    /// separadamente e os preços são somados.
+    async fn quote_service(
+        &self,
+        service: &str,
+        params: &FreightParams,
+    ) -> Result<FreightOption, ShippingError> {
+        let origin = digits(&params.origin_postal_code);
+        let destination = digits(&params.destination_postal_code);
+
+        let mut total_cents = 0;
+        let mut co_produto = service.to_string();
+        for volume in params.volumes() {
+            let mut preco_query = vec![
+                ("cepOrigem", origin.clone()),
+                ("cepDestino", destination.clone()),
+                ("psObjeto", volume.weight_grams.to_string()),
+                ("tpObjeto", "2".to_string()), // 2 = pacote
+                ("comprimento", volume.length_cm.to_string()),
+                ("largura", volume.width_cm.to_string()),
+                ("altura", volume.height_cm.to_string()),
+            ];
+            if volume.declared_value_cents > 0 {
+                preco_query.push(("servicosAdicionais", "019".to_string())); // valor declarado
+                preco_query.push((
+                    "vlDeclarado",
+                    format!("{:.2}", volume.declared_value_cents as f64 / 100.0),
+                ));
+            }
+            if let Some(contrato) = &self.config.contrato {
+                preco_query.push(("nuContrato", contrato.clone()));
+            }
+            if let Some(dr) = &self.config.dr {
+                preco_query.push(("nuDR", dr.clone()));
+            }
+
+            let preco: PrecoResponse = self
+                .get(&format!("/preco/v1/nacional/{}", service), &preco_query)
+                .await?;
+            if let Some(erro) = preco.tx_erro.filter(|e| !e.is_empty()) {
+                return Err(ShippingError::Rejected(erro));
+            }
+            total_cents += brl_to_cents(&preco.pc_final).ok_or_else(|| {
+                ShippingError::Parse(format!("pcFinal inválido: {}", preco.pc_final))
+            })?;
+            co_produto = preco.co_produto;
+        }
+
+        let prazo: PrazoResponse = self
+            .get(
+                &format!("/prazo/v1/nacional/{}", service),
+                &[("cepOrigem", origin), ("cepDestino", destination)],
+            )
+            .await?;
+
+        Ok(FreightOption {
+            provider: "correios_api".to_string(),
+            carrier: "Correios".to_string(),
+            service: service_name(&co_produto).to_string(),
+            price_cents: total_cents,
+            service_code: co_produto,
+            delivery_days: prazo.prazo_entrega.unwrap_or(0),
+            currency: "BRL".to_string(),
+        })
+    }
+
+    /// Cancela as pré-postagens dos volumes já criados quando um volume
+    /// seguinte falha. As que não forem canceladas são acrescentadas ao erro,
+    /// para serem tratadas no Pré-Postagem Web.
+    async fn rollback(
+        &self,
+        created: &[PrePostagemResponse],
+        error: ShippingError,
+    ) -> ShippingError {
+        let mut orphaned = vec![];
+        for item in created {
+            if let Err(e) = self.cancel_prepostagem(&item.id).await {
+                tracing::error!(prepostagem = %item.id, error = %e, "Correios: pré-postagem não cancelada");
+                orphaned.push(item.id.clone());
+            }
+        }
+        if orphaned.is_empty() {
+            return error;
+        }
+        let suffix = format!(" (pré-postagens não canceladas: {})", orphaned.join(","));
+        match error {
+            ShippingError::NotConfigured(m) => ShippingError::NotConfigured(m + &suffix),
+            ShippingError::Network(m) => ShippingError::Network(m + &suffix),
+            ShippingError::Parse(m) => ShippingError::Parse(m + &suffix),
+            ShippingError::UnsupportedCarrier(m) => ShippingError::UnsupportedCarrier(m + &suffix),
+            ShippingError::Unauthorized(m) => ShippingError::Unauthorized(m + &suffix),
+            ShippingError::Rejected(m) => ShippingError::Rejected(m + &suffix),
+        }
+    }
+
+    /// Monta remetente/destinatário no formato da pré-postagem
+    fn contact_json(contact: &ContactInfo) -> serde_json::Value {
+        let phone = contact.phone.as_deref().map(digits).unwrap_or_default();
+        let (ddd, number) = if phone.len() > 9 {
+            phone.split_at(2)
+        } else {
+            ("", phone.as_str())
+        };
+        serde_json::json!({
+            "nome": contact.name,
+            "email": contact.email,
+            "dddCelular": ddd,
+            "celular": number,
+            "cpfCnpj": contact.document.as_deref().map(digits),
+            "endereco": {
+                "cep": digits(&contact.postal_code),
+                "logradouro": contact.address_line1,
+                "numero": contact.number.as_deref().unwrap_or("S/N"),
+                "complemento": contact.address_line2,
+                "bairro": contact.district.as_deref().unwrap_or(""),
+                "cidade": contact.city,
+                "uf": contact.state,
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl ShippingProvider for CorreiosApi {
+    fn name(&self) -> &'static str {
+        "correios_api"
+    }
+
+    /// Cota todos os serviços configurados em `CORREIOS_SERVICOS`.
+    ///
+    /// Serviços que falham (ex.: trecho não atendido) são ignorados; se todos
+    /// falharem, o erro do primeiro é devolvido.
+    async fn calculate_freight(
+        &self,
+        params: FreightParams,
+    ) -> Result<Vec<FreightOption>, ShippingError> {
+        let mut options = Vec::new();
+        let mut first_error = None;
+
+        for service in &self.config.servicos {
+            match self.quote_service(service, &params).await {
+                Ok(option) => options.push(option),
+                Err(e) => {
+                    tracing::warn!(service = %service, error = %e, "Correios: serviço não cotado");
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        match (options.is_empty(), first_error) {
+            (true, Some(e)) => Err(e),
+            _ => Ok(options),
+        }
+    }
+
+    /// Cria uma pré-postagem por volume e devolve o código do objeto
+    /// (rastreio) do primeiro; os demais ficam em `raw_data.prepostagens`.
+    ///
+    /// A etiqueta em PDF é gerada de forma assíncrona pelos Correios e deve
+    /// ser impressa pelo Pré-Postagem Web; `label_url` fica vazio.
+    ///
+    /// Se um volume falhar, as pré-postagens dos volumes anteriores são
+    /// canceladas; as que não puderem ser canceladas vão na mensagem de erro.
+    async fn create_shipment(
+        &self,
+        params: CreateShipmentParams,
+    ) -> Result<ShipmentResult, ShippingError> {
+        let declaracao: Vec<serde_json::Value> = params
+            .items
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "conteudo": item.name,
+                    "quantidade": item.quantity.to_string(),
+                    "valor": format!("{:.2}", item.unit_price_cents as f64 / 100.0),
+                })
+            })
+            .collect();
+
+        let volumes = params.freight.volumes();
+        let total = volumes.len();
+        let mut created = Vec::with_capacity(total);
+        let mut raws = Vec::with_capacity(total);
+
+        for (index, volume) in volumes.iter().enumerate() {
+            let observacao = if total > 1 {
+                format!("{} ({}/{})", params.order_number, index + 1, total)
+            } else {
+                params.order_number.clone()
+            };
+            let body = serde_json::json!({
+                "idCorreios": self.config.usuario,
+                "remetente": Self::contact_json(&params.sender),
+                "destinatario": Self::contact_json(&params.recipient),
+                "codigoServico": params.service_code,
+                "numeroCartaoPostagem": self.config.cartao_postagem,
+                "observacao": observacao,
+                "pesoInformado": volume.weight_grams.to_string(),
+                "codigoFormatoObjetoInformado": "2",
+                "alturaInformada": volume.height_cm.to_string(),
+                "larguraInformada": volume.width_cm.to_string(),
+                "comprimentoInformado": volume.length_cm.to_string(),
+                "cienteObjetoNaoProibido": "1",
+                // A declaração de conteúdo acompanha o primeiro volume
+                "itensDeclaracaoConteudo": if index == 0 { declaracao.clone() } else { Vec::new() },
+            });
+
+            match self.create_prepostagem(&body).await {
+                Ok((item, raw)) => {
+                    created.push(item);
+                    raws.push(raw);
+                }
+                Err(e) => return Err(self.rollback(&created, e).await),
+            }
+        }
+
+        let first = created
+            .first()
+            .ok_or_else(|| ShippingError::Rejected("envio sem volumes".into()))?;
+
+        Ok(ShipmentResult {
+            provider_id: created
+                .iter()
+                .map(|c| c.id.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            tracking_url: first.codigo_objeto.as_ref().map(|code| {
+                format!(
+                    "https://rastreamento.correios.com.br/app/index.php?objeto={}",
+                    code
+                )
+            }),
+            tracking_code: first.codigo_objeto.clone(),
+            label_url: None,
+            raw_data: if total == 1 {
+                raws.remove(0)
+            } else {
+                serde_json::json!({ "prepostagens": raws })
+            },
+        })
+    }
+
+    /// Rastreia o objeto pelo SRO (`resultado=T` traz todos os eventos).
+    async fn track(&self, tracking_code: &str) -> Result<TrackingInfo, ShippingError> {
+        let data: SroResponse = self
+            .get(
+                &format!("/srorastro/v1/objetos/{}", tracking_code),
+                &[("resultado", "T".to_string())],
+            )
+            .await?;
+
+        let objeto = data.objetos.into_iter().next().ok_or_else(|| {
+            ShippingError::Rejected(format!("objeto não encontrado: {}", tracking_code))
+        })?;
+        if objeto.eventos.is_empty() {
+            if let Some(msg) = objeto.mensagem {
+                return Err(ShippingError::Rejected(msg));
+            }
+        }
+
+        // SRO devolve o evento mais recente primeiro
+        let current_status = objeto
+            .eventos
+            .first()
+            .map_or("pending", |e| map_event(&e.codigo, &e.tipo))
+            .to_string();
+
+        let events = objeto
+            .eventos
+            .into_iter()
+            .map(|e| TrackingEvent {
+                status: map_event(&e.codigo, &e.tipo).to_string(),
+                timestamp: e.dt_hr_criado,
+                description: e.descricao,
+                location: e.unidade.and_then(|u| u.endereco).map(|end| {
+                    format!(
+                        "{}/{}",
+                        end.cidade.unwrap_or_default(),
+                        end.uf.unwrap_or_default()
+                    )
+                }),
+            })
+            .collect();
+
+        Ok(TrackingInfo {
+            tracking_code: objeto.cod_objeto,
+            current_status,
+            events,
+            estimated_delivery: objeto.dt_prevista,
+        })
+    }
+}
@@ -1,13 +1,17 @@
-//! Integração com MelhorEnvio
+//! Integração com MelhorEnvio (API v2)
 //!
-//! Status: **stub** — estrutura pronta para implementação completa.
+//! # Variáveis de ambiente
 //!
-//! # Variáveis de ambiente necessárias
+//! | Variável                 | Descrição                                  | Exemplo                  |
+//! |--------------------------|--------------------------------------------|--------------------------|
+//! | `MELHOR_ENVIO_TOKEN`     | Token OAuth2 (Bearer) da conta             | `eyJ0eXAiOi...`          |
+//! | `MELHOR_ENVIO_SANDBOX`   | Usar ambiente de testes (`true`)           | `true`                   |
+//! | `MELHOR_ENVIO_BASE_URL`  | Sobrescreve a URL base (ex.: servidor mock)| `http://127.0.0.1:8089`  |
 //!
-//! | Variável                | Descrição                           | Exemplo                        |
-//! |-------------------------|-------------------------------------|--------------------------------|
-//! | `MELHOR_ENVIO_TOKEN`    | Token OAuth2 (Bearer) da conta      | `eyJ0eXAiOi...`               |
-//! | `MELHOR_ENVIO_SANDBOX`  | Usar ambiente de testes (`true`)    | `true`                         |
+//! Com `MELHOR_ENVIO_BASE_URL` o provider pode ser exercitado contra um
+//! servidor HTTP local que devolva respostas gravadas da API (os formatos
+//! esperados estão documentados em cada método). As respostas usadas nos
+//! testes ficam em `tests/fixtures/melhor_envio/`.
 //!
 //! # Documentação oficial
 //!
@@ -15,56 +19,251 @@
 //! - Sandbox: <https://sandbox.melhorenvio.com.br>
 //! - Produção: <https://melhorenvio.com.br>
 //!
-//! # Como completar a implementação
+//! # Fluxo de etiqueta
 //!
-//! 1. Obtenha um token OAuth2 em <https://melhorenvio.com.br/painel/gerenciar/tokens>
-//! 2. Implemente `calculate_freight` chamando `POST /api/v2/me/shipment/calculate`
-//! 3. Implemente `create_shipment` chamando `POST /api/v2/me/cart` + `POST /api/v2/me/shipment/checkout`
-//! 4. Implemente `track` chamando `GET /api/v2/me/shipment/tracking`
+//! 1. `POST /me/cart` — adiciona o envio ao carrinho do MelhorEnvio
+//!    ([`ShippingProvider::prepare_shipment`]; o ID é gravado no envio)
+//! 2. `GET /me/orders/{id}` — etapas já concluídas (retomada após falha)
+//! 3. `POST /me/shipment/checkout` — paga com o saldo da conta
+//! 4. `POST /me/shipment/generate` — gera a etiqueta
+//! 5. `POST /me/shipment/print` — obtém a URL de impressão
+//!
+//! Checkout e geração só acontecem se ainda não constarem no envio: repetir
+//! o fluxo com o mesmo ID nunca paga uma segunda etiqueta.
+
+use std::str::FromStr;
 
 use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Deserialize;
 
 use super::{
     CreateShipmentParams, FreightOption, FreightParams, ShipmentResult, ShippingError,
-    ShippingProvider, TrackingInfo,
+    ShippingProvider, TrackingEvent, TrackingInfo,
 };
 
 const SANDBOX_BASE: &str = "https://sandbox.melhorenvio.com.br/api/v2";
 const PROD_BASE: &str = "https://melhorenvio.com.br/api/v2";
 
+/// Serviços cotados: Correios (PAC, SEDEX, Mini) + Jadlog (.Package, .Com)
+const QUOTED_SERVICES: &str = "1,2,3,4,17";
+
 pub struct MelhorEnvio {
-    token: String,
     base_url: String,
+    /// Cliente com o header de autorização, criado uma vez por provider
+    client: reqwest::Client,
+}
+
+/// Item da resposta de `POST /me/shipment/calculate`
+#[derive(Debug, Deserialize)]
+struct QuoteItem {
+    id: u64,
+    name: String,
+    /// A API devolve o preço como string ("23.50"); em alguns casos, número
+    price: Option<serde_json::Value>,
+    custom_price: Option<serde_json::Value>,
+    delivery_time: Option<u32>,
+    custom_delivery_time: Option<u32>,
+    company: Option<QuoteCompany>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteCompany {
+    name: String,
+}
+
+/// Resposta de `POST /me/cart`
+#[derive(Debug, Deserialize)]
+struct CartOrder {
+    id: String,
+}
+
+/// Status de um envio em `POST /me/shipment/tracking` e `GET /me/orders/{id}`
+#[derive(Debug, Deserialize)]
+struct TrackingOrder {
+    id: String,
+    status: String,
+    protocol: Option<String>,
+    tracking: Option<String>,
+    melhorenvio_tracking: Option<String>,
+    created_at: Option<String>,
+    paid_at: Option<String>,
+    generated_at: Option<String>,
+    posted_at: Option<String>,
+    delivered_at: Option<String>,
+    canceled_at: Option<String>,
+    expired_at: Option<String>,
+}
+
+/// Converte o valor monetário (string ou número, em reais) para centavos
+fn money_to_cents(value: &serde_json::Value) -> Option<i64> {
+    let reais = match value {
+        serde_json::Value::String(s) => Decimal::from_str(s.trim()).ok()?,
+        serde_json::Value::Number(n) => Decimal::from_str(&n.to_string()).ok()?,
+        _ => return None,
+    };
+    (reais * Decimal::ONE_HUNDRED).round().to_i64()
+}
+
+/// Remove tudo que não for dígito (CEP, CPF/CNPJ, telefone)
+fn digits(value: &str) -> String {
+    value.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// Mapeia o status do MelhorEnvio para o status de `order_shippings`
+fn map_status(status: &str) -> &'static str {
+    match status {
+        "posted" => "in_transit",
+        "delivered" => "delivered",
+        "undelivered" | "canceled" | "expired" => "failed",
+        // pending | released (etiqueta paga/gerada, ainda não postada)
+        _ => "pending",
+    }
 }
 
 impl MelhorEnvio {
-    pub fn new(token: String, sandbox: bool) -> Self {
-        Self {
-            token,
-            base_url: if sandbox { SANDBOX_BASE } else { PROD_BASE }.to_string(),
-        }
+    pub fn new(token: String, sandbox: bool) -> Result<Self, ShippingError> {
+        let base_url = if sandbox { SANDBOX_BASE } else { PROD_BASE };
+        Self::with_base_url(token, base_url.to_string())
+    }
+
+    /// Usa uma URL base arbitrária (ex.: servidor mock local)
+    pub fn with_base_url(token: String, base_url: String) -> Result<Self, ShippingError> {
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Self::build_client(&token)?,
+        })
     }
 
     /// Constrói cliente HTTP com header de autorização
-    fn client(&self) -> reqwest::Client {
-        let mut headers = reqwest::header::HeaderMap::new();
+    fn build_client(token: &str) -> Result<reqwest::Client, ShippingError> {
+        let authorization = HeaderValue::from_str(&format!("Bearer {token}")).map_err(|_| {
+            ShippingError::NotConfigured("MELHOR_ENVIO_TOKEN contém caracteres inválidos".into())
+        })?;
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, authorization);
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
         headers.insert(
-            reqwest::header::AUTHORIZATION,
-            format!("Bearer {}", self.token).parse().unwrap(),
-        );
-        headers.insert(
-            reqwest::header::CONTENT_TYPE,
-            "application/json".parse().unwrap(),
-        );
-        headers.insert(
-            reqwest::header::HeaderName::from_static("user-agent"),
-            "LocoFastStore/1.0 (contato@example.com)".parse().unwrap(),
+            USER_AGENT,
+            HeaderValue::from_static("LocoFastStore/1.0 (contato@example.com)"),
         );
         reqwest::Client::builder()
             .default_headers(headers)
             .timeout(std::time::Duration::from_secs(30))
             .build()
-            .expect("falha ao construir reqwest::Client")
+            .map_err(|e| ShippingError::NotConfigured(e.to_string()))
+    }
+
+    /// POST JSON e devolve o corpo já decodificado, mapeando erros HTTP
+    async fn post<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<T, ShippingError> {
+        let request = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(body);
+        Self::send(request, path).await
+    }
+
+    /// GET e devolve o corpo já decodificado, mapeando erros HTTP
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, ShippingError> {
+        let request = self.client.get(format!("{}{}", self.base_url, path));
+        Self::send(request, path).await
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        request: reqwest::RequestBuilder,
+        path: &str,
+    ) -> Result<T, ShippingError> {
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| ShippingError::Network(e.to_string()))?;
+
+        let status = resp.status();
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| ShippingError::Network(e.to_string()))?;
+
+        if !status.is_success() {
+            return Err(Self::map_error(status, &text));
+        }
+
+        serde_json::from_str(&text).map_err(|e| ShippingError::Parse(format!("{}: {}", path, e)))
+    }
+
+    /// Traduz uma resposta de erro da API para `ShippingError`.
+    ///
+    /// Formato de erro da API:
+    /// ```json
+    /// { "message": "The given data was invalid.",
+    ///   "errors": { "to.postal_code": ["O CEP de destino é inválido."] } }
+    /// ```
+    fn map_error(status: reqwest::StatusCode, body: &str) -> ShippingError {
+        let parsed: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+        let mut message = parsed["message"]
+            .as_str()
+            .or_else(|| parsed["error"].as_str())
+            .unwrap_or(body)
+            .to_string();
+        if let Some(errors) = parsed["errors"].as_object() {
+            let details: Vec<String> = errors
+                .values()
+                .filter_map(|v| v.as_array())
+                .flatten()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect();
+            if !details.is_empty() {
+                message = format!("{} ({})", message, details.join("; "));
+            }
+        }
+
+        match status.as_u16() {
+            401 | 403 => ShippingError::Unauthorized(message),
+            400 | 404 | 409 | 422 => ShippingError::Rejected(message),
+            code => ShippingError::Network(format!("HTTP {}: {}", code, message)),
+        }
+    }
+
+    /// Volumes no formato da API (peso em kg)
+    fn volumes_json(params: &FreightParams) -> serde_json::Value {
+        params
+            .volumes()
+            .iter()
+            .map(|v| {
+                serde_json::json!({
+                    "height": v.height_cm,
+                    "width": v.width_cm,
+                    "length": v.length_cm,
+                    "weight": v.weight_grams as f64 / 1000.0,
+                    "insurance_value": v.declared_value_cents as f64 / 100.0,
+                })
+            })
+            .collect()
+    }
+
+    /// Monta o bloco `from`/`to` esperado pelo carrinho do MelhorEnvio
+    fn contact_json(contact: &super::ContactInfo) -> serde_json::Value {
+        serde_json::json!({
+            "name": contact.name,
+            "phone": contact.phone.as_deref().map(digits),
+            "email": contact.email,
+            "document": contact.document.as_deref().map(digits),
+            "address": contact.address_line1,
+            "complement": contact.address_line2,
+            "number": contact.number.as_deref().unwrap_or("S/N"),
+            "district": contact.district.as_deref().unwrap_or(""),
+            "city": contact.city,
+            "state_abbr": contact.state,
+            "country_id": contact.country,
+            "postal_code": digits(&contact.postal_code),
+        })
     }
 }
 
@@ -76,71 +275,56 @@ impl ShippingProvider for MelhorEnvio {
 
     /// Calcula fretes disponíveis.
     ///
-    /// Endpoint: `POST /api/v2/me/shipment/calculate`
+    /// Endpoint: `POST /me/shipment/calculate`, com um item em `volumes` por
+    /// caixa do empacotamento (o preço devolvido já soma todos os volumes).
     ///
-    /// Corpo esperado pela API:
+    /// Resposta (array, um item por serviço):
     /// ```json
-    /// {
-    ///   "from": { "postal_code": "01310100" },
-    ///   "to":   { "postal_code": "30130010" },
-    ///   "package": {
-    ///     "height": 10, "width": 15, "length": 20, "weight": 0.3
-    ///   },
-    ///   "options": { "insurance_value": 50.00 }
-    /// }
+    /// [{ "id": 1, "name": "PAC", "price": "23.50", "custom_price": "23.50",
+    ///    "delivery_time": 7, "custom_delivery_time": 7,
+    ///    "company": { "id": 1, "name": "Correios" } },
+    ///  { "id": 3, "name": ".Package", "error": "Transportadora não atende este trecho." }]
     /// ```
     async fn calculate_freight(
         &self,
         params: FreightParams,
     ) -> Result<Vec<FreightOption>, ShippingError> {
         let body = serde_json::json!({
-            "from": { "postal_code": params.origin_postal_code },
-            "to":   { "postal_code": params.destination_postal_code },
-            "package": {
-                "height": params.height_cm,
-                "width":  params.width_cm,
-                "length": params.length_cm,
-                "weight": params.weight_grams as f64 / 1000.0
-            },
+            "from": { "postal_code": digits(&params.origin_postal_code) },
+            "to":   { "postal_code": digits(&params.destination_postal_code) },
+            "volumes": Self::volumes_json(&params),
             "options": {
-                "insurance_value": params.declared_value_cents as f64 / 100.0
+                "insurance_value": params.declared_value_cents as f64 / 100.0,
+                "receipt": false,
+                "own_hand": false
             },
-            "services": "1,2,3,4,7,8" // Correios + Jadlog
+            "services": QUOTED_SERVICES
         });
 
-        let resp = self
-            .client()
-            .post(format!("{}/me/shipment/calculate", self.base_url))
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ShippingError::Network(e.to_string()))?;
-
-        if !resp.status().is_success() {
-            let msg = resp.text().await.unwrap_or_default();
-            return Err(ShippingError::Network(msg));
-        }
-
-        let data: serde_json::Value = resp
-            .json()
-            .await
-            .map_err(|e| ShippingError::Parse(e.to_string()))?;
+        let items: Vec<QuoteItem> = self.post("/me/shipment/calculate", &body).await?;
 
-        let options = data
-            .as_array()
-            .ok_or_else(|| ShippingError::Parse("resposta não é array".into()))?
-            .iter()
+        let options = items
+            .into_iter()
             .filter_map(|item| {
                 // Ignora serviços com erro (ex.: pacote fora do limite)
-                if item.get("error").is_some() {
+                if item.error.is_some() {
                     return None;
                 }
+                let price = item
+                    .custom_price
+                    .as_ref()
+                    .or(item.price.as_ref())
+                    .and_then(money_to_cents)?;
                 Some(FreightOption {
-                    carrier: item["company"]["name"].as_str().unwrap_or("").to_string(),
-                    service: item["name"].as_str().unwrap_or("").to_string(),
-                    service_code: item["id"].as_u64().unwrap_or(0).to_string(),
-                    price_cents: (item["price"].as_f64().unwrap_or(0.0) * 100.0) as i64,
-                    delivery_days: item["delivery_time"].as_u64().unwrap_or(0) as u32,
+                    provider: "melhor_envio".to_string(),
+                    carrier: item.company.map(|c| c.name).unwrap_or_default(),
+                    service: item.name,
+                    service_code: item.id.to_string(),
+                    price_cents: price,
+                    delivery_days: item
+                        .custom_delivery_time
+                        .or(item.delivery_time)
+                        .unwrap_or(0),
                     currency: "BRL".to_string(),
                 })
             })
@@ -149,38 +333,179 @@ impl ShippingProvider for MelhorEnvio {
         Ok(options)
     }
 
-    /// Cria etiqueta de envio.
+    /// Adiciona o envio ao carrinho (ainda sem pagar).
     ///
-    /// Fluxo MelhorEnvio:
-    /// 1. `POST /api/v2/me/cart`  → adiciona ao carrinho ME
-    /// 2. `POST /api/v2/me/shipment/checkout` → confirma pagamento (saldo ME)
-    /// 3. `POST /api/v2/me/shipment/generate` → gera etiqueta
+    /// Endpoint: `POST /me/cart` →
+    /// `{ "id": "9a1c…", "protocol": "ORD-2024…", "status": "pending" }`
+    async fn prepare_shipment(
+        &self,
+        params: &CreateShipmentParams,
+    ) -> Result<Option<String>, ShippingError> {
+        let service: u64 = params.service_code.parse().map_err(|_| {
+            ShippingError::Rejected(format!("service_code inválido: {}", params.service_code))
+        })?;
+
+        let products: Vec<serde_json::Value> = params
+            .items
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "name": item.name,
+                    "quantity": item.quantity,
+                    "unitary_value": item.unit_price_cents as f64 / 100.0,
+                })
+            })
+            .collect();
+
+        let cart_body = serde_json::json!({
+            "service": service,
+            "from": Self::contact_json(&params.sender),
+            "to": Self::contact_json(&params.recipient),
+            "products": products,
+            "volumes": Self::volumes_json(&params.freight),
+            "options": {
+                "insurance_value": params.freight.declared_value_cents as f64 / 100.0,
+                "receipt": false,
+                "own_hand": false,
+                "reverse": false,
+                "non_commercial": true,
+                "platform": "LocoFastStore",
+                "tags": [{ "tag": params.order_number }]
+            }
+        });
+        let cart: CartOrder = self.post("/me/cart", &cart_body).await?;
+        Ok(Some(cart.id))
+    }
+
+    /// Paga e gera a etiqueta do envio no carrinho (`params.provider_id`;
+    /// sem ele, adiciona ao carrinho antes).
+    ///
+    /// 1. `GET /me/orders/{id}` → `{ "id": "9a1c…", "status": "pending", "paid_at": null, … }`
+    /// 2. `POST /me/shipment/checkout` → `{ "purchase": { "id": "…", "status": "paid" } }`
+    /// 3. `POST /me/shipment/generate` → `{ "9a1c…": { "status": true, "message": "…" } }`
+    /// 4. `POST /me/shipment/print` → `{ "url": "https://…/imprimir/…" }`
     ///
-    /// TODO: implementar cada passo chamando os endpoints acima.
+    /// Etapas que já constam no envio (`paid_at`, `generated_at`) são
+    /// puladas. O `provider_id` retornado é o ID do envio no MelhorEnvio,
+    /// usado depois em [`ShippingProvider::track`].
     async fn create_shipment(
         &self,
-        _params: CreateShipmentParams,
+        params: CreateShipmentParams,
     ) -> Result<ShipmentResult, ShippingError> {
-        // ── STUB ────────────────────────────────────────────────────────────
-        // Implemente aqui o fluxo de 3 passos descrito no doc acima.
-        // ────────────────────────────────────────────────────────────────────
-        Err(ShippingError::NotConfigured(
-            "create_shipment do MelhorEnvio ainda não implementado. \
-             Siga os comentários em src/shipping/melhor_envio.rs"
-                .into(),
-        ))
+        let cart_id = match params.provider_id.clone() {
+            Some(id) => id,
+            None => self
+                .prepare_shipment(&params)
+                .await?
+                .ok_or_else(|| ShippingError::Parse("/me/cart sem ID".into()))?,
+        };
+
+        let current: TrackingOrder = self.get(&format!("/me/orders/{}", cart_id)).await?;
+        if current.canceled_at.is_some() || current.expired_at.is_some() {
+            return Err(ShippingError::Rejected(format!(
+                "envio {} cancelado ou expirado no MelhorEnvio",
+                cart_id
+            )));
+        }
+        let orders = serde_json::json!({ "orders": [cart_id] });
+
+        let checkout: serde_json::Value = if current.paid_at.is_none() {
+            self.post("/me/shipment/checkout", &orders).await?
+        } else {
+            serde_json::Value::Null
+        };
+
+        let generate: serde_json::Value = if current.generated_at.is_none() {
+            let generate: serde_json::Value = self.post("/me/shipment/generate", &orders).await?;
+            if generate[&cart_id]["status"].as_bool() == Some(false) {
+                let msg = generate[&cart_id]["message"]
+                    .as_str()
+                    .unwrap_or("falha ao gerar etiqueta")
+                    .to_string();
+                return Err(ShippingError::Rejected(msg));
+            }
+            generate
+        } else {
+            serde_json::Value::Null
+        };
+
+        let print_body = serde_json::json!({ "mode": "public", "orders": [cart_id] });
+        let print: serde_json::Value = self.post("/me/shipment/print", &print_body).await?;
+
+        // O código de rastreio só existe após a geração; busca o status atual
+        let tracking = self.track(&cart_id).await.ok();
+        let tracking_code = tracking.as_ref().map(|t| t.tracking_code.clone());
+
+        Ok(ShipmentResult {
+            provider_id: cart_id.clone(),
+            tracking_url: tracking_code
+                .as_ref()
+                .map(|code| format!("https://www.melhorrastreio.com.br/rastreio/{}", code)),
+            tracking_code,
+            label_url: print["url"].as_str().map(String::from),
+            raw_data: serde_json::json!({
+                "order_id": cart_id,
+                "protocol": current.protocol,
+                "purchase": checkout["purchase"],
+                "generate": generate,
+            }),
+        })
     }
 
-    /// Rastreia envio pelo código.
+    /// Rastreia envio.
+    ///
+    /// Endpoint: `POST /me/shipment/tracking` com `{ "orders": ["<id>"] }`.
+    /// Aceita o ID do envio no MelhorEnvio (`provider_id`) ou o código de
+    /// rastreio da transportadora.
     ///
-    /// Endpoint: `GET /api/v2/me/shipment/tracking?orders[]=<order_id>`
+    /// Resposta:
+    /// ```json
+    /// { "9a1c…": { "id": "9a1c…", "status": "posted", "tracking": "AA123456789BR",
+    ///              "melhorenvio_tracking": "ME2400001", "created_at": "2024-05-02 10:00:00",
+    ///              "paid_at": "…", "generated_at": "…", "posted_at": "…",
+    ///              "delivered_at": null, "canceled_at": null, "expired_at": null } }
+    /// ```
     ///
-    /// TODO: implementar chamada ao endpoint de rastreamento.
-    async fn track(&self, _tracking_code: &str) -> Result<TrackingInfo, ShippingError> {
-        Err(ShippingError::NotConfigured(
-            "track do MelhorEnvio ainda não implementado. \
-             Siga os comentários em src/shipping/melhor_envio.rs"
-                .into(),
-        ))
+    /// A API não devolve histórico detalhado; os eventos são montados a
+    /// partir das datas de cada etapa.
+    async fn track(&self, tracking_code: &str) -> Result<TrackingInfo, ShippingError> {
+        let body = serde_json::json!({ "orders": [tracking_code] });
+        let data: std::collections::HashMap<String, TrackingOrder> =
+            self.post("/me/shipment/tracking", &body).await?;
+
+        let order = data.into_values().next().ok_or_else(|| {
+            ShippingError::Rejected(format!("envio não encontrado: {}", tracking_code))
+        })?;
+
+        let steps = [
+            (&order.created_at, "pending", "Envio criado no MelhorEnvio"),
+            (&order.paid_at, "pending", "Etiqueta paga"),
+            (&order.generated_at, "pending", "Etiqueta gerada"),
+            (&order.posted_at, "in_transit", "Objeto postado"),
+            (&order.delivered_at, "delivered", "Objeto entregue"),
+            (&order.canceled_at, "failed", "Envio cancelado"),
+            (&order.expired_at, "failed", "Etiqueta expirada"),
+        ];
+        let events = steps
+            .iter()
+            .filter_map(|(at, status, description)| {
+                at.as_ref().map(|ts| TrackingEvent {
+                    timestamp: ts.clone(),
+                    status: (*status).to_string(),
+                    description: (*description).to_string(),
+                    location: None,
+                })
+            })
+            .collect();
+
+        Ok(TrackingInfo {
+            tracking_code: order
+                .tracking
+                .or(order.melhorenvio_tracking)
+                .unwrap_or(order.id),
+            current_status: map_status(&order.status).to_string(),
+            events,
+            estimated_delivery: None,
+        })
     }
 }
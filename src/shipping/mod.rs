@@ -11,15 +11,30 @@
 //! 2. Implemente o trait `ShippingProvider` para a sua struct
 //! 3. Adicione o módulo em `mod.rs` e registre em `provider_for()`
 //!
+//! # Volumes
+//!
+//! Os itens são empacotados em caixas por [`packing`] e chegam aos providers
+//! em [`FreightParams::packages`]; use [`FreightParams::volumes`] para
+//! cotar/despachar cada volume. O limite de peso por volume de cada
+//! transportadora vem de [`ShippingProvider::max_package_weight_grams`].
+//!
 //! # Providers disponíveis
 //!
 //! | Carrier slug     | Status       | Módulo                     |
 //! |------------------|--------------|----------------------------|
 //! | `manual`         | ✅ pronto    | — (sem integração externa) |
-//! | `melhor_envio`   | 🚧 stub      | `melhor_envio.rs`          |
-//! | `correios_api`   | 🔜 planejado | (não implementado)         |
+//! | `melhor_envio`   | ✅ pronto    | `melhor_envio.rs`          |
+//! | `correios_api`   | ✅ pronto    | `correios.rs`              |
+//! | `store_pickup`   | ✅ pronto    | `builtin.rs` (tarifas)     |
+//! | `flat_rate`      | ✅ pronto    | `builtin.rs` (tarifas)     |
+//! | `free_shipping`  | ✅ pronto    | `builtin.rs` (tarifas)     |
+//! | `table_rate`     | ✅ pronto    | `builtin.rs` (tarifas)     |
 
+pub mod builtin;
+pub mod correios;
 pub mod melhor_envio;
+pub mod packing;
+pub mod quote;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -27,6 +42,9 @@ use serde::{Deserialize, Serialize};
 /// Opção de frete retornada pelo cálculo
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FreightOption {
+    /// Slug do provider que cotou (ex.: "melhor_envio")
+    #[serde(default)]
+    pub provider: String,
     pub carrier: String,
     pub service: String,
     pub service_code: String,
@@ -47,6 +65,88 @@ pub struct FreightParams {
     pub width_cm: u32,
     pub height_cm: u32,
     pub declared_value_cents: i64,
+    /// Volumes do envio. Vazio = um único volume descrito pelos campos acima
+    #[serde(default)]
+    pub packages: Vec<packing::Package>,
+}
+
+/// Item a despachar, com peso e dimensões unitários
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParcelItem {
+    pub weight_grams: u32,
+    pub length_cm: u32,
+    pub width_cm: u32,
+    pub height_cm: u32,
+    pub quantity: u32,
+    pub unit_value_cents: i64,
+}
+
+/// Dimensões mínimas aceitas pelos Correios (cm) e peso mínimo cobrado (g)
+pub const MIN_LENGTH_CM: u32 = 16;
+pub const MIN_WIDTH_CM: u32 = 11;
+pub const MIN_HEIGHT_CM: u32 = 2;
+pub const MIN_WEIGHT_GRAMS: u32 = 300;
+
+impl FreightParams {
+    /// Empacota os itens no catálogo de caixas ([`packing::catalog`]) com o
+    /// limite de peso por volume informado.
+    pub fn from_items(
+        origin_postal_code: &str,
+        destination_postal_code: &str,
+        items: &[ParcelItem],
+        max_weight_grams: u32,
+    ) -> Result<Self, ShippingError> {
+        let packages = packing::pack(items, &packing::catalog(), max_weight_grams)?;
+        Ok(Self::from_packages(
+            origin_postal_code,
+            destination_postal_code,
+            packages,
+        ))
+    }
+
+    /// Monta os parâmetros a partir de volumes já definidos. Os campos de
+    /// peso/dimensão resumem o envio: peso e valor somados, dimensões do
+    /// maior volume.
+    pub fn from_packages(
+        origin_postal_code: &str,
+        destination_postal_code: &str,
+        packages: Vec<packing::Package>,
+    ) -> Self {
+        let largest = packages.iter().max_by_key(|p| {
+            u128::from(p.length_cm) * u128::from(p.width_cm) * u128::from(p.height_cm)
+        });
+
+        Self {
+            origin_postal_code: origin_postal_code.to_string(),
+            destination_postal_code: destination_postal_code.to_string(),
+            weight_grams: packages
+                .iter()
+                .map(|p| p.weight_grams)
+                .fold(0u32, u32::saturating_add)
+                .max(MIN_WEIGHT_GRAMS),
+            length_cm: largest.map_or(0, |p| p.length_cm).max(MIN_LENGTH_CM),
+            width_cm: largest.map_or(0, |p| p.width_cm).max(MIN_WIDTH_CM),
+            height_cm: largest.map_or(0, |p| p.height_cm).max(MIN_HEIGHT_CM),
+            declared_value_cents: packages.iter().map(|p| p.declared_value_cents).sum(),
+            packages,
+        }
+    }
+
+    /// Volumes a cotar/despachar: `packages`, ou um único volume com os
+    /// campos de peso/dimensão quando a lista estiver vazia.
+    pub fn volumes(&self) -> Vec<packing::Package> {
+        if !self.packages.is_empty() {
+            return self.packages.clone();
+        }
+        vec![packing::Package {
+            box_name: None,
+            length_cm: self.length_cm,
+            width_cm: self.width_cm,
+            height_cm: self.height_cm,
+            weight_grams: self.weight_grams,
+            declared_value_cents: self.declared_value_cents,
+        }]
+    }
 }
 
 /// Parâmetros para criação de etiqueta de envio
@@ -57,6 +157,21 @@ pub struct CreateShipmentParams {
     pub sender: ContactInfo,
     pub recipient: ContactInfo,
     pub freight: FreightParams,
+    /// Conteúdo do pacote (declaração de conteúdo)
+    #[serde(default)]
+    pub items: Vec<ShipmentItem>,
+    /// ID devolvido por [`ShippingProvider::prepare_shipment`]: retoma a
+    /// etiqueta já iniciada no provider em vez de criar outra
+    #[serde(default)]
+    pub provider_id: Option<String>,
+}
+
+/// Item declarado no envio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShipmentItem {
+    pub name: String,
+    pub quantity: u32,
+    pub unit_price_cents: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,12 +182,46 @@ pub struct ContactInfo {
     pub document: Option<String>,
     pub address_line1: String,
     pub address_line2: Option<String>,
+    /// Número do endereço (quando não vier embutido em `address_line1`)
+    #[serde(default)]
+    pub number: Option<String>,
+    /// Bairro
+    #[serde(default)]
+    pub district: Option<String>,
     pub city: String,
     pub state: String,
     pub postal_code: String,
     pub country: String,
 }
 
+impl ContactInfo {
+    /// Remetente padrão da loja, lido de variáveis de ambiente:
+    /// `STORE_SENDER_NAME`, `STORE_SENDER_DOCUMENT`, `STORE_SENDER_EMAIL`,
+    /// `STORE_SENDER_PHONE`, `STORE_SENDER_ADDRESS`, `STORE_SENDER_NUMBER`,
+    /// `STORE_SENDER_DISTRICT`, `STORE_SENDER_CITY`, `STORE_SENDER_STATE`,
+    /// `STORE_SENDER_POSTAL_CODE`.
+    pub fn store_sender() -> Result<Self, ShippingError> {
+        crate::env::load();
+        let required = |key: &str| {
+            std::env::var(key).map_err(|_| ShippingError::NotConfigured(format!("faltou {}", key)))
+        };
+        Ok(Self {
+            name: required("STORE_SENDER_NAME")?,
+            email: std::env::var("STORE_SENDER_EMAIL").ok(),
+            phone: std::env::var("STORE_SENDER_PHONE").ok(),
+            document: std::env::var("STORE_SENDER_DOCUMENT").ok(),
+            address_line1: required("STORE_SENDER_ADDRESS")?,
+            address_line2: None,
+            number: std::env::var("STORE_SENDER_NUMBER").ok(),
+            district: std::env::var("STORE_SENDER_DISTRICT").ok(),
+            city: required("STORE_SENDER_CITY")?,
+            state: required("STORE_SENDER_STATE")?,
+            postal_code: required("STORE_SENDER_POSTAL_CODE")?,
+            country: "BR".to_string(),
+        })
+    }
+}
+
 /// Resultado da criação de envio
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShipmentResult {
@@ -110,6 +259,10 @@ pub enum ShippingError {
     Network(String),
     Parse(String),
     UnsupportedCarrier(String),
+    /// Credenciais recusadas pelo provider (401/403)
+    Unauthorized(String),
+    /// Requisição recusada pelo provider (validação, saldo insuficiente, etc.)
+    Rejected(String),
 }
 
 impl std::fmt::Display for ShippingError {
@@ -119,6 +272,8 @@ impl std::fmt::Display for ShippingError {
             Self::Network(m) => write!(f, "Erro de comunicação com o provider: {}", m),
             Self::Parse(m) => write!(f, "Resposta inesperada do provider: {}", m),
             Self::UnsupportedCarrier(m) => write!(f, "Carrier não suportado: {}", m),
+            Self::Unauthorized(m) => write!(f, "Credenciais recusadas pelo provider: {}", m),
+            Self::Rejected(m) => write!(f, "Requisição recusada pelo provider: {}", m),
         }
     }
 }
@@ -164,7 +319,22 @@ pub trait ShippingProvider: Send + Sync {
         params: FreightParams,
     ) -> Result<Vec<FreightOption>, ShippingError>;
 
-    /// Cria etiqueta de envio e retorna dados do provider
+    /// Registra o envio no provider sem pagar a etiqueta e devolve o ID que
+    /// a identifica. O chamador grava esse ID antes de [`create_shipment`],
+    /// para que uma nova tentativa retome a mesma etiqueta em vez de comprar
+    /// outra. `None` = provider sem etapa anterior à compra.
+    ///
+    /// [`create_shipment`]: ShippingProvider::create_shipment
+    async fn prepare_shipment(
+        &self,
+        _params: &CreateShipmentParams,
+    ) -> Result<Option<String>, ShippingError> {
+        Ok(None)
+    }
+
+    /// Cria etiqueta de envio e retorna dados do provider. Com
+    /// `params.provider_id`, conclui a etiqueta iniciada por
+    /// [`prepare_shipment`](ShippingProvider::prepare_shipment).
     async fn create_shipment(
         &self,
         params: CreateShipmentParams,
@@ -172,6 +342,49 @@ pub trait ShippingProvider: Send + Sync {
 
     /// Consulta rastreamento pelo código
     async fn track(&self, tracking_code: &str) -> Result<TrackingInfo, ShippingError>;
+
+    /// Peso máximo aceito por volume (g), usado no empacotamento
+    fn max_package_weight_grams(&self) -> u32 {
+        packing::DEFAULT_MAX_WEIGHT_GRAMS
+    }
+}
+
+/// Slugs de todos os providers com integração externa
+pub const PROVIDERS: &[&str] = &["melhor_envio", "correios_api"];
+
+/// Providers com credenciais configuradas no ambiente
+pub fn configured_providers() -> Vec<Box<dyn ShippingProvider>> {
+    PROVIDERS.iter().copied().filter_map(provider_for).collect()
+}
+
+/// Providers externos configurados mais os internos com tarifas ativas.
+/// Falha ao carregar as tarifas é logada e não impede os externos.
+pub async fn available_providers(
+    db: &sea_orm::DatabaseConnection,
+) -> Vec<Box<dyn ShippingProvider>> {
+    let mut providers = configured_providers();
+    match builtin::load(db).await {
+        Ok(builtin) => providers.extend(builtin),
+        Err(e) => tracing::error!(error = %e, "Falha ao carregar tarifas de frete"),
+    }
+    providers
+}
+
+/// Menor limite de peso por volume entre os providers informados, para que
+/// o mesmo empacotamento sirva para todos na cotação
+pub fn max_package_weight(providers: &[Box<dyn ShippingProvider>]) -> u32 {
+    providers
+        .iter()
+        .map(|p| p.max_package_weight_grams())
+        .min()
+        .unwrap_or(packing::DEFAULT_MAX_WEIGHT_GRAMS)
+}
+
+/// CEP de origem dos envios (`STORE_SENDER_POSTAL_CODE`)
+pub fn origin_postal_code() -> Result<String, ShippingError> {
+    crate::env::load();
+    std::env::var("STORE_SENDER_POSTAL_CODE")
+        .map_err(|_| ShippingError::NotConfigured("faltou STORE_SENDER_POSTAL_CODE".into()))
 }
 
 /// Retorna o provider correspondente ao slug, se disponível e configurado.
@@ -183,13 +396,30 @@ pub fn provider_for(carrier: &str) -> Option<Box<dyn ShippingProvider>> {
     match carrier {
         "melhor_envio" => {
             // Lê credenciais de variáveis de ambiente
+            crate::env::load();
             let token = std::env::var("MELHOR_ENVIO_TOKEN").ok()?;
-            let sandbox = std::env::var("MELHOR_ENVIO_SANDBOX")
-                .map(|v| v == "true" || v == "1")
-                .unwrap_or(true);
-            Some(Box::new(melhor_envio::MelhorEnvio::new(token, sandbox)))
+            // MELHOR_ENVIO_BASE_URL aponta para outro host (ex.: servidor mock)
+            let provider = match std::env::var("MELHOR_ENVIO_BASE_URL") {
+                Ok(base_url) => melhor_envio::MelhorEnvio::with_base_url(token, base_url),
+                Err(_) => {
+                    let sandbox = std::env::var("MELHOR_ENVIO_SANDBOX")
+                        .map(|v| v == "true" || v == "1")
+                        .unwrap_or(true);
+                    melhor_envio::MelhorEnvio::new(token, sandbox)
+                }
+            };
+            match provider {
+                Ok(provider) => Some(Box::new(provider)),
+                Err(e) => {
+                    tracing::error!(error = %e, "MelhorEnvio indisponível");
+                    None
+                }
+            }
+        }
+        "correios_api" => {
+            let config = correios::CorreiosConfig::from_env()?;
+            Some(Box::new(correios::CorreiosApi::new(config)))
         }
-        // "correios_api" => Some(Box::new(correios_api::CorreiosApi::new(...))),
         _ => None, // 'manual' e desconhecidos não têm provider externo
     }
 }
@@ -0,0 +1,321 @@
+//! Empacotamento de itens em caixas
+//!
+//! Transforma os itens de um carrinho/pedido em um ou mais volumes usando um
+//! catálogo de caixas. Algoritmo (first-fit decreasing):
+//!
+//! 1. cada item vira um "bloco" com peso e dimensões unitários da variante e
+//!    a quantidade de unidades;
+//! 2. os blocos são ordenados do maior volume unitário para o menor;
+//! 3. as unidades de cada bloco entram nos volumes abertos, na ordem, até o
+//!    limite de volume, dimensões e peso da transportadora de cada um;
+//! 4. o que sobrar abre novos volumes com a maior caixa que comporta o bloco;
+//! 5. no fim, cada volume é trocado pela menor caixa que ainda comporta o
+//!    conteúdo.
+//!
+//! Itens maiores que qualquer caixa do catálogo seguem sozinhos, com as
+//! próprias dimensões (um volume por unidade). O custo depende do número de
+//! itens e de volumes, não da quantidade de unidades; envios com mais de
+//! [`MAX_PACKAGES`] volumes são recusados.
+//!
+//! # Catálogo
+//!
+//! `SHIPPING_BOXES` aceita um JSON com a lista de caixas:
+//!
+//! ```json
+//! [{ "name": "P", "length_cm": 20, "width_cm": 15, "height_cm": 10, "tare_grams": 100 }]
+//! ```
+//!
+//! Sem a variável, usa [`default_catalog`].
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ParcelItem, ShippingError, MIN_HEIGHT_CM, MIN_LENGTH_CM, MIN_WEIGHT_GRAMS, MIN_WIDTH_CM,
+};
+
+/// Limite de peso por volume quando a transportadora não informa (30 kg)
+pub const DEFAULT_MAX_WEIGHT_GRAMS: u32 = 30_000;
+
+/// Máximo de volumes num envio
+pub const MAX_PACKAGES: usize = 100;
+
+/// Caixa disponível para despacho
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoxSize {
+    pub name: String,
+    pub length_cm: u32,
+    pub width_cm: u32,
+    pub height_cm: u32,
+    /// Peso da caixa vazia
+    #[serde(default)]
+    pub tare_grams: u32,
+}
+
+impl BoxSize {
+    fn volume(&self) -> u128 {
+        u128::from(self.length_cm) * u128::from(self.width_cm) * u128::from(self.height_cm)
+    }
+
+    fn sorted_dims(&self) -> [u32; 3] {
+        sorted([self.length_cm, self.width_cm, self.height_cm])
+    }
+}
+
+/// Volume resultante do empacotamento
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Package {
+    /// Nome da caixa do catálogo (`None` = item despachado na própria embalagem)
+    pub box_name: Option<String>,
+    pub length_cm: u32,
+    pub width_cm: u32,
+    pub height_cm: u32,
+    /// Peso total, incluindo a caixa
+    pub weight_grams: u32,
+    pub declared_value_cents: i64,
+}
+
+/// Catálogo padrão (caixas de papelão comuns no varejo)
+pub fn default_catalog() -> Vec<BoxSize> {
+    let boxes = [
+        ("P", 20, 15, 10, 100),
+        ("M", 30, 20, 15, 180),
+        ("G", 40, 30, 25, 300),
+        ("GG", 60, 40, 40, 550),
+    ];
+    boxes
+        .into_iter()
+        .map(
+            |(name, length_cm, width_cm, height_cm, tare_grams)| BoxSize {
+                name: name.to_string(),
+                length_cm,
+                width_cm,
+                height_cm,
+                tare_grams,
+            },
+        )
+        .collect()
+}
+
+/// Catálogo de caixas (`SHIPPING_BOXES` ou o padrão)
+pub fn catalog() -> Vec<BoxSize> {
+    crate::env::load();
+    std::env::var("SHIPPING_BOXES")
+        .ok()
+        .and_then(|raw| match serde_json::from_str::<Vec<BoxSize>>(&raw) {
+            Ok(boxes) if !boxes.is_empty() => Some(boxes),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!(error = %e, "SHIPPING_BOXES inválido, usando catálogo padrão");
+                None
+            }
+        })
+        .unwrap_or_else(default_catalog)
+}
+
+fn sorted(mut dims: [u32; 3]) -> [u32; 3] {
+    dims.sort_unstable();
+    dims
+}
+
+/// Unidades de um item a acomodar, com dimensões e peso unitários
+#[derive(Debug, Clone)]
+struct Block {
+    dims: [u32; 3],
+    weight_grams: u32,
+    value_cents: i64,
+    count: u64,
+}
+
+impl Block {
+    fn volume(&self) -> u128 {
+        self.dims.iter().map(|d| u128::from(*d)).product()
+    }
+
+    fn fits_in(&self, b: &BoxSize) -> bool {
+        let inner = b.sorted_dims();
+        self.dims.iter().zip(inner.iter()).all(|(d, i)| d <= i)
+    }
+
+    /// Unidades que cabem no espaço e no peso livres (sem limite se a
+    /// unidade não tem volume ou peso)
+    fn units_within(&self, free_volume: u128, free_weight: u64) -> u64 {
+        let by_volume = match self.volume() {
+            0 => u64::MAX,
+            v => u64::try_from(free_volume / v).unwrap_or(u64::MAX),
+        };
+        let by_weight = match self.weight_grams {
+            0 => u64::MAX,
+            w => free_weight / u64::from(w),
+        };
+        by_volume.min(by_weight)
+    }
+}
+
+/// Volume em montagem
+#[derive(Debug)]
+struct OpenPackage {
+    boxed: BoxSize,
+    /// Blocos acomodados, cada um com a quantidade que entrou neste volume
+    blocks: Vec<Block>,
+    used_volume: u128,
+    content_weight: u64,
+}
+
+impl OpenPackage {
+    fn new(boxed: BoxSize) -> Self {
+        Self {
+            boxed,
+            blocks: vec![],
+            used_volume: 0,
+            content_weight: 0,
+        }
+    }
+
+    /// Quantas unidades do bloco ainda cabem
+    fn room_for(&self, block: &Block, max_weight_grams: u32) -> u64 {
+        if !block.fits_in(&self.boxed) {
+            return 0;
+        }
+        let free_volume = self.boxed.volume().saturating_sub(self.used_volume);
+        let free_weight = u64::from(max_weight_grams)
+            .saturating_sub(u64::from(self.boxed.tare_grams) + self.content_weight);
+        block.units_within(free_volume, free_weight)
+    }
+
+    fn add(&mut self, block: &Block, count: u64) {
+        self.used_volume += block.volume() * u128::from(count);
+        self.content_weight += u64::from(block.weight_grams) * count;
+        self.blocks.push(Block {
+            count,
+            ..block.clone()
+        });
+    }
+
+    /// Troca pela menor caixa do catálogo que comporta o conteúdo
+    fn shrink(&mut self, catalog: &[BoxSize], max_weight_grams: u32) {
+        let used = self.used_volume;
+        let weight = self.content_weight;
+        if let Some(smaller) = catalog
+            .iter()
+            .filter(|b| b.volume() >= used)
+            .filter(|b| u64::from(b.tare_grams) + weight <= u64::from(max_weight_grams))
+            .filter(|b| self.blocks.iter().all(|blk| blk.fits_in(b)))
+            .min_by_key(|b| b.volume())
+        {
+            self.boxed = smaller.clone();
+        }
+    }
+
+    fn into_package(self) -> Package {
+        let weight = u64::from(self.boxed.tare_grams) + self.content_weight;
+        Package {
+            box_name: Some(self.boxed.name.clone()),
+            length_cm: self.boxed.length_cm.max(MIN_LENGTH_CM),
+            width_cm: self.boxed.width_cm.max(MIN_WIDTH_CM),
+            height_cm: self.boxed.height_cm.max(MIN_HEIGHT_CM),
+            weight_grams: u32::try_from(weight)
+                .unwrap_or(u32::MAX)
+                .max(MIN_WEIGHT_GRAMS),
+            declared_value_cents: self
+                .blocks
+                .iter()
+                .map(|b| b.value_cents.saturating_mul(b.count as i64))
+                .fold(0, i64::saturating_add),
+        }
+    }
+}
+
+fn too_many_packages() -> ShippingError {
+    ShippingError::Rejected(format!("Envio excede {} volumes", MAX_PACKAGES))
+}
+
+/// Empacota os itens respeitando o limite de peso por volume. Envio que
+/// precisaria de mais de [`MAX_PACKAGES`] volumes é recusado.
+pub fn pack(
+    items: &[ParcelItem],
+    catalog: &[BoxSize],
+    max_weight_grams: u32,
+) -> Result<Vec<Package>, ShippingError> {
+    let mut blocks: Vec<Block> = items
+        .iter()
+        .filter(|item| item.quantity > 0)
+        .map(|item| Block {
+            dims: sorted([item.length_cm, item.width_cm, item.height_cm]),
+            weight_grams: item.weight_grams,
+            value_cents: item.unit_value_cents,
+            count: u64::from(item.quantity),
+        })
+        .collect();
+    blocks.sort_by_key(|b| std::cmp::Reverse(b.volume()));
+
+    let mut open: Vec<OpenPackage> = Vec::new();
+    let mut loose: Vec<Package> = Vec::new();
+
+    for block in blocks {
+        let mut remaining = block.count;
+        for pkg in open.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            let units = pkg.room_for(&block, max_weight_grams).min(remaining);
+            if units > 0 {
+                pkg.add(&block, units);
+                remaining -= units;
+            }
+        }
+        if remaining == 0 {
+            continue;
+        }
+
+        let largest = catalog
+            .iter()
+            .filter(|b| {
+                block.fits_in(b)
+                    && u64::from(b.tare_grams) + u64::from(block.weight_grams)
+                        <= u64::from(max_weight_grams)
+            })
+            .max_by_key(|b| b.volume());
+        match largest {
+            Some(boxed) => {
+                while remaining > 0 {
+                    if open.len() + loose.len() >= MAX_PACKAGES {
+                        return Err(too_many_packages());
+                    }
+                    let mut pkg = OpenPackage::new(boxed.clone());
+                    // A caixa comporta ao menos uma unidade (filtro acima)
+                    let units = pkg.room_for(&block, max_weight_grams).clamp(1, remaining);
+                    pkg.add(&block, units);
+                    remaining -= units;
+                    open.push(pkg);
+                }
+            }
+            // Não cabe em nenhuma caixa: cada unidade segue na própria embalagem
+            None => {
+                let count = usize::try_from(remaining).unwrap_or(usize::MAX);
+                if count > MAX_PACKAGES - (open.len() + loose.len()) {
+                    return Err(too_many_packages());
+                }
+                loose.extend(std::iter::repeat_n(
+                    Package {
+                        box_name: None,
+                        length_cm: block.dims[2].max(MIN_LENGTH_CM),
+                        width_cm: block.dims[1].max(MIN_WIDTH_CM),
+                        height_cm: block.dims[0].max(MIN_HEIGHT_CM),
+                        weight_grams: block.weight_grams.max(MIN_WEIGHT_GRAMS),
+                        declared_value_cents: block.value_cents,
+                    },
+                    count,
+                ));
+            }
+        }
+    }
+
+    Ok(open
+        .into_iter()
+        .map(|mut pkg| {
+            pkg.shrink(catalog, max_weight_grams);
+            pkg.into_package()
+        })
+        .chain(loose)
+        .collect())
+}
@@ -0,0 +1,113 @@
+//! Cotação de frete em várias transportadoras
+//!
+//! Consulta em paralelo todos os providers disponíveis — externos e internos
+//! (ver [`super::available_providers`]) —, cada um com seu próprio timeout, junta as
+//! opções e ordena por preço e prazo. O resultado fica em cache (moka) pela
+//! chave do carrinho — volumes + CEP de destino — para que a seleção da opção
+//! não precise cotar de novo.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use moka::future::Cache;
+use once_cell::sync::Lazy;
+
+use sea_orm::DatabaseConnection;
+
+use super::{available_providers, FreightOption, FreightParams};
+
+/// Opções cotadas por chave de carrinho (válidas por 15 minutos)
+static QUOTE_CACHE: Lazy<Cache<String, Vec<FreightOption>>> = Lazy::new(|| {
+    Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(15 * 60))
+        .build()
+});
+
+/// Timeout por provider (`SHIPPING_QUOTE_TIMEOUT_MS`, padrão 5s)
+fn provider_timeout() -> Duration {
+    crate::env::load();
+    let ms = std::env::var("SHIPPING_QUOTE_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(5_000);
+    Duration::from_millis(ms)
+}
+
+/// Chave de cache: muda sempre que itens, volumes ou destino mudam
+pub fn cache_key(cart_pid: &uuid::Uuid, params: &FreightParams) -> String {
+    let mut hasher = DefaultHasher::new();
+    params.origin_postal_code.hash(&mut hasher);
+    params.destination_postal_code.hash(&mut hasher);
+    params.weight_grams.hash(&mut hasher);
+    params.length_cm.hash(&mut hasher);
+    params.width_cm.hash(&mut hasher);
+    params.height_cm.hash(&mut hasher);
+    params.declared_value_cents.hash(&mut hasher);
+    params.packages.hash(&mut hasher);
+    format!("{}:{:x}", cart_pid, hasher.finish())
+}
+
+/// Consulta todos os providers disponíveis em paralelo.
+///
+/// Providers que falham ou estouram o timeout são ignorados (e logados);
+/// as opções restantes vêm ordenadas por preço e depois por prazo.
+pub async fn quote_all(db: &DatabaseConnection, params: &FreightParams) -> Vec<FreightOption> {
+    let timeout = provider_timeout();
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for provider in available_providers(db).await {
+        let params = params.clone();
+        tasks.spawn(async move {
+            let name = provider.name();
+            let result = tokio::time::timeout(timeout, provider.calculate_freight(params)).await;
+            (name, result)
+        });
+    }
+
+    let mut options = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((name, Ok(Ok(mut found)))) => {
+                for option in &mut found {
+                    option.provider = name.to_string();
+                }
+                options.extend(found);
+            }
+            Ok((name, Ok(Err(e)))) => {
+                tracing::warn!(provider = name, error = %e, "Falha ao cotar frete");
+            }
+            Ok((name, Err(_))) => {
+                tracing::warn!(provider = name, ?timeout, "Timeout ao cotar frete");
+            }
+            Err(e) => tracing::error!(error = %e, "Tarefa de cotação abortada"),
+        }
+    }
+
+    options.sort_by(|a, b| {
+        a.price_cents
+            .cmp(&b.price_cents)
+            .then(a.delivery_days.cmp(&b.delivery_days))
+    });
+    options
+}
+
+/// Cotação com cache pela chave do carrinho
+pub async fn quote_cached(
+    db: &DatabaseConnection,
+    key: &str,
+    params: &FreightParams,
+) -> Vec<FreightOption> {
+    if let Some(options) = QUOTE_CACHE.get(key).await {
+        return options;
+    }
+    let options = quote_all(db, params).await;
+    // Não guarda cotação vazia: pode ter sido falha temporária dos providers
+    if !options.is_empty() {
+        QUOTE_CACHE.insert(key.to_string(), options.clone()).await;
+    }
+    options
+}
@@ -0,0 +1,27 @@
+use loco_rs::prelude::*;
+
+use crate::workers::payment_expiry::{PaymentExpiryWorker, PaymentExpiryWorkerArgs};
+
+/// Cancela os pedidos com PIX/boleto vencido.
+/// Aceita `grace_days:<n>` e `limit:<n>`, com os defaults do worker.
+pub struct ExpirePayments;
+
+#[async_trait]
+impl Task for ExpirePayments {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "expire_payments".to_string(),
+            detail: "Enfileira o cancelamento dos pedidos com pagamento vencido".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let grace_days = vars.cli.get("grace_days").and_then(|v| v.parse().ok());
+        let limit = vars.cli.get("limit").and_then(|v| v.parse().ok());
+        super::enqueue::<PaymentExpiryWorker, _>(
+            app_context,
+            PaymentExpiryWorkerArgs { grace_days, limit },
+        )
+        .await
+    }
+}
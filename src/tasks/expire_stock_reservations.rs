@@ -0,0 +1,26 @@
+use loco_rs::prelude::*;
+
+use crate::workers::stock_reservations::{
+    StockReservationExpiryWorker, StockReservationExpiryWorkerArgs,
+};
+
+/// Libera as reservas de estoque vencidas
+pub struct ExpireStockReservations;
+
+#[async_trait]
+impl Task for ExpireStockReservations {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "expire_stock_reservations".to_string(),
+            detail: "Enfileira a liberação das reservas de estoque vencidas".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, _vars: &task::Vars) -> Result<()> {
+        super::enqueue::<StockReservationExpiryWorker, _>(
+            app_context,
+            StockReservationExpiryWorkerArgs {},
+        )
+        .await
+    }
+}
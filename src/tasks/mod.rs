@@ -1 +1,21 @@
+//! Tarefas disparadas pelo scheduler (`scheduler.jobs` na configuração)
 
+use loco_rs::{app::AppContext, bgworker::BackgroundWorker, config::WorkerMode, Result};
+use serde::Serialize;
+
+pub mod expire_payments;
+pub mod expire_stock_reservations;
+pub mod poll_shipment_tracking;
+
+/// Enfileira o job do worker. No modo `BackgroundAsync` não há fila: a tarefa
+/// roda num processo que termina em seguida, então o job é executado ali mesmo.
+pub async fn enqueue<W, A>(ctx: &AppContext, args: A) -> Result<()>
+where
+    W: BackgroundWorker<A>,
+    A: Send + Sync + Serialize + 'static,
+{
+    match ctx.config.workers.mode {
+        WorkerMode::BackgroundAsync => W::build(ctx).perform(args).await,
+        _ => W::perform_later(ctx, args).await,
+    }
+}
@@ -0,0 +1,26 @@
+use loco_rs::prelude::*;
+
+use crate::workers::shipment_tracking::{ShipmentTrackingWorker, ShipmentTrackingWorkerArgs};
+
+/// Consulta o rastreio dos envios em andamento.
+/// Aceita `limit:<n>` para o máximo de envios por execução.
+pub struct PollShipmentTracking;
+
+#[async_trait]
+impl Task for PollShipmentTracking {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "poll_shipment_tracking".to_string(),
+            detail: "Enfileira a consulta de rastreio dos envios em andamento".to_string(),
+        }
+    }
+
+    async fn run(&self, app_context: &AppContext, vars: &task::Vars) -> Result<()> {
+        let limit = vars.cli.get("limit").and_then(|v| v.parse().ok());
+        super::enqueue::<ShipmentTrackingWorker, _>(
+            app_context,
+            ShipmentTrackingWorkerArgs { limit },
+        )
+        .await
+    }
+}
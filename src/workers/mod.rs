@@ -2,3 +2,6 @@ pub mod abandoned_cart;
 pub mod analytics_flush;
 pub mod downloader;
 pub mod lead_scoring;
+pub mod payment_expiry;
+pub mod shipment_tracking;
+pub mod stock_reservations;
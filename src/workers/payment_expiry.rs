@@ -0,0 +1,122 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mailers::orders::OrderMailer,
+    models::{_entities::customers, orders::Model as OrderModel},
+    payments,
+};
+
+pub struct PaymentExpiryWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct PaymentExpiryWorkerArgs {
+    /// Dias de tolerância após o vencimento, para a compensação de boletos
+    /// pagos no último dia (default: `PAYMENT_EXPIRY_GRACE_DAYS` ou 1)
+    pub grace_days: Option<i64>,
+    /// Máximo de pedidos cancelados por execução (default: 100)
+    pub limit: Option<u64>,
+}
+
+fn default_grace_days() -> i64 {
+    crate::env::load();
+    std::env::var("PAYMENT_EXPIRY_GRACE_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1)
+}
+
+#[async_trait]
+impl BackgroundWorker<PaymentExpiryWorkerArgs> for PaymentExpiryWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    /// Cancela pedidos com PIX/boleto vencido: remove a cobrança no gateway,
+    /// cancela o pedido liberando o estoque reservado e avisa o cliente.
+    async fn perform(&self, args: PaymentExpiryWorkerArgs) -> Result<()> {
+        let grace_days = args.grace_days.unwrap_or_else(default_grace_days);
+        let orders =
+            OrderModel::list_payment_expired(&self.ctx.db, grace_days, args.limit.unwrap_or(100))
+                .await?;
+
+        let mut canceled = 0;
+        let mut failed = 0;
+        for order in &orders {
+            OrderModel::touch_payment_expiry_check(&self.ctx.db, order.id).await?;
+
+            // A cobrança é removida antes: se o gateway recusar (ex.: já foi
+            // paga) ou não puder ser consultado, o pedido fica como está — uma
+            // cobrança ainda pagável não pode cair num pedido cancelado
+            let provider = order
+                .payment_provider
+                .clone()
+                .or_else(|| {
+                    order
+                        .payment_data
+                        .get("provider")
+                        .and_then(|v| v.as_str())
+                        .map(String::from)
+                })
+                .and_then(|slug| payments::provider_for(&slug));
+            let Some(provider) = provider else {
+                failed += 1;
+                tracing::warn!(
+                    order_id = order.id,
+                    "Gateway da cobrança vencida indisponível, pedido mantido"
+                );
+                continue;
+            };
+            let Some(payment_id) = order.provider_payment_id(&self.ctx.db).await? else {
+                failed += 1;
+                tracing::warn!(
+                    order_id = order.id,
+                    provider = provider.name(),
+                    "Cobrança vencida sem ID no gateway, pedido mantido"
+                );
+                continue;
+            };
+            if let Err(e) = provider.cancel(&payment_id).await {
+                failed += 1;
+                tracing::warn!(
+                    order_id = order.id,
+                    provider = provider.name(),
+                    error = %e,
+                    "Falha ao cancelar cobrança vencida no gateway"
+                );
+                continue;
+            }
+
+            let Some(expired) = OrderModel::expire_unpaid(&self.ctx.db, order.id).await? else {
+                continue;
+            };
+            canceled += 1;
+
+            let customer = customers::Entity::find_by_id(expired.customer_id)
+                .one(&self.ctx.db)
+                .await?;
+            if let Some(customer) = customer {
+                if let Err(e) =
+                    OrderMailer::send_payment_expired(&self.ctx, &customer, &expired).await
+                {
+                    tracing::warn!(
+                        order_id = expired.id,
+                        error = %e,
+                        "Falha ao enviar email de pedido cancelado"
+                    );
+                }
+            }
+        }
+
+        tracing::info!(
+            orders_expired = orders.len(),
+            orders_canceled = canceled,
+            orders_failed = failed,
+            "Payment expiry worker completed"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{models::order_shippings::Model as ShippingModel, shipping};
+
+pub struct ShipmentTrackingWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ShipmentTrackingWorkerArgs {
+    /// Máximo de envios consultados por execução (default: 100)
+    pub limit: Option<u64>,
+}
+
+#[async_trait]
+impl BackgroundWorker<ShipmentTrackingWorkerArgs> for ShipmentTrackingWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    /// Consulta o rastreio dos envios em andamento e registra as novidades.
+    /// Envios consultados há mais tempo vão primeiro.
+    async fn perform(&self, args: ShipmentTrackingWorkerArgs) -> Result<()> {
+        let shippings =
+            ShippingModel::list_trackable(&self.ctx.db, args.limit.unwrap_or(100)).await?;
+
+        let mut updated = 0;
+        let mut failed = 0;
+        for shipment in &shippings {
+            ShippingModel::touch_polled(&self.ctx.db, shipment.id).await?;
+            let Some(provider) = shipment
+                .provider
+                .as_deref()
+                .and_then(shipping::provider_for)
+            else {
+                continue;
+            };
+            let Some(reference) = shipment.tracking_reference() else {
+                continue;
+            };
+
+            let info = match provider.track(&reference).await {
+                Ok(info) => info,
+                Err(e) => {
+                    failed += 1;
+                    tracing::warn!(
+                        shipping_id = shipment.id,
+                        provider = provider.name(),
+                        error = %e,
+                        "Falha ao consultar rastreio"
+                    );
+                    continue;
+                }
+            };
+
+            match ShippingModel::record_tracking(&self.ctx.db, shipment.id, &info, "poll").await {
+                Ok(_) => updated += 1,
+                Err(e) => {
+                    failed += 1;
+                    tracing::error!(
+                        shipping_id = shipment.id,
+                        error = %e,
+                        "Falha ao registrar rastreio"
+                    );
+                }
+            }
+        }
+
+        tracing::info!(
+            shipments_checked = shippings.len(),
+            shipments_updated = updated,
+            shipments_failed = failed,
+            "Shipment tracking worker completed"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use loco_rs::prelude::*;
+use sea_orm::TransactionTrait;
+use serde::{Deserialize, Serialize};
+
+use crate::models::stock_reservations::Model as StockReservationModel;
+
+pub struct StockReservationExpiryWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct StockReservationExpiryWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<StockReservationExpiryWorkerArgs> for StockReservationExpiryWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: StockReservationExpiryWorkerArgs) -> Result<()> {
+        let txn = self.ctx.db.begin().await?;
+        let released = StockReservationModel::release_expired(&txn).await?;
+        txn.commit().await?;
+
+        tracing::info!(
+            reservations_released = released,
+            "Stock reservation expiry worker completed"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::{Duration, NaiveDate, Utc};
+use loco_fast_store::models::{
+    _entities::{items, product_variants, stocks, warehouses},
+    stock_reservations::{Model as StockReservationModel, ReservationLine},
+    stocks::Model as StockModel,
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait, TransactionTrait};
+use serial_test::serial;
+use uuid::Uuid;
+
+use super::{boot, order, reload_stock, variant, warehouse};
+
+/// Lote com validade `days` dias a partir de hoje (negativo: já vencido)
+async fn batch(
+    db: &DatabaseConnection,
+    warehouse: &warehouses::Model,
+    variant: &product_variants::Model,
+    code: &str,
+    days: Option<i64>,
+    quantity: i32,
+) -> stocks::Model {
+    let item = items::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        variant_id: ActiveValue::set(variant.id),
+        batch: ActiveValue::set(Some(code.to_string())),
+        expiration: ActiveValue::set(days.map(expires_in)),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+    let stock = stocks::ActiveModel {
+        warehouse_id: ActiveValue::set(warehouse.id),
+        item_id: ActiveValue::set(item.id),
+        quantity: ActiveValue::set(quantity),
+        reserved: ActiveValue::set(0),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    let current = product_variants::Entity::find_by_id(variant.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    let on_hand = current.inventory_quantity + quantity;
+    let mut active: product_variants::ActiveModel = current.into();
+    active.inventory_quantity = ActiveValue::set(on_hand);
+    active.update(db).await.unwrap();
+    stock
+}
+
+fn expires_in(days: i64) -> NaiveDate {
+    Utc::now().date_naive() + Duration::days(days)
+}
+
+async fn reserve(
+    db: &DatabaseConnection,
+    variant: &product_variants::Model,
+    quantity: i32,
+) -> Result<(), ModelError> {
+    let (order, _) = order(
+        db,
+        "01310-100",
+        &[(variant, quantity, 1_500)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+    // Como no checkout: pedido recusado não deixa reserva parcial
+    let txn = db.begin().await?;
+    StockReservationModel::reserve_for_order(
+        &txn,
+        order.id,
+        &[ReservationLine {
+            variant_id: variant.id,
+            quantity,
+        }],
+    )
+    .await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn reservation_takes_the_nearest_expiry_and_skips_expired_batches() {
+    let db = boot().await.db;
+    let iogurte = variant(&db, "IOG-MORANGO").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    // Cadastrados fora de ordem para a ordem não vir do id
+    let no_date = batch(&db, &sp, &iogurte, "L-SEM", None, 10).await;
+    let later = batch(&db, &sp, &iogurte, "L-30", Some(30), 5).await;
+    let expired = batch(&db, &sp, &iogurte, "L-VENC", Some(-1), 8).await;
+    let soon = batch(&db, &sp, &iogurte, "L-03", Some(3), 3).await;
+
+    reserve(&db, &iogurte, 4).await.unwrap();
+    assert_eq!(reload_stock(&db, &soon).await.reserved, 3);
+    assert_eq!(reload_stock(&db, &later).await.reserved, 1);
+    assert_eq!(reload_stock(&db, &no_date).await.reserved, 0);
+    assert_eq!(reload_stock(&db, &expired).await.reserved, 0);
+
+    // Lote sem validade só depois dos datados
+    reserve(&db, &iogurte, 6).await.unwrap();
+    assert_eq!(reload_stock(&db, &later).await.reserved, 5);
+    assert_eq!(reload_stock(&db, &no_date).await.reserved, 2);
+
+    // Restam 8 válidas; as 8 vencidas não contam
+    let err = reserve(&db, &iogurte, 9).await.unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+    assert_eq!(reload_stock(&db, &expired).await.reserved, 0);
+    assert_eq!(reload_stock(&db, &no_date).await.reserved, 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn only_expired_units_left_is_insufficient_stock() {
+    let db = boot().await.db;
+    let leite = variant(&db, "LEITE-1L").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let expired = batch(&db, &sp, &leite, "L-ONTEM", Some(-1), 20).await;
+
+    let err = reserve(&db, &leite, 1).await.unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+    assert_eq!(reload_stock(&db, &expired).await.reserved, 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn expiring_report_lists_expired_and_near_batches_by_date() {
+    let db = boot().await.db;
+    let queijo = variant(&db, "QUEIJO-MINAS").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let rj = warehouse(&db, "RJ", "20040-002").await;
+    let expired = batch(&db, &sp, &queijo, "Q-VENC", Some(-2), 4).await;
+    let soon = batch(&db, &rj, &queijo, "Q-05", Some(5), 6).await;
+    batch(&db, &sp, &queijo, "Q-60", Some(60), 6).await;
+    batch(&db, &sp, &queijo, "Q-SEM", None, 6).await;
+    batch(&db, &sp, &queijo, "Q-ZERADO", Some(1), 0).await;
+
+    let report = StockModel::expiring_batches(&db, 7, None).await.unwrap();
+    let batches: Vec<(Option<&str>, i64)> = report
+        .iter()
+        .map(|b| (b.batch.as_deref(), b.days_left))
+        .collect();
+    assert_eq!(batches, vec![(Some("Q-VENC"), -2), (Some("Q-05"), 5)]);
+    assert_eq!(report[0].stock_id, expired.id);
+    assert_eq!(report[1].warehouse_id, rj.id);
+    assert_eq!(report[1].quantity, soon.quantity);
+
+    let in_sp = StockModel::expiring_batches(&db, 7, Some(sp.id))
+        .await
+        .unwrap();
+    assert_eq!(in_sp.len(), 1);
+    assert_eq!(in_sp[0].stock_id, expired.id);
+}
@@ -0,0 +1,248 @@
+use loco_fast_store::models::{
+    _entities::{customers, orders, product_variants},
+    carts::Model as CartModel,
+    coupons::{CreateCouponParams, Model as CouponModel},
+    orders::{CreateOrderFromCartParams, Model as OrderModel},
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter};
+use serial_test::serial;
+
+use super::{boot, customer, price, stock, variant, warehouse};
+
+fn coupon_params(code: &str, discount_type: &str, value: i64) -> CreateCouponParams {
+    CreateCouponParams {
+        code: code.to_string(),
+        description: None,
+        discount_type: discount_type.to_string(),
+        value: Some(value),
+        min_subtotal: None,
+        usage_limit: None,
+        usage_limit_per_customer: None,
+        product_ids: None,
+        category_ids: None,
+        collection_ids: None,
+        starts_at: None,
+        ends_at: None,
+        active: None,
+    }
+}
+
+/// Variante de R$ 100 com estoque para os pedidos
+async fn camiseta(db: &DatabaseConnection, sku: &str) -> product_variants::Model {
+    let camiseta = variant(db, sku).await;
+    price(db, &camiseta, 10_000).await;
+    let sp = warehouse(db, "SP", "01310-100").await;
+    stock(db, &sp, &camiseta, 10).await;
+    camiseta
+}
+
+/// Carrinho do cliente com uma unidade de cada variante
+async fn cart(
+    db: &DatabaseConnection,
+    customer: &customers::Model,
+    variants: &[&product_variants::Model],
+) -> CartModel {
+    let cart = CartModel::create_cart(db, "sessao", Some(customer.id), None, None)
+        .await
+        .unwrap();
+    for variant in variants {
+        CartModel::add_item(db, &cart, variant.id, 1).await.unwrap();
+    }
+    CartModel::recalculate_totals(db, cart.id).await.unwrap()
+}
+
+/// Pedido com o carrinho como está, sem recalcular os totais
+async fn checkout(
+    db: &DatabaseConnection,
+    customer: &customers::Model,
+    cart: &CartModel,
+) -> Result<orders::Model, ModelError> {
+    let items = CartModel::get_items(db, cart.id).await.unwrap();
+    OrderModel::create_from_cart(
+        db,
+        cart,
+        &items,
+        &CreateOrderFromCartParams {
+            customer_id: customer.id,
+            shipping_address_id: None,
+            billing_address_id: None,
+            payment_method: None,
+            notes: None,
+        },
+    )
+    .await
+}
+
+async fn usage_count(db: &DatabaseConnection, code: &str) -> i32 {
+    CouponModel::find_by_code(db, code)
+        .await
+        .unwrap()
+        .usage_count
+}
+
+#[tokio::test]
+#[serial]
+async fn global_limit_is_checked_again_at_checkout() {
+    let db = boot().await.db;
+    let camiseta = camiseta(&db, "CAM-CUPOM").await;
+    let mut params = coupon_params("ultimo", "percentage", 10);
+    params.usage_limit = Some(1);
+    CouponModel::create_coupon(&db, &params).await.unwrap();
+
+    // Os dois carrinhos aplicaram o cupom antes de qualquer pedido
+    let (maria, joao) = (customer(&db).await, customer(&db).await);
+    let first = cart(&db, &maria, &[&camiseta]).await;
+    let second = cart(&db, &joao, &[&camiseta]).await;
+    let mut applied = Vec::new();
+    for cart in [&first, &second] {
+        let cart = CartModel::apply_coupon(&db, cart.id, "ULTIMO")
+            .await
+            .unwrap();
+        assert_eq!(cart.discount, 1_000);
+        assert_eq!(cart.total, 9_000);
+        applied.push(cart);
+    }
+
+    let order = checkout(&db, &maria, &applied[0]).await.unwrap();
+    assert_eq!(order.discount, 1_000);
+    assert_eq!(order.coupon_code.as_deref(), Some("ULTIMO"));
+
+    // Ao voltar ao checkout o segundo carrinho é recalculado e perde o cupom
+    let second = CartModel::recalculate_totals(&db, second.id).await.unwrap();
+    assert_eq!(second.coupon_code, None);
+    let order = checkout(&db, &joao, &second).await.unwrap();
+    assert_eq!(order.discount, 0);
+    assert_eq!(order.total, 10_000);
+    assert_eq!(usage_count(&db, "ULTIMO").await, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn concurrent_redemption_of_the_last_use_is_refused() {
+    let db = boot().await.db;
+    let camiseta = camiseta(&db, "CAM-CORRIDA").await;
+    let mut params = coupon_params("corrida", "fixed_amount", 2_000);
+    params.usage_limit = Some(1);
+    CouponModel::create_coupon(&db, &params).await.unwrap();
+
+    let (maria, joao) = (customer(&db).await, customer(&db).await);
+    let first = cart(&db, &maria, &[&camiseta]).await;
+    let second = cart(&db, &joao, &[&camiseta]).await;
+    let first = CartModel::apply_coupon(&db, first.id, "CORRIDA")
+        .await
+        .unwrap();
+    let second = CartModel::apply_coupon(&db, second.id, "CORRIDA")
+        .await
+        .unwrap();
+
+    // Os dois checkouts passaram da reavaliação do carrinho ao mesmo tempo:
+    // só o resgate, com a linha do cupom bloqueada, decide quem leva
+    checkout(&db, &maria, &first).await.unwrap();
+    let err = checkout(&db, &joao, &second).await.unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+
+    // Nada do pedido recusado ficou gravado
+    let orders = orders::Entity::find()
+        .filter(orders::Column::CartId.eq(second.id))
+        .count(&db)
+        .await
+        .unwrap();
+    assert_eq!(orders, 0);
+    assert_eq!(usage_count(&db, "CORRIDA").await, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn canceled_order_gives_the_use_back() {
+    let db = boot().await.db;
+    let camiseta = camiseta(&db, "CAM-DEVOLVE").await;
+    let mut params = coupon_params("volta", "percentage", 10);
+    params.usage_limit = Some(1);
+    CouponModel::create_coupon(&db, &params).await.unwrap();
+
+    let maria = customer(&db).await;
+    let first = cart(&db, &maria, &[&camiseta]).await;
+    let first = CartModel::apply_coupon(&db, first.id, "VOLTA")
+        .await
+        .unwrap();
+    let order = checkout(&db, &maria, &first).await.unwrap();
+    assert_eq!(usage_count(&db, "VOLTA").await, 1);
+
+    CouponModel::release_for_order(&db, order.id).await.unwrap();
+    assert_eq!(usage_count(&db, "VOLTA").await, 0);
+
+    let joao = customer(&db).await;
+    let second = cart(&db, &joao, &[&camiseta]).await;
+    let second = CartModel::apply_coupon(&db, second.id, "VOLTA")
+        .await
+        .unwrap();
+    assert_eq!(checkout(&db, &joao, &second).await.unwrap().discount, 1_000);
+}
+
+#[tokio::test]
+#[serial]
+async fn per_customer_limit_only_blocks_the_same_customer() {
+    let db = boot().await.db;
+    let camiseta = camiseta(&db, "CAM-CLIENTE").await;
+    let mut params = coupon_params("primeira", "fixed_amount", 1_500);
+    params.usage_limit_per_customer = Some(1);
+    CouponModel::create_coupon(&db, &params).await.unwrap();
+
+    let maria = customer(&db).await;
+    let first = cart(&db, &maria, &[&camiseta]).await;
+    let first = CartModel::apply_coupon(&db, first.id, "PRIMEIRA")
+        .await
+        .unwrap();
+    checkout(&db, &maria, &first).await.unwrap();
+
+    let again = cart(&db, &maria, &[&camiseta]).await;
+    let err = CartModel::apply_coupon(&db, again.id, "PRIMEIRA")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+
+    let joao = customer(&db).await;
+    let other = cart(&db, &joao, &[&camiseta]).await;
+    let applied = CartModel::apply_coupon(&db, other.id, "PRIMEIRA")
+        .await
+        .unwrap();
+    assert_eq!(applied.discount, 1_500);
+}
+
+#[tokio::test]
+#[serial]
+async fn scoped_coupon_discounts_only_eligible_items() {
+    let db = boot().await.db;
+    let camiseta = camiseta(&db, "CAM-ESCOPO").await;
+    let bone = variant(&db, "BONE-ESCOPO").await;
+    price(&db, &bone, 5_000).await;
+    let mut params = coupon_params("camisetas", "percentage", 20);
+    params.product_ids = Some(vec![camiseta.product_id]);
+    params.min_subtotal = Some(12_000);
+    CouponModel::create_coupon(&db, &params).await.unwrap();
+
+    let maria = customer(&db).await;
+    let only_bone = cart(&db, &maria, &[&bone]).await;
+    assert!(CartModel::apply_coupon(&db, only_bone.id, "CAMISETAS")
+        .await
+        .is_err());
+
+    let both = cart(&db, &maria, &[&camiseta, &bone]).await;
+    let applied = CartModel::apply_coupon(&db, both.id, "CAMISETAS")
+        .await
+        .unwrap();
+    // 20% só sobre a camiseta
+    assert_eq!(applied.discount, 2_000);
+    assert_eq!(applied.total, 13_000);
+
+    // Abaixo do subtotal mínimo o cupom sai do carrinho
+    let items = CartModel::get_items(&db, both.id).await.unwrap();
+    let camiseta_line = items.iter().find(|i| i.variant_id == camiseta.id).unwrap();
+    CartModel::remove_item(&db, &applied, camiseta_line.id)
+        .await
+        .unwrap();
+    let recalculated = CartModel::recalculate_totals(&db, both.id).await.unwrap();
+    assert_eq!(recalculated.coupon_code, None);
+    assert_eq!(recalculated.discount, 0);
+}
@@ -0,0 +1,273 @@
+//! Regras dos models contra um banco SQLite em memória (`config/test.yaml`),
+//! com as migrations aplicadas no boot
+
+mod batches;
+mod coupons;
+mod order_edits;
+mod order_sequences;
+mod order_status;
+mod order_timeline;
+mod payment_events;
+mod payment_expiry;
+mod payment_providers;
+mod pricing;
+mod purchase_orders;
+mod refunds;
+mod returns;
+mod routing;
+mod shipments;
+mod shipping_rates;
+mod stock_movements;
+mod stock_reservations;
+mod stock_transfers;
+
+use loco_fast_store::{
+    app::App,
+    models::{
+        _entities::{
+            addresses, customers, items, order_items, orders, prices, product_variants, products,
+            stocks, warehouses,
+        },
+        users::{self, RegisterParams},
+    },
+};
+use loco_rs::{app::AppContext, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait};
+use uuid::Uuid;
+
+pub async fn boot() -> AppContext {
+    boot_test::<App>().await.unwrap().app_context
+}
+
+/// Colaborador do painel
+pub async fn user(db: &DatabaseConnection, name: &str) -> users::Model {
+    users::Model::create_with_password(
+        db,
+        &RegisterParams {
+            email: format!("{}@example.com", name.to_lowercase()),
+            password: "12341234".to_string(),
+            name: name.to_string(),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// Variante de um produto novo, sem estoque
+pub async fn variant(db: &DatabaseConnection, sku: &str) -> product_variants::Model {
+    let product = products::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        title: ActiveValue::set(format!("Produto {sku}")),
+        slug: ActiveValue::set(sku.to_lowercase()),
+        description: ActiveValue::set(String::new()),
+        handle: ActiveValue::set(sku.to_lowercase()),
+        status: ActiveValue::set("published".to_string()),
+        product_type: ActiveValue::set("physical".to_string()),
+        tags: ActiveValue::set(serde_json::json!([])),
+        metadata: ActiveValue::set(serde_json::json!({})),
+        featured: ActiveValue::set(false),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+    product_variants::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        product_id: ActiveValue::set(product.id),
+        sku: ActiveValue::set(sku.to_string()),
+        title: ActiveValue::set(sku.to_string()),
+        option_values: ActiveValue::set(serde_json::json!({})),
+        inventory_quantity: ActiveValue::set(0),
+        allow_backorder: ActiveValue::set(false),
+        metadata: ActiveValue::set(serde_json::json!({})),
+        sort_order: ActiveValue::set(0),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+/// Preço em BRL da variante, para qualquer quantidade
+pub async fn price(
+    db: &DatabaseConnection,
+    variant: &product_variants::Model,
+    amount: i64,
+) -> prices::Model {
+    prices::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        variant_id: ActiveValue::set(variant.id),
+        amount: ActiveValue::set(amount),
+        currency: ActiveValue::set("BRL".to_string()),
+        min_quantity: ActiveValue::set(1),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+/// Cliente da loja, sem conta
+pub async fn customer(db: &DatabaseConnection) -> customers::Model {
+    customers::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        email: ActiveValue::set(format!("{}@example.com", Uuid::new_v4())),
+        first_name: ActiveValue::set("Maria".to_string()),
+        last_name: ActiveValue::set("Silva".to_string()),
+        has_account: ActiveValue::set(false),
+        metadata: ActiveValue::set(serde_json::json!({})),
+        marketing_consent: ActiveValue::set(false),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+/// Depósito localizado pelo CEP
+pub async fn warehouse(
+    db: &DatabaseConnection,
+    name: &str,
+    postal_code: &str,
+) -> warehouses::Model {
+    warehouses::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        name: ActiveValue::set(name.to_string()),
+        latitude: ActiveValue::set(0.0),
+        longitude: ActiveValue::set(0.0),
+        postal_code: ActiveValue::set(Some(postal_code.to_string())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+/// Lote da variante com `quantity` unidades no depósito
+pub async fn stock(
+    db: &DatabaseConnection,
+    warehouse: &warehouses::Model,
+    variant: &product_variants::Model,
+    quantity: i32,
+) -> stocks::Model {
+    let item = items::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        variant_id: ActiveValue::set(variant.id),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+    let stock = stocks::ActiveModel {
+        warehouse_id: ActiveValue::set(warehouse.id),
+        item_id: ActiveValue::set(item.id),
+        quantity: ActiveValue::set(quantity),
+        reserved: ActiveValue::set(0),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    let current = product_variants::Entity::find_by_id(variant.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    let on_hand = current.inventory_quantity + quantity;
+    let mut active: product_variants::ActiveModel = current.into();
+    active.inventory_quantity = ActiveValue::set(on_hand);
+    active.update(db).await.unwrap();
+    stock
+}
+
+pub async fn reload_stock(db: &DatabaseConnection, stock: &stocks::Model) -> stocks::Model {
+    stocks::Entity::find_by_id(stock.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+pub async fn reload_order(db: &DatabaseConnection, order: &orders::Model) -> orders::Model {
+    orders::Entity::find_by_id(order.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+/// Pedido com as linhas (variante, quantidade, preço unitário), entregue no
+/// CEP informado. `payment_status` e `fulfillment_status` como no banco.
+pub async fn order(
+    db: &DatabaseConnection,
+    postal_code: &str,
+    lines: &[(&product_variants::Model, i32, i64)],
+    payment_status: &str,
+    fulfillment_status: &str,
+) -> (orders::Model, Vec<order_items::Model>) {
+    let customer = customer(db).await;
+    let address = addresses::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        customer_id: ActiveValue::set(customer.id),
+        first_name: ActiveValue::set("Maria".to_string()),
+        last_name: ActiveValue::set("Silva".to_string()),
+        address_line_1: ActiveValue::set("Rua A, 10".to_string()),
+        city: ActiveValue::set("Cidade".to_string()),
+        state: ActiveValue::set("UF".to_string()),
+        postal_code: ActiveValue::set(postal_code.to_string()),
+        country: ActiveValue::set("BR".to_string()),
+        is_default_shipping: ActiveValue::set(true),
+        is_default_billing: ActiveValue::set(true),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    let subtotal: i64 = lines.iter().map(|(_, q, p)| i64::from(*q) * p).sum();
+    let order = orders::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        customer_id: ActiveValue::set(customer.id),
+        order_number: ActiveValue::set(format!("T-{}", Uuid::new_v4().simple())),
+        status: ActiveValue::set("pending".to_string()),
+        payment_status: ActiveValue::set(payment_status.to_string()),
+        fulfillment_status: ActiveValue::set(fulfillment_status.to_string()),
+        currency: ActiveValue::set("BRL".to_string()),
+        subtotal: ActiveValue::set(subtotal),
+        tax: ActiveValue::set(0),
+        shipping: ActiveValue::set(0),
+        discount: ActiveValue::set(0),
+        total: ActiveValue::set(subtotal),
+        shipping_address_id: ActiveValue::set(Some(address.id)),
+        payment_provider: ActiveValue::set(Some("asaas".to_string())),
+        payment_data: ActiveValue::set(serde_json::json!({ "payment_id": "pay_original" })),
+        metadata: ActiveValue::set(serde_json::json!({})),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    let mut saved = Vec::new();
+    for (variant, quantity, unit_price) in lines {
+        saved.push(
+            order_items::ActiveModel {
+                pid: ActiveValue::set(Uuid::new_v4()),
+                order_id: ActiveValue::set(order.id),
+                variant_id: ActiveValue::set(Some(variant.id)),
+                title: ActiveValue::set(variant.title.clone()),
+                sku: ActiveValue::set(variant.sku.clone()),
+                quantity: ActiveValue::set(*quantity),
+                unit_price: ActiveValue::set(*unit_price),
+                total: ActiveValue::set(i64::from(*quantity) * unit_price),
+                metadata: ActiveValue::set(serde_json::json!({})),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .unwrap(),
+        );
+    }
+    (order, saved)
+}
@@ -0,0 +1,248 @@
+use loco_fast_store::models::{
+    _entities::{order_items, orders, stocks},
+    order_edits::{EditItemParams, EditOrderParams, Model as OrderEditModel},
+    order_status::PaymentStatus,
+    order_status_history::StatusChange,
+    orders::Model as OrderModel,
+    stock_reservations::{Model as StockReservationModel, ReservationLine},
+};
+use loco_rs::model::ModelError;
+use sea_orm::DatabaseConnection;
+use serial_test::serial;
+
+use super::{boot, order, price, reload_stock, stock, variant, warehouse};
+
+/// Pedido de 2 camisetas de R$ 50 com estoque reservado. Pago, as
+/// reservas já viraram baixa.
+async fn order_with_stock(
+    db: &DatabaseConnection,
+    sku: &str,
+    paid: bool,
+) -> (orders::Model, order_items::Model, stocks::Model) {
+    let camiseta = variant(db, sku).await;
+    price(db, &camiseta, 5_000).await;
+    let sp = warehouse(db, "SP", "01310-100").await;
+    let lot = stock(db, &sp, &camiseta, 10).await;
+    let (order, items) = order(
+        db,
+        "01310-100",
+        &[(&camiseta, 2, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+    StockReservationModel::reserve_for_order(
+        db,
+        order.id,
+        &[ReservationLine {
+            variant_id: camiseta.id,
+            quantity: 2,
+        }],
+    )
+    .await
+    .unwrap();
+    let order = if paid {
+        OrderModel::update_payment_status(
+            db,
+            order.id,
+            PaymentStatus::Paid,
+            None,
+            &StatusChange::system("webhook", None),
+        )
+        .await
+        .unwrap()
+    } else {
+        order
+    };
+    (order, items[0].clone(), lot)
+}
+
+fn quantity(item: &order_items::Model, quantity: i32) -> EditOrderParams {
+    EditOrderParams {
+        items: vec![EditItemParams {
+            order_item_pid: Some(item.pid),
+            variant_pid: None,
+            quantity,
+        }],
+        ..Default::default()
+    }
+}
+
+fn is_message(err: &ModelError) -> bool {
+    matches!(err, ModelError::Message(_))
+}
+
+#[tokio::test]
+#[serial]
+async fn paid_order_is_charged_for_more_and_refunded_for_less() {
+    let db = boot().await.db;
+    let (order, item, lot) = order_with_stock(&db, "CAM-EDITA", true).await;
+    assert_eq!(reload_stock(&db, &lot).await.quantity, 8);
+
+    let (edit, updated) = OrderEditModel::apply(&db, order.id, &quantity(&item, 3), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        (edit.settlement.as_str(), edit.settlement_status.as_str()),
+        ("charge", "pending")
+    );
+    assert_eq!((edit.previous_total, edit.new_total), (10_000, 15_000));
+    assert_eq!(edit.difference, 5_000);
+    assert_eq!(updated.total, 15_000);
+    // A unidade nova sai do estoque na hora
+    assert_eq!(reload_stock(&db, &lot).await.quantity, 7);
+
+    let params = EditOrderParams {
+        shipping: Some(1_000),
+        discount: Some(3_000),
+        ..quantity(&item, 1)
+    };
+    let (edit, updated) = OrderEditModel::apply(&db, order.id, &params, None)
+        .await
+        .unwrap();
+    assert_eq!(edit.settlement, "refund");
+    assert_eq!(edit.difference, -12_000);
+    assert_eq!(
+        (updated.subtotal, updated.shipping, updated.discount),
+        (5_000, 1_000, 3_000)
+    );
+    assert_eq!(updated.total, 3_000);
+    // As duas unidades removidas voltam para o lote de onde saíram
+    assert_eq!(reload_stock(&db, &lot).await.quantity, 9);
+    assert_eq!(
+        OrderEditModel::list_for_order(&db, order.id)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn open_order_reissues_the_charge_and_moves_its_reservations() {
+    let db = boot().await.db;
+    let (order, item, lot) = order_with_stock(&db, "CAM-REEMITE", false).await;
+
+    let (edit, updated) = OrderEditModel::apply(&db, order.id, &quantity(&item, 4), None)
+        .await
+        .unwrap();
+    assert_eq!(edit.settlement, "reissue");
+    assert_eq!(edit.payment_id.as_deref(), Some("pay_original"));
+    assert_eq!(updated.total, 20_000);
+    let lot = reload_stock(&db, &lot).await;
+    assert_eq!((lot.quantity, lot.reserved), (10, 4));
+
+    // Sem diferença de valor não há acerto
+    let params = EditOrderParams {
+        shipping: Some(2_000),
+        discount: Some(2_000),
+        ..Default::default()
+    };
+    let (edit, _) = OrderEditModel::apply(&db, order.id, &params, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        (edit.settlement.as_str(), edit.settlement_status.as_str()),
+        ("none", "none")
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn invalid_edits_leave_the_order_untouched() {
+    let db = boot().await.db;
+    let (order, item, lot) = order_with_stock(&db, "CAM-RECUSA", false).await;
+
+    let err = OrderEditModel::apply(&db, order.id, &quantity(&item, 2), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+    let err = OrderEditModel::apply(&db, order.id, &quantity(&item, 0), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+    // Mais do que o estoque disponível
+    let err = OrderEditModel::apply(&db, order.id, &quantity(&item, 11), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+    assert_eq!(reload_stock(&db, &lot).await.reserved, 2);
+
+    let camiseta = variant(&db, "CAM-SEPARADA").await;
+    let (fulfilled, items) = super::order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "paid",
+        "fulfilled",
+    )
+    .await;
+    let err = OrderEditModel::apply(&db, fulfilled.id, &quantity(&items[0], 2), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+    assert!(OrderEditModel::list_for_order(&db, order.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_settlement_is_retried_once_and_charge_follows_the_webhook() {
+    let db = boot().await.db;
+    let (order, item, _) = order_with_stock(&db, "CAM-ACERTO", true).await;
+    let (edit, order) = OrderEditModel::apply(&db, order.id, &quantity(&item, 3), None)
+        .await
+        .unwrap();
+
+    // Ainda pendente: nada a refazer
+    let err = OrderEditModel::begin_retry(&db, &order, edit.clone())
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+
+    let failed = OrderEditModel::settle(
+        &db,
+        edit,
+        "failed",
+        None,
+        None,
+        serde_json::json!({ "error": "gateway fora do ar" }),
+    )
+    .await
+    .unwrap();
+    let retrying = OrderEditModel::begin_retry(&db, &order, failed.clone())
+        .await
+        .unwrap();
+    assert_eq!(retrying.settlement_status, "pending");
+    // A segunda tentativa simultânea não reivindica o mesmo acerto
+    assert!(OrderEditModel::begin_retry(&db, &order, failed)
+        .await
+        .is_err());
+
+    let charged = OrderEditModel::settle(
+        &db,
+        retrying,
+        "pending",
+        Some("pay_edit".to_string()),
+        None,
+        serde_json::json!({}),
+    )
+    .await
+    .unwrap();
+    let found = OrderEditModel::find_by_payment_id(&db, "pay_edit")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.id, charged.id);
+    let awaiting = OrderEditModel::apply_charge_status(&db, charged, "awaiting")
+        .await
+        .unwrap();
+    assert_eq!(awaiting.settlement_status, "pending");
+    let done = OrderEditModel::apply_charge_status(&db, awaiting, "paid")
+        .await
+        .unwrap();
+    assert_eq!(done.settlement_status, "done");
+}
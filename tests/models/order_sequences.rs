@@ -0,0 +1,40 @@
+use loco_fast_store::models::{
+    _entities::orders,
+    order_sequences::{Model as OrderSequenceModel, OrderNumberConfig},
+};
+use sea_orm::{ActiveModelTrait, ActiveValue};
+use serial_test::serial;
+
+use super::{boot, order, variant};
+
+#[tokio::test]
+#[serial]
+async fn resync_continues_after_the_highest_order_number() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-SEQ").await;
+    let config = OrderNumberConfig {
+        prefix: "SEQ-".to_string(),
+        padding: 6,
+    };
+
+    // Importados: o maior já passou da largura do preenchimento
+    for number in ["SEQ-000009", "SEQ-1000000", "SEQ-000010"] {
+        let (created, _) = order(
+            &db,
+            "01310-100",
+            &[(&camiseta, 1, 5_000)],
+            "awaiting",
+            "not_fulfilled",
+        )
+        .await;
+        let mut active: orders::ActiveModel = created.into();
+        active.order_number = ActiveValue::set(number.to_string());
+        active.update(&db).await.unwrap();
+    }
+
+    OrderSequenceModel::resync(&db, &config).await.unwrap();
+    let next = OrderSequenceModel::next_value(&db, &config.prefix)
+        .await
+        .unwrap();
+    assert_eq!(next, 1_000_001);
+}
@@ -0,0 +1,227 @@
+use loco_fast_store::models::{
+    _entities::orders,
+    order_status::{FulfillmentStatus, OrderStatus, PaymentStatus, StatusAxis, TransitionError},
+    order_status_history::{Model as StatusHistoryModel, StatusChange},
+    orders::{Model as OrderModel, StatusUpdate},
+};
+use loco_rs::model::ModelError;
+use sea_orm::DatabaseConnection;
+use serial_test::serial;
+
+use super::{boot, order, reload_order, user, variant};
+
+async fn awaiting_order(db: &DatabaseConnection, sku: &str) -> orders::Model {
+    let camiseta = variant(db, sku).await;
+    order(
+        db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await
+    .0
+}
+
+fn painel(user_id: i32) -> StatusChange {
+    StatusChange::by_user(user_id, "painel", Some("Conferido".to_string()))
+}
+
+fn transition(err: &ModelError) -> &TransitionError {
+    TransitionError::from_model_error(err).unwrap_or_else(|| panic!("{err:?}"))
+}
+
+/// Histórico do pedido como (eixo, de, para)
+async fn history(db: &DatabaseConnection, order: &orders::Model) -> Vec<(String, String, String)> {
+    StatusHistoryModel::list_for_order(db, order.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|h| (h.axis, h.from_status, h.to_status))
+        .collect()
+}
+
+fn entry(axis: StatusAxis, from: &str, to: &str) -> (String, String, String) {
+    (axis.as_str().to_string(), from.to_string(), to.to_string())
+}
+
+#[tokio::test]
+#[serial]
+async fn unpaid_order_is_not_shipped_and_the_error_lists_the_allowed_states() {
+    let db = boot().await.db;
+    let ana = user(&db, "Ana").await;
+    let order = awaiting_order(&db, "CAM-ENVIO").await;
+
+    let err = OrderModel::update_status(&db, order.id, OrderStatus::Shipped, &painel(ana.id))
+        .await
+        .unwrap_err();
+    let refused = transition(&err);
+    assert_eq!(refused.axis, StatusAxis::Status);
+    assert_eq!(
+        (refused.from.as_str(), refused.to.as_str()),
+        ("pending", "shipped")
+    );
+    assert_eq!(refused.allowed, vec!["confirmed", "processing", "canceled"]);
+    let err = OrderModel::update_fulfillment_status(
+        &db,
+        order.id,
+        FulfillmentStatus::Fulfilled,
+        &painel(ana.id),
+    )
+    .await
+    .unwrap_err();
+    assert!(transition(&err).allowed.is_empty());
+
+    OrderModel::update_payment_status(
+        &db,
+        order.id,
+        PaymentStatus::Paid,
+        None,
+        &StatusChange::system("webhook", None),
+    )
+    .await
+    .unwrap();
+    for status in [OrderStatus::Confirmed, OrderStatus::Shipped] {
+        OrderModel::update_status(&db, order.id, status, &painel(ana.id))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        history(&db, &order).await,
+        vec![
+            entry(StatusAxis::PaymentStatus, "awaiting", "paid"),
+            entry(StatusAxis::Status, "pending", "confirmed"),
+            entry(StatusAxis::Status, "confirmed", "shipped"),
+        ]
+    );
+    let entries = StatusHistoryModel::list_for_order(&db, order.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        (entries[0].changed_by, entries[0].source.as_str()),
+        (None, "webhook")
+    );
+    assert_eq!(entries[1].changed_by, Some(ana.id));
+    assert_eq!(entries[1].reason.as_deref(), Some("Conferido"));
+}
+
+#[tokio::test]
+#[serial]
+async fn canceled_order_is_final() {
+    let db = boot().await.db;
+    let ana = user(&db, "Ana").await;
+    let order = awaiting_order(&db, "CAM-CANCELA").await;
+
+    let canceled = OrderModel::update_status(&db, order.id, OrderStatus::Canceled, &painel(ana.id))
+        .await
+        .unwrap();
+    assert!(canceled.canceled_at.is_some());
+
+    // Cancelar de novo não muda nada nem entra no histórico
+    OrderModel::update_status(&db, order.id, OrderStatus::Canceled, &painel(ana.id))
+        .await
+        .unwrap();
+    assert_eq!(history(&db, &order).await.len(), 1);
+
+    let err = OrderModel::update_status(&db, order.id, OrderStatus::Pending, &painel(ana.id))
+        .await
+        .unwrap_err();
+    assert!(transition(&err).allowed.is_empty(), "{err}");
+    let err = OrderModel::update_fulfillment_status(
+        &db,
+        order.id,
+        FulfillmentStatus::PartiallyFulfilled,
+        &painel(ana.id),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(transition(&err).axis, StatusAxis::FulfillmentStatus);
+}
+
+#[tokio::test]
+#[serial]
+async fn fulfilled_order_is_not_canceled_and_refunded_is_final() {
+    let db = boot().await.db;
+    let ana = user(&db, "Ana").await;
+    let camiseta = variant(&db, "CAM-SEPARADO").await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "paid",
+        "fulfilled",
+    )
+    .await;
+
+    let err = OrderModel::update_status(&db, order.id, OrderStatus::Canceled, &painel(ana.id))
+        .await
+        .unwrap_err();
+    assert!(!transition(&err).allowed.contains(&"canceled"));
+
+    for status in [PaymentStatus::PartiallyRefunded, PaymentStatus::Refunded] {
+        OrderModel::update_payment_status(&db, order.id, status, None, &painel(ana.id))
+            .await
+            .unwrap();
+    }
+    let err = OrderModel::update_payment_status(
+        &db,
+        order.id,
+        PaymentStatus::Paid,
+        None,
+        &painel(ana.id),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(transition(&err).from, "refunded");
+}
+
+#[tokio::test]
+#[serial]
+async fn status_update_is_all_or_nothing() {
+    let db = boot().await.db;
+    let ana = user(&db, "Ana").await;
+    let order = awaiting_order(&db, "CAM-LOTE").await;
+    OrderModel::update_status(&db, order.id, OrderStatus::Confirmed, &painel(ana.id))
+        .await
+        .unwrap();
+
+    // Pago e enviado na mesma chamada: o pagamento vale para o envio
+    let update = StatusUpdate {
+        payment_status: Some("paid".to_string()),
+        status: Some("shipped".to_string()),
+        ..Default::default()
+    };
+    let updated = OrderModel::apply_status_update(&db, order.id, &update, &painel(ana.id))
+        .await
+        .unwrap();
+    assert_eq!(
+        (updated.payment_status.as_str(), updated.status.as_str()),
+        ("paid", "shipped")
+    );
+
+    // A entrega inválida desfaz o estorno parcial pedido junto
+    let update = StatusUpdate {
+        payment_status: Some("partially_refunded".to_string()),
+        fulfillment_status: Some("delivered".to_string()),
+        ..Default::default()
+    };
+    let err = OrderModel::apply_status_update(&db, order.id, &update, &painel(ana.id))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        transition(&err).allowed,
+        vec!["partially_fulfilled", "fulfilled"]
+    );
+    assert_eq!(reload_order(&db, &order).await.payment_status, "paid");
+    assert_eq!(history(&db, &order).await.len(), 3);
+
+    let update = StatusUpdate {
+        status: Some("enviado".to_string()),
+        ..Default::default()
+    };
+    let err = OrderModel::apply_status_update(&db, order.id, &update, &painel(ana.id))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+}
@@ -0,0 +1,189 @@
+use chrono::{Duration, FixedOffset, Utc};
+use loco_fast_store::models::{
+    _entities::{order_shippings, orders, shipment_tracking_events},
+    order_notes::{mention_handles, CreateNoteParams, Model as NoteModel},
+    order_status::OrderStatus,
+    order_status_history::StatusChange,
+    order_timeline,
+    orders::Model as OrderModel,
+    store_collaborators::{AddCollaboratorParams, Model as CollaboratorModel},
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serial_test::serial;
+use uuid::Uuid;
+
+use super::{boot, order, user, variant};
+
+/// Usuário `<nome>@example.com` como colaborador ativo
+async fn collaborator(db: &DatabaseConnection, name: &str) -> CollaboratorModel {
+    let user = user(db, name).await;
+    CollaboratorModel::add_collaborator(
+        db,
+        &AddCollaboratorParams {
+            user_id: user.id,
+            role: "admin".to_string(),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+async fn pending_order(db: &DatabaseConnection, sku: &str) -> orders::Model {
+    let camiseta = variant(db, sku).await;
+    order(
+        db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await
+    .0
+}
+
+async fn note(
+    db: &DatabaseConnection,
+    order: &orders::Model,
+    author: &CollaboratorModel,
+    body: &str,
+) -> Result<Vec<String>, ModelError> {
+    let (_, mentioned) = NoteModel::create(
+        db,
+        order.id,
+        author,
+        &CreateNoteParams {
+            body: body.to_string(),
+        },
+    )
+    .await?;
+    let mut handles: Vec<String> = mentioned.iter().map(|c| c.handle()).collect();
+    handles.sort();
+    Ok(handles)
+}
+
+#[tokio::test]
+#[serial]
+async fn mentions_reach_only_other_known_collaborators() {
+    let db = boot().await.db;
+    let ana = collaborator(&db, "Ana").await;
+    let bruno = collaborator(&db, "Bruno").await;
+    let carla = collaborator(&db, "Carla").await;
+    let order = pending_order(&db, "CAM-NOTA").await;
+
+    assert_eq!(
+        mention_handles("@Bruno, veja com @carla@example.com (contato: sac@loja.com)"),
+        vec!["bruno", "carla@example.com"]
+    );
+
+    let mentioned = note(
+        &db,
+        &order,
+        &ana,
+        "@ana @bruno e @carla@example.com: cliente pediu troca. @zeca não existe",
+    )
+    .await
+    .unwrap();
+    assert_eq!(mentioned, vec!["bruno", "carla"]);
+
+    let err = note(&db, &order, &ana, "   ").await.unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+    let err = note(&db, &order, &ana, &"a".repeat(5_001))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+    assert_eq!(
+        NoteModel::list_for_order(&db, order.id)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // Só o mencionado marca a própria menção como lida
+    let unread = NoteModel::mentions_for(&db, bruno.id, true, 10)
+        .await
+        .unwrap();
+    assert_eq!(unread.len(), 1);
+    let (mention, _) = &unread[0];
+    let err = NoteModel::mark_mention_read(&db, mention.id, carla.id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ModelError::EntityNotFound), "{err:?}");
+    NoteModel::mark_mention_read(&db, mention.id, bruno.id)
+        .await
+        .unwrap();
+    assert!(NoteModel::mentions_for(&db, bruno.id, true, 10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        NoteModel::mentions_for(&db, bruno.id, false, 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn timeline_merges_status_notes_and_tracking_in_time_order() {
+    let db = boot().await.db;
+    let ana = collaborator(&db, "Ana").await;
+    let order = pending_order(&db, "CAM-LINHA").await;
+
+    OrderModel::update_status(
+        &db,
+        order.id,
+        OrderStatus::Confirmed,
+        &StatusChange::by_user(ana.user_id, "painel", None),
+    )
+    .await
+    .unwrap();
+    note(&db, &order, &ana, "Separar com embalagem de presente")
+        .await
+        .unwrap();
+
+    // Evento de rastreio sem fuso, no horário de Brasília, daqui a 1h
+    let shipping = order_shippings::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        order_id: ActiveValue::set(order.id),
+        carrier: ActiveValue::set("correios".to_string()),
+        status: ActiveValue::set("posted".to_string()),
+        provider_data: ActiveValue::set(serde_json::json!({})),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+    let brasilia = FixedOffset::west_opt(3 * 3600).unwrap();
+    let posted_at = (Utc::now() + Duration::hours(1)).with_timezone(&brasilia);
+    shipment_tracking_events::ActiveModel {
+        shipping_id: ActiveValue::set(shipping.id),
+        status: ActiveValue::set("posted".to_string()),
+        description: ActiveValue::set("Objeto postado".to_string()),
+        occurred_at: ActiveValue::set(posted_at.format("%d/%m/%Y %H:%M").to_string()),
+        source: ActiveValue::set("poll".to_string()),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    // O autor desativado continua identificado nas notas antigas
+    CollaboratorModel::deactivate(&db, ana.user_id)
+        .await
+        .unwrap();
+
+    let entries = order_timeline::for_order(&db, &order).await.unwrap();
+    let kinds: Vec<&str> = entries.iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, vec!["created", "status", "note", "tracking"]);
+    assert_eq!(entries[1].author.as_deref(), Some("Ana"));
+    assert_eq!(entries[1].data["to"], "confirmed");
+    assert_eq!(entries[2].author.as_deref(), Some("Ana"));
+    assert_eq!(entries[2].data["body"], "Separar com embalagem de presente");
+    assert_eq!(entries[3].title, "Objeto postado");
+    assert_eq!(entries[3].data["carrier"], "correios");
+    assert_eq!(entries[3].author, None);
+}
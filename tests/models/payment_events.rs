@@ -0,0 +1,154 @@
+use chrono::{Duration, Utc};
+use loco_fast_store::models::{
+    _entities::orders,
+    payment_events::{EventOutcome, Model as PaymentEventModel, NewPaymentEvent},
+};
+use sea_orm::DatabaseConnection;
+use serial_test::serial;
+
+use super::{boot, order, reload_order, variant};
+
+fn event(
+    order: &orders::Model,
+    event_id: &str,
+    payment_status: &str,
+    occurred_minutes_ago: Option<i64>,
+) -> NewPaymentEvent {
+    NewPaymentEvent {
+        provider: "asaas".to_string(),
+        event_id: event_id.to_string(),
+        event_type: format!("PAYMENT_{}", payment_status.to_uppercase()),
+        payment_id: Some("pay_original".to_string()),
+        order_id: Some(order.id),
+        payment_status: Some(payment_status.to_string()),
+        occurred_at: occurred_minutes_ago.map(|m| (Utc::now() - Duration::minutes(m)).into()),
+        payload: serde_json::json!({ "payment": { "id": "pay_original" } }),
+    }
+}
+
+async fn awaiting_order(db: &DatabaseConnection) -> orders::Model {
+    let camiseta = variant(db, "CAM-PIX").await;
+    order(
+        db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await
+    .0
+}
+
+#[tokio::test]
+#[serial]
+async fn redelivered_event_is_recorded_and_applied_once() {
+    let db = boot().await.db;
+    let order = awaiting_order(&db).await;
+    let received = event(&order, "evt_1", "paid", Some(1));
+
+    let (first, is_new) = PaymentEventModel::record(&db, &received).await.unwrap();
+    assert!(is_new);
+    let outcome = PaymentEventModel::process(&db, first).await.unwrap();
+    assert!(matches!(outcome, EventOutcome::Applied(ref o) if o.payment_status == "paid"));
+
+    let (again, is_new) = PaymentEventModel::record(&db, &received).await.unwrap();
+    assert!(!is_new);
+    assert_eq!(again.status, "processed");
+    let outcome = PaymentEventModel::process(&db, again).await.unwrap();
+    assert!(matches!(outcome, EventOutcome::Duplicate), "{outcome:?}");
+    assert_eq!(reload_order(&db, &order).await.payment_status, "paid");
+}
+
+#[tokio::test]
+#[serial]
+async fn event_older_than_the_last_applied_is_skipped() {
+    let db = boot().await.db;
+    let order = awaiting_order(&db).await;
+
+    let (paid, _) = PaymentEventModel::record(&db, &event(&order, "evt_paid", "paid", Some(5)))
+        .await
+        .unwrap();
+    PaymentEventModel::process(&db, paid).await.unwrap();
+
+    // Vencimento emitido antes do pagamento, entregue depois
+    let (late, _) =
+        PaymentEventModel::record(&db, &event(&order, "evt_overdue", "overdue", Some(30)))
+            .await
+            .unwrap();
+    let outcome = PaymentEventModel::process(&db, late).await.unwrap();
+    assert!(matches!(outcome, EventOutcome::Stale), "{outcome:?}");
+
+    let skipped = PaymentEventModel::find_by_event_id(&db, "evt_overdue")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(skipped.status, "skipped");
+    assert_eq!(reload_order(&db, &order).await.payment_status, "paid");
+}
+
+#[tokio::test]
+#[serial]
+async fn undated_event_does_not_undo_a_higher_status() {
+    let db = boot().await.db;
+    let order = awaiting_order(&db).await;
+
+    let (paid, _) = PaymentEventModel::record(&db, &event(&order, "evt_paid", "paid", None))
+        .await
+        .unwrap();
+    PaymentEventModel::process(&db, paid).await.unwrap();
+    let (created, _) =
+        PaymentEventModel::record(&db, &event(&order, "evt_created", "awaiting", None))
+            .await
+            .unwrap();
+
+    let outcome = PaymentEventModel::process(&db, created).await.unwrap();
+    assert!(matches!(outcome, EventOutcome::Stale), "{outcome:?}");
+    assert_eq!(reload_order(&db, &order).await.payment_status, "paid");
+}
+
+#[tokio::test]
+#[serial]
+async fn transition_refused_by_the_state_machine_is_skipped() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-CANC").await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "canceled",
+        "not_fulfilled",
+    )
+    .await;
+
+    let (paid, _) = PaymentEventModel::record(&db, &event(&order, "evt_paid", "paid", Some(1)))
+        .await
+        .unwrap();
+    let outcome = PaymentEventModel::process(&db, paid).await.unwrap();
+    assert!(matches!(outcome, EventOutcome::Rejected(_)), "{outcome:?}");
+
+    let skipped = PaymentEventModel::find_by_event_id(&db, "evt_paid")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(skipped.status, "skipped");
+    assert_eq!(reload_order(&db, &order).await.payment_status, "canceled");
+}
+
+#[tokio::test]
+#[serial]
+async fn concurrent_delivery_applies_the_event_once() {
+    let db = boot().await.db;
+    let order = awaiting_order(&db).await;
+    let received = event(&order, "evt_twice", "paid", Some(1));
+
+    // As duas entregas leram o evento ainda pendente
+    let (first, _) = PaymentEventModel::record(&db, &received).await.unwrap();
+    let (second, is_new) = PaymentEventModel::record(&db, &received).await.unwrap();
+    assert!(!is_new);
+    assert_eq!(second.status, "pending");
+
+    let outcome = PaymentEventModel::process(&db, first).await.unwrap();
+    assert!(matches!(outcome, EventOutcome::Applied(_)), "{outcome:?}");
+    let outcome = PaymentEventModel::process(&db, second).await.unwrap();
+    assert!(matches!(outcome, EventOutcome::Duplicate), "{outcome:?}");
+}
@@ -0,0 +1,155 @@
+use chrono::{Duration, Utc};
+use loco_fast_store::{
+    models::{
+        _entities::orders,
+        order_status::PaymentStatus,
+        order_status_history::StatusChange,
+        orders::Model as OrderModel,
+        payment_events::{Model as PaymentEventModel, NewPaymentEvent},
+    },
+    workers::payment_expiry::{PaymentExpiryWorker, PaymentExpiryWorkerArgs},
+};
+use loco_rs::{app::AppContext, prelude::BackgroundWorker};
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serial_test::serial;
+
+use super::{boot, order, reload_order, variant};
+
+fn days_ago(days: i64) -> String {
+    (Utc::now() - Duration::days(days))
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// Pedido com boleto emitido no checkout, vencido há `overdue_days` dias
+async fn boleto_order(db: &DatabaseConnection, overdue_days: i64) -> orders::Model {
+    std::env::set_var("PAYMENT_FAKE_ENABLED", "true");
+    let camiseta = variant(db, "CAM-BOLETO").await;
+    let (order, _) = order(
+        db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "pending",
+        "not_fulfilled",
+    )
+    .await;
+    let mut active: orders::ActiveModel = order.into();
+    active.payment_provider = ActiveValue::set(Some("fake".to_string()));
+    let order = active.update(db).await.unwrap();
+
+    OrderModel::update_payment_status(
+        db,
+        order.id,
+        PaymentStatus::Awaiting,
+        Some(serde_json::json!({
+            "provider": "fake",
+            "payment_id": "pay_original",
+            "bank_slip_url": "https://boletos.example.com/pay_original",
+            "due_date": days_ago(overdue_days),
+        })),
+        &StatusChange::system("checkout", None),
+    )
+    .await
+    .unwrap()
+}
+
+async fn deliver_webhook(
+    db: &DatabaseConnection,
+    order: &orders::Model,
+    event_id: &str,
+    payment_status: &str,
+    payment: serde_json::Value,
+) {
+    let (event, _) = PaymentEventModel::record(
+        db,
+        &NewPaymentEvent {
+            provider: "fake".to_string(),
+            event_id: event_id.to_string(),
+            event_type: format!("PAYMENT_{}", payment_status.to_uppercase()),
+            payment_id: Some("pay_original".to_string()),
+            order_id: Some(order.id),
+            payment_status: Some(payment_status.to_string()),
+            occurred_at: Some(Utc::now().into()),
+            payload: serde_json::json!({ "payment": payment }),
+        },
+    )
+    .await
+    .unwrap();
+    PaymentEventModel::process(db, event).await.unwrap();
+}
+
+async fn run_expiry(ctx: &AppContext) {
+    PaymentExpiryWorker::build(ctx)
+        .perform(PaymentExpiryWorkerArgs {
+            grace_days: Some(1),
+            limit: None,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn webhook_without_due_date_keeps_the_charge_data() {
+    let ctx = boot().await;
+    let order = boleto_order(&ctx.db, 5).await;
+
+    deliver_webhook(
+        &ctx.db,
+        &order,
+        "evt_overdue",
+        "overdue",
+        serde_json::json!({ "id": "pay_original", "status": "OVERDUE" }),
+    )
+    .await;
+
+    let overdue = reload_order(&ctx.db, &order).await;
+    assert_eq!(overdue.payment_status, "overdue");
+    assert_eq!(overdue.payment_due_date, order.payment_due_date);
+    assert!(overdue.payment_due_date.is_some());
+    assert_eq!(overdue.payment_data["payment_id"], "pay_original");
+    assert_eq!(
+        overdue.payment_data["bank_slip_url"],
+        "https://boletos.example.com/pay_original"
+    );
+    assert_eq!(overdue.payment_data["event_id"], "evt_overdue");
+
+    run_expiry(&ctx).await;
+    let expired = reload_order(&ctx.db, &order).await;
+    assert_eq!(expired.status, "canceled");
+    assert_eq!(expired.payment_status, "canceled");
+}
+
+#[tokio::test]
+#[serial]
+async fn due_date_sent_by_the_gateway_moves_the_expiry() {
+    let ctx = boot().await;
+    let order = boleto_order(&ctx.db, 5).await;
+
+    // Vencimento prorrogado no gateway
+    let extended = (Utc::now() + Duration::days(3))
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string();
+    deliver_webhook(
+        &ctx.db,
+        &order,
+        "evt_updated",
+        "awaiting",
+        serde_json::json!({ "id": "pay_original", "dueDate": extended }),
+    )
+    .await;
+
+    let updated = reload_order(&ctx.db, &order).await;
+    assert_eq!(
+        updated
+            .payment_due_date
+            .map(|d| d.format("%Y-%m-%d").to_string()),
+        Some(extended.clone())
+    );
+    assert_eq!(updated.payment_data["due_date"], extended);
+
+    run_expiry(&ctx).await;
+    assert_eq!(reload_order(&ctx.db, &order).await.status, "pending");
+}
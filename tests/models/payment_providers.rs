@@ -0,0 +1,194 @@
+use axum::http::HeaderMap;
+use loco_fast_store::payments::{
+    self, CardCharge, CardDetails, ChargeParams, PaymentCustomer, PaymentError, PaymentProvider,
+};
+use serial_test::serial;
+
+fn fake() -> Box<dyn PaymentProvider> {
+    std::env::set_var("PAYMENT_FAKE_ENABLED", "true");
+    payments::provider_for("fake").unwrap()
+}
+
+fn charge(method: &str, card: Option<CardCharge>) -> ChargeParams {
+    ChargeParams {
+        order_reference: "pedido-1".to_string(),
+        order_number: "T-1".to_string(),
+        amount_cents: 10_000,
+        method: method.to_string(),
+        description: "Pedido T-1".to_string(),
+        due_date: None,
+        customer: PaymentCustomer {
+            name: "Maria".to_string(),
+            email: "maria@example.com".to_string(),
+            phone: None,
+            external_reference: "cliente-1".to_string(),
+            provider_customer_id: None,
+        },
+        card,
+    }
+}
+
+/// Cartão novo digitado no checkout, em uma parcela
+fn new_card(number: &str) -> CardCharge {
+    CardCharge {
+        installments: 1,
+        installment_cents: 10_000,
+        token: None,
+        card: Some(CardDetails {
+            holder_name: "MARIA".to_string(),
+            number: number.to_string(),
+            expiry_month: "12".to_string(),
+            expiry_year: "2030".to_string(),
+            ccv: "123".to_string(),
+        }),
+        holder: None,
+        remote_ip: "127.0.0.1".to_string(),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn registry_only_serves_known_and_enabled_providers() {
+    std::env::set_var("PAYMENT_FAKE_ENABLED", "false");
+    assert!(payments::provider_for("fake").is_none());
+    assert!(payments::provider_for("paypal").is_none());
+
+    assert_eq!(fake().name(), "fake");
+    assert!(payments::PROVIDERS.contains(&"fake"));
+}
+
+#[tokio::test]
+#[serial]
+async fn pix_and_boleto_charges_wait_for_payment() {
+    let provider = fake();
+
+    let pix = provider.create_charge(charge("PIX", None)).await.unwrap();
+    assert_eq!(pix.status, "awaiting");
+    assert_eq!(pix.provider_id, "fake_pay_pedido-1");
+    assert_eq!(
+        pix.provider_customer_id.as_deref(),
+        Some("fake_cus_cliente-1")
+    );
+    assert!(pix.pix_qr_code.is_some());
+    assert!(pix.due_date.is_some());
+
+    let boleto = provider
+        .create_charge(charge("BOLETO", None))
+        .await
+        .unwrap();
+    assert_eq!(boleto.status, "awaiting");
+    assert!(boleto.bank_slip_url.is_some());
+    assert!(boleto.pix_qr_code.is_none());
+
+    let err = provider
+        .create_charge(charge("CHEQUE", None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PaymentError::Rejected(_)), "{err}");
+}
+
+#[tokio::test]
+#[serial]
+async fn card_is_paid_at_once_and_saved_as_a_token() {
+    let provider = fake();
+
+    let paid = provider
+        .create_charge(charge("CREDIT_CARD", Some(new_card("4111 1111 1111 1111"))))
+        .await
+        .unwrap();
+    assert_eq!(paid.status, "paid");
+    let saved = paid.saved_card.unwrap();
+    assert_eq!(
+        (saved.token.as_str(), saved.last4.as_str()),
+        ("fake_tok_1111", "1111")
+    );
+
+    // A próxima compra usa o token salvo
+    let mut with_token = new_card("");
+    with_token.card = None;
+    with_token.token = Some(saved.token);
+    let again = provider
+        .create_charge(charge("CREDIT_CARD", Some(with_token)))
+        .await
+        .unwrap();
+    assert_eq!(again.status, "paid");
+    assert!(again.saved_card.is_none());
+
+    let err = provider
+        .create_charge(charge("CREDIT_CARD", Some(new_card("4000000000000002"))))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PaymentError::Rejected(_)), "{err}");
+    let err = provider
+        .create_charge(charge("CREDIT_CARD", None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PaymentError::Rejected(_)), "{err}");
+}
+
+#[tokio::test]
+#[serial]
+async fn capture_cancel_and_refund_are_accepted() {
+    let provider = fake();
+
+    assert_eq!(provider.capture("fake_pay_1").await.unwrap(), "paid");
+    let canceled = provider.cancel("fake_pay_1").await.unwrap();
+    assert_eq!(canceled["deleted"], true);
+
+    let partial = provider
+        .refund("fake_pay_1", Some(2_500), Some("Troca"))
+        .await
+        .unwrap();
+    assert_eq!(partial["status"], "PARTIALLY_REFUNDED");
+    assert_eq!(partial["value"], 2_500);
+    let full = provider.refund("fake_pay_1", None, None).await.unwrap();
+    assert_eq!(full["status"], "REFUNDED");
+    assert!(full["value"].is_null());
+}
+
+#[tokio::test]
+#[serial]
+async fn webhook_status_comes_from_the_event_unless_informed() {
+    let provider = fake();
+    let headers = HeaderMap::new();
+
+    let received = provider
+        .parse_webhook(
+            &headers,
+            &serde_json::json!({
+                "id": "evt_1",
+                "event": "PAYMENT_RECEIVED",
+                "payment_id": "fake_pay_pedido-1",
+                "order_reference": "pedido-1",
+                "occurred_at": "2026-03-06T12:00:00-03:00",
+            }),
+        )
+        .unwrap();
+    assert_eq!(received.event_id, "evt_1");
+    assert_eq!(received.payment_status.as_deref(), Some("paid"));
+    assert_eq!(received.order_reference.as_deref(), Some("pedido-1"));
+    assert!(received.occurred_at.is_some());
+
+    let informed = provider
+        .parse_webhook(
+            &headers,
+            &serde_json::json!({ "id": "evt_2", "event": "PAYMENT_UPDATED", "status": "overdue" }),
+        )
+        .unwrap();
+    assert_eq!(informed.payment_status.as_deref(), Some("overdue"));
+    let unknown = provider
+        .parse_webhook(
+            &headers,
+            &serde_json::json!({ "id": "evt_3", "event": "SUBSCRIPTION_CREATED" }),
+        )
+        .unwrap();
+    assert_eq!(unknown.payment_status, None);
+
+    let err = provider
+        .parse_webhook(
+            &headers,
+            &serde_json::json!({ "event": "PAYMENT_RECEIVED" }),
+        )
+        .unwrap_err();
+    assert!(matches!(err, PaymentError::Parse(_)), "{err}");
+}
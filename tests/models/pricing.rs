@@ -0,0 +1,254 @@
+use chrono::{Duration, Utc};
+use loco_fast_store::{
+    models::{
+        _entities::{prices, product_variants},
+        carts::Model as CartModel,
+    },
+    services::pricing::{resolve_price, PriceQuery},
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serial_test::serial;
+use uuid::Uuid;
+
+use super::{boot, price, variant};
+
+/// Linha de preço em BRL: região, faixa de quantidade e vigência
+struct Row {
+    amount: i64,
+    region: Option<&'static str>,
+    min_quantity: i32,
+    max_quantity: Option<i32>,
+    /// Início e fim da vigência, em dias a partir de hoje
+    window: Option<(i64, i64)>,
+}
+
+impl Row {
+    fn base(amount: i64) -> Self {
+        Self {
+            amount,
+            region: None,
+            min_quantity: 1,
+            max_quantity: None,
+            window: None,
+        }
+    }
+}
+
+async fn insert(db: &DatabaseConnection, variant: &product_variants::Model, row: Row) {
+    let now = Utc::now();
+    prices::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        variant_id: ActiveValue::set(variant.id),
+        amount: ActiveValue::set(row.amount),
+        currency: ActiveValue::set("BRL".to_string()),
+        region: ActiveValue::set(row.region.map(String::from)),
+        min_quantity: ActiveValue::set(row.min_quantity),
+        max_quantity: ActiveValue::set(row.max_quantity),
+        starts_at: ActiveValue::set(row.window.map(|(s, _)| (now + Duration::days(s)).into())),
+        ends_at: ActiveValue::set(row.window.map(|(_, e)| (now + Duration::days(e)).into())),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+}
+
+async fn unit_price(
+    db: &DatabaseConnection,
+    variant: &product_variants::Model,
+    quantity: i32,
+    region: Option<&str>,
+) -> i64 {
+    resolve_price(db, &PriceQuery::now(variant.id, "BRL", quantity, region))
+        .await
+        .unwrap()
+        .unit_price
+}
+
+#[tokio::test]
+#[serial]
+async fn regional_price_beats_the_global_one() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-REGIAO").await;
+    price(&db, &camiseta, 10_000).await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            region: Some("NE"),
+            ..Row::base(11_000)
+        },
+    )
+    .await;
+    // Faixa de atacado global: mais específica, mas sem a região
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            min_quantity: 10,
+            ..Row::base(8_000)
+        },
+    )
+    .await;
+
+    assert_eq!(unit_price(&db, &camiseta, 1, None).await, 10_000);
+    assert_eq!(unit_price(&db, &camiseta, 1, Some("ne")).await, 11_000);
+    assert_eq!(unit_price(&db, &camiseta, 10, None).await, 8_000);
+    assert_eq!(unit_price(&db, &camiseta, 10, Some("NE")).await, 11_000);
+}
+
+#[tokio::test]
+#[serial]
+async fn most_specific_tier_wins_within_its_range() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-FAIXA").await;
+    price(&db, &camiseta, 10_000).await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            min_quantity: 5,
+            max_quantity: Some(9),
+            ..Row::base(9_000)
+        },
+    )
+    .await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            min_quantity: 10,
+            ..Row::base(8_000)
+        },
+    )
+    .await;
+
+    assert_eq!(unit_price(&db, &camiseta, 4, None).await, 10_000);
+    assert_eq!(unit_price(&db, &camiseta, 5, None).await, 9_000);
+    assert_eq!(unit_price(&db, &camiseta, 9, None).await, 9_000);
+    assert_eq!(unit_price(&db, &camiseta, 50, None).await, 8_000);
+}
+
+#[tokio::test]
+#[serial]
+async fn running_promotion_beats_the_permanent_price() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-PROMO").await;
+    price(&db, &camiseta, 10_000).await;
+    // Promoção encerrada e uma futura não valem
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            window: Some((-10, -1)),
+            ..Row::base(5_000)
+        },
+    )
+    .await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            window: Some((1, 10)),
+            ..Row::base(6_000)
+        },
+    )
+    .await;
+    assert_eq!(unit_price(&db, &camiseta, 1, None).await, 10_000);
+
+    // Com duas vigentes, vale a que começou por último, mesmo mais cara
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            window: Some((-5, 5)),
+            ..Row::base(7_000)
+        },
+    )
+    .await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            window: Some((-2, 5)),
+            ..Row::base(7_500)
+        },
+    )
+    .await;
+    assert_eq!(unit_price(&db, &camiseta, 1, None).await, 7_500);
+}
+
+#[tokio::test]
+#[serial]
+async fn variant_without_an_applicable_price_is_not_found() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-SEM-PRECO").await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            region: Some("SUL"),
+            ..Row::base(9_000)
+        },
+    )
+    .await;
+
+    let err = resolve_price(&db, &PriceQuery::now(camiseta.id, "BRL", 1, None))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ModelError::EntityNotFound), "{err:?}");
+}
+
+#[tokio::test]
+#[serial]
+async fn cart_lines_are_repriced_by_quantity_and_region() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-CARRINHO").await;
+    price(&db, &camiseta, 10_000).await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            min_quantity: 3,
+            ..Row::base(9_000)
+        },
+    )
+    .await;
+    insert(
+        &db,
+        &camiseta,
+        Row {
+            region: Some("NE"),
+            ..Row::base(9_500)
+        },
+    )
+    .await;
+
+    let cart = CartModel::create_cart(&db, "sessao", None, None, None)
+        .await
+        .unwrap();
+    let line = CartModel::add_item(&db, &cart, camiseta.id, 1)
+        .await
+        .unwrap();
+    assert_eq!(line.unit_price, 10_000);
+
+    // Adicionar mais unidades muda a faixa da linha inteira
+    let line = CartModel::add_item(&db, &cart, camiseta.id, 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        (line.quantity, line.unit_price, line.total),
+        (3, 9_000, 27_000)
+    );
+
+    let line = CartModel::update_item_quantity(&db, &cart, line.id, 2)
+        .await
+        .unwrap();
+    assert_eq!((line.unit_price, line.total), (10_000, 20_000));
+
+    let cart = CartModel::set_region(&db, cart, "NE").await.unwrap();
+    assert_eq!(cart.subtotal, 19_000);
+    let items = CartModel::get_items(&db, cart.id).await.unwrap();
+    assert_eq!(items[0].unit_price, 9_500);
+}
@@ -0,0 +1,235 @@
+use chrono::{Duration, Utc};
+use loco_fast_store::models::{
+    _entities::{product_variants, purchase_order_items, stocks, warehouses},
+    purchase_orders::{
+        CreatePurchaseOrderParams, Model as PurchaseOrderModel, PurchaseOrderItemParams,
+        ReceivePurchaseOrderParams, ReceivedPurchaseItemParams,
+    },
+    stock_movements::Model as MovementModel,
+    suppliers::{Model as SupplierModel, SupplierParams},
+};
+use loco_rs::model::ModelError;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serial_test::serial;
+
+use super::{boot, user, variant, warehouse};
+
+/// Compra de 10 camisetas a R$ 20 e 10 bonés a R$ 10, com R$ 24 de frete e
+/// R$ 6 de despesas
+async fn purchase(
+    db: &DatabaseConnection,
+    warehouse: &warehouses::Model,
+    camiseta: &product_variants::Model,
+    bone: &product_variants::Model,
+) -> PurchaseOrderModel {
+    let supplier = SupplierModel::create(
+        db,
+        &SupplierParams {
+            name: "Malharia Sul".to_string(),
+            document: Some("12.345.678/0001-90".to_string()),
+            email: None,
+            phone: None,
+            notes: None,
+        },
+    )
+    .await
+    .unwrap();
+    PurchaseOrderModel::create(
+        db,
+        &CreatePurchaseOrderParams {
+            supplier_id: supplier.id,
+            warehouse_id: warehouse.id,
+            supplier_reference: Some("NF 4512".to_string()),
+            freight_cost: 2_400,
+            other_costs: 600,
+            expected_at: None,
+            notes: None,
+            items: vec![
+                PurchaseOrderItemParams {
+                    variant_id: camiseta.id,
+                    quantity: 10,
+                    unit_cost: 2_000,
+                },
+                PurchaseOrderItemParams {
+                    variant_id: bone.id,
+                    quantity: 10,
+                    unit_cost: 1_000,
+                },
+            ],
+        },
+        None,
+    )
+    .await
+    .unwrap()
+}
+
+fn received(
+    line: &purchase_order_items::Model,
+    quantity: i32,
+    expires_in: Option<i64>,
+) -> ReceivedPurchaseItemParams {
+    ReceivedPurchaseItemParams {
+        purchase_order_item_id: line.id,
+        quantity,
+        batch: Some(format!("L-{}", line.id)),
+        expiration: expires_in.map(|days| Utc::now().date_naive() + Duration::days(days)),
+    }
+}
+
+fn receipt(items: Vec<ReceivedPurchaseItemParams>) -> ReceivePurchaseOrderParams {
+    ReceivePurchaseOrderParams { items, notes: None }
+}
+
+async fn inventory(db: &DatabaseConnection, variant: &product_variants::Model) -> i32 {
+    product_variants::Entity::find_by_id(variant.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+        .inventory_quantity
+}
+
+#[tokio::test]
+#[serial]
+async fn partial_receipt_enters_stock_at_the_landed_cost() {
+    let db = boot().await.db;
+    let estoquista = user(&db, "Estoquista").await;
+    let camiseta = variant(&db, "CAM-COMPRA").await;
+    let bone = variant(&db, "BONE-COMPRA").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let po = purchase(&db, &sp, &camiseta, &bone).await;
+    assert_eq!(po.status, "open");
+
+    // R$ 30 rateados pelo valor: R$ 20 para as camisetas, R$ 10 para os bonés
+    let lines = po.items(&db).await.unwrap();
+    let landed: Vec<i64> = lines.iter().map(|l| l.landed_unit_cost).collect();
+    assert_eq!(landed, vec![2_200, 1_100]);
+
+    let po = PurchaseOrderModel::receive(
+        &db,
+        po,
+        &receipt(vec![received(&lines[0], 4, Some(90))]),
+        Some(estoquista.id),
+    )
+    .await
+    .unwrap();
+    assert_eq!(po.status, "partially_received");
+    assert!(po.received_at.is_none());
+    let lines = po.items(&db).await.unwrap();
+    assert_eq!(lines[0].received_quantity, 4);
+    assert_eq!(inventory(&db, &camiseta).await, 4);
+
+    let receipts = po.receipts(&db).await.unwrap();
+    assert_eq!(receipts.len(), 1);
+    let (first, batch) = &receipts[0];
+    let batch = batch.as_ref().unwrap();
+    assert_eq!(batch.landed_cost, Some(2_200));
+    assert_eq!(batch.batch, Some(format!("L-{}", lines[0].id)));
+    assert_eq!(first.landed_unit_cost, 2_200);
+    let stock = stocks::Entity::find_by_id(first.stock_id.unwrap())
+        .one(&db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((stock.warehouse_id, stock.quantity), (sp.id, 4));
+    let ledger = MovementModel::list_for_stock(&db, stock.id, None, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(ledger.len(), 1);
+    assert_eq!(ledger[0].kind, "receipt");
+    assert_eq!(ledger[0].reference, Some(po.pid.to_string()));
+    assert_eq!(ledger[0].created_by, Some(estoquista.id));
+
+    // Só cabem as 6 que faltam
+    let err = PurchaseOrderModel::receive(
+        &db,
+        po.clone(),
+        &receipt(vec![received(&lines[0], 7, None)]),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+
+    let po = PurchaseOrderModel::receive(
+        &db,
+        po,
+        &receipt(vec![
+            received(&lines[0], 6, Some(120)),
+            received(&lines[1], 10, None),
+        ]),
+        Some(estoquista.id),
+    )
+    .await
+    .unwrap();
+    assert_eq!(po.status, "received");
+    assert!(po.received_at.is_some());
+    assert_eq!(inventory(&db, &camiseta).await, 10);
+    assert_eq!(inventory(&db, &bone).await, 10);
+    // Cada conferência é um lote próprio
+    let receipts = po.receipts(&db).await.unwrap();
+    let costs: Vec<(i32, Option<i64>)> = receipts
+        .iter()
+        .map(|(r, batch)| (r.quantity, batch.as_ref().and_then(|b| b.landed_cost)))
+        .collect();
+    assert_eq!(
+        costs,
+        vec![(4, Some(2_200)), (6, Some(2_200)), (10, Some(1_100))]
+    );
+
+    let err = PurchaseOrderModel::receive(
+        &db,
+        po.clone(),
+        &receipt(vec![received(&lines[1], 1, None)]),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+    let err = PurchaseOrderModel::cancel(&db, po).await.unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+}
+
+#[tokio::test]
+#[serial]
+async fn rejected_receipt_changes_nothing_and_cancel_keeps_what_arrived() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-CANCELA").await;
+    let bone = variant(&db, "BONE-CANCELA").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let po = purchase(&db, &sp, &camiseta, &bone).await;
+    let lines = po.items(&db).await.unwrap();
+
+    // O boné vencido derruba a conferência inteira, camisetas incluídas
+    let err = PurchaseOrderModel::receive(
+        &db,
+        po.clone(),
+        &receipt(vec![
+            received(&lines[0], 5, None),
+            received(&lines[1], 5, Some(-1)),
+        ]),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ModelError::Message(_)), "{err:?}");
+    assert_eq!(inventory(&db, &camiseta).await, 0);
+    assert!(po.receipts(&db).await.unwrap().is_empty());
+    assert_eq!(po.items(&db).await.unwrap()[0].received_quantity, 0);
+
+    let po =
+        PurchaseOrderModel::receive(&db, po, &receipt(vec![received(&lines[0], 5, None)]), None)
+            .await
+            .unwrap();
+    let po = PurchaseOrderModel::cancel(&db, po).await.unwrap();
+    assert_eq!(po.status, "canceled");
+    assert!(po.canceled_at.is_some());
+    assert_eq!(inventory(&db, &camiseta).await, 5);
+    assert_eq!(
+        PurchaseOrderModel::list(&db, Some("canceled"), None, Some(sp.id), None, 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
@@ -0,0 +1,259 @@
+use loco_fast_store::{
+    models::{
+        _entities::{order_edits, orders},
+        refunds::{CreateRefundParams, Model as RefundModel},
+    },
+    services::refunds::{issue_refund, RefundOutcome},
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serial_test::serial;
+use uuid::Uuid;
+
+use super::{boot, order, reload_order, user, variant};
+
+fn amount(amount: Option<i64>) -> CreateRefundParams {
+    CreateRefundParams {
+        amount,
+        reason: None,
+        restock: false,
+        items: vec![],
+    }
+}
+
+async fn paid_order(db: &DatabaseConnection, sku: &str, total: i64) -> orders::Model {
+    let camiseta = variant(db, sku).await;
+    order(
+        db,
+        "01310-100",
+        &[(&camiseta, 1, total)],
+        "paid",
+        "not_fulfilled",
+    )
+    .await
+    .0
+}
+
+/// Edição paga com cobrança adicional de `difference`, já somada ao total
+async fn paid_edit_charge(
+    db: &DatabaseConnection,
+    order: &orders::Model,
+    difference: i64,
+) -> orders::Model {
+    order_edits::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        order_id: ActiveValue::set(order.id),
+        changes: ActiveValue::set(serde_json::json!([])),
+        previous_total: ActiveValue::set(order.total),
+        new_total: ActiveValue::set(order.total + difference),
+        difference: ActiveValue::set(difference),
+        settlement: ActiveValue::set("charge".to_string()),
+        settlement_status: ActiveValue::set("done".to_string()),
+        payment_id: ActiveValue::set(Some("pay_edit".to_string())),
+        settlement_data: ActiveValue::set(serde_json::json!({})),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+    let mut active: orders::ActiveModel = order.clone().into();
+    active.total = ActiveValue::set(order.total + difference);
+    active.update(db).await.unwrap()
+}
+
+fn is_message(err: &ModelError) -> bool {
+    matches!(err, ModelError::Message(_))
+}
+
+#[tokio::test]
+#[serial]
+async fn refunds_are_capped_by_what_is_left_to_refund() {
+    let db = boot().await.db;
+    let order = paid_order(&db, "CAM-EST", 10_000).await;
+
+    let first = RefundModel::start(&db, order.id, &amount(Some(6_000)), None)
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 1);
+    assert_eq!(
+        first[0].provider_payment_id.as_deref(),
+        Some("pay_original")
+    );
+
+    // O estorno pendente já conta contra o saldo
+    let err = RefundModel::start(&db, order.id, &amount(Some(5_000)), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+
+    let (_, updated) = RefundModel::complete(&db, first[0].clone(), serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(updated.payment_status, "partially_refunded");
+
+    let rest = RefundModel::start(&db, order.id, &amount(None), None)
+        .await
+        .unwrap();
+    assert_eq!(rest.iter().map(|r| r.amount).sum::<i64>(), 4_000);
+    RefundModel::complete(&db, rest[0].clone(), serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(reload_order(&db, &order).await.payment_status, "refunded");
+
+    let err = RefundModel::start(&db, order.id, &amount(Some(1)), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+}
+
+#[tokio::test]
+#[serial]
+async fn installment_interest_can_be_refunded() {
+    let db = boot().await.db;
+    let order = paid_order(&db, "CAM-JUROS", 10_000).await;
+    // Cartão em 10x com R$ 6 de juros
+    let mut active: orders::ActiveModel = order.into();
+    active.payment_data = ActiveValue::set(serde_json::json!({
+        "payment_id": "pay_original",
+        "amount_cents": 10_600,
+        "installments": 10,
+        "interest_cents": 600,
+    }));
+    let order = active.update(&db).await.unwrap();
+
+    assert_eq!(
+        RefundModel::charged_amount(&db, &order).await.unwrap(),
+        10_600
+    );
+    let full = RefundModel::start(&db, order.id, &amount(None), None)
+        .await
+        .unwrap();
+    assert_eq!(full[0].amount, 10_600);
+    RefundModel::complete(&db, full[0].clone(), serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(reload_order(&db, &order).await.payment_status, "refunded");
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_refund_frees_its_balance() {
+    let db = boot().await.db;
+    let order = paid_order(&db, "CAM-FAIL", 10_000).await;
+
+    let refused = RefundModel::start(&db, order.id, &amount(None), None)
+        .await
+        .unwrap();
+    RefundModel::fail(&db, refused[0].clone(), "saldo insuficiente")
+        .await
+        .unwrap();
+
+    let retried = RefundModel::start(&db, order.id, &amount(None), None)
+        .await
+        .unwrap();
+    assert_eq!(retried[0].amount, 10_000);
+}
+
+#[tokio::test]
+#[serial]
+async fn refund_larger_than_a_charge_is_split_across_charges() {
+    let db = boot().await.db;
+    let order = paid_order(&db, "CAM-EDIT", 10_000).await;
+    let order = paid_edit_charge(&db, &order, 2_000).await;
+
+    let parts = RefundModel::start(&db, order.id, &amount(Some(3_000)), None)
+        .await
+        .unwrap();
+    let split: Vec<(Option<&str>, i64)> = parts
+        .iter()
+        .map(|r| (r.provider_payment_id.as_deref(), r.amount))
+        .collect();
+    assert_eq!(
+        split,
+        [(Some("pay_edit"), 2_000), (Some("pay_original"), 1_000)]
+    );
+
+    // A cobrança da edição já foi toda estornada: o resto sai da original
+    let err = RefundModel::start(&db, order.id, &amount(Some(9_001)), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+    let rest = RefundModel::start(&db, order.id, &amount(None), None)
+        .await
+        .unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0].provider_payment_id.as_deref(), Some("pay_original"));
+    assert_eq!(rest[0].amount, 9_000);
+}
+
+#[tokio::test]
+#[serial]
+async fn unpaid_order_is_not_refunded() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-PEND").await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+
+    let err = RefundModel::start(&db, order.id, &amount(None), None)
+        .await
+        .unwrap_err();
+    assert!(is_message(&err), "{err:?}");
+}
+
+/// Pedido pago pelo gateway de testes
+async fn paid_with_fake_gateway(db: &DatabaseConnection, sku: &str) -> orders::Model {
+    std::env::set_var("PAYMENT_FAKE_ENABLED", "true");
+    let order = paid_order(db, sku, 10_000).await;
+    let mut active: orders::ActiveModel = order.into();
+    active.payment_provider = ActiveValue::set(Some("fake".to_string()));
+    active.update(db).await.unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn gateway_refund_is_issued_per_charge() {
+    let db = boot().await.db;
+    let admin = user(&db, "Financeiro").await;
+    let order = paid_with_fake_gateway(&db, "CAM-GW").await;
+    let order = paid_edit_charge(&db, &order, 2_000).await;
+
+    let outcome = issue_refund(&db, &order, &amount(None), admin.id)
+        .await
+        .unwrap();
+    let RefundOutcome::Done(refunds, updated) = outcome else {
+        panic!("estorno recusado");
+    };
+    assert_eq!(refunds.len(), 2);
+    assert!(refunds.iter().all(|r| r.status == "done"));
+    // Cada cobrança estornada por inteiro: sem valor no pedido ao gateway
+    assert!(refunds.iter().all(|r| r.raw_data["value"].is_null()));
+    assert_eq!(updated.payment_status, "refunded");
+}
+
+#[tokio::test]
+#[serial]
+async fn refund_without_a_configured_gateway_is_rejected() {
+    let db = boot().await.db;
+    let admin = user(&db, "Financeiro").await;
+    let order = paid_with_fake_gateway(&db, "CAM-SEMGW").await;
+    let mut active: orders::ActiveModel = order.into();
+    active.payment_provider = ActiveValue::set(Some("desconhecido".to_string()));
+    let order = active.update(&db).await.unwrap();
+
+    let outcome = issue_refund(&db, &order, &amount(Some(1_000)), admin.id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        outcome,
+        RefundOutcome::Rejected("UNSUPPORTED_PROVIDER", _)
+    ));
+    let refunds = RefundModel::list_for_order(&db, order.id).await.unwrap();
+    assert!(refunds.iter().all(|r| r.status == "failed"));
+    assert_eq!(reload_order(&db, &order).await.payment_status, "paid");
+}
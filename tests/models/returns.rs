@@ -0,0 +1,211 @@
+use loco_fast_store::models::{
+    _entities::{order_items, orders},
+    refunds::{CreateRefundParams, Model as RefundModel},
+    returns::{
+        InspectItemParams, Model as ReturnModel, OpenReturnParams, ReceiveReturnParams,
+        ReturnItemParams,
+    },
+};
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serial_test::serial;
+
+use super::{boot, order, reload_stock, stock, user, variant, warehouse};
+
+fn open_params(resolution: &str, item: &order_items::Model, quantity: i32) -> OpenReturnParams {
+    OpenReturnParams {
+        reason: "Tamanho errado".to_string(),
+        resolution: Some(resolution.to_string()),
+        items: vec![ReturnItemParams {
+            order_item_pid: item.pid,
+            quantity,
+            reason: None,
+            exchange_variant_pid: None,
+        }],
+    }
+}
+
+/// Aprova e recebe a devolução com todos os itens revendáveis
+async fn approve_and_receive(
+    db: &DatabaseConnection,
+    ret: ReturnModel,
+    warehouse_id: i32,
+) -> ReturnModel {
+    let user = user(db, "Estoque").await;
+    let ret = ReturnModel::review(db, ret, true, user.id, None)
+        .await
+        .unwrap();
+    let items = ret
+        .items(db)
+        .await
+        .unwrap()
+        .iter()
+        .map(|line| InspectItemParams {
+            return_item_id: line.id,
+            condition: "sellable".to_string(),
+            stock_id: None,
+        })
+        .collect();
+    let params = ReceiveReturnParams {
+        warehouse_id,
+        items,
+        notes: None,
+    };
+    ReturnModel::receive(db, ret, &params, Some(user.id))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn only_paid_and_shipped_orders_accept_returns() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-DEV").await;
+
+    let (unpaid, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "awaiting",
+        "fulfilled",
+    )
+    .await;
+    assert!(
+        ReturnModel::open(&db, &unpaid, &open_params("refund", &items[0], 1))
+            .await
+            .is_err()
+    );
+
+    let (not_shipped, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "paid",
+        "not_fulfilled",
+    )
+    .await;
+    assert!(
+        ReturnModel::open(&db, &not_shipped, &open_params("refund", &items[0], 1))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn returned_quantity_is_capped_by_the_order_item() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-QTD").await;
+    let (order, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 2, 5_000)],
+        "paid",
+        "fulfilled",
+    )
+    .await;
+
+    ReturnModel::open(&db, &order, &open_params("refund", &items[0], 2))
+        .await
+        .unwrap();
+    assert!(
+        ReturnModel::open(&db, &order, &open_params("refund", &items[0], 1))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn refund_return_restocks_and_is_resolved_once() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-REEMB").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let lot = stock(&db, &sp, &camiseta, 0).await;
+    let (order, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 2, 5_000)],
+        "paid",
+        "fulfilled",
+    )
+    .await;
+    // Cupom de R$ 10 no pedido de R$ 100
+    let mut active: orders::ActiveModel = order.clone().into();
+    active.discount = ActiveValue::set(1_000);
+    active.total = ActiveValue::set(9_000);
+    let order = active.update(&db).await.unwrap();
+
+    let (ret, _) = ReturnModel::open(&db, &order, &open_params("refund", &items[0], 1))
+        .await
+        .unwrap();
+    let ret = approve_and_receive(&db, ret, sp.id).await;
+    assert_eq!(ret.status, "received");
+    assert_eq!(reload_stock(&db, &lot).await.quantity, 1);
+
+    // Metade do subtotal devolvida leva metade do desconto
+    let amount = ret.refund_amount(&db).await.unwrap();
+    assert_eq!(amount, 4_500);
+
+    let ret = ReturnModel::start_refund(&db, ret).await.unwrap();
+    assert_eq!(ret.status, "refunding");
+    assert!(ReturnModel::start_refund(&db, ret.clone()).await.is_err());
+
+    // Gateway recusou: a devolução volta a aceitar a conclusão
+    let ret = ReturnModel::abort_refund(&db, ret).await.unwrap();
+    assert_eq!(ret.status, "received");
+    let ret = ReturnModel::start_refund(&db, ret).await.unwrap();
+
+    let refunds = RefundModel::start(
+        &db,
+        order.id,
+        &CreateRefundParams {
+            amount: Some(amount),
+            reason: Some(ret.reason.clone()),
+            restock: false,
+            items: vec![],
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    let ret = ReturnModel::complete_with_refund(&db, ret, refunds[0].id)
+        .await
+        .unwrap();
+    assert_eq!(ret.status, "completed");
+    assert_eq!(ret.refund_id, Some(refunds[0].id));
+    assert!(ReturnModel::start_refund(&db, ret).await.is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn exchange_creates_a_single_replacement_order() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-TROCA").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let lot = stock(&db, &sp, &camiseta, 0).await;
+    let (order, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "paid",
+        "fulfilled",
+    )
+    .await;
+
+    let (ret, _) = ReturnModel::open(&db, &order, &open_params("exchange", &items[0], 1))
+        .await
+        .unwrap();
+    let ret = approve_and_receive(&db, ret, sp.id).await;
+    assert!(ReturnModel::start_refund(&db, ret.clone()).await.is_err());
+
+    let (done, replacement) = ReturnModel::complete_with_exchange(&db, ret.clone())
+        .await
+        .unwrap();
+    assert_eq!(done.status, "completed");
+    assert_eq!(done.exchange_order_id, Some(replacement.id));
+    assert_eq!(replacement.total, 0);
+    // A unidade devolvida sai de novo no pedido de troca
+    assert_eq!(reload_stock(&db, &lot).await.quantity, 0);
+
+    assert!(ReturnModel::complete_with_exchange(&db, ret).await.is_err());
+}
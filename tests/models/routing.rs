@@ -0,0 +1,155 @@
+use loco_fast_store::{
+    models::{
+        order_shippings::CreateShippingParams,
+        stock_reservations::{Model as StockReservationModel, ReservationLine},
+    },
+    services::{
+        routing,
+        shipments::{create_shipment, CreatedShipment, ShipmentOutcome},
+    },
+};
+use serial_test::serial;
+
+use super::{boot, order, reload_order, stock, user, variant, warehouse};
+
+/// Volume sem integração e sem depósito informado: a origem vem das reservas
+fn routed() -> CreateShippingParams {
+    CreateShippingParams {
+        carrier: "manual".to_string(),
+        service: None,
+        tracking_code: None,
+        tracking_url: None,
+        estimated_delivery_at: None,
+        notes: None,
+        service_code: None,
+        package: None,
+        items: vec![],
+        warehouse_pid: None,
+    }
+}
+
+fn done(outcome: ShipmentOutcome) -> CreatedShipment {
+    match outcome {
+        ShipmentOutcome::Done(created) => created,
+        ShipmentOutcome::Rejected(code, msg, _) => panic!("{code}: {msg}"),
+    }
+}
+
+/// Linhas do volume como (sku, quantidade)
+fn skus(created: &CreatedShipment) -> Vec<(String, i32)> {
+    created
+        .lines
+        .iter()
+        .map(|(item, quantity)| (item.sku.clone(), *quantity))
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn warehouses_are_ranked_by_distance_to_the_destination() {
+    let db = boot().await.db;
+    let recife = warehouse(&db, "Recife", "50030-230").await;
+    let poa = warehouse(&db, "POA", "90010-150").await;
+    let sem_cep = warehouse(&db, "Sem CEP", "1234").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+
+    assert!(routing::geocode("13010-000").is_some());
+    assert!(routing::geocode("1234").is_none());
+
+    // Destino em Campinas: SP, depois Porto Alegre, depois Recife
+    let ranked = routing::warehouses_by_distance(&db, Some("13010-000"))
+        .await
+        .unwrap();
+    let names: Vec<&str> = ranked.iter().map(|(w, _)| w.name.as_str()).collect();
+    assert_eq!(names, vec!["SP", "POA", "Recife", "Sem CEP"]);
+    assert!(ranked[0].1.unwrap() < 150.0);
+    assert_eq!(ranked[3].1, None);
+
+    // Sem CEP de destino a ordem é a de cadastro
+    let ranking = routing::warehouse_ranking(&db, None).await.unwrap();
+    assert_eq!(ranking[&recife.id], 0);
+    assert_eq!(ranking[&sp.id], 3);
+
+    let camiseta = variant(&db, "CAM-ROTA").await;
+    stock(&db, &recife, &camiseta, 10).await;
+    stock(&db, &poa, &camiseta, 10).await;
+    stock(&db, &sem_cep, &camiseta, 10).await;
+    stock(&db, &sp, &camiseta, 1).await;
+    // SP é o mais próximo, mas não atende sozinho
+    let chosen = routing::fulfilling_warehouse(&db, Some("13010-000"), &[(camiseta.id, 2)])
+        .await
+        .unwrap();
+    assert_eq!(chosen.map(|w| w.id), Some(poa.id));
+    let chosen = routing::fulfilling_warehouse(&db, Some("13010-000"), &[(camiseta.id, 11)])
+        .await
+        .unwrap();
+    assert!(chosen.is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn split_order_ships_one_package_per_warehouse() {
+    let db = boot().await.db;
+    let admin = user(&db, "Expedicao").await;
+    let camiseta = variant(&db, "CAM-DIVIDE").await;
+    let bone = variant(&db, "BONE-DIVIDE").await;
+    let rio = warehouse(&db, "Rio", "20040-002").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    stock(&db, &rio, &camiseta, 10).await;
+    stock(&db, &rio, &bone, 5).await;
+    stock(&db, &sp, &bone, 5).await;
+    let (order, _) = order(
+        &db,
+        "20040-002",
+        &[(&camiseta, 2, 5_000), (&bone, 8, 3_000)],
+        "paid",
+        "not_fulfilled",
+    )
+    .await;
+    StockReservationModel::reserve_for_order(
+        &db,
+        order.id,
+        &[
+            ReservationLine {
+                variant_id: camiseta.id,
+                quantity: 2,
+            },
+            ReservationLine {
+                variant_id: bone.id,
+                quantity: 8,
+            },
+        ],
+    )
+    .await
+    .unwrap();
+
+    // Primeiro o depósito com mais unidades reservadas, só com o que é dele
+    let first = done(
+        create_shipment(&db, &order, &routed(), admin.id)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(first.shipping.warehouse_id, Some(rio.id));
+    assert_eq!(
+        skus(&first),
+        vec![
+            ("CAM-DIVIDE".to_string(), 2),
+            ("BONE-DIVIDE".to_string(), 5)
+        ]
+    );
+    assert_eq!(first.order.fulfillment_status, "partially_fulfilled");
+
+    let order = reload_order(&db, &order).await;
+    let second = done(
+        create_shipment(&db, &order, &routed(), admin.id)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(second.shipping.warehouse_id, Some(sp.id));
+    assert_eq!(skus(&second), vec![("BONE-DIVIDE".to_string(), 3)]);
+    assert_eq!(second.order.fulfillment_status, "fulfilled");
+    assert_eq!(
+        routing::origin_postal_code(second.warehouse.as_ref()).unwrap(),
+        "01310-100"
+    );
+}
@@ -0,0 +1,186 @@
+use loco_fast_store::{
+    models::{
+        _entities::{order_items, orders, warehouses},
+        order_shippings::{
+            CreateShippingParams, Model as ShippingModel, ShipmentLineParams,
+            UpdateShippingStatusParams,
+        },
+    },
+    services::shipments::{create_shipment, CreatedShipment, ShipmentOutcome},
+};
+use sea_orm::DatabaseConnection;
+use serial_test::serial;
+
+use super::{boot, order, reload_order, user, variant, warehouse};
+
+/// Volume sem integração saindo do depósito, com os itens informados
+fn manual(
+    warehouse: &warehouses::Model,
+    items: &[(&order_items::Model, i32)],
+) -> CreateShippingParams {
+    CreateShippingParams {
+        carrier: "manual".to_string(),
+        service: None,
+        tracking_code: None,
+        tracking_url: None,
+        estimated_delivery_at: None,
+        notes: None,
+        service_code: None,
+        package: None,
+        items: items
+            .iter()
+            .map(|(item, quantity)| ShipmentLineParams {
+                order_item_pid: item.pid,
+                quantity: *quantity,
+            })
+            .collect(),
+        warehouse_pid: Some(warehouse.pid),
+    }
+}
+
+async fn ship(
+    db: &DatabaseConnection,
+    order: &orders::Model,
+    params: &CreateShippingParams,
+    user_id: i32,
+) -> ShipmentOutcome {
+    create_shipment(db, order, params, user_id).await.unwrap()
+}
+
+fn done(outcome: ShipmentOutcome) -> CreatedShipment {
+    match outcome {
+        ShipmentOutcome::Done(created) => created,
+        ShipmentOutcome::Rejected(code, msg, _) => panic!("{code}: {msg}"),
+    }
+}
+
+/// Linhas do volume como (sku, quantidade)
+fn skus(created: &CreatedShipment) -> Vec<(String, i32)> {
+    created
+        .lines
+        .iter()
+        .map(|(item, quantity)| (item.sku.clone(), *quantity))
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn order_ships_in_packages_from_different_warehouses() {
+    let db = boot().await.db;
+    let admin = user(&db, "Expedicao").await;
+    let camiseta = variant(&db, "CAM-VOLUME").await;
+    let bone = variant(&db, "BONE-VOLUME").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let rj = warehouse(&db, "RJ", "20040-002").await;
+    let (order, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 3, 5_000), (&bone, 1, 3_000)],
+        "paid",
+        "not_fulfilled",
+    )
+    .await;
+
+    let first = done(ship(&db, &order, &manual(&sp, &[(&items[0], 2)]), admin.id).await);
+    assert_eq!(skus(&first), vec![("CAM-VOLUME".to_string(), 2)]);
+    assert_eq!(first.shipping.warehouse_id, Some(sp.id));
+    assert_eq!(first.order.fulfillment_status, "partially_fulfilled");
+
+    // Mais do que o saldo pendente do item é recusado
+    let outcome = ship(&db, &order, &manual(&rj, &[(&items[0], 2)]), admin.id).await;
+    assert!(matches!(
+        outcome,
+        ShipmentOutcome::Rejected("INVALID_SHIPMENT", _, _)
+    ));
+
+    // Sem itens, o volume leva tudo o que falta
+    let order = reload_order(&db, &order).await;
+    let second = done(ship(&db, &order, &manual(&rj, &[]), admin.id).await);
+    assert_eq!(
+        skus(&second),
+        vec![
+            ("CAM-VOLUME".to_string(), 1),
+            ("BONE-VOLUME".to_string(), 1)
+        ]
+    );
+    assert_eq!(second.shipping.warehouse_id, Some(rj.id));
+    assert_eq!(second.order.fulfillment_status, "fulfilled");
+
+    let outcome = ship(&db, &second.order, &manual(&rj, &[]), admin.id).await;
+    assert!(matches!(
+        outcome,
+        ShipmentOutcome::Rejected("INVALID_SHIPMENT", _, _)
+    ));
+    let shipped = ShippingModel::shipped_quantities(&db, order.id)
+        .await
+        .unwrap();
+    assert_eq!(shipped[&items[0].id], 3);
+    assert_eq!(shipped[&items[1].id], 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_package_releases_its_items() {
+    let db = boot().await.db;
+    let admin = user(&db, "Expedicao").await;
+    let camiseta = variant(&db, "CAM-EXTRAVIO").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let (order, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 2, 5_000)],
+        "paid",
+        "not_fulfilled",
+    )
+    .await;
+
+    let lost = done(ship(&db, &order, &manual(&sp, &[(&items[0], 1)]), admin.id).await);
+    ShippingModel::update_status(
+        &db,
+        lost.shipping.id,
+        &UpdateShippingStatusParams {
+            status: "failed".to_string(),
+            notes: Some("Extraviado".to_string()),
+        },
+    )
+    .await
+    .unwrap();
+
+    let pending = ShippingModel::pending_lines(&db, order.id, &[])
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].1, 2);
+
+    let order = reload_order(&db, &order).await;
+    let resent = done(ship(&db, &order, &manual(&sp, &[]), admin.id).await);
+    assert_eq!(skus(&resent), vec![("CAM-EXTRAVIO".to_string(), 2)]);
+    assert_eq!(resent.order.fulfillment_status, "fulfilled");
+}
+
+#[tokio::test]
+#[serial]
+async fn unpaid_order_is_not_shipped() {
+    let db = boot().await.db;
+    let admin = user(&db, "Expedicao").await;
+    let camiseta = variant(&db, "CAM-NAOPAGO").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let (order, items) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 1, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+
+    let outcome = ship(&db, &order, &manual(&sp, &[(&items[0], 1)]), admin.id).await;
+    assert!(matches!(
+        outcome,
+        ShipmentOutcome::Rejected("INVALID_TRANSITION", _, Some(_))
+    ));
+    assert!(ShippingModel::list_for_order(&db, order.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
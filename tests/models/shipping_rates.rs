@@ -0,0 +1,158 @@
+use loco_fast_store::{
+    models::{
+        _entities::warehouses,
+        shipping_rates::{CreateShippingRateParams, Model as ShippingRateModel},
+    },
+    shipping::{builtin, FreightParams},
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serial_test::serial;
+
+use super::{boot, warehouse};
+
+fn rate_params(name: &str, kind: &str, price: i64) -> CreateShippingRateParams {
+    CreateShippingRateParams {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        price: Some(price),
+        min_subtotal: None,
+        postal_code_start: None,
+        postal_code_end: None,
+        min_weight_grams: None,
+        max_weight_grams: None,
+        delivery_days: Some(3),
+        warehouse_id: None,
+        instructions: None,
+        sort_order: None,
+        active: None,
+    }
+}
+
+fn freight(destination: &str, weight_grams: u32, declared_value_cents: i64) -> FreightParams {
+    FreightParams {
+        origin_postal_code: "01310-100".to_string(),
+        destination_postal_code: destination.to_string(),
+        weight_grams,
+        length_cm: 30,
+        width_cm: 20,
+        height_cm: 10,
+        declared_value_cents,
+        packages: vec![],
+    }
+}
+
+/// Opções (provider, preço) de todos os providers internos, ordenadas
+async fn quote(db: &DatabaseConnection, params: FreightParams) -> Vec<(String, i64)> {
+    let mut options = Vec::new();
+    for provider in builtin::load(db).await.unwrap() {
+        for option in provider.calculate_freight(params.clone()).await.unwrap() {
+            options.push((option.provider, option.price_cents));
+        }
+    }
+    options.sort();
+    options
+}
+
+fn pair(provider: &str, price: i64) -> (String, i64) {
+    (provider.to_string(), price)
+}
+
+#[tokio::test]
+#[serial]
+async fn builtin_rates_are_quoted_by_destination_weight_and_subtotal() {
+    let db = boot().await.db;
+    let loja = warehouse(&db, "Loja Paulista", "01310-100").await;
+
+    let mut pickup = rate_params("Retirada", "pickup", 0);
+    pickup.warehouse_id = Some(loja.id);
+    ShippingRateModel::create_rate(&db, &pickup).await.unwrap();
+    ShippingRateModel::create_rate(&db, &rate_params("Motoboy", "flat_rate", 1_500))
+        .await
+        .unwrap();
+    let mut free = rate_params("Grátis acima de R$ 200", "free_shipping", 0);
+    free.min_subtotal = Some(20_000);
+    ShippingRateModel::create_rate(&db, &free).await.unwrap();
+    let mut capital = rate_params("Capital SP", "table_rate", 2_500);
+    capital.postal_code_start = Some("01000-000".to_string());
+    capital.postal_code_end = Some("05999-999".to_string());
+    capital.max_weight_grams = Some(5_000);
+    ShippingRateModel::create_rate(&db, &capital).await.unwrap();
+
+    assert_eq!(
+        quote(&db, freight("01310-100", 1_000, 10_000)).await,
+        vec![
+            pair("flat_rate", 1_500),
+            pair("store_pickup", 0),
+            pair("table_rate", 2_500),
+        ]
+    );
+    // Acima do mínimo o frete grátis aparece
+    assert!(quote(&db, freight("01310-100", 1_000, 25_000))
+        .await
+        .contains(&pair("free_shipping", 0)));
+    // Fora da faixa de CEP ou acima do peso a tabela não vale
+    assert!(!quote(&db, freight("20040-002", 1_000, 10_000))
+        .await
+        .contains(&pair("table_rate", 2_500)));
+    assert!(!quote(&db, freight("01310-100", 6_000, 10_000))
+        .await
+        .contains(&pair("table_rate", 2_500)));
+}
+
+#[tokio::test]
+#[serial]
+async fn pickup_is_withdrawn_with_its_warehouse_and_inactive_rates_are_hidden() {
+    let db = boot().await.db;
+    let loja = warehouse(&db, "Loja Centro", "01010-000").await;
+    let mut pickup = rate_params("Retirada", "pickup", 0);
+    pickup.warehouse_id = Some(loja.id);
+    ShippingRateModel::create_rate(&db, &pickup).await.unwrap();
+    let mut paused = rate_params("Motoboy", "flat_rate", 1_500);
+    paused.active = Some(false);
+    ShippingRateModel::create_rate(&db, &paused).await.unwrap();
+
+    assert_eq!(
+        quote(&db, freight("01310-100", 500, 5_000)).await,
+        vec![pair("store_pickup", 0)]
+    );
+
+    let mut removed: warehouses::ActiveModel = loja.into();
+    removed.deleted_at = ActiveValue::set(Some(chrono::Utc::now().into()));
+    removed.update(&db).await.unwrap();
+    assert!(quote(&db, freight("01310-100", 500, 5_000))
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn invalid_rates_are_refused() {
+    let db = boot().await.db;
+    let is_message = |result: Result<ShippingRateModel, ModelError>| {
+        matches!(result, Err(ModelError::Message(_)))
+    };
+
+    // Frete grátis sem mínimo e retirada sem depósito
+    let free = rate_params("Grátis", "free_shipping", 0);
+    assert!(is_message(ShippingRateModel::create_rate(&db, &free).await));
+    let pickup = rate_params("Retirada", "pickup", 0);
+    assert!(is_message(
+        ShippingRateModel::create_rate(&db, &pickup).await
+    ));
+
+    let mut inverted = rate_params("Interior", "table_rate", 3_000);
+    inverted.postal_code_start = Some("19999-999".to_string());
+    inverted.postal_code_end = Some("10000-000".to_string());
+    assert!(is_message(
+        ShippingRateModel::create_rate(&db, &inverted).await
+    ));
+    let mut malformed = rate_params("Interior", "table_rate", 3_000);
+    malformed.postal_code_start = Some("1000".to_string());
+    assert!(is_message(
+        ShippingRateModel::create_rate(&db, &malformed).await
+    ));
+    assert!(is_message(
+        ShippingRateModel::create_rate(&db, &rate_params("Drone", "drone", 9_000)).await
+    ));
+}
@@ -0,0 +1,201 @@
+use loco_fast_store::models::{
+    _entities::{items, product_variants, stocks},
+    stock_movements::Model as MovementModel,
+    stock_reservations::{Model as StockReservationModel, ReservationLine},
+    stocks::{Model as StockModel, UpdateStockParams},
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serial_test::serial;
+use uuid::Uuid;
+
+use super::{boot, order, reload_stock, user, variant, warehouse};
+
+/// Saldo definido pelo ajuste manual, como no painel
+fn count(stock: &stocks::Model, quantity: i32, reason: Option<&str>) -> UpdateStockParams {
+    UpdateStockParams {
+        warehouse_id: stock.warehouse_id,
+        item_id: stock.item_id,
+        quantity,
+        reason: reason.map(String::from),
+        notes: None,
+    }
+}
+
+/// Linha de estoque nova da variante, aberta pela contagem inicial
+async fn ledger_stock(
+    db: &DatabaseConnection,
+    variant: &product_variants::Model,
+    quantity: i32,
+    user_id: i32,
+) -> stocks::Model {
+    let sp = warehouse(db, "SP", "01310-100").await;
+    let item = items::ActiveModel {
+        pid: ActiveValue::set(Uuid::new_v4()),
+        variant_id: ActiveValue::set(variant.id),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .unwrap();
+    StockModel::set_stock(
+        db,
+        &UpdateStockParams {
+            warehouse_id: sp.id,
+            item_id: item.id,
+            quantity,
+            reason: Some("inventory_count".to_string()),
+            notes: Some("Contagem inicial".to_string()),
+        },
+        Some(user_id),
+    )
+    .await
+    .unwrap()
+}
+
+async fn movements(db: &DatabaseConnection, stock: &stocks::Model) -> Vec<MovementModel> {
+    MovementModel::list_for_stock(db, stock.id, None, None, 100)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn balance_is_the_sum_of_the_movements() {
+    let db = boot().await.db;
+    let estoquista = user(&db, "Estoquista").await;
+    let camiseta = variant(&db, "CAM-RAZAO").await;
+    let lot = ledger_stock(&db, &camiseta, 10, estoquista.id).await;
+
+    let line = |quantity| ReservationLine {
+        variant_id: camiseta.id,
+        quantity,
+    };
+    let (paid, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 3, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+    StockReservationModel::reserve_for_order(&db, paid.id, &[line(3)])
+        .await
+        .unwrap();
+    StockReservationModel::commit_for_order(&db, paid.id)
+        .await
+        .unwrap();
+    StockReservationModel::restock_for_order(&db, paid.id, camiseta.id, 1)
+        .await
+        .unwrap();
+    let (canceled, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 2, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+    StockReservationModel::reserve_for_order(&db, canceled.id, &[line(2)])
+        .await
+        .unwrap();
+    StockReservationModel::release_for_order(&db, canceled.id)
+        .await
+        .unwrap();
+    StockModel::set_stock(&db, &count(&lot, 6, Some("damaged")), Some(estoquista.id))
+        .await
+        .unwrap();
+
+    let ledger = movements(&db, &lot).await;
+    let kinds: Vec<&str> = ledger.iter().map(|m| m.kind.as_str()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "adjustment",
+            "reservation",
+            "sale",
+            "return",
+            "reservation",
+            "release",
+            "adjustment"
+        ]
+    );
+    let lot = reload_stock(&db, &lot).await;
+    assert_eq!((lot.quantity, lot.reserved), (6, 0));
+    assert_eq!(ledger.iter().map(|m| m.quantity).sum::<i32>(), lot.quantity);
+    assert_eq!(ledger.iter().map(|m| m.reserved).sum::<i32>(), lot.reserved);
+
+    // Cada movimento guarda o saldo acumulado até ele
+    let (mut quantity, mut reserved) = (0, 0);
+    for movement in &ledger {
+        quantity += movement.quantity;
+        reserved += movement.reserved;
+        assert_eq!(
+            (movement.balance_after, movement.reserved_after),
+            (quantity, reserved)
+        );
+    }
+    assert_eq!(ledger[0].created_by, Some(estoquista.id));
+    assert_eq!(ledger[0].reason.as_deref(), Some("inventory_count"));
+    assert_eq!(ledger[2].order_id, Some(paid.id));
+    assert_eq!(ledger[6].reason.as_deref(), Some("damaged"));
+
+    let sales = MovementModel::list_for_stock(&db, lot.id, Some("sale"), None, 100)
+        .await
+        .unwrap();
+    assert_eq!(sales.len(), 1);
+    let after_sale = MovementModel::list_for_stock(&db, lot.id, None, Some(sales[0].id), 2)
+        .await
+        .unwrap();
+    assert_eq!(after_sale[0].kind, "return");
+    assert_eq!(after_sale.len(), 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn adjustment_below_the_reserved_units_is_refused() {
+    let db = boot().await.db;
+    let estoquista = user(&db, "Estoquista").await;
+    let camiseta = variant(&db, "CAM-AJUSTE").await;
+    let lot = ledger_stock(&db, &camiseta, 5, estoquista.id).await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 4, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+    StockReservationModel::reserve_for_order(
+        &db,
+        order.id,
+        &[ReservationLine {
+            variant_id: camiseta.id,
+            quantity: 4,
+        }],
+    )
+    .await
+    .unwrap();
+
+    let is_message =
+        |result: Result<stocks::Model, ModelError>| matches!(result, Err(ModelError::Message(_)));
+    assert!(is_message(
+        StockModel::set_stock(&db, &count(&lot, 3, Some("lost")), None).await
+    ));
+    assert!(is_message(
+        StockModel::set_stock(&db, &count(&lot, 8, Some("presente")), None).await
+    ));
+    assert!(is_message(
+        StockModel::set_stock(&db, &count(&lot, -1, None), None).await
+    ));
+    assert_eq!(movements(&db, &lot).await.len(), 2);
+
+    // Sem motivo o ajuste é uma correção
+    let lot = StockModel::set_stock(&db, &count(&lot, 4, None), None)
+        .await
+        .unwrap();
+    assert_eq!((lot.quantity, lot.reserved), (4, 4));
+    let ledger = movements(&db, &lot).await;
+    assert_eq!(ledger.last().unwrap().reason.as_deref(), Some("correction"));
+    assert_eq!(ledger.last().unwrap().quantity, -1);
+}
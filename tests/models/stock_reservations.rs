@@ -0,0 +1,222 @@
+use loco_fast_store::models::{
+    _entities::product_variants,
+    stock_reservations::{Model as StockReservationModel, ReservationLine},
+};
+use loco_rs::model::ModelError;
+use sea_orm::EntityTrait;
+use serial_test::serial;
+
+use super::{boot, order, reload_stock, stock, variant, warehouse};
+
+fn line(variant: &product_variants::Model, quantity: i32) -> ReservationLine {
+    ReservationLine {
+        variant_id: variant.id,
+        quantity,
+    }
+}
+
+async fn inventory(db: &sea_orm::DatabaseConnection, variant: &product_variants::Model) -> i32 {
+    product_variants::Entity::find_by_id(variant.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+        .inventory_quantity
+}
+
+#[tokio::test]
+#[serial]
+async fn reserve_then_commit_moves_units_out_of_stock() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-P").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let lot = stock(&db, &sp, &camiseta, 5).await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 3, 5_000)],
+        "pending",
+        "not_fulfilled",
+    )
+    .await;
+
+    let reservations =
+        StockReservationModel::reserve_for_order(&db, order.id, &[line(&camiseta, 3)])
+            .await
+            .unwrap();
+    assert_eq!(reservations.len(), 1);
+    assert_eq!(reservations[0].stock_id, Some(lot.id));
+    let reserved = reload_stock(&db, &lot).await;
+    assert_eq!((reserved.quantity, reserved.reserved), (5, 3));
+
+    StockReservationModel::commit_for_order(&db, order.id)
+        .await
+        .unwrap();
+    let sold = reload_stock(&db, &lot).await;
+    assert_eq!((sold.quantity, sold.reserved), (2, 0));
+    assert_eq!(inventory(&db, &camiseta).await, 2);
+    let statuses: Vec<String> = StockReservationModel::find_by_order(&db, order.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.status)
+        .collect();
+    assert_eq!(statuses, ["committed"]);
+
+    // Idempotente: nova confirmação não baixa de novo
+    StockReservationModel::commit_for_order(&db, order.id)
+        .await
+        .unwrap();
+    assert_eq!(reload_stock(&db, &lot).await.quantity, 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn reserve_refuses_more_than_available() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-M").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    stock(&db, &sp, &camiseta, 2).await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 3, 5_000)],
+        "pending",
+        "not_fulfilled",
+    )
+    .await;
+
+    let err = StockReservationModel::reserve_for_order(&db, order.id, &[line(&camiseta, 3)])
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ModelError::Message(msg) if msg.contains("CAM-M")),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn release_returns_reserved_units() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-G").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let lot = stock(&db, &sp, &camiseta, 4).await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 4, 5_000)],
+        "pending",
+        "not_fulfilled",
+    )
+    .await;
+
+    StockReservationModel::reserve_for_order(&db, order.id, &[line(&camiseta, 4)])
+        .await
+        .unwrap();
+    StockReservationModel::release_for_order(&db, order.id)
+        .await
+        .unwrap();
+
+    let released = reload_stock(&db, &lot).await;
+    assert_eq!((released.quantity, released.reserved), (4, 0));
+    assert_eq!(inventory(&db, &camiseta).await, 4);
+    let reservations = StockReservationModel::find_by_order(&db, order.id)
+        .await
+        .unwrap();
+    assert!(reservations.iter().all(|r| r.status == "released"));
+}
+
+#[tokio::test]
+#[serial]
+async fn late_payment_reserves_again_before_committing() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-GG").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let lot = stock(&db, &sp, &camiseta, 3).await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 2, 5_000)],
+        "pending",
+        "not_fulfilled",
+    )
+    .await;
+
+    StockReservationModel::reserve_for_order(&db, order.id, &[line(&camiseta, 2)])
+        .await
+        .unwrap();
+    // Reserva expirou antes do pagamento
+    StockReservationModel::release_for_order(&db, order.id)
+        .await
+        .unwrap();
+    StockReservationModel::commit_for_order(&db, order.id)
+        .await
+        .unwrap();
+
+    let sold = reload_stock(&db, &lot).await;
+    assert_eq!((sold.quantity, sold.reserved), (1, 0));
+    assert_eq!(inventory(&db, &camiseta).await, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn whole_order_ships_from_the_warehouse_that_covers_it() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-AZ").await;
+    let bone = variant(&db, "BONE-AZ").await;
+    // Destino no Rio: o depósito do Rio é o mais próximo, mas só tem camisetas
+    let rio = warehouse(&db, "Rio", "20040-002").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let rio_camisetas = stock(&db, &rio, &camiseta, 10).await;
+    let sp_camisetas = stock(&db, &sp, &camiseta, 10).await;
+    let sp_bones = stock(&db, &sp, &bone, 10).await;
+    let (order, _) = order(
+        &db,
+        "20040-002",
+        &[(&camiseta, 2, 5_000), (&bone, 1, 3_000)],
+        "pending",
+        "not_fulfilled",
+    )
+    .await;
+
+    StockReservationModel::reserve_for_order(&db, order.id, &[line(&camiseta, 2), line(&bone, 1)])
+        .await
+        .unwrap();
+
+    assert_eq!(reload_stock(&db, &rio_camisetas).await.reserved, 0);
+    assert_eq!(reload_stock(&db, &sp_camisetas).await.reserved, 2);
+    assert_eq!(reload_stock(&db, &sp_bones).await.reserved, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn order_is_split_when_no_warehouse_covers_it() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-VD").await;
+    let bone = variant(&db, "BONE-VD").await;
+    let rio = warehouse(&db, "Rio", "20040-002").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let rio_camisetas = stock(&db, &rio, &camiseta, 10).await;
+    let sp_camisetas = stock(&db, &sp, &camiseta, 10).await;
+    let sp_bones = stock(&db, &sp, &bone, 5).await;
+    let rio_bones = stock(&db, &rio, &bone, 5).await;
+    let (order, _) = order(
+        &db,
+        "20040-002",
+        &[(&camiseta, 2, 5_000), (&bone, 8, 3_000)],
+        "pending",
+        "not_fulfilled",
+    )
+    .await;
+
+    StockReservationModel::reserve_for_order(&db, order.id, &[line(&camiseta, 2), line(&bone, 8)])
+        .await
+        .unwrap();
+
+    // Camisetas do mais próximo; bonés divididos a partir dele
+    assert_eq!(reload_stock(&db, &rio_camisetas).await.reserved, 2);
+    assert_eq!(reload_stock(&db, &sp_camisetas).await.reserved, 0);
+    assert_eq!(reload_stock(&db, &rio_bones).await.reserved, 5);
+    assert_eq!(reload_stock(&db, &sp_bones).await.reserved, 3);
+}
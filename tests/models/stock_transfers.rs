@@ -0,0 +1,226 @@
+use loco_fast_store::models::{
+    _entities::{product_variants, stocks, warehouses},
+    stock_reservations::{Model as StockReservationModel, ReservationLine},
+    stock_transfers::{
+        CreateTransferParams, Model as TransferModel, ReceiveTransferParams, ReceivedItemParams,
+        TransferItemParams,
+    },
+};
+use loco_rs::model::ModelError;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serial_test::serial;
+
+use super::{boot, order, reload_stock, stock, user, variant, warehouse};
+
+fn transfer_params(
+    from: &warehouses::Model,
+    to: &warehouses::Model,
+    stock: &stocks::Model,
+    quantity: i32,
+) -> CreateTransferParams {
+    CreateTransferParams {
+        source_warehouse_id: from.id,
+        destination_warehouse_id: to.id,
+        items: vec![TransferItemParams {
+            item_id: stock.item_id,
+            quantity,
+        }],
+        notes: None,
+    }
+}
+
+fn received(transfer_item_id: i32, quantity: i32, reason: Option<&str>) -> ReceiveTransferParams {
+    ReceiveTransferParams {
+        items: vec![ReceivedItemParams {
+            transfer_item_id,
+            received_quantity: quantity,
+            reason: reason.map(String::from),
+        }],
+        notes: Some("Conferido na doca".to_string()),
+    }
+}
+
+async fn inventory(db: &DatabaseConnection, variant: &product_variants::Model) -> i32 {
+    product_variants::Entity::find_by_id(variant.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap()
+        .inventory_quantity
+}
+
+async fn stock_at(
+    db: &DatabaseConnection,
+    warehouse: &warehouses::Model,
+    item_id: i32,
+) -> Option<stocks::Model> {
+    stocks::Entity::find()
+        .filter(stocks::Column::WarehouseId.eq(warehouse.id))
+        .filter(stocks::Column::ItemId.eq(item_id))
+        .one(db)
+        .await
+        .unwrap()
+}
+
+fn is_message<T: std::fmt::Debug>(result: Result<T, ModelError>) -> bool {
+    matches!(result, Err(ModelError::Message(_)))
+}
+
+#[tokio::test]
+#[serial]
+async fn missing_units_need_a_reason_and_are_written_off() {
+    let db = boot().await.db;
+    let estoquista = user(&db, "Estoquista").await;
+    let camiseta = variant(&db, "CAM-TRANSF").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let rj = warehouse(&db, "RJ", "20040-002").await;
+    let origin = stock(&db, &sp, &camiseta, 10).await;
+
+    let transfer = TransferModel::create(
+        &db,
+        &transfer_params(&sp, &rj, &origin, 4),
+        Some(estoquista.id),
+    )
+    .await
+    .unwrap();
+    assert_eq!(transfer.status, "pending");
+    assert_eq!(reload_stock(&db, &origin).await.quantity, 10);
+
+    let transfer = TransferModel::ship(&db, transfer, Some(estoquista.id))
+        .await
+        .unwrap();
+    assert_eq!(transfer.status, "shipped");
+    assert_eq!(reload_stock(&db, &origin).await.quantity, 6);
+    let line = transfer.items(&db).await.unwrap().remove(0);
+    assert_eq!(line.shipped_quantity, 4);
+
+    // Diferença sem motivo ou acima do enviado não é aceita
+    assert!(is_message(
+        TransferModel::receive(&db, transfer.clone(), &received(line.id, 3, None), None).await
+    ));
+    assert!(is_message(
+        TransferModel::receive(
+            &db,
+            transfer.clone(),
+            &received(line.id, 5, Some("Sobra")),
+            None
+        )
+        .await
+    ));
+    assert!(stock_at(&db, &rj, origin.item_id).await.is_none());
+
+    let transfer = TransferModel::receive(
+        &db,
+        transfer,
+        &received(line.id, 3, Some("Caixa avariada")),
+        Some(estoquista.id),
+    )
+    .await
+    .unwrap();
+    assert_eq!(transfer.status, "received");
+    assert!(transfer.discrepancy);
+    assert_eq!(
+        stock_at(&db, &rj, origin.item_id).await.unwrap().quantity,
+        3
+    );
+    let line = transfer.items(&db).await.unwrap().remove(0);
+    assert_eq!(line.received_quantity, Some(3));
+    assert_eq!(line.discrepancy_reason.as_deref(), Some("Caixa avariada"));
+    // A unidade extraviada sai do saldo da variante
+    assert_eq!(inventory(&db, &camiseta).await, 9);
+}
+
+#[tokio::test]
+#[serial]
+async fn full_receipt_keeps_the_variant_balance() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-TRANSF-OK").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let rj = warehouse(&db, "RJ", "20040-002").await;
+    let origin = stock(&db, &sp, &camiseta, 5).await;
+    let existing = stock(&db, &rj, &camiseta, 1).await;
+
+    let transfer = TransferModel::create(&db, &transfer_params(&sp, &rj, &origin, 5), None)
+        .await
+        .unwrap();
+    let transfer = TransferModel::ship(&db, transfer, None).await.unwrap();
+    let transfer = TransferModel::receive(
+        &db,
+        transfer,
+        &ReceiveTransferParams {
+            items: vec![],
+            notes: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(!transfer.discrepancy);
+    assert_eq!(reload_stock(&db, &origin).await.quantity, 0);
+    assert_eq!(
+        stock_at(&db, &rj, origin.item_id).await.unwrap().quantity,
+        5
+    );
+    // O lote que já estava no destino não é tocado
+    assert_eq!(reload_stock(&db, &existing).await.quantity, 1);
+    assert_eq!(inventory(&db, &camiseta).await, 6);
+}
+
+#[tokio::test]
+#[serial]
+async fn reserved_units_are_not_shipped_and_only_pending_transfers_are_canceled() {
+    let db = boot().await.db;
+    let camiseta = variant(&db, "CAM-TRANSF-RES").await;
+    let sp = warehouse(&db, "SP", "01310-100").await;
+    let rj = warehouse(&db, "RJ", "20040-002").await;
+    let origin = stock(&db, &sp, &camiseta, 5).await;
+    let (order, _) = order(
+        &db,
+        "01310-100",
+        &[(&camiseta, 3, 5_000)],
+        "awaiting",
+        "not_fulfilled",
+    )
+    .await;
+    StockReservationModel::reserve_for_order(
+        &db,
+        order.id,
+        &[ReservationLine {
+            variant_id: camiseta.id,
+            quantity: 3,
+        }],
+    )
+    .await
+    .unwrap();
+
+    assert!(is_message(
+        TransferModel::create(&db, &transfer_params(&sp, &sp, &origin, 1), None).await
+    ));
+
+    let transfer = TransferModel::create(&db, &transfer_params(&sp, &rj, &origin, 3), None)
+        .await
+        .unwrap();
+    assert!(is_message(
+        TransferModel::ship(&db, transfer.clone(), None).await
+    ));
+    let transfer = TransferModel::find_by_pid(&db, &transfer.pid)
+        .await
+        .unwrap();
+    assert_eq!(transfer.status, "pending");
+    assert_eq!(reload_stock(&db, &origin).await.quantity, 5);
+    let canceled = TransferModel::cancel(&db, transfer).await.unwrap();
+    assert_eq!(canceled.status, "canceled");
+
+    let transfer = TransferModel::create(&db, &transfer_params(&sp, &rj, &origin, 2), None)
+        .await
+        .unwrap();
+    let shipped = TransferModel::ship(&db, transfer, None).await.unwrap();
+    assert!(is_message(TransferModel::cancel(&db, shipped).await));
+    assert_eq!(
+        TransferModel::list(&db, Some("shipped"), Some(rj.id), None, 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
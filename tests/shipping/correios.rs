@@ -0,0 +1,362 @@
+use axum::http::Method;
+use loco_fast_store::shipping::{
+    correios::{CorreiosApi, CorreiosConfig},
+    packing::Package,
+    ContactInfo, CreateShipmentParams, FreightParams, ShipmentItem, ShippingError,
+    ShippingProvider,
+};
+
+use super::{fixture, MockServer};
+
+const TOKEN: &str = "/token/v1/autentica/cartaopostagem";
+const PREPOSTAGENS: &str = "/prepostagem/v1/prepostagens";
+
+fn correios(name: &str) -> String {
+    fixture(&format!("correios/{name}"))
+}
+
+fn provider(server: &MockServer, servicos: &[&str]) -> CorreiosApi {
+    CorreiosApi::new(CorreiosConfig {
+        usuario: "12345678000199".to_string(),
+        codigo_acesso: "codigo-teste".to_string(),
+        cartao_postagem: "0067599079".to_string(),
+        contrato: Some("9912208555".to_string()),
+        dr: Some("20".to_string()),
+        servicos: servicos.iter().map(|s| s.to_string()).collect(),
+        base_url: server.base_url.clone(),
+    })
+}
+
+fn package(weight_grams: u32) -> Package {
+    Package {
+        box_name: Some("M".to_string()),
+        length_cm: 30,
+        width_cm: 20,
+        height_cm: 10,
+        weight_grams,
+        declared_value_cents: 15_000,
+    }
+}
+
+fn freight(packages: Vec<Package>) -> FreightParams {
+    FreightParams::from_packages("01310-100", "20040-002", packages)
+}
+
+fn contact(name: &str, postal_code: &str) -> ContactInfo {
+    ContactInfo {
+        name: name.to_string(),
+        email: Some("contato@example.com".to_string()),
+        phone: Some("(21) 99999-0000".to_string()),
+        document: Some("123.456.789-09".to_string()),
+        address_line1: "Rua da Assembleia".to_string(),
+        address_line2: None,
+        number: Some("10".to_string()),
+        district: Some("Centro".to_string()),
+        city: "Rio de Janeiro".to_string(),
+        state: "RJ".to_string(),
+        postal_code: postal_code.to_string(),
+        country: "BR".to_string(),
+    }
+}
+
+fn shipment(packages: Vec<Package>) -> CreateShipmentParams {
+    CreateShipmentParams {
+        service_code: "03298".to_string(),
+        order_number: "LFS-000123".to_string(),
+        sender: contact("Loja", "01310-100"),
+        recipient: contact("Cliente", "20040-002"),
+        freight: freight(packages),
+        items: vec![ShipmentItem {
+            name: "Camiseta".to_string(),
+            quantity: 2,
+            unit_price_cents: 7_500,
+        }],
+        provider_id: None,
+    }
+}
+
+#[tokio::test]
+async fn quote_sums_volumes_and_skips_failed_services() {
+    let server = MockServer::builder()
+        .route(Method::POST, TOKEN, 201, correios("token.json"))
+        .route(
+            Method::GET,
+            "/preco/v1/nacional/03220",
+            200,
+            correios("preco_sedex.json"),
+        )
+        .route(
+            Method::GET,
+            "/prazo/v1/nacional/03220",
+            200,
+            correios("prazo.json"),
+        )
+        .route(
+            Method::GET,
+            "/preco/v1/nacional/03298",
+            200,
+            correios("preco_erro.json"),
+        )
+        .start()
+        .await;
+
+    let options = provider(&server, &["03220", "03298"])
+        .calculate_freight(freight(vec![package(1000), package(1000)]))
+        .await
+        .unwrap();
+
+    assert_eq!(options.len(), 1);
+    assert_eq!(options[0].provider, "correios_api");
+    assert_eq!(options[0].service, "SEDEX");
+    assert_eq!(options[0].service_code, "03220");
+    // Um preço por volume, somados
+    assert_eq!(options[0].price_cents, 2 * 3350);
+    assert_eq!(options[0].delivery_days, 2);
+
+    assert_eq!(server.hits(Method::GET, "/preco/v1/nacional/03220"), 2);
+    let preco = server
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/preco/v1/nacional/03220")
+        .unwrap();
+    assert!(
+        preco.query.contains("cepDestino=20040002"),
+        "{}",
+        preco.query
+    );
+    assert!(preco.query.contains("psObjeto=1000"), "{}", preco.query);
+    assert!(
+        preco.query.contains("nuContrato=9912208555"),
+        "{}",
+        preco.query
+    );
+}
+
+#[tokio::test]
+async fn quote_returns_first_error_when_every_service_fails() {
+    let server = MockServer::builder()
+        .route(Method::POST, TOKEN, 201, correios("token.json"))
+        .route(
+            Method::GET,
+            "/preco/v1/nacional/03298",
+            200,
+            correios("preco_erro.json"),
+        )
+        .start()
+        .await;
+
+    let err = provider(&server, &["03298"])
+        .calculate_freight(freight(vec![package(1000)]))
+        .await
+        .unwrap_err();
+
+    match err {
+        ShippingError::Rejected(msg) => {
+            assert_eq!(msg, "CEP de destino não atendido pelo serviço");
+        }
+        other => panic!("esperado Rejected, veio {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn token_rejected_maps_unauthorized() {
+    let server = MockServer::builder()
+        .route(
+            Method::POST,
+            TOKEN,
+            401,
+            r#"{"msgs":["Usuário ou código de acesso inválido"]}"#.to_string(),
+        )
+        .start()
+        .await;
+
+    let err = provider(&server, &["03220"])
+        .calculate_freight(freight(vec![package(1000)]))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ShippingError::Unauthorized(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn create_registers_one_prepostagem_per_volume() {
+    let server = MockServer::builder()
+        .route(Method::POST, TOKEN, 201, correios("token.json"))
+        .route(
+            Method::POST,
+            PREPOSTAGENS,
+            200,
+            correios("prepostagem_1.json"),
+        )
+        .route(
+            Method::POST,
+            PREPOSTAGENS,
+            200,
+            correios("prepostagem_2.json"),
+        )
+        .start()
+        .await;
+
+    let result = provider(&server, &["03298"])
+        .create_shipment(shipment(vec![package(1000), package(800)]))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result.provider_id,
+        "PRvjPBvgM8gNnLrVjZ4Xb3,PRkQ9wLm3TzXc7VbN2Yd8F"
+    );
+    assert_eq!(result.tracking_code.as_deref(), Some("AB123456789BR"));
+    assert!(result.label_url.is_none());
+    assert_eq!(
+        result.raw_data["prepostagens"].as_array().map(Vec::len),
+        Some(2)
+    );
+
+    let bodies: Vec<_> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == PREPOSTAGENS)
+        .map(|r| r.body)
+        .collect();
+    assert_eq!(bodies[0]["observacao"], "LFS-000123 (1/2)");
+    assert_eq!(bodies[0]["pesoInformado"], "1000");
+    assert_eq!(bodies[0]["destinatario"]["dddCelular"], "21");
+    assert_eq!(bodies[0]["destinatario"]["endereco"]["cep"], "20040002");
+    // Declaração de conteúdo só no primeiro volume
+    assert_eq!(bodies[0]["itensDeclaracaoConteudo"][0]["valor"], "75.00");
+    assert_eq!(bodies[1]["itensDeclaracaoConteudo"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn failed_volume_cancels_previous_prepostagens() {
+    let server = MockServer::builder()
+        .route(Method::POST, TOKEN, 201, correios("token.json"))
+        .route(
+            Method::POST,
+            PREPOSTAGENS,
+            200,
+            correios("prepostagem_1.json"),
+        )
+        .route(
+            Method::POST,
+            PREPOSTAGENS,
+            422,
+            correios("erro_prepostagem.json"),
+        )
+        .route(
+            Method::DELETE,
+            "/prepostagem/v1/prepostagens/cancelar/PRvjPBvgM8gNnLrVjZ4Xb3",
+            200,
+            correios("cancelar.json"),
+        )
+        .start()
+        .await;
+
+    let err = provider(&server, &["03298"])
+        .create_shipment(shipment(vec![package(1000), package(40_000)]))
+        .await
+        .unwrap_err();
+
+    match err {
+        ShippingError::Rejected(msg) => {
+            assert_eq!(msg, "PPN-295: Peso informado excede o limite do serviço");
+        }
+        other => panic!("esperado Rejected, veio {other:?}"),
+    }
+    assert_eq!(
+        server.hits(
+            Method::DELETE,
+            "/prepostagem/v1/prepostagens/cancelar/PRvjPBvgM8gNnLrVjZ4Xb3"
+        ),
+        1
+    );
+}
+
+#[tokio::test]
+async fn failed_cancel_reports_orphaned_prepostagens() {
+    // Sem rota de cancelamento: o stub responde 404
+    let server = MockServer::builder()
+        .route(Method::POST, TOKEN, 201, correios("token.json"))
+        .route(
+            Method::POST,
+            PREPOSTAGENS,
+            200,
+            correios("prepostagem_1.json"),
+        )
+        .route(
+            Method::POST,
+            PREPOSTAGENS,
+            422,
+            correios("erro_prepostagem.json"),
+        )
+        .start()
+        .await;
+
+    let err = provider(&server, &["03298"])
+        .create_shipment(shipment(vec![package(1000), package(40_000)]))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("PRvjPBvgM8gNnLrVjZ4Xb3"), "{err}");
+}
+
+#[tokio::test]
+async fn track_maps_sro_events() {
+    let server = MockServer::builder()
+        .route(Method::POST, TOKEN, 201, correios("token.json"))
+        .route(
+            Method::GET,
+            "/srorastro/v1/objetos/AB123456789BR",
+            200,
+            correios("sro.json"),
+        )
+        .start()
+        .await;
+
+    let info = provider(&server, &["03298"])
+        .track("AB123456789BR")
+        .await
+        .unwrap();
+
+    assert_eq!(info.tracking_code, "AB123456789BR");
+    assert_eq!(info.current_status, "out_for_delivery");
+    let statuses: Vec<&str> = info.events.iter().map(|e| e.status.as_str()).collect();
+    assert_eq!(statuses, ["out_for_delivery", "in_transit", "posted"]);
+    assert_eq!(
+        info.events[0].location.as_deref(),
+        Some("RIO DE JANEIRO/RJ")
+    );
+    assert_eq!(
+        info.estimated_delivery.as_deref(),
+        Some("2024-05-08T23:59:59")
+    );
+    let sro = server
+        .requests()
+        .into_iter()
+        .find(|r| r.path.starts_with("/srorastro"))
+        .unwrap();
+    assert_eq!(sro.query, "resultado=T");
+}
+
+#[tokio::test]
+async fn track_unknown_object_is_rejected() {
+    let server = MockServer::builder()
+        .route(Method::POST, TOKEN, 201, correios("token.json"))
+        .route(
+            Method::GET,
+            "/srorastro/v1/objetos/XX000000000BR",
+            200,
+            correios("sro_nao_encontrado.json"),
+        )
+        .start()
+        .await;
+
+    let err = provider(&server, &["03298"])
+        .track("XX000000000BR")
+        .await
+        .unwrap_err();
+    match err {
+        ShippingError::Rejected(msg) => assert!(msg.starts_with("SRO-020"), "{msg}"),
+        other => panic!("esperado Rejected, veio {other:?}"),
+    }
+}
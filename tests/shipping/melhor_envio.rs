@@ -0,0 +1,329 @@
+use axum::http::Method;
+use loco_fast_store::shipping::{
+    melhor_envio::MelhorEnvio, packing::Package, ContactInfo, CreateShipmentParams, FreightParams,
+    ShipmentItem, ShippingError, ShippingProvider,
+};
+
+use super::{fixture, MockServer};
+
+const CART_ID: &str = "9a1c0f6e-3b2d-4c4a-8f1e-7d2b5c6a9e01";
+
+fn me(name: &str) -> String {
+    fixture(&format!("melhor_envio/{name}"))
+}
+
+fn provider(server: &MockServer) -> MelhorEnvio {
+    MelhorEnvio::with_base_url("token-teste".to_string(), server.base_url.clone()).unwrap()
+}
+
+fn freight() -> FreightParams {
+    FreightParams::from_packages(
+        "01310-100",
+        "20040-002",
+        vec![Package {
+            box_name: Some("M".to_string()),
+            length_cm: 30,
+            width_cm: 20,
+            height_cm: 10,
+            weight_grams: 800,
+            declared_value_cents: 15_000,
+        }],
+    )
+}
+
+fn contact(name: &str, postal_code: &str) -> ContactInfo {
+    ContactInfo {
+        name: name.to_string(),
+        email: Some("contato@example.com".to_string()),
+        phone: Some("(11) 99999-0000".to_string()),
+        document: Some("123.456.789-09".to_string()),
+        address_line1: "Av. Paulista".to_string(),
+        address_line2: None,
+        number: Some("1000".to_string()),
+        district: Some("Bela Vista".to_string()),
+        city: "São Paulo".to_string(),
+        state: "SP".to_string(),
+        postal_code: postal_code.to_string(),
+        country: "BR".to_string(),
+    }
+}
+
+fn shipment() -> CreateShipmentParams {
+    CreateShipmentParams {
+        service_code: "1".to_string(),
+        order_number: "LFS-000123".to_string(),
+        sender: contact("Loja", "01310-100"),
+        recipient: contact("Cliente", "20040-002"),
+        freight: freight(),
+        items: vec![ShipmentItem {
+            name: "Camiseta".to_string(),
+            quantity: 2,
+            unit_price_cents: 7_500,
+        }],
+        provider_id: None,
+    }
+}
+
+/// Fluxo completo de etiqueta, com o status do envio em `order`
+async fn label_server(order: &str) -> MockServer {
+    MockServer::builder()
+        .route(Method::POST, "/me/cart", 201, me("cart.json"))
+        .route(
+            Method::GET,
+            &format!("/me/orders/{CART_ID}"),
+            200,
+            me(order),
+        )
+        .route(
+            Method::POST,
+            "/me/shipment/checkout",
+            200,
+            me("checkout.json"),
+        )
+        .route(
+            Method::POST,
+            "/me/shipment/generate",
+            200,
+            me("generate.json"),
+        )
+        .route(Method::POST, "/me/shipment/print", 200, me("print.json"))
+        .route(
+            Method::POST,
+            "/me/shipment/tracking",
+            200,
+            me("tracking.json"),
+        )
+        .start()
+        .await
+}
+
+#[tokio::test]
+async fn quote_maps_services_and_skips_errors() {
+    let server = MockServer::builder()
+        .route(
+            Method::POST,
+            "/me/shipment/calculate",
+            200,
+            me("calculate.json"),
+        )
+        .start()
+        .await;
+
+    let options = provider(&server)
+        .calculate_freight(freight())
+        .await
+        .unwrap();
+
+    assert_eq!(options.len(), 2);
+    // custom_price/custom_delivery_time têm precedência
+    assert_eq!(options[0].service, "PAC");
+    assert_eq!(options[0].service_code, "1");
+    assert_eq!(options[0].carrier, "Correios");
+    assert_eq!(options[0].price_cents, 2190);
+    assert_eq!(options[0].delivery_days, 8);
+    // Preço numérico e sem custom_*
+    assert_eq!(options[1].service, "SEDEX");
+    assert_eq!(options[1].price_cents, 4120);
+    assert_eq!(options[1].delivery_days, 2);
+    assert!(options.iter().all(|o| o.provider == "melhor_envio"));
+
+    let body = server
+        .last_body(Method::POST, "/me/shipment/calculate")
+        .unwrap();
+    assert_eq!(body["from"]["postal_code"], "01310100");
+    assert_eq!(body["to"]["postal_code"], "20040002");
+    assert_eq!(body["volumes"][0]["weight"], 0.8);
+    assert_eq!(body["volumes"][0]["insurance_value"], 150.0);
+}
+
+#[tokio::test]
+async fn quote_maps_validation_errors() {
+    let server = MockServer::builder()
+        .route(
+            Method::POST,
+            "/me/shipment/calculate",
+            422,
+            me("error_invalid.json"),
+        )
+        .start()
+        .await;
+
+    let err = provider(&server)
+        .calculate_freight(freight())
+        .await
+        .unwrap_err();
+
+    match err {
+        ShippingError::Rejected(msg) => {
+            assert!(msg.contains("O CEP de destino é inválido."), "{msg}");
+        }
+        other => panic!("esperado Rejected, veio {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn quote_maps_unauthorized() {
+    let server = MockServer::builder()
+        .route(
+            Method::POST,
+            "/me/shipment/calculate",
+            401,
+            r#"{"message":"Unauthenticated."}"#.to_string(),
+        )
+        .start()
+        .await;
+
+    let err = provider(&server)
+        .calculate_freight(freight())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ShippingError::Unauthorized(_)), "{err:?}");
+}
+
+#[test]
+fn token_with_invalid_characters_is_not_configured() {
+    let err = MelhorEnvio::with_base_url("token\nquebrado".to_string(), "http://127.0.0.1".into())
+        .err()
+        .unwrap();
+    assert!(matches!(err, ShippingError::NotConfigured(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn create_buys_and_prints_label() {
+    let server = label_server("order_pending.json").await;
+    let me = provider(&server);
+
+    let mut params = shipment();
+    let cart_id = me.prepare_shipment(&params).await.unwrap();
+    assert_eq!(cart_id.as_deref(), Some(CART_ID));
+    params.provider_id = cart_id;
+
+    let result = me.create_shipment(params).await.unwrap();
+
+    assert_eq!(result.provider_id, CART_ID);
+    assert_eq!(result.tracking_code.as_deref(), Some("AA123456789BR"));
+    assert_eq!(
+        result.tracking_url.as_deref(),
+        Some("https://www.melhorrastreio.com.br/rastreio/AA123456789BR")
+    );
+    assert_eq!(
+        result.label_url.as_deref(),
+        Some("https://sandbox.melhorenvio.com.br/imprimir/mwz9J2Xk")
+    );
+    assert_eq!(result.raw_data["protocol"], "ORD-202405021234");
+    assert_eq!(result.raw_data["purchase"]["status"], "paid");
+
+    assert_eq!(server.hits(Method::POST, "/me/cart"), 1);
+    assert_eq!(server.hits(Method::POST, "/me/shipment/checkout"), 1);
+    assert_eq!(server.hits(Method::POST, "/me/shipment/generate"), 1);
+
+    let cart = server.last_body(Method::POST, "/me/cart").unwrap();
+    assert_eq!(cart["service"], 1);
+    assert_eq!(cart["from"]["document"], "12345678909");
+    assert_eq!(cart["to"]["postal_code"], "20040002");
+    assert_eq!(cart["products"][0]["unitary_value"], 75.0);
+    assert_eq!(cart["options"]["tags"][0]["tag"], "LFS-000123");
+    let checkout = server
+        .last_body(Method::POST, "/me/shipment/checkout")
+        .unwrap();
+    assert_eq!(checkout["orders"][0], CART_ID);
+}
+
+#[tokio::test]
+async fn create_without_prepare_adds_to_cart() {
+    let server = label_server("order_pending.json").await;
+
+    let result = provider(&server).create_shipment(shipment()).await.unwrap();
+
+    assert_eq!(result.provider_id, CART_ID);
+    assert_eq!(server.hits(Method::POST, "/me/cart"), 1);
+    assert_eq!(server.hits(Method::POST, "/me/shipment/checkout"), 1);
+}
+
+#[tokio::test]
+async fn resume_does_not_pay_twice() {
+    // Checkout feito numa tentativa anterior, que falhou antes de gerar
+    let server = label_server("order_paid.json").await;
+
+    let mut params = shipment();
+    params.provider_id = Some(CART_ID.to_string());
+    let result = provider(&server).create_shipment(params).await.unwrap();
+
+    assert_eq!(result.provider_id, CART_ID);
+    assert_eq!(server.hits(Method::POST, "/me/cart"), 0);
+    assert_eq!(server.hits(Method::POST, "/me/shipment/checkout"), 0);
+    assert_eq!(server.hits(Method::POST, "/me/shipment/generate"), 1);
+    assert_eq!(server.hits(Method::POST, "/me/shipment/print"), 1);
+}
+
+#[tokio::test]
+async fn create_reports_generate_failure() {
+    let server = MockServer::builder()
+        .route(
+            Method::GET,
+            &format!("/me/orders/{CART_ID}"),
+            200,
+            me("order_pending.json"),
+        )
+        .route(
+            Method::POST,
+            "/me/shipment/checkout",
+            200,
+            me("checkout.json"),
+        )
+        .route(
+            Method::POST,
+            "/me/shipment/generate",
+            200,
+            format!(r#"{{"{CART_ID}":{{"status":false,"message":"Saldo insuficiente"}}}}"#),
+        )
+        .start()
+        .await;
+
+    let mut params = shipment();
+    params.provider_id = Some(CART_ID.to_string());
+    let err = provider(&server).create_shipment(params).await.unwrap_err();
+
+    match err {
+        ShippingError::Rejected(msg) => assert_eq!(msg, "Saldo insuficiente"),
+        other => panic!("esperado Rejected, veio {other:?}"),
+    }
+    assert_eq!(server.hits(Method::POST, "/me/shipment/print"), 0);
+}
+
+#[tokio::test]
+async fn track_builds_events_from_steps() {
+    let server = MockServer::builder()
+        .route(
+            Method::POST,
+            "/me/shipment/tracking",
+            200,
+            me("tracking.json"),
+        )
+        .start()
+        .await;
+
+    let info = provider(&server).track(CART_ID).await.unwrap();
+
+    assert_eq!(info.tracking_code, "AA123456789BR");
+    assert_eq!(info.current_status, "in_transit");
+    let statuses: Vec<&str> = info.events.iter().map(|e| e.status.as_str()).collect();
+    assert_eq!(statuses, ["pending", "pending", "pending", "in_transit"]);
+    assert_eq!(info.events[3].timestamp, "2024-05-03 14:20:00");
+
+    let body = server
+        .last_body(Method::POST, "/me/shipment/tracking")
+        .unwrap();
+    assert_eq!(body["orders"][0], CART_ID);
+}
+
+#[tokio::test]
+async fn track_unknown_shipment_is_rejected() {
+    let server = MockServer::builder()
+        .route(Method::POST, "/me/shipment/tracking", 200, "{}".to_string())
+        .start()
+        .await;
+
+    let err = provider(&server).track("inexistente").await.unwrap_err();
+    assert!(matches!(err, ShippingError::Rejected(_)), "{err:?}");
+}
@@ -0,0 +1,146 @@
+//! Providers de frete contra um servidor HTTP local que devolve respostas
+//! gravadas das APIs (`tests/fixtures/<provider>/*.json`)
+
+mod correios;
+mod melhor_envio;
+mod packing;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+
+/// Conteúdo de `tests/fixtures/<name>`
+pub fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("fixture {path}: {e}"))
+}
+
+/// Requisição recebida pelo servidor mock
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: Method,
+    pub path: String,
+    pub query: String,
+    pub body: serde_json::Value,
+}
+
+#[derive(Clone)]
+struct Route {
+    method: Method,
+    path: String,
+    status: StatusCode,
+    body: String,
+}
+
+#[derive(Clone, Default)]
+struct MockState {
+    routes: Arc<Vec<Route>>,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+}
+
+/// Servidor HTTP local com respostas fixas por método e caminho. Com várias
+/// rotas iguais, a N-ésima chamada recebe a N-ésima resposta (a última se
+/// repete). Caminho sem rota responde 404; todas as requisições ficam
+/// registradas.
+pub struct MockServer {
+    pub base_url: String,
+    state: MockState,
+}
+
+pub struct MockServerBuilder {
+    routes: Vec<Route>,
+}
+
+impl MockServer {
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder { routes: vec![] }
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Quantas vezes `method path` foi chamado
+    pub fn hits(&self, method: Method, path: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|r| r.method == method && r.path == path)
+            .count()
+    }
+
+    /// Corpo da última chamada a `method path`
+    pub fn last_body(&self, method: Method, path: &str) -> Option<serde_json::Value> {
+        self.requests()
+            .into_iter()
+            .rev()
+            .find(|r| r.method == method && r.path == path)
+            .map(|r| r.body)
+    }
+}
+
+impl MockServerBuilder {
+    pub fn route(mut self, method: Method, path: &str, status: u16, body: String) -> Self {
+        self.routes.push(Route {
+            method,
+            path: path.to_string(),
+            status: StatusCode::from_u16(status).unwrap(),
+            body,
+        });
+        self
+    }
+
+    pub async fn start(self) -> MockServer {
+        let state = MockState {
+            routes: Arc::new(self.routes),
+            requests: Arc::default(),
+        };
+        let app = Router::new().fallback(handle).with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockServer {
+            base_url: format!("http://{addr}"),
+            state,
+        }
+    }
+}
+
+async fn handle(State(state): State<MockState>, method: Method, uri: Uri, body: Bytes) -> Response {
+    let path = uri.path().to_string();
+    let previous = {
+        let mut requests = state.requests.lock().unwrap();
+        let previous = requests
+            .iter()
+            .filter(|r| r.method == method && r.path == path)
+            .count();
+        requests.push(Recorded {
+            method: method.clone(),
+            path: path.clone(),
+            query: uri.query().unwrap_or_default().to_string(),
+            body: serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        });
+        previous
+    };
+    let routes: Vec<&Route> = state
+        .routes
+        .iter()
+        .filter(|r| r.method == method && r.path == path)
+        .collect();
+    match routes.get(previous.min(routes.len().saturating_sub(1))) {
+        Some(route) => (
+            route.status,
+            [(header::CONTENT_TYPE, "application/json")],
+            route.body.clone(),
+        )
+            .into_response(),
+        None => (StatusCode::NOT_FOUND, format!("sem rota: {method} {path}")).into_response(),
+    }
+}
@@ -0,0 +1,124 @@
+use loco_fast_store::shipping::{
+    packing::{default_catalog, pack, Package, MAX_PACKAGES},
+    FreightParams, ParcelItem, ShippingError, MIN_WEIGHT_GRAMS,
+};
+
+fn item(dims: [u32; 3], weight_grams: u32, quantity: u32) -> ParcelItem {
+    ParcelItem {
+        weight_grams,
+        length_cm: dims[0],
+        width_cm: dims[1],
+        height_cm: dims[2],
+        quantity,
+        unit_value_cents: 1_000,
+    }
+}
+
+#[test]
+fn units_of_a_line_share_the_smallest_box() {
+    let packages = pack(&[item([10, 10, 5], 200, 3)], &default_catalog(), 30_000).unwrap();
+
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].box_name.as_deref(), Some("P"));
+    // Caixa P (100 g) + 3 × 200 g
+    assert_eq!(packages[0].weight_grams, 700);
+    assert_eq!(packages[0].declared_value_cents, 3_000);
+}
+
+#[test]
+fn weight_limit_splits_units_across_packages() {
+    let packages = pack(&[item([10, 10, 10], 4_000, 5)], &default_catalog(), 10_000).unwrap();
+
+    // Até 2 unidades por volume (tara + 2 × 4 kg ≤ 10 kg)
+    assert_eq!(packages.len(), 3);
+    let units: i64 = packages
+        .iter()
+        .map(|p| p.declared_value_cents / 1_000)
+        .sum();
+    assert_eq!(units, 5);
+    assert!(packages.iter().all(|p| p.weight_grams <= 10_000));
+}
+
+#[test]
+fn lines_fill_open_packages_before_opening_new_ones() {
+    let packages = pack(
+        &[item([20, 15, 10], 500, 2), item([5, 5, 5], 50, 10)],
+        &default_catalog(),
+        30_000,
+    )
+    .unwrap();
+
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].declared_value_cents, 12_000);
+}
+
+#[test]
+fn oversized_units_ship_alone() {
+    let packages = pack(&[item([120, 50, 50], 8_000, 2)], &default_catalog(), 30_000).unwrap();
+
+    assert_eq!(packages.len(), 2);
+    assert!(packages.iter().all(|p| p.box_name.is_none()));
+    assert!(packages.iter().all(|p| p.length_cm == 120));
+}
+
+#[test]
+fn huge_quantity_is_rejected_without_expanding_units() {
+    let err = pack(
+        &[item([2, 2, 2], 10, 2_000_000_000)],
+        &default_catalog(),
+        30_000,
+    )
+    .unwrap_err();
+    assert!(matches!(err, ShippingError::Rejected(_)), "{err:?}");
+
+    let err = pack(
+        &[item([200, 200, 200], 10, u32::MAX)],
+        &default_catalog(),
+        30_000,
+    )
+    .unwrap_err();
+    assert!(matches!(err, ShippingError::Rejected(_)), "{err:?}");
+}
+
+#[test]
+fn package_limit_is_inclusive() {
+    // Cada unidade de 25 kg ocupa um volume sozinha
+    let fits = pack(
+        &[item([10, 10, 10], 25_000, MAX_PACKAGES as u32)],
+        &default_catalog(),
+        30_000,
+    )
+    .unwrap();
+    assert_eq!(fits.len(), MAX_PACKAGES);
+
+    let exceeds = pack(
+        &[item([10, 10, 10], 25_000, MAX_PACKAGES as u32 + 1)],
+        &default_catalog(),
+        30_000,
+    );
+    assert!(exceeds.is_err());
+}
+
+#[test]
+fn units_without_volume_or_weight_do_not_divide_by_zero() {
+    let packages = pack(&[item([0, 0, 0], 0, 1_000)], &default_catalog(), 30_000).unwrap();
+
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].weight_grams, MIN_WEIGHT_GRAMS);
+}
+
+#[test]
+fn summary_of_large_packages_does_not_overflow() {
+    let huge = Package {
+        box_name: None,
+        length_cm: u32::MAX,
+        width_cm: u32::MAX,
+        height_cm: u32::MAX,
+        weight_grams: u32::MAX,
+        declared_value_cents: 1,
+    };
+    let params = FreightParams::from_packages("01310-100", "20040-002", vec![huge.clone(), huge]);
+
+    assert_eq!(params.length_cm, u32::MAX);
+    assert_eq!(params.weight_grams, u32::MAX);
+}